@@ -21,22 +21,27 @@ fn main() {
         Ok(git_path) => {
             // Check whether .git repository belongs to oracle-core, since GitHub releases do not include .git
             if git_path.trim_end() == "../" {
-                let mut commit_hash = String::new();
-                let mut commit_date = String::new();
+                // Only set on success, so that the version falls back to "unknown"
                 match run_command("git", &["rev-parse", "HEAD"]) {
-                    Ok(hash) => commit_hash = hash,
+                    Ok(hash) => match hash.get(0..7) {
+                        Some(short_hash) => {
+                            println!("cargo:rustc-env=GIT_COMMIT_HASH={}", short_hash)
+                        }
+                        None => println!("cargo:warning=Unexpected commit hash {:?}", hash),
+                    },
                     Err(e) => {
                         println!("cargo:warning=Error getting commit hash, error: {}", e)
                     }
                 }
                 match run_command("git", &["log", "-1", "--format=%cd"]) {
-                    Ok(date) => commit_date = date,
+                    Ok(date) if !date.trim().is_empty() => {
+                        println!("cargo:rustc-env=GIT_COMMIT_DATE={}", date.trim_end())
+                    }
+                    Ok(_) => println!("cargo:warning=Empty commit date"),
                     Err(e) => {
-                        println!("cargo:warning=Error getting commit hash, error: {}", e)
+                        println!("cargo:warning=Error getting commit date, error: {}", e)
                     }
                 }
-                println!("cargo:rustc-env=GIT_COMMIT_HASH={}", &commit_hash[0..7]);
-                println!("cargo:rustc-env=GIT_COMMIT_DATE={}", commit_date);
             }
         }
         Err(e) => {
@@ -44,8 +49,6 @@ fn main() {
                 "cargo:warning=Error getting commit hash, error: {}. Omitting commit hash",
                 e
             );
-            println!("cargo:rustc-env=GIT_COMMIT_HASH= ");
-            println!("cargo:rustc-env=GIT_COMMIT_DATE= ");
         }
     };
 }
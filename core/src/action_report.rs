@@ -6,6 +6,7 @@ use crate::oracle_types::Rate;
 #[derive(Debug)]
 pub struct RefreshActionReport {
     pub oracle_boxes_collected: Vec<EcPoint>,
+    pub pool_rate: Rate,
 }
 
 #[derive(Debug)]
@@ -43,4 +44,8 @@ impl ActionReportStorage {
     pub fn get_last_refresh_report(&self) -> Option<&RefreshActionReport> {
         self.refresh.as_ref()
     }
+
+    pub fn get_last_publish_datapoint_report(&self) -> Option<&PublishDatapointActionReport> {
+        self.publish_datapoint.as_ref()
+    }
 }
@@ -1,7 +1,10 @@
 /// This file holds all the actions which can be performed
 /// by an oracle part of the oracle pool. These actions
 /// are implemented on the `OraclePool` struct.
+use std::time::Duration;
+
 use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+use ergo_lib::chain::transaction::TxId;
 
 use derive_more::From;
 use ergo_node_interface::node_interface::NodeError;
@@ -11,22 +14,34 @@ use crate::explorer_api::ergo_explorer_transaction_link;
 use crate::node_interface::node_api::NodeApi;
 use crate::node_interface::node_api::NodeApiError;
 use crate::oracle_config::ORACLE_CONFIG;
+use crate::shutdown;
 
 mod action_result;
 
+const SUBMIT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, From)]
+#[derive(Debug, Clone, From)]
 pub enum PoolAction {
     Refresh(RefreshAction),
     PublishDatapoint(PublishDataPointAction),
 }
 
-#[derive(Debug)]
+impl PoolAction {
+    pub fn tx_id(&self) -> TxId {
+        match self {
+            PoolAction::Refresh(action) => action.tx.id(),
+            PoolAction::PublishDatapoint(action) => action.tx.id(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RefreshAction {
     pub tx: UnsignedTransaction,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PublishDataPointAction {
     pub tx: UnsignedTransaction,
 }
@@ -57,6 +72,44 @@ pub fn execute_action(action: PoolAction, node_api: &NodeApi) -> Result<(), anyh
     }
 }
 
+/// Submit the action, retrying it `retry_attempts` more times if it fails. Unlike the build
+/// failures, the submit failures are mostly transient (node busy or restarting).
+pub fn execute_action_with_retries(
+    action: PoolAction,
+    node_api: &NodeApi,
+    retry_attempts: u32,
+) -> Result<(), anyhow::Error> {
+    retry(retry_attempts, SUBMIT_RETRY_DELAY, || {
+        execute_action(action.clone(), node_api)
+    })
+}
+
+fn retry<T>(
+    retry_attempts: u32,
+    delay: Duration,
+    mut f: impl FnMut() -> Result<T, anyhow::Error>,
+) -> Result<T, anyhow::Error> {
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(e) if attempt < retry_attempts => {
+                attempt += 1;
+                log::warn!(
+                    "Failed to submit the tx, retrying ({}/{}): {:?}",
+                    attempt,
+                    retry_attempts,
+                    e
+                );
+                if shutdown::sleep(delay) {
+                    log::info!("Shutdown requested, not retrying the tx submit");
+                    return Err(e);
+                }
+            }
+            res => return res,
+        }
+    }
+}
+
 fn execute_refresh_action(
     action: RefreshAction,
     node_api: &NodeApi,
@@ -82,3 +135,29 @@ fn execute_publish_datapoint_action(
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry() {
+        let mut calls = 0;
+        let res = retry(2, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 {
+                Err(anyhow::anyhow!("node is busy"))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(res.unwrap(), 3);
+        let mut calls = 0;
+        let res: Result<(), anyhow::Error> = retry(1, Duration::ZERO, || {
+            calls += 1;
+            Err(anyhow::anyhow!("node is busy"))
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 2);
+    }
+}
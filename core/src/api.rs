@@ -1,23 +1,44 @@
+use std::any::Any;
 use std::convert::From;
-use std::net::SocketAddr;
+#[cfg(unix)]
+use std::pin::Pin;
 use std::sync::Arc;
+#[cfg(unix)]
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::box_kind::{OracleBoxWrapper, PoolBox};
+use crate::cli_commands::consensus_value::consensus_value_info;
+use crate::datapoint_source::source_health;
+use crate::loop_timing;
+use crate::metrics;
 use crate::node_interface::node_api::NodeApi;
-use crate::oracle_config::{get_core_api_port, ORACLE_CONFIG};
+use crate::node_interface::request_cache;
+use crate::node_interface::retry_delay;
+use crate::oracle_config::{RestApiBind, RestServerPanicPolicy, ORACLE_CONFIG};
 use crate::oracle_state::{DataSourceError, LocalDatapointState, OraclePool};
+use crate::oracle_types::BlockHeight;
+use crate::pause;
 use crate::pool_config::POOL_CONFIG;
-use axum::http::StatusCode;
+use crate::value_format::{describe_rate, DEFAULT_DISPLAY_DECIMALS};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{Json, Router};
 use crossbeam::channel::Receiver;
-use ergo_lib::ergotree_ir::chain::address::{Address, AddressEncoder};
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::address::{Address, AddressEncoder, NetworkAddress};
 use ergo_node_interface::scanning::NodeError;
 use serde_json::json;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
 use tokio::task;
+use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::cors::CorsLayer;
 
+/// Running this long resets the REST server restart backoff
+const REST_SERVER_STABLE_RUN: Duration = Duration::from_secs(300);
+
 /// Basic welcome endpoint
 async fn root() -> &'static str {
     "This is an Oracle Core. Please use one of the endpoints to interact with it: 
@@ -27,6 +48,9 @@ async fn root() -> &'static str {
         /oracleStatus - status of the oracle
         /oracleHealth - returns OK if our collected datapoint box height is the same as the pool box height OR our posted datapoint box height is greater than the pool box height
         /poolHealth - returns OK if the pool box height is greater or equal to (current height - epoch length)
+        /ready - returns 200 if the node is reachable and its best block is not older than max_node_height_staleness_secs, 503 otherwise
+        /loopTiming - durations of the phases of the last main loop iteration in milliseconds
+        /pause, /resume (POST, api_key header) - stop/resume submitting the transactions
         "
 }
 
@@ -126,7 +150,7 @@ async fn pool_status(oracle_pool: Arc<OraclePool>) -> Result<Json<serde_json::Va
 
 fn pool_status_sync(oracle_pool: Arc<OraclePool>) -> Result<Json<serde_json::Value>, ApiError> {
     let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
-    let current_height = node_api.node.current_block_height()? as u32;
+    let current_height = request_cache::current_height(&node_api)?;
     let pool_box = oracle_pool.get_pool_box_source().get_pool_box()?;
     let epoch_length = POOL_CONFIG
         .refresh_box_wrapper_inputs
@@ -139,46 +163,168 @@ fn pool_status_sync(oracle_pool: Arc<OraclePool>) -> Result<Json<serde_json::Val
     let posted_boxes = oracle_pool
         .get_posted_datapoint_boxes_source()
         .get_posted_datapoint_boxes()?;
-    let posted_count_current_epoch = posted_boxes
+    let posted_pks_current_epoch: Vec<EcPoint> = posted_boxes
         .into_iter()
         .filter(|b| b.get_box().creation_height >= pool_box_height)
-        .count();
+        .map(|b| b.public_key())
+        .collect();
+    let posted_count_current_epoch = posted_pks_current_epoch.len();
 
     let collected_boxes = oracle_pool
         .get_collected_datapoint_boxes_source()
         .get_collected_datapoint_boxes()?;
-    let collected_count_previous_epoch = collected_boxes
+    let collected_pks_previous_epoch: Vec<EcPoint> = collected_boxes
         .into_iter()
         .filter(|b| b.get_box().creation_height == pool_box_height)
-        .count();
+        .map(|b| b.public_key())
+        .collect();
+    let collected_count_previous_epoch = collected_pks_previous_epoch.len();
+
+    let network = ORACLE_CONFIG.oracle_address.network();
+    let unknown_oracles: Vec<String> = posted_pks_current_epoch
+        .into_iter()
+        .chain(collected_pks_previous_epoch)
+        .filter(|pk| !ORACLE_CONFIG.is_known_oracle(pk))
+        .map(|pk| NetworkAddress::new(network, &Address::P2Pk(pk.into())).to_base58())
+        .collect();
 
     let active_oracle_count = collected_count_previous_epoch + posted_count_current_epoch;
     let pool_health = pool_health_sync(oracle_pool)?;
 
     let json = Json(json!({
         "latest_pool_datapoint": pool_box.rate(),
+        "latest_pool_datapoint_human": describe_rate(
+            pool_box.rate().into(),
+            POOL_CONFIG.data_point_source,
+            ORACLE_CONFIG
+                .value_display_decimals
+                .unwrap_or(DEFAULT_DISPLAY_DECIMALS)
+        ),
         "latest_pool_box_height": pool_box_height,
         "pool_box_epoch_id" : pool_box.epoch_counter(),
         "current_block_height": current_height,
         "epoch_end_height": epoch_end_height,
         "reward_tokens_in_pool_box": pool_box.reward_token().amount.as_u64(),
         "number_of_oracles": active_oracle_count,
+        "unknown_oracles": unknown_oracles,
         "pool_health": pool_health,
+        "paused": pause::is_paused(),
     }));
     Ok(json)
 }
 
+/// Pool value with the number of contributing oracles, the spread of the pending datapoints and
+/// its age
+async fn consensus_value(
+    oracle_pool: Arc<OraclePool>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let json = task::spawn_blocking(|| consensus_value_sync(oracle_pool))
+        .await
+        .unwrap()?;
+    Ok(json)
+}
+
+fn consensus_value_sync(oracle_pool: Arc<OraclePool>) -> Result<Json<serde_json::Value>, ApiError> {
+    let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
+    let current_height = request_cache::current_height(&node_api)?;
+    let info = consensus_value_info(&oracle_pool, BlockHeight(current_height))?;
+    Ok(Json(json!(info)))
+}
+
+/// Prometheus metrics, see [`crate::metrics::METRICS`]
+async fn metrics(oracle_pool: Arc<OraclePool>) -> Result<impl IntoResponse, ApiError> {
+    let body = task::spawn_blocking(|| metrics_sync(oracle_pool))
+        .await
+        .unwrap()?;
+    Ok((
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4",
+        )],
+        body,
+    ))
+}
+
+fn metrics_sync(oracle_pool: Arc<OraclePool>) -> Result<String, ApiError> {
+    let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
+    let current_height = request_cache::current_height(&node_api)?;
+    let pool_box = oracle_pool.get_pool_box_source().get_pool_box()?;
+    let pool_box_height = pool_box.get_box().creation_height;
+    let consensus_oracles = oracle_pool
+        .get_collected_datapoint_boxes_source()
+        .get_collected_datapoint_boxes()?
+        .into_iter()
+        .filter(|b| b.get_box().creation_height == pool_box_height)
+        .count();
+    Ok(metrics::render(&metrics::MetricValues {
+        block_height: current_height,
+        pool_epoch: pool_box.epoch_counter().0,
+        pool_rate: pool_box.rate(),
+        consensus_oracles,
+        seconds_since_last_post: metrics::seconds_since_last_post(),
+        excluded_sources: source_health::excluded_sources().len(),
+    }))
+}
+
 /// Block height of the Ergo blockchain
 async fn block_height() -> Result<impl IntoResponse, ApiError> {
     let current_height = task::spawn_blocking(move || {
         let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
-        node_api.node.current_block_height()
+        request_cache::current_height(&node_api)
     })
     .await
     .unwrap()?;
     Ok(format!("{}", current_height))
 }
 
+/// Durations of the phases of the last main loop iteration
+async fn loop_timing_info() -> Json<serde_json::Value> {
+    Json(json!(loop_timing::last_iteration()))
+}
+
+/// Stop submitting the transactions until `/resume`, the main loop and the API keep running
+async fn pause_oracle(headers: HeaderMap) -> Result<&'static str, (StatusCode, String)> {
+    check_control_api_key(control_api_key().as_deref(), &headers)?;
+    pause::set_paused(true);
+    log::info!("Oracle is paused with the REST API");
+    Ok("paused")
+}
+
+async fn resume_oracle(headers: HeaderMap) -> Result<&'static str, (StatusCode, String)> {
+    check_control_api_key(control_api_key().as_deref(), &headers)?;
+    pause::set_paused(false);
+    log::info!("Oracle is resumed with the REST API");
+    Ok("resumed")
+}
+
+fn control_api_key() -> Option<String> {
+    ORACLE_CONFIG
+        .rest_api_control_key_env_var
+        .as_ref()
+        .and_then(|var| std::env::var(var).ok())
+        .filter(|key| !key.is_empty())
+}
+
+fn check_control_api_key(
+    expected_key: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, String)> {
+    let expected_key = expected_key.ok_or_else(|| {
+        (
+            StatusCode::FORBIDDEN,
+            "Control endpoints are disabled, set rest_api_control_key_env_var in the oracle config"
+                .to_string(),
+        )
+    })?;
+    match headers.get("api_key").and_then(|key| key.to_str().ok()) {
+        Some(key) if key == expected_key => Ok(()),
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid api_key header".to_string(),
+        )),
+    }
+}
+
 /// Whether the Core requires the Connector to repost a new Datapoint
 async fn require_datapoint_repost(repost_receiver: Receiver<bool>) -> impl IntoResponse {
     let mut response_text = "false".to_string();
@@ -240,7 +386,7 @@ async fn pool_health(oracle_pool: Arc<OraclePool>) -> Result<Json<serde_json::Va
 fn pool_health_sync(oracle_pool: Arc<OraclePool>) -> Result<serde_json::Value, ApiError> {
     let pool_conf = &POOL_CONFIG;
     let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
-    let current_height = node_api.node.current_block_height()? as u32;
+    let current_height = request_cache::current_height(&node_api)?;
     let pool_box_height = oracle_pool
         .get_pool_box_source()
         .get_pool_box()?
@@ -265,6 +411,51 @@ fn pool_health_sync(oracle_pool: Arc<OraclePool>) -> Result<serde_json::Value, A
     Ok(json)
 }
 
+/// Whether the node is reachable and synced (best block is fresh enough)
+async fn ready() -> (StatusCode, Json<serde_json::Value>) {
+    let json = task::spawn_blocking(ready_sync).await.unwrap();
+    let status_code = if json["status"] == "OK" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status_code, Json(json))
+}
+
+fn ready_sync() -> serde_json::Value {
+    let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
+    let best_block_timestamp = match node_api.get_best_block_timestamp() {
+        Ok(timestamp) => timestamp,
+        Err(e) => {
+            return json!({
+                "status": "DOWN",
+                "details": { "error": format!("Node is not reachable: {}", e) },
+            })
+        }
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let staleness_secs = now.saturating_sub(best_block_timestamp) / 1000;
+    let max_staleness_secs = ORACLE_CONFIG.max_node_height_staleness_secs;
+    let is_ready = is_node_fresh(staleness_secs, max_staleness_secs);
+    json!({
+        "status": if is_ready { "OK" } else { "DOWN" },
+        "details": {
+            "best_block_timestamp": best_block_timestamp,
+            "best_block_age_secs": staleness_secs,
+            "max_node_height_staleness_secs": max_staleness_secs,
+        },
+    })
+}
+
+fn is_node_fresh(staleness_secs: u64, max_staleness_secs: Option<u64>) -> bool {
+    max_staleness_secs
+        .map(|max| staleness_secs <= max)
+        .unwrap_or(true)
+}
+
 pub async fn start_rest_server(
     repost_receiver: Receiver<bool>,
     oracle_pool: Arc<OraclePool>,
@@ -272,15 +463,23 @@ pub async fn start_rest_server(
     let op_clone = oracle_pool.clone();
     let op_clone2 = oracle_pool.clone();
     let op_clone3 = oracle_pool.clone();
+    let op_clone4 = oracle_pool.clone();
+    let op_clone5 = oracle_pool.clone();
     let app = Router::new()
         .route("/", get(root))
         .route("/oracleInfo", get(oracle_info))
         .route("/oracleStatus", get(|| oracle_status(oracle_pool)))
         .route("/poolInfo", get(pool_info))
         .route("/poolStatus", get(|| pool_status(op_clone)))
+        .route("/consensusValue", get(|| consensus_value(op_clone4)))
         .route("/blockHeight", get(block_height))
         .route("/oracleHealth", get(|| oracle_health(op_clone2)))
         .route("/poolHealth", get(|| pool_health(op_clone3)))
+        .route("/ready", get(ready))
+        .route("/loopTiming", get(loop_timing_info))
+        .route("/metrics", get(|| metrics(op_clone5)))
+        .route("/pause", post(pause_oracle))
+        .route("/resume", post(resume_oracle))
         .route(
             "/requireDatapointRepost",
             get(|| require_datapoint_repost(repost_receiver)),
@@ -288,15 +487,157 @@ pub async fn start_rest_server(
         .layer(
             CorsLayer::new()
                 .allow_origin(tower_http::cors::Any)
-                .allow_methods([axum::http::Method::GET]),
-        );
-    let addr = SocketAddr::from(([0, 0, 0, 0], get_core_api_port().parse().unwrap()));
-    axum::Server::try_bind(&addr)?
-        .serve(app.into_make_service())
-        .await?;
+                .allow_methods([axum::http::Method::GET, axum::http::Method::POST]),
+        )
+        .layer(CatchPanicLayer::custom(handler_panic_response));
+    match ORACLE_CONFIG.rest_api_bind()? {
+        RestApiBind::Tcp(addr) => {
+            axum::Server::try_bind(&addr)?
+                .serve(app.into_make_service())
+                .await?
+        }
+        #[cfg(unix)]
+        RestApiBind::Unix { path, mode } => {
+            let listener = bind_unix_socket(&path, mode)?;
+            log::info!("REST API is listening on {}", path.display());
+            axum::Server::builder(UnixSocketAccept(listener))
+                .serve(app.into_make_service())
+                .await?
+        }
+    }
     Ok(())
 }
 
+/// Run the REST server, starting it again after a backoff if it panics (or exiting, see
+/// `rest_server_panic_policy`). Exits if the server fails to start.
+pub async fn supervise_rest_server(repost_receiver: Receiver<bool>, oracle_pool: Arc<OraclePool>) {
+    let mut attempt = 1;
+    loop {
+        let started = Instant::now();
+        let server = tokio::spawn(start_rest_server(
+            repost_receiver.clone(),
+            oracle_pool.clone(),
+        ));
+        let message = match server.await {
+            Ok(Ok(())) => "stopped".to_string(),
+            Ok(Err(e)) => {
+                log::error!("An error occurred while starting the REST server: {}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+            Err(e) if e.is_panic() => format!("panicked: {}", panic_message(e.into_panic())),
+            Err(e) => format!("was cancelled: {}", e),
+        };
+        match ORACLE_CONFIG
+            .rest_server_panic_policy
+            .unwrap_or(RestServerPanicPolicy::Restart)
+        {
+            RestServerPanicPolicy::Restart => {
+                // a server that ran for a while before failing starts the backoff over
+                if started.elapsed() > REST_SERVER_STABLE_RUN {
+                    attempt = 1;
+                }
+                let delay = retry_delay(attempt);
+                log::error!(
+                    "The REST server {}. Restarting it in {} seconds",
+                    message,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            RestServerPanicPolicy::Exit => {
+                log::error!(
+                    "The REST server {}. Exiting (rest_server_panic_policy: exit)",
+                    message
+                );
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+    }
+}
+
+/// A panicking handler is logged and answered with a 500, the server keeps running
+fn handler_panic_response(payload: Box<dyn Any + Send>) -> Response {
+    let message = panic_message(payload);
+    log::error!("A REST API handler panicked: {}", message);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Error: handler panicked: {}", message),
+    )
+        .into_response()
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Bind the socket in a private directory next to `path`, set its `mode` and move it to `path`,
+/// so that it's never reachable with wider permissions. Only a socket left by the previous run is
+/// removed from `path`.
+#[cfg(unix)]
+fn bind_unix_socket(path: &std::path::Path, mode: u32) -> Result<UnixListener, anyhow::Error> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(anyhow::anyhow!(
+                "rest_api_bind {} exists and is not a socket",
+                path.display()
+            ))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+        Err(e) => return Err(e.into()),
+    }
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("rest_api_bind {} has no file name", path.display()))?;
+    let mut private_dir_name = std::ffi::OsString::from(".");
+    private_dir_name.push(file_name);
+    private_dir_name.push(".bind");
+    let private_dir = path.with_file_name(private_dir_name);
+    if private_dir.exists() {
+        std::fs::remove_dir_all(&private_dir)?;
+    }
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let private_path = private_dir.join(file_name);
+    let res = UnixListener::bind(&private_path)
+        .and_then(|listener| {
+            std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(mode))?;
+            std::fs::rename(&private_path, path)?;
+            Ok(listener)
+        })
+        .map_err(anyhow::Error::from);
+    let _ = std::fs::remove_dir_all(&private_dir);
+    res
+}
+
+/// Accepts the REST API connections on a Unix domain socket
+#[cfg(unix)]
+struct UnixSocketAccept(UnixListener);
+
+#[cfg(unix)]
+impl hyper::server::accept::Accept for UnixSocketAccept {
+    type Conn = UnixStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let (stream, _) = ready!(self.0.poll_accept(cx))?;
+        Poll::Ready(Some(Ok(stream)))
+    }
+}
+
 struct ApiError(String);
 
 impl From<DataSourceError> for ApiError {
@@ -322,3 +663,50 @@ impl From<anyhow::Error> for ApiError {
         ApiError(format!("Error: {:?}", err))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(Box::new("boom")), "boom");
+        assert_eq!(panic_message(Box::new("boom".to_string())), "boom");
+        assert_eq!(panic_message(Box::new(1)), "unknown panic payload");
+        assert_eq!(
+            handler_panic_response(Box::new("boom")).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_is_node_fresh() {
+        assert!(is_node_fresh(1000, None));
+        assert!(is_node_fresh(600, Some(600)));
+        assert!(!is_node_fresh(601, Some(600)));
+    }
+
+    #[test]
+    fn test_check_control_api_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            check_control_api_key(Some("secret"), &headers)
+                .unwrap_err()
+                .0,
+            StatusCode::UNAUTHORIZED
+        );
+        headers.insert("api_key", "wrong".parse().unwrap());
+        assert_eq!(
+            check_control_api_key(Some("secret"), &headers)
+                .unwrap_err()
+                .0,
+            StatusCode::UNAUTHORIZED
+        );
+        headers.insert("api_key", "secret".parse().unwrap());
+        assert!(check_control_api_key(Some("secret"), &headers).is_ok());
+        assert_eq!(
+            check_control_api_key(None, &headers).unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+    }
+}
@@ -276,6 +276,14 @@ impl CollectedOracleBox {
     pub fn get_box(&self) -> &ErgoBox {
         &self.ergo_box
     }
+
+    pub fn public_key(&self) -> EcPoint {
+        self.ergo_box
+            .get_register(NonMandatoryRegisterId::R4.into())
+            .unwrap()
+            .try_extract_into::<EcPoint>()
+            .unwrap()
+    }
 }
 
 #[derive(Clone, Debug)]
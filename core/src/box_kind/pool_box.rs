@@ -1,10 +1,15 @@
 use ergo_lib::chain::ergo_box::box_builder::ErgoBoxCandidateBuilder;
 use ergo_lib::chain::ergo_box::box_builder::ErgoBoxCandidateBuilderError;
+use ergo_lib::ergo_chain_types::blake2b256_hash;
+use ergo_lib::ergo_chain_types::Digest32;
 use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
 use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
 use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBoxCandidate;
 use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisterId;
+use ergo_lib::ergotree_ir::ergo_tree::ErgoTree;
 use ergo_lib::ergotree_ir::mir::constant::TryExtractInto;
+use ergo_lib::ergotree_ir::serialization::SigmaSerializable;
+use ergo_lib::ergotree_ir::serialization::SigmaSerializationError;
 use thiserror::Error;
 
 use crate::contracts::pool::PoolContract;
@@ -20,6 +25,12 @@ use crate::spec_token::SpecToken;
 use crate::spec_token::TokenIdKind;
 use crate::spec_token::UpdateTokenId;
 
+/// Hash of the pool box contract, as voted for by the ballots and checked by the update
+/// contract
+pub fn pool_box_hash(ergo_tree: &ErgoTree) -> Result<Digest32, SigmaSerializationError> {
+    Ok(blake2b256_hash(&ergo_tree.sigma_serialize_bytes()?))
+}
+
 pub trait PoolBox {
     fn contract(&self) -> &PoolContract;
     fn pool_nft_token(&self) -> SpecToken<PoolTokenId>;
@@ -1,8 +1,51 @@
+pub mod address_from_mnemonic;
+pub mod backtest_source;
+pub mod benchmark_sources;
 pub mod bootstrap;
+pub mod check_defaults;
+pub mod check_join_eligibility;
+pub mod check_pool_integrity;
+pub mod check_stuck_update;
+pub mod collection_priority;
+pub mod compare_reference;
+pub mod consensus_value;
+pub mod debug_source;
+pub mod derive_config;
+pub mod diagnostics;
+pub mod dump_registers;
+pub mod explain_config;
+pub mod export_history;
 pub mod extract_reward_tokens;
+pub mod find_reward_tokens;
+pub mod generate_dashboard;
+pub mod generate_monitor_config;
+pub mod generate_service_file;
+pub mod hash_contract;
+pub mod health_attestation;
+pub mod identical_datapoints;
 pub mod import_pool_update;
+pub mod inspect_pool;
+pub mod manage_scans;
+pub mod monitor_pool_value;
+pub mod participation_report;
 pub mod prepare_update;
+pub mod preview_funding;
+pub mod preview_refresh;
+pub mod print_config_schema;
 pub mod print_reward_tokens;
+pub mod refresh_readiness;
+pub mod refresh_tx;
+pub mod reward_audit;
+pub mod reward_extraction_history;
+pub mod self_test_pipeline;
+pub mod snapshot_state;
+pub mod suggest_fee;
+pub mod test_source;
+pub mod trace_tx;
 pub mod transfer_oracle_token;
 pub mod update_pool;
+pub mod update_status;
+pub mod validate_contract_params;
+pub mod verify_ballot;
+pub mod verify_pool_box;
 pub mod vote_update_pool;
@@ -0,0 +1,180 @@
+use std::io::BufRead;
+use std::io::Write;
+
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+use ergo_lib::wallet::derivation_path::ChildIndexError;
+use ergo_lib::wallet::derivation_path::ChildIndexHardened;
+use ergo_lib::wallet::derivation_path::ChildIndexNormal;
+use ergo_lib::wallet::derivation_path::DerivationPath;
+use ergo_lib::wallet::ext_secret_key::ExtSecretKey;
+use ergo_lib::wallet::ext_secret_key::ExtSecretKeyError;
+use ergo_lib::wallet::mnemonic::Mnemonic;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AddressFromMnemonicError {
+    #[error("Environment variable {0} is not set")]
+    EnvVarNotSet(String),
+    #[error("Empty mnemonic")]
+    EmptyMnemonic,
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Derivation path error: {0:?}")]
+    ChildIndex(#[from] ChildIndexError),
+    #[error("Key derivation error: {0:?}")]
+    ExtSecretKey(#[from] ExtSecretKeyError),
+}
+
+/// Derive the first wallet address (EIP-3 path m/44'/429'/0'/0/0) from the mnemonic and compare
+/// it with the configured oracle address. The mnemonic is read from `mnemonic_env_var` or stdin
+/// and is never printed or logged.
+pub fn address_from_mnemonic(
+    oracle_address: &NetworkAddress,
+    mnemonic_env_var: Option<String>,
+    mnemonic_password_env_var: Option<String>,
+) -> Result<(), AddressFromMnemonicError> {
+    let (mnemonic, mnemonic_password) = read_mnemonic(mnemonic_env_var, mnemonic_password_env_var)?;
+    let address = derive_first_address(&mnemonic, &mnemonic_password, oracle_address.network())?;
+    println!("Address derived from the mnemonic: {}", address.to_base58());
+    if address.address() == oracle_address.address() {
+        println!("It matches the configured oracle_address");
+    } else {
+        println!(
+            "It does NOT match the configured oracle_address {}",
+            oracle_address.to_base58()
+        );
+        println!("(a node wallet created before EIP-3 may derive its addresses differently)");
+    }
+    Ok(())
+}
+
+/// The mnemonic from `mnemonic_env_var` or stdin and the mnemonic password from
+/// `mnemonic_password_env_var` (empty if not given)
+fn read_mnemonic(
+    mnemonic_env_var: Option<String>,
+    mnemonic_password_env_var: Option<String>,
+) -> Result<(String, String), AddressFromMnemonicError> {
+    let mnemonic = match mnemonic_env_var {
+        Some(env_var) => {
+            std::env::var(&env_var).map_err(|_| AddressFromMnemonicError::EnvVarNotSet(env_var))?
+        }
+        None => {
+            eprint!("Enter the mnemonic (the input is not hidden, use --mnemonic-env-var to avoid it showing in the terminal): ");
+            std::io::stderr().flush()?;
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            line
+        }
+    };
+    let mnemonic_password = match mnemonic_password_env_var {
+        Some(env_var) => {
+            std::env::var(&env_var).map_err(|_| AddressFromMnemonicError::EnvVarNotSet(env_var))?
+        }
+        None => String::new(),
+    };
+    Ok((mnemonic, mnemonic_password))
+}
+
+fn derive_first_address(
+    mnemonic: &str,
+    mnemonic_password: &str,
+    network: NetworkPrefix,
+) -> Result<NetworkAddress, AddressFromMnemonicError> {
+    let public_key = derive_first_secret_key(mnemonic, mnemonic_password)?.public_key()?;
+    Ok(NetworkAddress::new(network, &Address::from(public_key)))
+}
+
+/// Secret key of the first wallet address (EIP-3 path m/44'/429'/0'/0/0)
+pub fn derive_first_secret_key(
+    mnemonic: &str,
+    mnemonic_password: &str,
+) -> Result<ExtSecretKey, AddressFromMnemonicError> {
+    let mnemonic = mnemonic.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if mnemonic.is_empty() {
+        return Err(AddressFromMnemonicError::EmptyMnemonic);
+    }
+    let seed = Mnemonic::to_seed(&mnemonic, mnemonic_password);
+    let master_key = ExtSecretKey::derive_master(seed)?;
+    let path = DerivationPath::new(
+        ChildIndexHardened::from_31_bit(0)?,
+        vec![ChildIndexNormal::normal(0)?],
+    );
+    Ok(master_key.derive(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "change me do not use me change me do not use me";
+
+    #[test]
+    fn test_derive_first_address() {
+        let address = derive_first_address(MNEMONIC, "", NetworkPrefix::Mainnet).unwrap();
+        assert!(matches!(address.address(), Address::P2Pk(_)));
+        assert_eq!(address.network(), NetworkPrefix::Mainnet);
+        // whitespace doesn't matter
+        assert_eq!(
+            derive_first_address(
+                "  change me do not use me\nchange me do not use me \n",
+                "",
+                NetworkPrefix::Mainnet
+            )
+            .unwrap()
+            .address(),
+            address.address()
+        );
+        // the mnemonic password changes the seed
+        assert_ne!(
+            derive_first_address(MNEMONIC, "password", NetworkPrefix::Mainnet)
+                .unwrap()
+                .address(),
+            address.address()
+        );
+        assert!(matches!(
+            derive_first_address(" \n", "", NetworkPrefix::Mainnet),
+            Err(AddressFromMnemonicError::EmptyMnemonic)
+        ));
+    }
+
+    #[test]
+    fn test_derive_first_address_vectors() {
+        // first EIP-3 address of the ergo-appkit test mnemonic
+        assert_eq!(
+            derive_first_address(
+                "slow silly start wash bundle suffer bulb ancient height spin express remind today effort helmet",
+                "",
+                NetworkPrefix::Mainnet
+            )
+            .unwrap()
+            .to_base58(),
+            "9eatpGQdYNjTi5ZZLK7Bo7C3ms6oECPnxbQTRn6sDcBNLMYSCa8"
+        );
+        for (password, network, expected) in [
+            (
+                "",
+                NetworkPrefix::Mainnet,
+                "9fHqrP7wvx7MRpzbaH73kaMw8YSRMYwuMqvtJVMVNx1LzkrfTfp",
+            ),
+            (
+                "password",
+                NetworkPrefix::Mainnet,
+                "9gbvNsQnq7m3bg9PifppPsAGqVUhLqAsvR28rvCVwp5rcKDVAnM",
+            ),
+            (
+                "",
+                NetworkPrefix::Testnet,
+                "3WwNzS9WkPcJwi3HSnbPnY69v5ksn3taWRp1qa2sM31PrHx9RPms",
+            ),
+        ] {
+            assert_eq!(
+                derive_first_address(MNEMONIC, password, network)
+                    .unwrap()
+                    .to_base58(),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::datapoint_source::historical::fetch_historical_series;
+use crate::datapoint_source::historical::historical_datapoint;
+use crate::datapoint_source::historical::sources_without_history;
+use crate::datapoint_source::historical::MAX_PRICE_GAP_MS;
+use crate::datapoint_source::reference_feed::deviation_percent;
+use crate::datapoint_source::RuntimeDataPointSource;
+use crate::epoch_history::read_records;
+use crate::node_interface::node_api::NodeApi;
+use crate::oracle_config::OracleConfig;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::Rate;
+use crate::pool_config::PoolConfig;
+
+/// Recorded epoch and the datapoint the configured source would have given at its time
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestEpoch {
+    pub height: BlockHeight,
+    pub epoch: EpochCounter,
+    /// Pool box rate
+    pub pool_rate: Rate,
+    /// None if there's no historical price close enough to the epoch
+    pub backtested: Option<f64>,
+}
+
+impl BacktestEpoch {
+    /// Deviation of the pool rate from the backtested datapoint, positive if the pool rate is
+    /// higher
+    pub fn deviation_percent(&self) -> Option<f64> {
+        self.backtested
+            .filter(|backtested| *backtested != 0.0)
+            .map(|backtested| deviation_percent(self.pool_rate, backtested))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestSummary {
+    pub epochs: usize,
+    /// Epochs with a backtested datapoint
+    pub compared: usize,
+    /// Average signed deviation, i.e. the bias of the source against the pool
+    pub mean_deviation_percent: f64,
+    pub mean_abs_deviation_percent: f64,
+    pub max_abs_deviation_percent: f64,
+}
+
+/// Compare the pool rates of the epochs recorded in the epoch history between the heights with
+/// the datapoints the configured predefined source and `datapoint_transform` would have posted at
+/// the time of each epoch, from the historical prices of the sources that serve them
+pub fn backtest_source(
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+    node_api: &NodeApi,
+    history_file_path: &Path,
+    from: BlockHeight,
+    to: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    if from > to {
+        return Err(anyhow!("--from {} is above --to {}", from, to));
+    }
+    let datapoint_source = RuntimeDataPointSource::new(
+        pool_config.data_point_source,
+        oracle_config.data_point_source_custom_script.clone(),
+        oracle_config.data_point_source_json.clone(),
+    )?;
+    let predef = match &datapoint_source {
+        RuntimeDataPointSource::Predefined(predef) => *predef,
+        RuntimeDataPointSource::ExternalScript(_) | RuntimeDataPointSource::Json(_) => {
+            return Err(anyhow!(
+                "historical prices are only available for the predefined data_point_source of the \
+                pool config, the configured source is {}",
+                datapoint_source.source_names().join(", ")
+            ))
+        }
+    };
+    let records: Vec<_> = read_records(history_file_path)?
+        .into_iter()
+        .filter(|record| record.height >= from && record.height <= to)
+        .collect();
+    if records.is_empty() {
+        println!(
+            "No epochs between heights {} and {} in the history at {}, they are recorded while \
+            running the oracle with `run`",
+            from,
+            to,
+            history_file_path.display()
+        );
+        return Ok(());
+    }
+    let timestamps = records
+        .iter()
+        .map(|record| node_api.get_block_timestamp(record.height.0))
+        .collect::<Result<Vec<u64>, _>>()?;
+    let first_ms = timestamps.iter().min().copied().unwrap_or(0);
+    let last_ms = timestamps.iter().max().copied().unwrap_or(0);
+    let series = fetch_historical_series(
+        &predef,
+        first_ms.saturating_sub(MAX_PRICE_GAP_MS) / 1000,
+        (last_ms + MAX_PRICE_GAP_MS) / 1000,
+    )?;
+    let without_history = sources_without_history(&predef);
+    if !without_history.is_empty() {
+        println!(
+            "No historical prices from {}, backtesting with {}",
+            without_history.join(", "),
+            series
+                .iter()
+                .map(|s| s.source)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    let transform = oracle_config.datapoint_transform.as_ref();
+    let epochs: Vec<BacktestEpoch> = records
+        .iter()
+        .zip(timestamps)
+        .map(|(record, timestamp_ms)| BacktestEpoch {
+            height: record.height,
+            epoch: record.epoch,
+            pool_rate: record.consensus_value,
+            backtested: historical_datapoint(&series, timestamp_ms, transform),
+        })
+        .collect();
+    println!("height,epoch,pool_rate,backtested,deviation_percent");
+    for epoch in &epochs {
+        println!(
+            "{},{},{},{},{}",
+            epoch.height,
+            epoch.epoch.0,
+            epoch.pool_rate,
+            epoch
+                .backtested
+                .map(|v| format!("{:.0}", v))
+                .unwrap_or_default(),
+            epoch
+                .deviation_percent()
+                .map(|d| format!("{:.2}", d))
+                .unwrap_or_default()
+        );
+    }
+    let summary = backtest_summary(&epochs);
+    println!(
+        "{} of {} epochs compared: pool rate deviates from the backtested source by {:.2}% on \
+        average ({:.2}% bias), at most {:.2}%",
+        summary.compared,
+        summary.epochs,
+        summary.mean_abs_deviation_percent,
+        summary.mean_deviation_percent,
+        summary.max_abs_deviation_percent
+    );
+    Ok(())
+}
+
+fn backtest_summary(epochs: &[BacktestEpoch]) -> BacktestSummary {
+    let deviations: Vec<f64> = epochs
+        .iter()
+        .filter_map(BacktestEpoch::deviation_percent)
+        .collect();
+    BacktestSummary {
+        epochs: epochs.len(),
+        compared: deviations.len(),
+        mean_deviation_percent: mean(deviations.iter().copied()),
+        mean_abs_deviation_percent: mean(deviations.iter().map(|d| d.abs())),
+        max_abs_deviation_percent: deviations.iter().map(|d| d.abs()).fold(0.0, f64::max),
+    }
+}
+
+fn mean(values: impl ExactSizeIterator<Item = f64>) -> f64 {
+    let len = values.len();
+    if len == 0 {
+        0.0
+    } else {
+        values.sum::<f64>() / len as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn epoch(pool_rate: i64, backtested: Option<f64>) -> BacktestEpoch {
+        BacktestEpoch {
+            height: BlockHeight(1000),
+            epoch: EpochCounter(1),
+            pool_rate: pool_rate.into(),
+            backtested,
+        }
+    }
+
+    #[test]
+    fn test_backtest_summary() {
+        let epochs = vec![
+            epoch(110, Some(100.0)),
+            epoch(95, Some(100.0)),
+            epoch(100, None),
+        ];
+        let summary = backtest_summary(&epochs);
+        assert_eq!(summary.epochs, 3);
+        assert_eq!(summary.compared, 2);
+        assert!((summary.mean_deviation_percent - 2.5).abs() < 1e-9);
+        assert!((summary.mean_abs_deviation_percent - 7.5).abs() < 1e-9);
+        assert!((summary.max_abs_deviation_percent - 10.0).abs() < 1e-9);
+        let empty = backtest_summary(&[]);
+        assert_eq!(empty.compared, 0);
+        assert_eq!(empty.mean_abs_deviation_percent, 0.0);
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use crate::datapoint_source::RuntimeDataPointSource;
+use crate::oracle_config::OracleConfig;
+use crate::pool_config::PoolConfig;
+
+pub fn benchmark_sources(
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+    samples: usize,
+) -> Result<(), anyhow::Error> {
+    let datapoint_source = RuntimeDataPointSource::new(
+        pool_config.data_point_source,
+        oracle_config.data_point_source_custom_script.clone(),
+        oracle_config.data_point_source_json.clone(),
+    )?;
+    println!("Fetching from each source {} times...", samples);
+    let format_latency = |latency: Option<Duration>| {
+        latency
+            .map(|l| format!("{}ms", l.as_millis()))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    for benchmark in datapoint_source.benchmark(samples) {
+        println!(
+            "{}: success rate {:.0}% ({}/{}), latency min {} / median {} / max {}, value variance {}",
+            benchmark.name,
+            benchmark.success_rate() * 100.0,
+            benchmark.values.len(),
+            benchmark.samples,
+            format_latency(benchmark.min_latency()),
+            format_latency(benchmark.median_latency()),
+            format_latency(benchmark.max_latency()),
+            benchmark
+                .value_variance()
+                .map(|v| format!("{:.2}", v))
+                .unwrap_or_else(|| "-".to_string()),
+        );
+    }
+    Ok(())
+}
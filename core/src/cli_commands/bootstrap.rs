@@ -1,5 +1,5 @@
 //! Bootstrap a new oracle pool
-use std::{convert::TryInto, io::Write, path::Path};
+use std::{cell::RefCell, convert::TryInto, io::Write, path::Path};
 
 use ergo_lib::{
     chain::{
@@ -63,31 +63,52 @@ use crate::{
 
 /// Loads bootstrap configuration file and performs the chain-transactions for minting of tokens and
 /// box creations. An oracle configuration file is then created which contains the `TokenId`s of the
-/// minted tokens.
-pub fn bootstrap(config_file_name: String) -> Result<(), anyhow::Error> {
+/// minted tokens. With `dry_run` the transactions are built and signed but not submitted, and the
+/// ERG cost is printed instead.
+pub fn bootstrap(config_file_name: String, dry_run: bool) -> Result<(), anyhow::Error> {
     let oracle_config = &ORACLE_CONFIG;
     let s = std::fs::read_to_string(config_file_name)?;
     let config: BootstrapConfig = serde_yaml::from_str(&s)?;
 
     let node_api = NodeApi::new(oracle_config.node_api_key.clone(), &oracle_config.node_url);
-    assert_wallet_unlocked(&node_api.node);
+    assert_wallet_unlocked(&node_api);
     let change_address = node_api.get_change_address()?;
     debug!("Change address: {:?}", change_address);
     let erg_value_per_box = config.oracle_contract_parameters.min_storage_rent;
+    let dry_run_submit_tx = DryRunSubmitTx::default();
     let input = BootstrapInput {
         oracle_address: oracle_config.oracle_address.clone(),
         config,
         wallet: &node_api as &dyn WalletDataSource,
         tx_signer: &node_api.node as &dyn SignTransactionWithInputs,
-        submit_tx: &node_api.node as &dyn SubmitTransaction,
+        submit_tx: if dry_run {
+            &dry_run_submit_tx as &dyn SubmitTransaction
+        } else {
+            &node_api.node as &dyn SubmitTransaction
+        },
         tx_fee: *BASE_FEE,
         erg_value_per_box,
         change_address: change_address.address(),
-        height: BlockHeight(node_api.node.current_block_height()? as u32),
+        height: BlockHeight(node_api.current_block_height()? as u32),
     };
+    if dry_run {
+        info!("Dry run: the transactions are built and signed but NOT submitted");
+        let wallet_balance: u64 = node_api
+            .get_unspent_wallet_boxes()?
+            .iter()
+            .map(|b| *b.value.as_u64())
+            .sum();
+        perform_bootstrap_chained_transaction(input)?;
+        print_dry_run_report(
+            &dry_run_submit_tx.transactions.borrow(),
+            erg_value_per_box,
+            *BASE_FEE,
+            wallet_balance,
+        );
+        return Ok(());
+    }
     let (oracle_config, submitted_tx_ids) = perform_bootstrap_chained_transaction(input)?;
-    wait_for_txs_confirmation(submitted_tx_ids);
-    info!("Bootstrap chain-transaction complete");
+    // written before the wait, the txs are submitted even if they aren't confirmed in time
     let s = serde_yaml::to_string(&oracle_config)?;
     let mut file = std::fs::File::create(DEFAULT_POOL_CONFIG_FILE_NAME)?;
     file.write_all(s.as_bytes())?;
@@ -95,9 +116,52 @@ pub fn bootstrap(config_file_name: String) -> Result<(), anyhow::Error> {
         "Pool configuration file created: {}",
         DEFAULT_POOL_CONFIG_FILE_NAME
     );
+    wait_for_txs_confirmation(submitted_tx_ids)?;
+    info!("Bootstrap chain-transaction complete");
     Ok(())
 }
 
+/// Collects the transactions instead of submitting them
+#[derive(Default)]
+struct DryRunSubmitTx {
+    transactions: RefCell<Vec<Transaction>>,
+}
+
+impl SubmitTransaction for DryRunSubmitTx {
+    fn submit_transaction(&self, tx: &Transaction) -> crate::node_interface::Result<TxId> {
+        self.transactions.borrow_mut().push(tx.clone());
+        Ok(tx.id())
+    }
+}
+
+fn print_dry_run_report(
+    transactions: &[Transaction],
+    erg_value_per_box: BoxValue,
+    tx_fee: BoxValue,
+    wallet_balance: u64,
+) {
+    let num_txs = transactions.len() as u64;
+    let fees = num_txs * tx_fee.as_u64();
+    let box_values = num_txs * erg_value_per_box.as_u64();
+    println!(
+        "Bootstrap dry run: {} transactions built and signed, none submitted",
+        num_txs
+    );
+    for tx in transactions {
+        println!("  tx {}", String::from(tx.id()));
+    }
+    println!("Fees: {} nanoERG", fees);
+    println!(
+        "Value of the created token, pool and refresh boxes: {} nanoERG",
+        box_values
+    );
+    println!(
+        "Total cost: {} nanoERG, wallet balance: {} nanoERG",
+        fees + box_values,
+        wallet_balance
+    );
+}
+
 pub fn generate_bootstrap_config_template(config_file_name: String) -> Result<(), BootstrapError> {
     if Path::new(&config_file_name).exists() {
         return Err(BootstrapError::ConfigFilenameAlreadyExists);
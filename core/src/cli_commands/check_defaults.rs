@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use ergo_lib::ergo_chain_types::blake2b256_hash;
+use schemars::schema_for;
+use serde_yaml::Mapping;
+use serde_yaml::Value;
+
+use crate::contracts::ballot::BallotContractParameters;
+use crate::contracts::oracle::OracleContractParameters;
+use crate::contracts::pool::PoolContractParameters;
+use crate::contracts::refresh::RefreshContractParameters;
+use crate::contracts::update::UpdateContractParameters;
+use crate::oracle_config::OracleConfig;
+use crate::pool_config::PoolConfig;
+
+/// Oracle specific fields, their defaults are placeholders
+const SKIPPED_ORACLE_CONFIG_FIELDS: [&str; 2] = ["oracle_address", "node_api_key"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigDrift {
+    /// Field is set to a value other than the default
+    Differs {
+        field: String,
+        value: String,
+        default: String,
+    },
+    /// Field is not set in the config file but has a default
+    NotSet { field: String, default: String },
+    /// Optional field without a default is not set in the config file, with what its doc comment
+    /// says happens then
+    NotSetDocumented { field: String, behaviour: String },
+}
+
+impl Display for ConfigDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigDrift::Differs {
+                field,
+                value,
+                default,
+            } => write!(f, "{}: {} (default: {})", field, value, default),
+            ConfigDrift::NotSet { field, default } => {
+                write!(f, "{}: not set (default: {})", field, default)
+            }
+            ConfigDrift::NotSetDocumented { field, behaviour } => {
+                write!(f, "{}: not set ({})", field, behaviour)
+            }
+        }
+    }
+}
+
+/// Print the oracle and pool config fields that differ from the defaults of this binary
+pub fn check_defaults(
+    oracle_config_str: &str,
+    pool_config: &PoolConfig,
+) -> Result<(), anyhow::Error> {
+    let oracle_config_yaml: Mapping = serde_yaml::from_str(oracle_config_str)?;
+    let oracle_config_drift = oracle_config_drift(&oracle_config_yaml, &OracleConfig::default())?;
+    if oracle_config_drift.is_empty() {
+        println!("Oracle config: all fields are set to their defaults");
+    } else {
+        println!("Oracle config:");
+        for drift in oracle_config_drift {
+            println!("  {}", drift);
+        }
+    }
+    let pool_config_drift = pool_config_drift(pool_config);
+    if pool_config_drift.is_empty() {
+        println!("Pool config: contracts and parameters match the defaults");
+    } else {
+        println!("Pool config (set at bootstrap, can only be changed with a pool update):");
+        for drift in pool_config_drift {
+            println!("  {}", drift);
+        }
+    }
+    Ok(())
+}
+
+fn oracle_config_drift(
+    config_yaml: &Mapping,
+    default_config: &OracleConfig,
+) -> Result<Vec<ConfigDrift>, serde_yaml::Error> {
+    let default_yaml: Mapping = serde_yaml::from_value(serde_yaml::to_value(default_config)?)?;
+    let unset_behaviours = documented_unset_behaviours();
+    let mut drift = Vec::new();
+    for (key, default_value) in default_yaml.iter() {
+        let field = key.as_str().unwrap_or_default().to_string();
+        if SKIPPED_ORACLE_CONFIG_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        match config_yaml.get(key) {
+            None if !default_value.is_null() => drift.push(ConfigDrift::NotSet {
+                field,
+                default: yaml_value_to_string(default_value),
+            }),
+            None => {
+                if let Some(behaviour) = unset_behaviours.get(&field) {
+                    drift.push(ConfigDrift::NotSetDocumented {
+                        behaviour: behaviour.clone(),
+                        field,
+                    })
+                }
+            }
+            Some(value) if value != default_value => drift.push(ConfigDrift::Differs {
+                field,
+                value: yaml_value_to_string(value),
+                default: yaml_value_to_string(default_value),
+            }),
+            Some(_) => (),
+        }
+    }
+    Ok(drift)
+}
+
+/// The "X if not set" of the oracle config field doc comments, by field name
+fn documented_unset_behaviours() -> BTreeMap<String, String> {
+    let schema = serde_json::to_value(schema_for!(OracleConfig)).unwrap_or_default();
+    schema["properties"]
+        .as_object()
+        .map(|properties| {
+            properties
+                .iter()
+                .filter_map(|(field, property)| {
+                    let behaviour = unset_behaviour(property["description"].as_str()?)?;
+                    Some((field.clone(), behaviour))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// What the doc comment says happens when the field is not set, either in parentheses like
+/// "(5 if not set)" or as the last sentence like "Unlimited if not set."
+fn unset_behaviour(description: &str) -> Option<String> {
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    let end = description.find(" if not set")?;
+    let start = description[..end]
+        .rfind('(')
+        .map(|i| i + 1)
+        .max(description[..end].rfind(". ").map(|i| i + 2))
+        .unwrap_or(0);
+    let behaviour = description[start..end].replace('`', "");
+    let mut chars = behaviour.chars();
+    match (chars.next(), chars.next()) {
+        // lowercase the start of a sentence but not an acronym like ISO
+        (Some(first), Some(second)) if first.is_uppercase() && second.is_lowercase() => Some(
+            first
+                .to_lowercase()
+                .chain(behaviour.chars().skip(1))
+                .collect(),
+        ),
+        (Some(_), _) => Some(behaviour),
+        (None, _) => None,
+    }
+}
+
+fn pool_config_drift(pool_config: &PoolConfig) -> Vec<ConfigDrift> {
+    let mut drift = Vec::new();
+    let mut check = |field: &str, value: String, default: String| {
+        if value != default {
+            drift.push(ConfigDrift::Differs {
+                field: field.to_string(),
+                value,
+                default,
+            });
+        }
+    };
+    let encoded_hash = |bytes: &[u8]| base64::encode(blake2b256_hash(bytes));
+    check(
+        "oracle contract hash",
+        encoded_hash(
+            &pool_config
+                .oracle_box_wrapper_inputs
+                .contract_inputs
+                .contract_parameters()
+                .ergo_tree_bytes(),
+        ),
+        encoded_hash(&OracleContractParameters::default().ergo_tree_bytes()),
+    );
+    check(
+        "pool contract hash",
+        encoded_hash(
+            &pool_config
+                .pool_box_wrapper_inputs
+                .contract_inputs
+                .contract_parameters()
+                .ergo_tree_bytes(),
+        ),
+        encoded_hash(&PoolContractParameters::default().ergo_tree_bytes()),
+    );
+    let refresh_params = pool_config
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let default_refresh_params = RefreshContractParameters::default();
+    check(
+        "refresh contract hash",
+        encoded_hash(&refresh_params.ergo_tree_bytes()),
+        encoded_hash(&default_refresh_params.ergo_tree_bytes()),
+    );
+    check(
+        "epoch_length",
+        refresh_params.epoch_length().0.to_string(),
+        default_refresh_params.epoch_length().0.to_string(),
+    );
+    check(
+        "min_data_points",
+        refresh_params.min_data_points().0.to_string(),
+        default_refresh_params.min_data_points().0.to_string(),
+    );
+    check(
+        "max_deviation_percent",
+        refresh_params.max_deviation_percent().to_string(),
+        default_refresh_params.max_deviation_percent().to_string(),
+    );
+    check(
+        "buffer_length",
+        refresh_params.buffer_length().to_string(),
+        default_refresh_params.buffer_length().to_string(),
+    );
+    let update_params = pool_config
+        .update_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let default_update_params = UpdateContractParameters::default();
+    check(
+        "update contract hash",
+        encoded_hash(&update_params.ergo_tree_bytes()),
+        encoded_hash(&default_update_params.ergo_tree_bytes()),
+    );
+    check(
+        "min_votes",
+        update_params.min_votes().to_string(),
+        default_update_params.min_votes().to_string(),
+    );
+    check(
+        "ballot contract hash",
+        encoded_hash(
+            &pool_config
+                .ballot_box_wrapper_inputs
+                .contract_inputs
+                .contract_parameters()
+                .ergo_tree_bytes(),
+        ),
+        encoded_hash(&BallotContractParameters::default().ergo_tree_bytes()),
+    );
+    drift
+}
+
+fn yaml_value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "null".to_string(),
+        Value::Bool(_) | Value::Number(_) | Value::Sequence(_) | Value::Mapping(_) => {
+            serde_yaml::to_string(value)
+                .map(|s| s.trim_end().replace('\n', ", "))
+                .unwrap_or_default()
+        }
+        Value::Tagged(tagged) => yaml_value_to_string(&tagged.value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle_config_drift() {
+        let default_config = OracleConfig::default();
+        let config_yaml: Mapping = serde_yaml::from_str(
+            r#"
+node_url: http://127.0.0.1:9053/
+node_api_key: secret
+base_fee: 1100000
+core_api_port: 9011
+oracle_address: 3WvsT2Gm4EpsM9Pg18PdY6XyhNNMqXDsvJTbbf6ihLvAmSb7u5RN
+explorer_url: null
+"#,
+        )
+        .unwrap();
+        let drift = oracle_config_drift(&config_yaml, &default_config).unwrap();
+        assert!(drift.contains(&ConfigDrift::Differs {
+            field: "core_api_port".to_string(),
+            value: "9011".to_string(),
+            default: "9010".to_string(),
+        }));
+        assert!(drift.contains(&ConfigDrift::NotSet {
+            field: "allow_non_positive_datapoints".to_string(),
+            default: "false".to_string(),
+        }));
+        assert!(drift.contains(&ConfigDrift::NotSet {
+            field: "log_level".to_string(),
+            default: "INFO".to_string(),
+        }));
+        assert!(drift
+            .iter()
+            .any(|d| matches!(d, ConfigDrift::Differs { field, .. } if field == "explorer_url")));
+        // fields with no default are reported with their documented behaviour when not set
+        assert!(drift.contains(&ConfigDrift::NotSetDocumented {
+            field: "submit_retry_attempts".to_string(),
+            behaviour: "0".to_string(),
+        }));
+        assert!(drift.contains(&ConfigDrift::NotSetDocumented {
+            field: "node_max_requests_per_sec".to_string(),
+            behaviour: "unlimited".to_string(),
+        }));
+        // and not reported if it isn't documented
+        assert!(!drift.iter().any(|d| matches!(
+            d,
+            ConfigDrift::NotSet { field, .. } | ConfigDrift::NotSetDocumented { field, .. }
+                if field == "wallet_password_env_var"
+        )));
+        assert!(!drift.iter().any(
+            |d| matches!(d, ConfigDrift::Differs { field, .. } if field == "node_api_key" || field == "node_url" || field == "base_fee")
+        ));
+    }
+
+    #[test]
+    fn test_unset_behaviour() {
+        assert_eq!(
+            unset_behaviour("Idle connections are closed after this many seconds (90 if not set)"),
+            Some("90".to_string())
+        );
+        assert_eq!(
+            unset_behaviour("What to do when a tx can't be built (`continue` if not\nset)"),
+            Some("continue".to_string())
+        );
+        assert_eq!(
+            unset_behaviour("Maximum requests per second. Unlimited if not set."),
+            Some("unlimited".to_string())
+        );
+        assert_eq!(
+            unset_behaviour("Format of the log timestamps (ISO 8601 if not set)"),
+            Some("ISO 8601".to_string())
+        );
+        assert_eq!(unset_behaviour("Name of the environment variable"), None);
+    }
+}
@@ -0,0 +1,176 @@
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisterId;
+use ergo_lib::ergotree_ir::chain::token::TokenId;
+use ergo_lib::ergotree_ir::mir::constant::TryExtractInto;
+
+use crate::contracts::oracle::OracleContract;
+use crate::node_interface::node_api::NodeApi;
+use crate::pool_config::PoolConfig;
+use crate::scans::NodeScanRegistry;
+use crate::scans::ScanGetBoxes;
+use crate::spec_token::TokenIdKind;
+use crate::wallet::WalletDataSource;
+
+/// Tokens of the pool held by the prospective oracle's wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WalletPoolTokens {
+    pub oracle_tokens: u64,
+    pub reward_tokens: u64,
+    pub ballot_tokens: u64,
+}
+
+/// Print the pool's oracles and contract parameters, whether there are oracle tokens left for a
+/// new oracle, the tokens the oracle needs and the remaining steps to join
+pub fn check_join_eligibility(
+    node_scan_registry: &NodeScanRegistry,
+    node_api: &NodeApi,
+    pool_config: &PoolConfig,
+    oracle_pk: &EcPoint,
+) -> Result<(), anyhow::Error> {
+    let token_ids = &pool_config.token_ids;
+    let oracle_token_id = token_ids.oracle_token_id.token_id();
+    let oracle_tree =
+        OracleContract::checked_load(&pool_config.oracle_box_wrapper_inputs.contract_inputs)?
+            .ergo_tree();
+    // the scan also returns other boxes holding oracle tokens, e.g. the operators' wallet boxes
+    let oracle_boxes: Vec<ErgoBox> = node_scan_registry
+        .oracle_token_scan
+        .get_boxes()?
+        .into_iter()
+        .filter(|b| b.ergo_tree == oracle_tree)
+        .collect();
+    let in_oracle_boxes = token_amount(&oracle_boxes, oracle_token_id);
+    let mut oracles: Vec<EcPoint> = Vec::new();
+    for owner in oracle_boxes.iter().filter_map(box_owner) {
+        if !oracles.contains(&owner) {
+            oracles.push(owner);
+        }
+    }
+    let refresh_parameters = pool_config
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    println!("Oracles in the pool: {}", oracles.len());
+    println!(
+        "Refresh: epoch length {} blocks, at least {} datapoints, max deviation {}%",
+        refresh_parameters.epoch_length().0,
+        refresh_parameters.min_data_points().0,
+        refresh_parameters.max_deviation_percent()
+    );
+    println!(
+        "Pool update: at least {} ballot token votes",
+        pool_config
+            .update_box_wrapper_inputs
+            .contract_inputs
+            .contract_parameters()
+            .min_votes()
+    );
+    match node_api.get_token_emission_amount(&oracle_token_id)? {
+        Some(emission) => {
+            let free = emission.saturating_sub(in_oracle_boxes);
+            println!(
+                "Oracle tokens: {} minted, {} in oracle boxes, {} free",
+                emission, in_oracle_boxes, free
+            );
+            if free == 0 {
+                println!(
+                    "The pool is full: every oracle token is used by an oracle. A new oracle can \
+                    only join with a token transferred by a leaving operator \
+                    (`transfer-oracle-token`)"
+                );
+            } else {
+                println!(
+                    "There is room for {} more oracle(s). The free oracle tokens are held by the \
+                    pool operators (usually the one who bootstrapped the pool)",
+                    free
+                );
+            }
+        }
+        None => println!(
+            "Oracle tokens: {} in oracle boxes. The minted amount needs the node's extra indexer \
+            (`extraIndex = true`), ask the pool operators whether oracle tokens are left",
+            in_oracle_boxes
+        ),
+    }
+    println!("Tokens needed:");
+    println!("  1 oracle token {}", String::from(oracle_token_id));
+    println!(
+        "  1 reward token {}",
+        String::from(token_ids.reward_token_id.token_id())
+    );
+    println!(
+        "  1 ballot token {} (only to vote for pool updates)",
+        String::from(token_ids.ballot_token_id.token_id())
+    );
+    let wallet_boxes = node_api.get_unspent_wallet_boxes()?;
+    let wallet = WalletPoolTokens {
+        oracle_tokens: token_amount(&wallet_boxes, oracle_token_id),
+        reward_tokens: token_amount(&wallet_boxes, token_ids.reward_token_id.token_id()),
+        ballot_tokens: token_amount(&wallet_boxes, token_ids.ballot_token_id.token_id()),
+    };
+    let steps = join_steps(oracles.contains(oracle_pk), &wallet);
+    if steps.is_empty() {
+        println!("This oracle is already in the pool");
+    } else {
+        println!("Steps to join:");
+        for (i, step) in steps.iter().enumerate() {
+            println!("  {}. {}", i + 1, step);
+        }
+    }
+    Ok(())
+}
+
+fn box_owner(ergo_box: &ErgoBox) -> Option<EcPoint> {
+    ergo_box
+        .get_register(NonMandatoryRegisterId::R4.into())?
+        .try_extract_into::<EcPoint>()
+        .ok()
+}
+
+fn token_amount(boxes: &[ErgoBox], token_id: TokenId) -> u64 {
+    boxes
+        .iter()
+        .flat_map(|b| b.tokens.iter().flat_map(|tokens| tokens.iter()))
+        .filter(|t| t.token_id == token_id)
+        .map(|t| *t.amount.as_u64())
+        .sum()
+}
+
+/// What the oracle still has to do to join, nothing if it already has an oracle box
+fn join_steps(has_oracle_box: bool, wallet: &WalletPoolTokens) -> Vec<&'static str> {
+    if has_oracle_box {
+        return vec![];
+    }
+    let mut steps = vec![];
+    if wallet.oracle_tokens == 0 || wallet.reward_tokens == 0 {
+        steps.push(
+            "get 1 oracle token and 1 reward token sent to `oracle_address` from the pool \
+            operators",
+        );
+    }
+    if wallet.ballot_tokens == 0 {
+        steps.push("optionally get a ballot token too, to vote for pool updates");
+    }
+    steps.push("put the `pool_config.yaml` of the pool next to the oracle config");
+    steps.push("run `oracle-core run`, the first datapoint creates the oracle box");
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_steps() {
+        let no_tokens = WalletPoolTokens::default();
+        assert_eq!(join_steps(false, &no_tokens).len(), 4);
+        let tokens = WalletPoolTokens {
+            oracle_tokens: 1,
+            reward_tokens: 1,
+            ballot_tokens: 1,
+        };
+        assert_eq!(join_steps(false, &tokens).len(), 2);
+        assert!(join_steps(true, &no_tokens).is_empty());
+    }
+}
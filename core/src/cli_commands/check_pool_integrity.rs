@@ -0,0 +1,240 @@
+use anyhow::anyhow;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::token::TokenId;
+use ergo_lib::ergotree_ir::mir::constant::Constant;
+use ergo_lib::ergotree_ir::mir::constant::TryExtractInto;
+
+use crate::pool_config::PoolConfig;
+use crate::scans::NodeScanRegistry;
+use crate::scans::ScanGetBoxes;
+use crate::spec_token::TokenIdKind;
+
+/// Check that the pool box and the refresh box on-chain reference each other and match the pool
+/// config, and print every mismatch found
+pub fn check_pool_integrity(
+    node_scan_registry: &NodeScanRegistry,
+    pool_config: &PoolConfig,
+) -> Result<(), anyhow::Error> {
+    let pool_box = node_scan_registry
+        .pool_token_scan
+        .get_box()?
+        .ok_or_else(|| anyhow!("pool box not found"))?;
+    let refresh_box = node_scan_registry
+        .refresh_token_scan
+        .get_box()?
+        .ok_or_else(|| anyhow!("refresh box not found"))?;
+    println!(
+        "Pool box {}, refresh box {}",
+        String::from(pool_box.box_id()),
+        String::from(refresh_box.box_id())
+    );
+    let issues = integrity_issues(&pool_box, &refresh_box, pool_config);
+    if issues.is_empty() {
+        println!("Pool box and refresh box are consistent with each other and the pool config");
+    } else {
+        println!("Found {} mismatch(es):", issues.len());
+        for issue in issues {
+            println!("  {}", issue);
+        }
+    }
+    Ok(())
+}
+
+fn integrity_issues(
+    pool_box: &ErgoBox,
+    refresh_box: &ErgoBox,
+    pool_config: &PoolConfig,
+) -> Vec<String> {
+    let mut issues = Vec::new();
+    let token_ids = &pool_config.token_ids;
+    let mut check = |what: &str, actual: Option<String>, expected: String| match actual {
+        Some(actual) if actual == expected => (),
+        Some(actual) => issues.push(format!("{}: {} (expected {})", what, actual, expected)),
+        None => issues.push(format!("{}: not found (expected {})", what, expected)),
+    };
+    let token_at = |b: &ErgoBox, index: usize| {
+        b.tokens
+            .as_ref()
+            .and_then(|tokens| tokens.get(index).map(|t| String::from(t.token_id)))
+    };
+    let tree_constant = |b: &ErgoBox, index: usize| b.ergo_tree.get_constant(index).ok().flatten();
+    let pool_box_pool_nft = token_at(pool_box, 0);
+    let refresh_box_refresh_nft = token_at(refresh_box, 0);
+
+    check(
+        "pool box pool NFT (tokens(0))",
+        pool_box_pool_nft.clone(),
+        String::from(token_ids.pool_nft_token_id.token_id()),
+    );
+    check(
+        "pool box reward token (tokens(1))",
+        token_at(pool_box, 1),
+        String::from(token_ids.reward_token_id.token_id()),
+    );
+    check(
+        "refresh box refresh NFT (tokens(0))",
+        refresh_box_refresh_nft.clone(),
+        String::from(token_ids.refresh_nft_token_id.token_id()),
+    );
+
+    let pool_params = pool_config
+        .pool_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let pool_contract_refresh_nft =
+        extract_token_id(tree_constant(pool_box, pool_params.refresh_nft_index()));
+    check(
+        "pool contract refresh NFT vs the refresh box",
+        pool_contract_refresh_nft.clone(),
+        refresh_box_refresh_nft.unwrap_or_default(),
+    );
+    check(
+        "pool contract refresh NFT vs the pool config",
+        pool_contract_refresh_nft,
+        String::from(token_ids.refresh_nft_token_id.token_id()),
+    );
+    check(
+        "pool contract update NFT",
+        extract_token_id(tree_constant(pool_box, pool_params.update_nft_index())),
+        String::from(token_ids.update_nft_token_id.token_id()),
+    );
+
+    let refresh_params = pool_config
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    check(
+        "refresh contract pool NFT vs the pool box",
+        extract_token_id(tree_constant(refresh_box, refresh_params.pool_nft_index())),
+        pool_box_pool_nft.unwrap_or_default(),
+    );
+    check(
+        "refresh contract oracle token",
+        extract_token_id(tree_constant(
+            refresh_box,
+            refresh_params.oracle_token_id_index(),
+        )),
+        String::from(token_ids.oracle_token_id.token_id()),
+    );
+    check(
+        "refresh contract epoch length",
+        extract_i32(tree_constant(
+            refresh_box,
+            refresh_params.epoch_length_index(),
+        )),
+        refresh_params.epoch_length().0.to_string(),
+    );
+    check(
+        "refresh contract min data points",
+        extract_i32(tree_constant(
+            refresh_box,
+            refresh_params.min_data_points_index(),
+        )),
+        refresh_params.min_data_points().0.to_string(),
+    );
+    check(
+        "refresh contract max deviation percent",
+        extract_i32(tree_constant(
+            refresh_box,
+            refresh_params.max_deviation_percent_index(),
+        )),
+        refresh_params.max_deviation_percent().to_string(),
+    );
+    check(
+        "refresh contract buffer length",
+        extract_i32(tree_constant(
+            refresh_box,
+            refresh_params.buffer_length_index(),
+        )),
+        refresh_params.buffer_length().to_string(),
+    );
+    issues
+}
+
+fn extract_token_id(constant: Option<Constant>) -> Option<String> {
+    constant
+        .and_then(|c| c.try_extract_into::<TokenId>().ok())
+        .map(String::from)
+}
+
+fn extract_i32(constant: Option<Constant>) -> Option<String> {
+    constant
+        .and_then(|c| c.try_extract_into::<i32>().ok())
+        .map(|v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use ergo_lib::chain::transaction::TxId;
+    use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+    use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisters;
+    use ergo_lib::ergotree_ir::chain::token::Token;
+    use sigma_test_util::force_any_val;
+
+    use super::*;
+    use crate::box_kind::PoolBox;
+    use crate::cli_commands::bootstrap::BootstrapConfig;
+    use crate::contracts::pool::PoolContractParameters;
+    use crate::contracts::refresh::RefreshContract;
+    use crate::oracle_types::BlockHeight;
+    use crate::oracle_types::EpochCounter;
+    use crate::pool_commands::test_utils::generate_token_ids;
+    use crate::pool_commands::test_utils::make_pool_box;
+
+    fn make_refresh_box(pool_config: &PoolConfig) -> ErgoBox {
+        let inputs = &pool_config.refresh_box_wrapper_inputs;
+        let tokens = vec![Token::from((
+            inputs.refresh_nft_token_id.token_id(),
+            1u64.try_into().unwrap(),
+        ))]
+        .try_into()
+        .unwrap();
+        ErgoBox::new(
+            BoxValue::SAFE_USER_MIN,
+            RefreshContract::checked_load(&inputs.contract_inputs)
+                .unwrap()
+                .ergo_tree(),
+            Some(tokens),
+            NonMandatoryRegisters::empty(),
+            100,
+            force_any_val::<TxId>(),
+            0,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_integrity_issues() {
+        let token_ids = generate_token_ids();
+        let pool_config =
+            PoolConfig::create(BootstrapConfig::default(), token_ids.clone()).unwrap();
+        let pool_box = make_pool_box(
+            1,
+            EpochCounter(1),
+            BoxValue::SAFE_USER_MIN,
+            BlockHeight(100),
+            &PoolContractParameters::default(),
+            &token_ids,
+        );
+        let refresh_box = make_refresh_box(&pool_config);
+        assert!(integrity_issues(pool_box.get_box(), &refresh_box, &pool_config).is_empty());
+
+        // refresh box of another pool
+        let other_pool_config =
+            PoolConfig::create(BootstrapConfig::default(), generate_token_ids()).unwrap();
+        let other_refresh_box = make_refresh_box(&other_pool_config);
+        let issues = integrity_issues(pool_box.get_box(), &other_refresh_box, &pool_config);
+        assert!(issues
+            .iter()
+            .any(|i| i.starts_with("refresh box refresh NFT (tokens(0))")));
+        assert!(issues
+            .iter()
+            .any(|i| i.starts_with("pool contract refresh NFT vs the refresh box")));
+        assert!(issues
+            .iter()
+            .any(|i| i.starts_with("refresh contract pool NFT vs the pool box")));
+        assert!(!issues.iter().any(|i| i.contains("epoch length")));
+    }
+}
@@ -0,0 +1,160 @@
+use ergo_lib::ergo_chain_types::Digest32;
+
+use crate::box_kind::pool_box_hash;
+use crate::box_kind::BallotBox;
+use crate::box_kind::PoolBox;
+use crate::contracts::pool::PoolContract;
+use crate::oracle_state::OraclePool;
+use crate::pool_config::POOL_CONFIG;
+
+/// Half-finished pool update found by `check-stuck-update`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StuckUpdate {
+    /// Enough votes for a new pool box, but the pool box wasn't moved to it
+    VotesPassedPoolNotUpdated { pool_box_hash: Digest32, votes: u64 },
+    /// The pool box on-chain is not the one of the local pool config, i.e. the pool was updated
+    /// but the local pool config wasn't
+    PoolUpdatedConfigNot { on_chain_pool_box_hash: Digest32 },
+}
+
+impl StuckUpdate {
+    fn next_step(&self) -> &'static str {
+        match self {
+            StuckUpdate::VotesPassedPoolNotUpdated { .. } => {
+                "Run `update-pool` with the updated pool config to move the pool box to the new \
+                contract, or cast new votes if the update was abandoned"
+            }
+            StuckUpdate::PoolUpdatedConfigNot { .. } => {
+                "Get the updated pool config from the pool operator and load it with \
+                `import-pool-update`"
+            }
+        }
+    }
+}
+
+/// Report a pool update left half-way: the votes passed but the pool box wasn't updated, or the
+/// pool box was updated but the local pool config wasn't
+pub fn check_stuck_update(op: &OraclePool) -> Result<(), anyhow::Error> {
+    let local_pool_box_hash = pool_box_hash(
+        &PoolContract::checked_load(&POOL_CONFIG.pool_box_wrapper_inputs.contract_inputs)?
+            .ergo_tree(),
+    )?;
+    let on_chain_pool_box_hash =
+        pool_box_hash(&op.get_pool_box_source().get_pool_box()?.get_box().ergo_tree)?;
+    let update_box = op.get_update_box_source().get_update_box()?;
+    let update_box_creation_height = update_box.get_box().creation_height as i32;
+    let mut votes: Vec<(Digest32, u64)> = Vec::new();
+    for ballot_box in op.get_ballot_boxes_source().get_ballot_boxes()? {
+        let vote = ballot_box.vote_parameters();
+        if vote.update_box_creation_height != update_box_creation_height {
+            continue;
+        }
+        let amount = *ballot_box.ballot_token().amount.as_u64();
+        match votes
+            .iter_mut()
+            .find(|(hash, _)| *hash == vote.pool_box_address_hash)
+        {
+            Some((_, votes)) => *votes += amount,
+            None => votes.push((vote.pool_box_address_hash, amount)),
+        }
+    }
+    let found = find_stuck_updates(
+        &local_pool_box_hash,
+        &on_chain_pool_box_hash,
+        &votes,
+        update_box.min_votes() as u64,
+    );
+    if found.is_empty() {
+        println!("No stuck pool update found");
+        for (pool_box_hash, votes) in votes {
+            println!(
+                "Update in progress: {} of {} votes for pool box hash {}",
+                votes,
+                update_box.min_votes(),
+                String::from(pool_box_hash)
+            );
+        }
+    }
+    for stuck in found {
+        match &stuck {
+            StuckUpdate::VotesPassedPoolNotUpdated {
+                pool_box_hash,
+                votes,
+            } => println!(
+                "Stuck update: {} votes (of {} required) for pool box hash {}, but the pool box \
+                is still {}",
+                votes,
+                update_box.min_votes(),
+                String::from(*pool_box_hash),
+                String::from(on_chain_pool_box_hash)
+            ),
+            StuckUpdate::PoolUpdatedConfigNot {
+                on_chain_pool_box_hash,
+            } => println!(
+                "Stuck update: the pool box on-chain has hash {}, the local pool config {}",
+                String::from(*on_chain_pool_box_hash),
+                String::from(local_pool_box_hash)
+            ),
+        }
+        println!("  Next step: {}", stuck.next_step());
+    }
+    Ok(())
+}
+
+/// `votes` are the ballot tokens voting for each pool box hash for the current update box
+fn find_stuck_updates(
+    local_pool_box_hash: &Digest32,
+    on_chain_pool_box_hash: &Digest32,
+    votes: &[(Digest32, u64)],
+    min_votes: u64,
+) -> Vec<StuckUpdate> {
+    let mut found: Vec<StuckUpdate> = votes
+        .iter()
+        .filter(|(pool_box_hash, votes)| {
+            **votes >= min_votes && *pool_box_hash != on_chain_pool_box_hash
+        })
+        .map(
+            |(pool_box_hash, votes)| StuckUpdate::VotesPassedPoolNotUpdated {
+                pool_box_hash: *pool_box_hash,
+                votes: *votes,
+            },
+        )
+        .collect();
+    if local_pool_box_hash != on_chain_pool_box_hash {
+        found.push(StuckUpdate::PoolUpdatedConfigNot {
+            on_chain_pool_box_hash: *on_chain_pool_box_hash,
+        });
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergo_chain_types::blake2b256_hash;
+
+    use super::*;
+
+    #[test]
+    fn test_find_stuck_updates() {
+        let current = blake2b256_hash(b"current pool contract");
+        let new = blake2b256_hash(b"new pool contract");
+        let other = blake2b256_hash(b"other pool contract");
+        let votes = vec![(new, 3), (other, 1)];
+        assert_eq!(
+            find_stuck_updates(&current, &current, &votes, 3),
+            vec![StuckUpdate::VotesPassedPoolNotUpdated {
+                pool_box_hash: new,
+                votes: 3
+            }]
+        );
+        // in progress
+        assert_eq!(find_stuck_updates(&current, &current, &votes, 4), vec![]);
+        // the pool box was updated, the local config wasn't
+        assert_eq!(
+            find_stuck_updates(&current, &new, &votes, 3),
+            vec![StuckUpdate::PoolUpdatedConfigNot {
+                on_chain_pool_box_hash: new
+            }]
+        );
+    }
+}
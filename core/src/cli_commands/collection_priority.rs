@@ -0,0 +1,160 @@
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+
+use crate::box_kind::OracleBox;
+use crate::box_kind::PoolBox;
+use crate::oracle_state::OraclePool;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::Rate;
+use crate::pool_commands::refresh::filtered_oracle_boxes_by_rate;
+use crate::pool_config::POOL_CONFIG;
+
+/// Oracle box in the order the refresh tx spends them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionEntry {
+    pub public_key: EcPoint,
+    pub rate: Rate,
+    /// Within `max_deviation_percent` of the others, i.e. collected by the next refresh
+    pub valid: bool,
+}
+
+/// Print the datapoints posted for the next refresh in the order the refresh tx spends them,
+/// the position of this oracle and its chance of being the one submitting the refresh. There
+/// is no fixed priority in the contracts: any oracle with a valid datapoint may submit it once
+/// the epoch ends and the first tx to be mined wins.
+pub fn collection_priority(
+    op: &OraclePool,
+    my_public_key: &EcPoint,
+    network_prefix: NetworkPrefix,
+    height: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    let refresh_params = POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let pool_box = op.get_pool_box_source().get_pool_box()?;
+    let datapoints: Vec<(EcPoint, Rate)> = op
+        .get_posted_datapoint_boxes_source()
+        .get_posted_datapoint_boxes()?
+        .iter()
+        .filter(|b| b.epoch_counter() == pool_box.epoch_counter())
+        .map(|b| (b.public_key(), b.rate()))
+        .collect();
+    let entries = collection_order(datapoints, refresh_params.max_deviation_percent() as u32);
+    let epoch_end = pool_box.get_box().creation_height + refresh_params.epoch_length().0 as u32;
+    if height.0 < epoch_end {
+        println!(
+            "Epoch {} ends at height {} ({} blocks left)",
+            pool_box.epoch_counter().0,
+            epoch_end,
+            epoch_end - height.0
+        );
+    } else {
+        println!(
+            "Epoch {} ended at height {}, a refresh can be submitted",
+            pool_box.epoch_counter().0,
+            epoch_end
+        );
+    }
+    if entries.is_empty() {
+        println!("No datapoints posted for the next refresh yet");
+        return Ok(());
+    }
+    println!("Datapoints in the refresh order (by rate):");
+    for (i, entry) in entries.iter().enumerate() {
+        println!(
+            "  {}. {} {}{}{}",
+            i + 1,
+            entry.rate,
+            NetworkAddress::new(
+                network_prefix,
+                &Address::P2Pk(entry.public_key.clone().into())
+            )
+            .to_base58(),
+            if entry.valid {
+                ""
+            } else {
+                " (outside max_deviation_percent, not collected)"
+            },
+            if entry.public_key == *my_public_key {
+                " <- this oracle"
+            } else {
+                ""
+            }
+        );
+    }
+    let valid_count = entries.iter().filter(|e| e.valid).count();
+    let min_data_points = refresh_params.min_data_points().0;
+    if (valid_count as i32) < min_data_points {
+        println!(
+            "{} valid datapoints, {} needed (min_data_points) to refresh",
+            valid_count, min_data_points
+        );
+    }
+    match entries.iter().find(|e| e.public_key == *my_public_key) {
+        Some(entry) if entry.valid => println!(
+            "This oracle can submit the refresh, any of the {} oracles with a valid datapoint \
+            can and the first mined tx wins (about 1 in {} chance if they all try)",
+            valid_count, valid_count
+        ),
+        Some(_) => println!(
+            "This oracle's datapoint is outside max_deviation_percent, it can't submit the \
+            refresh and won't be collected"
+        ),
+        None => println!(
+            "This oracle hasn't posted a datapoint for the epoch, it can't submit the refresh"
+        ),
+    }
+    Ok(())
+}
+
+/// The datapoints sorted by rate with the ones `filtered_oracle_boxes_by_rate` keeps marked as
+/// valid
+fn collection_order(
+    mut datapoints: Vec<(EcPoint, Rate)>,
+    max_deviation_percent: u32,
+) -> Vec<CollectionEntry> {
+    datapoints.sort_by_key(|(_, rate)| *rate);
+    let valid_rates = filtered_oracle_boxes_by_rate(
+        datapoints.iter().map(|(_, rate)| *rate).collect(),
+        max_deviation_percent,
+    )
+    .unwrap_or_default();
+    datapoints
+        .into_iter()
+        .map(|(public_key, rate)| CollectionEntry {
+            public_key,
+            rate,
+            valid: valid_rates.contains(&rate),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use sigma_test_util::force_any_val;
+
+    use super::*;
+
+    #[test]
+    fn test_collection_order() {
+        let pks: Vec<EcPoint> = (0..4)
+            .map(|_| *force_any_val::<DlogProverInput>().public_image().h)
+            .collect();
+        let datapoints = vec![
+            (pks[0].clone(), Rate::from(102)),
+            (pks[1].clone(), Rate::from(100)),
+            (pks[2].clone(), Rate::from(150)),
+            (pks[3].clone(), Rate::from(101)),
+        ];
+        let entries = collection_order(datapoints, 5);
+        let rates: Vec<i64> = entries.iter().map(|e| e.rate.into()).collect();
+        assert_eq!(rates, vec![100, 101, 102, 150]);
+        let valid: Vec<bool> = entries.iter().map(|e| e.valid).collect();
+        assert_eq!(valid, vec![true, true, true, false]);
+        assert_eq!(entries[0].public_key, pks[1]);
+    }
+}
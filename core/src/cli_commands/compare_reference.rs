@@ -0,0 +1,42 @@
+use anyhow::anyhow;
+
+use crate::box_kind::OracleBoxWrapper;
+use crate::box_kind::PoolBox;
+use crate::datapoint_source::reference_feed::deviation_percent;
+use crate::datapoint_source::reference_feed::fetch_reference;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_state::OraclePool;
+
+/// Compare the datapoint of the local oracle box and the pool box rate against the
+/// `reference_feed` of the oracle config
+pub fn compare_reference(op: &OraclePool) -> Result<(), anyhow::Error> {
+    let feed = ORACLE_CONFIG
+        .reference_feed
+        .as_ref()
+        .ok_or_else(|| anyhow!("reference_feed is not set in the oracle config"))?;
+    let reference = fetch_reference(feed)?;
+    println!("Reference feed value: {:.0}", reference);
+    match op
+        .get_local_datapoint_box_source()
+        .get_local_oracle_datapoint_box()?
+    {
+        Some(OracleBoxWrapper::Posted(posted_box)) => {
+            println!(
+                "Local oracle datapoint: {}, deviation: {:.2}%",
+                posted_box.rate(),
+                deviation_percent(posted_box.rate(), reference)
+            );
+        }
+        Some(OracleBoxWrapper::Collected(_)) => {
+            println!("Local oracle datapoint was collected by the last refresh")
+        }
+        None => println!("Local oracle box not found"),
+    }
+    let pool_rate = op.get_pool_box_source().get_pool_box()?.rate();
+    println!(
+        "Pool box rate: {}, deviation: {:.2}%",
+        pool_rate,
+        deviation_percent(pool_rate.into(), reference)
+    );
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use serde::Serialize;
+
+use crate::box_kind::PoolBox;
+use crate::cli_commands::refresh_tx::refresh_tx_inputs;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_state::OraclePool;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::Rate;
+use crate::oracle_types::BLOCK_TIME_MINUTES;
+use crate::output::write_json_result;
+use crate::pool_config::POOL_CONFIG;
+use crate::value_format::describe_rate;
+use crate::value_format::DEFAULT_DISPLAY_DECIMALS;
+
+/// The pool value with the signals of how much to trust it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConsensusValueInfo {
+    pub value: Rate,
+    pub value_human: String,
+    pub epoch: u32,
+    pub set_at_height: u32,
+    pub age_blocks: u32,
+    /// Approximate, from the average block interval
+    pub age_minutes: u32,
+    /// Oracles whose datapoints were collected into the pool box, from the refresh tx inputs.
+    /// `None` if the pool box wasn't created by a refresh or the explorer couldn't be reached.
+    pub contributing_oracles: Option<usize>,
+    pub min_data_points: i32,
+    /// Relative difference in percent of the lowest and the highest collected datapoint from the
+    /// value
+    pub min_deviation_percent: Option<f64>,
+    pub max_deviation_percent: Option<f64>,
+    /// Datapoints posted for the next refresh
+    pub pending_datapoints: usize,
+}
+
+/// Print the pool value with its quality metadata as JSON, or write it to the global `--output`
+pub fn consensus_value(op: &OraclePool, height: BlockHeight) -> Result<(), anyhow::Error> {
+    let info = serde_json::to_string_pretty(&consensus_value_info(op, height)?)?;
+    write_json_result("Consensus value", &info)?;
+    Ok(())
+}
+
+pub fn consensus_value_info(
+    op: &OraclePool,
+    height: BlockHeight,
+) -> Result<ConsensusValueInfo, anyhow::Error> {
+    let pool_box = op.get_pool_box_source().get_pool_box()?;
+    let pool_box_height = pool_box.get_box().creation_height;
+    let collected_rates: Option<Vec<Rate>> =
+        match refresh_tx_inputs(pool_box.get_box(), &POOL_CONFIG) {
+            Ok(inputs) => inputs.map(|inputs| {
+                inputs
+                    .oracle_boxes
+                    .iter()
+                    .map(|b| b.rate())
+                    .collect::<Vec<Rate>>()
+            }),
+            Err(e) => {
+                log::warn!(
+                    "Failed to get the refresh tx of the pool box from the explorer: {}",
+                    e
+                );
+                None
+            }
+        };
+    let pending_datapoints = op
+        .get_posted_datapoint_boxes_source()
+        .get_posted_datapoint_boxes()?
+        .iter()
+        .filter(|b| b.epoch_counter() == pool_box.epoch_counter())
+        .count();
+    let min_data_points = POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters()
+        .min_data_points()
+        .0;
+    let decimals = ORACLE_CONFIG
+        .value_display_decimals
+        .unwrap_or(DEFAULT_DISPLAY_DECIMALS);
+    Ok(ConsensusValueInfo {
+        value_human: describe_rate(
+            pool_box.rate().into(),
+            POOL_CONFIG.data_point_source,
+            decimals,
+        ),
+        ..consensus_info(
+            pool_box.rate().into(),
+            pool_box.epoch_counter().0,
+            BlockHeight(pool_box_height),
+            height,
+            collected_rates.as_deref(),
+            min_data_points,
+            pending_datapoints,
+        )
+    })
+}
+
+fn consensus_info(
+    value: Rate,
+    epoch: u32,
+    set_at_height: BlockHeight,
+    height: BlockHeight,
+    collected_rates: Option<&[Rate]>,
+    min_data_points: i32,
+    pending_datapoints: usize,
+) -> ConsensusValueInfo {
+    let age_blocks = height.0.saturating_sub(set_at_height.0);
+    let deviation = |rate: Rate| {
+        let value = i64::from(value) as f64;
+        (value != 0.0).then(|| (i64::from(rate) as f64 - value) / value.abs() * 100.0)
+    };
+    let rates = collected_rates.unwrap_or_default();
+    ConsensusValueInfo {
+        value,
+        value_human: String::new(),
+        epoch,
+        set_at_height: set_at_height.0,
+        age_blocks,
+        age_minutes: age_blocks * BLOCK_TIME_MINUTES,
+        contributing_oracles: collected_rates.map(|rates| rates.len()),
+        min_data_points,
+        min_deviation_percent: rates.iter().min().and_then(|r| deviation(*r)),
+        max_deviation_percent: rates.iter().max().and_then(|r| deviation(*r)),
+        pending_datapoints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consensus_info() {
+        let info = consensus_info(
+            1000.into(),
+            5,
+            BlockHeight(100),
+            BlockHeight(110),
+            Some(&[990.into(), 1050.into(), 1000.into()]),
+            3,
+            2,
+        );
+        assert_eq!(info.age_blocks, 10);
+        assert_eq!(info.age_minutes, 20);
+        assert_eq!(info.contributing_oracles, Some(3));
+        assert_eq!(info.min_deviation_percent, Some(-1.0));
+        assert_eq!(info.max_deviation_percent, Some(5.0));
+        assert_eq!(info.pending_datapoints, 2);
+
+        let info = consensus_info(
+            1000.into(),
+            5,
+            BlockHeight(100),
+            BlockHeight(100),
+            None,
+            3,
+            0,
+        );
+        assert_eq!(info.contributing_oracles, None);
+        assert_eq!(info.min_deviation_percent, None);
+        assert_eq!(info.max_deviation_percent, None);
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::datapoint_source::RuntimeDataPointSource;
+use crate::http_client::replay_responses;
+use crate::oracle_config::OracleConfig;
+use crate::pool_config::PoolConfig;
+
+/// HTTP response of a source fetch as saved with `debug-source --dump`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedResponse {
+    pub url: String,
+    pub body: String,
+}
+
+/// Fetch once from the named source, or parse the responses saved in `replay` without sending
+/// any request, and print the full responses and the parsed rate or the error. The responses
+/// are saved to `dump` if given.
+pub fn debug_source(
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+    name: &str,
+    dump: Option<&Path>,
+    replay: Option<&Path>,
+) -> Result<(), anyhow::Error> {
+    let datapoint_source = RuntimeDataPointSource::new(
+        pool_config.data_point_source,
+        oracle_config.data_point_source_custom_script.clone(),
+        oracle_config.data_point_source_json.clone(),
+    )?;
+    if let Some(path) = replay {
+        let saved = read_responses(path)?;
+        println!(
+            "Replaying {} saved response(s) from {}",
+            saved.len(),
+            path.display()
+        );
+        replay_responses(saved.into_iter().map(|r| (r.url, r.body)).collect());
+    }
+    let result = datapoint_source.test_source(name)?;
+    for (url, body) in &result.responses {
+        println!("Response from {}:", url);
+        println!("{}", body);
+    }
+    match &result.rate {
+        Ok(rate) => println!("Parsed rate: {}", rate),
+        Err(e) => println!("Fetch failed: {}", e),
+    }
+    if let Some(path) = dump {
+        if result.responses.is_empty() {
+            return Err(anyhow!("{} made no HTTP request, nothing to dump", name));
+        }
+        let saved: Vec<SavedResponse> = result
+            .responses
+            .into_iter()
+            .map(|(url, body)| SavedResponse { url, body })
+            .collect();
+        std::fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        println!(
+            "Responses written to {}, parse them again with `debug-source {} --replay {}`",
+            path.display(),
+            name,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn read_responses(path: &Path) -> Result<Vec<SavedResponse>, anyhow::Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_responses() {
+        let path = std::env::temp_dir().join("oracle_core_test_debug_source.json");
+        let saved = vec![SavedResponse {
+            url: "https://api.coingecko.com/api/v3/simple/price?ids=ergo&vs_currencies=USD"
+                .to_string(),
+            body: "{\"ergo\":{\"usd\":1.5}}".to_string(),
+        }];
+        std::fs::write(&path, serde_json::to_string_pretty(&saved).unwrap()).unwrap();
+        assert_eq!(read_responses(&path).unwrap(), saved);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
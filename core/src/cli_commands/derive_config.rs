@@ -0,0 +1,210 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::cli_commands::bootstrap::BootstrapConfig;
+use crate::oracle_config::OracleConfig;
+use crate::pool_config::PoolConfig;
+use crate::pool_config::PredefinedDataPointSource;
+use crate::pool_config::TokenIds;
+
+/// Parameters the oracle and pool configs are derived from, shared by the team
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigSeed {
+    /// The network of the configs is the network of the address
+    pub oracle_address: NetworkAddress,
+    pub node_url: Url,
+    pub token_ids: TokenIds,
+    /// Bootstrap config file with the contract parameters of the pool, the EIP-23 defaults if
+    /// not set. Relative to the seed file.
+    pub bootstrap_config: Option<PathBuf>,
+    /// Overrides the `data_point_source` of the bootstrap config
+    pub data_point_source: Option<PredefinedDataPointSource>,
+}
+
+/// Write the oracle and pool configs derived from the seed file, refusing to overwrite existing
+/// ones
+pub fn derive_config(
+    seed_path: &Path,
+    oracle_config_path: &Path,
+    pool_config_path: &Path,
+) -> Result<(), anyhow::Error> {
+    for path in [oracle_config_path, pool_config_path] {
+        if path.exists() {
+            return Err(anyhow!(
+                "{} already exists. Please, remove it and run again",
+                path.display()
+            ));
+        }
+    }
+    let (oracle_yaml, pool_yaml) = derived_yaml(&read_seed(seed_path)?, seed_path)?;
+    std::fs::write(oracle_config_path, oracle_yaml)?;
+    std::fs::write(pool_config_path, pool_yaml)?;
+    println!(
+        "Derived {} and {} from {}",
+        oracle_config_path.display(),
+        pool_config_path.display(),
+        seed_path.display()
+    );
+    Ok(())
+}
+
+/// Check that the pool config matches the one derived from the seed file and that the oracle
+/// config has the seed's oracle address and node URL
+pub fn verify_derived_config(
+    seed_path: &Path,
+    oracle_config_path: &Path,
+    pool_config_path: &Path,
+) -> Result<(), anyhow::Error> {
+    let seed = read_seed(seed_path)?;
+    let (oracle_yaml, pool_yaml) = derived_yaml(&seed, seed_path)?;
+    let pool_config = PoolConfig::load_from_str(&std::fs::read_to_string(pool_config_path)?)?;
+    let mut mismatches = yaml_differences(&serde_yaml::to_string(&pool_config)?, &pool_yaml)?
+        .into_iter()
+        .map(|path| format!("{}: {}", pool_config_path.display(), path))
+        .collect::<Vec<String>>();
+    let oracle_config = OracleConfig::load_from_str(&std::fs::read_to_string(oracle_config_path)?)?;
+    if oracle_config.oracle_address.to_base58() != seed.oracle_address.to_base58() {
+        mismatches.push(format!("{}: oracle_address", oracle_config_path.display()));
+    }
+    if oracle_config.node_url != seed.node_url {
+        mismatches.push(format!("{}: node_url", oracle_config_path.display()));
+    }
+    if !mismatches.is_empty() {
+        return Err(anyhow!(
+            "the configs don't match the seed {}, differing fields:\n  {}",
+            seed_path.display(),
+            mismatches.join("\n  ")
+        ));
+    }
+    println!(
+        "{} matches the seed {}",
+        pool_config_path.display(),
+        seed_path.display()
+    );
+    if std::fs::read_to_string(pool_config_path)? != pool_yaml {
+        println!("  (same values, but not byte-identical to the derived file, e.g. reformatted)");
+    }
+    if std::fs::read_to_string(oracle_config_path)? == oracle_yaml {
+        println!(
+            "{} is byte-identical to the derived one",
+            oracle_config_path.display()
+        );
+    } else {
+        println!(
+            "{} has the seed's oracle_address and node_url, its other settings are local",
+            oracle_config_path.display()
+        );
+    }
+    Ok(())
+}
+
+fn read_seed(path: &Path) -> Result<ConfigSeed, anyhow::Error> {
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// The oracle and pool config YAML of the seed, the same bytes for the same seed
+fn derived_yaml(seed: &ConfigSeed, seed_path: &Path) -> Result<(String, String), anyhow::Error> {
+    let mut bootstrap = match &seed.bootstrap_config {
+        Some(path) => {
+            let path = seed_path
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(path);
+            serde_yaml::from_str::<BootstrapConfig>(&std::fs::read_to_string(&path)?)?
+        }
+        None => BootstrapConfig::default(),
+    };
+    if seed.data_point_source.is_some() {
+        bootstrap.data_point_source = seed.data_point_source;
+    }
+    let pool_config = PoolConfig::create(bootstrap, seed.token_ids.clone())?;
+    let oracle_config = OracleConfig {
+        oracle_address: seed.oracle_address.clone(),
+        node_url: seed.node_url.clone(),
+        ..OracleConfig::default()
+    };
+    Ok((
+        serde_yaml::to_string(&oracle_config)?,
+        serde_yaml::to_string(&pool_config)?,
+    ))
+}
+
+/// Dot-separated paths of the values that differ between two YAML documents
+fn yaml_differences(a: &str, b: &str) -> Result<Vec<String>, anyhow::Error> {
+    let a: serde_yaml::Value = serde_yaml::from_str(a)?;
+    let b: serde_yaml::Value = serde_yaml::from_str(b)?;
+    let mut differences = vec![];
+    collect_differences("", &a, &b, &mut differences);
+    Ok(differences)
+}
+
+fn collect_differences(
+    path: &str,
+    a: &serde_yaml::Value,
+    b: &serde_yaml::Value,
+    differences: &mut Vec<String>,
+) {
+    match (a.as_mapping(), b.as_mapping()) {
+        (Some(a_map), Some(b_map)) => {
+            let mut keys: Vec<&serde_yaml::Value> = a_map.keys().collect();
+            keys.extend(b_map.keys().filter(|k| !a_map.contains_key(*k)));
+            for key in keys {
+                let name = key
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("{:?}", key));
+                let child = if path.is_empty() {
+                    name
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                match (a_map.get(key), b_map.get(key)) {
+                    (Some(a), Some(b)) => collect_differences(&child, a, b, differences),
+                    _ => differences.push(child),
+                }
+            }
+        }
+        _ => {
+            if a != b {
+                differences.push(path.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool_commands::test_utils::generate_token_ids;
+
+    #[test]
+    fn test_derived_yaml_is_deterministic() {
+        let seed = ConfigSeed {
+            oracle_address: OracleConfig::default().oracle_address,
+            node_url: Url::parse("http://10.0.0.1:9053").unwrap(),
+            token_ids: generate_token_ids(),
+            bootstrap_config: None,
+            data_point_source: Some(PredefinedDataPointSource::NanoErgXau),
+        };
+        let (oracle_yaml, pool_yaml) = derived_yaml(&seed, Path::new("seed.yaml")).unwrap();
+        assert_eq!(
+            derived_yaml(&seed, Path::new("seed.yaml")).unwrap(),
+            (oracle_yaml.clone(), pool_yaml.clone())
+        );
+        assert!(oracle_yaml.contains("10.0.0.1"));
+        assert!(yaml_differences(&pool_yaml, &pool_yaml).unwrap().is_empty());
+        let other = ConfigSeed {
+            token_ids: generate_token_ids(),
+            ..seed
+        };
+        let (_, other_pool_yaml) = derived_yaml(&other, Path::new("seed.yaml")).unwrap();
+        assert!(yaml_differences(&pool_yaml, &other_pool_yaml)
+            .unwrap()
+            .contains(&"token_ids.pool_nft_token_id".to_string()));
+    }
+}
@@ -0,0 +1,92 @@
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisterId;
+use ergo_lib::ergotree_ir::serialization::SigmaSerializable;
+use ergo_lib::ergotree_ir::serialization::SigmaSerializationError;
+
+use crate::box_kind::OracleBox;
+use crate::box_kind::PoolBox;
+use crate::oracle_state::LocalDatapointBoxSource;
+use crate::oracle_state::PoolBoxSource;
+
+const REGISTER_IDS: [NonMandatoryRegisterId; 6] = [
+    NonMandatoryRegisterId::R4,
+    NonMandatoryRegisterId::R5,
+    NonMandatoryRegisterId::R6,
+    NonMandatoryRegisterId::R7,
+    NonMandatoryRegisterId::R8,
+    NonMandatoryRegisterId::R9,
+];
+
+/// Print R4-R9 of the local oracle box and the pool box as serialized hex and decoded values
+pub fn dump_registers(
+    local_datapoint_box_source: &dyn LocalDatapointBoxSource,
+    pool_box_source: &dyn PoolBoxSource,
+) -> Result<(), anyhow::Error> {
+    match local_datapoint_box_source.get_local_oracle_datapoint_box()? {
+        Some(oracle_box) => print_registers("Local oracle box", oracle_box.get_box())?,
+        None => println!("Local oracle box not found"),
+    }
+    let pool_box = pool_box_source.get_pool_box()?;
+    print_registers("Pool box", pool_box.get_box())?;
+    Ok(())
+}
+
+fn print_registers(name: &str, ergo_box: &ErgoBox) -> Result<(), SigmaSerializationError> {
+    println!(
+        "{} {} (creation height {}):",
+        name,
+        String::from(ergo_box.box_id()),
+        ergo_box.creation_height
+    );
+    for line in register_lines(ergo_box)? {
+        println!("  {}", line);
+    }
+    Ok(())
+}
+
+fn register_lines(ergo_box: &ErgoBox) -> Result<Vec<String>, SigmaSerializationError> {
+    REGISTER_IDS
+        .iter()
+        .map(|id| {
+            Ok(match ergo_box.get_register((*id).into()) {
+                Some(constant) => format!(
+                    "{:?}: {} = {:?}: {:?}",
+                    id,
+                    base16::encode_lower(&constant.sigma_serialize_bytes()?),
+                    constant.v,
+                    constant.tpe
+                ),
+                None => format!("{:?}: empty", id),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+
+    use super::*;
+    use crate::contracts::pool::PoolContractParameters;
+    use crate::oracle_types::BlockHeight;
+    use crate::oracle_types::EpochCounter;
+    use crate::pool_commands::test_utils::generate_token_ids;
+    use crate::pool_commands::test_utils::make_pool_box;
+
+    #[test]
+    fn test_register_lines() {
+        let pool_box = make_pool_box(
+            1,
+            EpochCounter(1),
+            BoxValue::SAFE_USER_MIN,
+            BlockHeight(100),
+            &PoolContractParameters::default(),
+            &generate_token_ids(),
+        );
+        let lines = register_lines(pool_box.get_box()).unwrap();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("R4: 0502 = Long(1)"));
+        assert!(lines[1].starts_with("R5: 0402 = Int(1)"));
+        assert_eq!(lines[5], "R9: empty");
+    }
+}
@@ -0,0 +1,69 @@
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+
+use crate::datapoint_source::RuntimeDataPointSource;
+use crate::oracle_config::OracleConfig;
+use crate::oracle_types::BLOCK_TIME_MINUTES;
+use crate::pool_config::PoolConfig;
+use crate::pool_config::PredefinedDataPointSource;
+use crate::spec_token::TokenIdKind;
+
+pub fn explain_config(
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+) -> Result<(), anyhow::Error> {
+    let datapoint_source = RuntimeDataPointSource::new(
+        pool_config.data_point_source,
+        oracle_config.data_point_source_custom_script.clone(),
+        oracle_config.data_point_source_json.clone(),
+    )?;
+    println!("{}", explain(oracle_config, pool_config, &datapoint_source));
+    Ok(())
+}
+
+fn explain(
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+    datapoint_source: &RuntimeDataPointSource,
+) -> String {
+    let refresh_params = pool_config
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let epoch_length = refresh_params.epoch_length().0;
+    let posted_value = match datapoint_source {
+        RuntimeDataPointSource::Predefined(predef) => predef_datapoint_description(*predef),
+        RuntimeDataPointSource::ExternalScript(_) => "the value from a custom script",
+        RuntimeDataPointSource::Json(_) => "the value read from a JSON API",
+    };
+    let network = match oracle_config.oracle_address.network() {
+        NetworkPrefix::Mainnet => "mainnet",
+        NetworkPrefix::Testnet => "testnet",
+    };
+    format!(
+        "This oracle posts {} to pool {}, participates with address {} on {}, \
+        with epoch length {} blocks (~{:.1} hours), fetching from sources [{}].\n\
+        A refresh needs at least {} datapoints within {}% of each other. \
+        Reward token {} is paid for each accepted datapoint.\n\
+        The node at {} is used for scanning and signing, the REST API listens on port {}.",
+        posted_value,
+        String::from(pool_config.token_ids.pool_nft_token_id.token_id()),
+        oracle_config.oracle_address.to_base58(),
+        network,
+        epoch_length,
+        epoch_length as f64 * BLOCK_TIME_MINUTES as f64 / 60.0,
+        datapoint_source.source_names().join(", "),
+        refresh_params.min_data_points().0,
+        refresh_params.max_deviation_percent(),
+        String::from(pool_config.token_ids.reward_token_id.token_id()),
+        oracle_config.node_url,
+        oracle_config.core_api_port,
+    )
+}
+
+fn predef_datapoint_description(predef: PredefinedDataPointSource) -> &'static str {
+    match predef {
+        PredefinedDataPointSource::NanoErgUsd => "ERG/USD (nanoERG per 1 USD)",
+        PredefinedDataPointSource::NanoErgXau => "ERG/XAU (nanoERG per 1 kg of gold)",
+        PredefinedDataPointSource::NanoAdaUsd => "ADA/USD (lovelace per 1 USD)",
+    }
+}
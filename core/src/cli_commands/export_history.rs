@@ -0,0 +1,36 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::epoch_history::write_csv;
+use crate::epoch_history::EpochHistoryError;
+use crate::output::output_path;
+use crate::output::write_atomically;
+
+/// Stream the recorded epoch history as CSV to stdout, or write it to the global `--output`
+pub fn export_history_csv(history_file_path: &Path) -> Result<(), EpochHistoryError> {
+    match output_path() {
+        Some(path) => {
+            let mut csv = Vec::new();
+            write_history_csv(history_file_path, &mut csv)?;
+            write_atomically(path, &csv)?;
+            println!("Epoch history written to {}", path.display());
+            Ok(())
+        }
+        None => write_history_csv(history_file_path, std::io::stdout().lock()),
+    }
+}
+
+fn write_history_csv(
+    history_file_path: &Path,
+    writer: impl std::io::Write,
+) -> Result<(), EpochHistoryError> {
+    if !history_file_path.exists() {
+        log::warn!(
+            "No epoch history found at {}, it's recorded while running the oracle with `run`",
+            history_file_path.display()
+        );
+        return write_csv(std::io::empty(), writer);
+    }
+    write_csv(BufReader::new(File::open(history_file_path)?), writer)
+}
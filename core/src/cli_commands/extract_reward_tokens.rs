@@ -27,9 +27,14 @@ use crate::{
     },
     explorer_api::ergo_explorer_transaction_link,
     node_interface::{SignTransaction, SubmitTransaction},
-    oracle_config::BASE_FEE,
+    oracle_config::{action_fee, FeeKind, BASE_FEE},
     oracle_state::{DataSourceError, LocalDatapointBoxSource},
     oracle_types::BlockHeight,
+    output::{output_path, write_json_result},
+    reward_extraction_history::{
+        append_record, get_reward_extraction_history_file_path, RewardExtractionRecord,
+        RewardTransfer,
+    },
     spec_token::SpecToken,
     wallet::{WalletDataError, WalletDataSource},
 };
@@ -62,6 +67,40 @@ pub enum ExtractRewardTokensActionError {
     Io(#[from] std::io::Error),
     #[error("WalletData error: {0}")]
     WalletData(#[from] WalletDataError),
+    #[error("Split amounts add up to {sum}, but {available} reward tokens can be extracted")]
+    SplitAmountsMismatch { sum: u64, available: u64 },
+    #[error("Split amount for {0} is zero")]
+    ZeroSplitAmount(String),
+}
+
+/// Where the extracted reward tokens go
+#[derive(Debug, Clone)]
+pub enum RewardsDestination {
+    /// All of them to the base58 encoded address
+    All(String),
+    /// Split among the recipients, the amounts adding up to all the extractable tokens
+    Split(Vec<RewardSplit>),
+}
+
+/// `ADDRESS:AMOUNT` of `extract-reward-tokens --split`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardSplit {
+    pub address: String,
+    pub amount: u64,
+}
+
+/// clap value parser of `ADDRESS:AMOUNT`
+pub fn parse_reward_split(s: &str) -> Result<RewardSplit, String> {
+    let (address, amount) = s
+        .rsplit_once(':')
+        .ok_or_else(|| format!("expected ADDRESS:AMOUNT, got {}", s))?;
+    let amount = amount
+        .parse()
+        .map_err(|_| format!("invalid amount {}", amount))?;
+    Ok(RewardSplit {
+        address: address.to_string(),
+        amount,
+    })
 }
 
 pub fn extract_reward_tokens(
@@ -69,47 +108,127 @@ pub fn extract_reward_tokens(
     tx_signer: &dyn SignTransaction,
     tx_submit: &dyn SubmitTransaction,
     local_datapoint_box_source: &dyn LocalDatapointBoxSource,
-    rewards_destination_str: String,
+    rewards_destination: RewardsDestination,
     height: BlockHeight,
+    preview: bool,
 ) -> Result<(), anyhow::Error> {
-    let rewards_destination =
-        AddressEncoder::unchecked_parse_network_address_from_str(&rewards_destination_str)?;
-    let network_prefix = rewards_destination.network();
     let change_address = wallet
         .get_change_address()
         .map_err(ExtractRewardTokensActionError::WalletData)?;
+    let network_prefix = change_address.network();
     let (unsigned_tx, num_reward_tokens) = build_extract_reward_tokens_tx(
         local_datapoint_box_source,
         wallet,
-        rewards_destination.address(),
+        &rewards_destination,
         height,
         change_address.address(),
     )?;
+    let transfers = match &rewards_destination {
+        RewardsDestination::All(address) => {
+            format!("{} REWARD TOKENS TO {}", num_reward_tokens, address)
+        }
+        RewardsDestination::Split(splits) => splits
+            .iter()
+            .map(|split| format!("{} REWARD TOKENS TO {}", split.amount, split.address))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    if preview {
+        print_extract_reward_tokens_preview(&unsigned_tx, &transfers)?;
+        return Ok(());
+    }
 
     println!(
-        "YOU WILL BE TRANSFERRING {} REWARD TOKENS TO {}. TYPE 'YES' TO INITIATE THE TRANSACTION.",
-        num_reward_tokens, rewards_destination_str
+        "YOU WILL BE TRANSFERRING {}. TYPE 'YES' TO INITIATE THE TRANSACTION.",
+        transfers
     );
     let mut input = String::new();
     std::io::stdin().read_line(&mut input)?;
     if input.trim() == "YES" {
         let signed_tx = tx_signer.sign_transaction(&unsigned_tx)?;
         let tx_id = tx_submit.submit_transaction(&signed_tx)?;
-        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id());
+        let record = RewardExtractionRecord {
+            tx_id: tx_id.to_string(),
+            height,
+            reward_token_id: reward_token_id(&unsigned_tx).unwrap_or_default(),
+            transfers: reward_transfers(&rewards_destination, num_reward_tokens),
+        };
+        if let Err(e) = append_record(&get_reward_extraction_history_file_path(), &record) {
+            log::warn!("Failed to record the reward tokens extraction: {}", e);
+        }
         println!(
-            "Transaction made. Check status here: {}",
+            "Transaction submitted. Check status here: {}",
             ergo_explorer_transaction_link(tx_id, network_prefix)
         );
+        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id())?;
+        println!("Transaction confirmed");
     } else {
         println!("Aborting the transaction.")
     }
     Ok(())
 }
 
+/// The unsigned tx JSON is written to the global `--output` if given
+fn print_extract_reward_tokens_preview(
+    unsigned_tx: &UnsignedTransaction,
+    transfers: &str,
+) -> Result<(), std::io::Error> {
+    println!("Reward tokens extraction preview (NOT submitted)");
+    println!(
+        "Reward token id: {}",
+        reward_token_id(unsigned_tx).unwrap_or_else(|| "unknown".to_string())
+    );
+    println!(
+        "Transferring {}, 1 stays in the oracle box",
+        transfers.to_lowercase()
+    );
+    println!(
+        "Fee: {} nanoERG",
+        action_fee(FeeKind::ExtractRewardTokens).as_u64()
+    );
+    if output_path().is_none() {
+        println!("Unsigned tx:");
+    }
+    write_json_result(
+        "Unsigned tx",
+        &serde_json::to_string_pretty(unsigned_tx).unwrap(),
+    )
+}
+
+fn reward_token_id(unsigned_tx: &UnsignedTransaction) -> Option<String> {
+    // outputs are built in order: oracle box, reward boxes
+    unsigned_tx.output_candidates.get(1).and_then(|b| {
+        b.tokens
+            .as_ref()
+            .map(|tokens| String::from(tokens.first().token_id))
+    })
+}
+
+/// Amounts sent to each address, `extracted` being all the extracted reward tokens
+fn reward_transfers(
+    rewards_destination: &RewardsDestination,
+    extracted: u64,
+) -> Vec<RewardTransfer> {
+    match rewards_destination {
+        RewardsDestination::All(address) => vec![RewardTransfer {
+            address: address.clone(),
+            amount: extracted,
+        }],
+        RewardsDestination::Split(splits) => splits
+            .iter()
+            .map(|split| RewardTransfer {
+                address: split.address.clone(),
+                amount: split.amount,
+            })
+            .collect(),
+    }
+}
+
 fn build_extract_reward_tokens_tx(
     local_datapoint_box_source: &dyn LocalDatapointBoxSource,
     wallet: &dyn WalletDataSource,
-    rewards_destination: Address,
+    rewards_destination: &RewardsDestination,
     height: BlockHeight,
     change_address: Address,
 ) -> Result<(UnsignedTransaction, u64), ExtractRewardTokensActionError> {
@@ -124,7 +243,11 @@ fn build_extract_reward_tokens_tx(
             ),
         );
     }
-    if let Address::P2Pk(_) = &rewards_destination {
+    let recipients = reward_recipients(rewards_destination, num_reward_tokens - 1)?;
+    if recipients
+        .iter()
+        .all(|(address, _)| matches!(address, Address::P2Pk(_)))
+    {
         let single_reward_token = SpecToken {
             token_id: in_oracle_box.reward_token().token_id,
             amount: 1.try_into().unwrap(),
@@ -152,22 +275,26 @@ fn build_extract_reward_tokens_tx(
                 )?
             };
 
-        // Build box to hold extracted tokens
-        let mut builder =
-            ErgoBoxCandidateBuilder::new(*BASE_FEE, rewards_destination.script()?, height.0);
-
-        let extracted_reward_tokens = Token {
-            token_id: in_oracle_box.reward_token().token_id(),
-            amount: (num_reward_tokens - 1).try_into().unwrap(),
-        };
-
-        builder.add_token(extracted_reward_tokens);
-        let reward_box_candidate = builder.build()?;
+        let mut output_candidates = vec![oracle_box_candidate];
+        // Build a box to hold the extracted tokens for each recipient
+        for (address, amount) in &recipients {
+            let mut builder = ErgoBoxCandidateBuilder::new(*BASE_FEE, address.script()?, height.0);
+            builder.add_token(Token {
+                token_id: in_oracle_box.reward_token().token_id(),
+                amount: (*amount).try_into().unwrap(),
+            });
+            output_candidates.push(builder.build()?);
+        }
 
         let unspent_boxes = wallet.get_unspent_wallet_boxes()?;
 
-        // `BASE_FEE` each for the fee and the box holding the extracted reward tokens.
-        let target_balance = BASE_FEE.checked_mul_u32(2).unwrap();
+        let tx_fee = action_fee(FeeKind::ExtractRewardTokens);
+        // `BASE_FEE` for each box holding the extracted reward tokens.
+        let target_balance = BASE_FEE
+            .checked_mul_u32(recipients.len() as u32)
+            .unwrap()
+            .checked_add(&tx_fee)
+            .unwrap();
 
         let box_selector = SimpleBoxSelector::new();
         let selection = box_selector.select(unspent_boxes, target_balance, &[])?;
@@ -179,9 +306,9 @@ fn build_extract_reward_tokens_tx(
         };
         let mut tx_builder = TxBuilder::new(
             box_selection,
-            vec![oracle_box_candidate, reward_box_candidate],
+            output_candidates,
             height.0,
-            *BASE_FEE,
+            tx_fee,
             change_address,
         );
         // The following context value ensures that `outIndex` in the oracle contract is properly set.
@@ -196,6 +323,39 @@ fn build_extract_reward_tokens_tx(
     }
 }
 
+/// Addresses and amounts of the reward boxes, `available` being the extractable reward tokens
+fn reward_recipients(
+    rewards_destination: &RewardsDestination,
+    available: u64,
+) -> Result<Vec<(Address, u64)>, ExtractRewardTokensActionError> {
+    let parse = |address: &str| {
+        AddressEncoder::unchecked_parse_network_address_from_str(address).map(|a| a.address())
+    };
+    match rewards_destination {
+        RewardsDestination::All(address) => Ok(vec![(parse(address)?, available)]),
+        RewardsDestination::Split(splits) => {
+            let sum: u64 = splits.iter().map(|split| split.amount).sum();
+            if sum != available {
+                return Err(ExtractRewardTokensActionError::SplitAmountsMismatch {
+                    sum,
+                    available,
+                });
+            }
+            splits
+                .iter()
+                .map(|split| {
+                    if split.amount == 0 {
+                        return Err(ExtractRewardTokensActionError::ZeroSplitAmount(
+                            split.address.clone(),
+                        ));
+                    }
+                    Ok((parse(&split.address)?, split.amount))
+                })
+                .collect()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -262,7 +422,7 @@ mod tests {
         let (tx, num_reward_tokens) = build_extract_reward_tokens_tx(
             &local_datapoint_box_source,
             &wallet_mock,
-            change_address.address(),
+            &RewardsDestination::All(change_address.to_base58()),
             height,
             change_address.address(),
         )
@@ -286,4 +446,57 @@ mod tests {
 
         let _signed_tx = wallet.sign_transaction(tx_context, &ctx, None).unwrap();
     }
+
+    #[test]
+    fn test_reward_recipients() {
+        let address = "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r";
+        let split = |amounts: &[u64]| {
+            RewardsDestination::Split(
+                amounts
+                    .iter()
+                    .map(|amount| RewardSplit {
+                        address: address.to_string(),
+                        amount: *amount,
+                    })
+                    .collect(),
+            )
+        };
+        let recipients = reward_recipients(&split(&[3, 2]), 5).unwrap();
+        assert_eq!(
+            recipients.iter().map(|(_, a)| *a).collect::<Vec<_>>(),
+            vec![3, 2]
+        );
+        assert!(matches!(
+            reward_recipients(&split(&[3, 1]), 5),
+            Err(ExtractRewardTokensActionError::SplitAmountsMismatch {
+                sum: 4,
+                available: 5
+            })
+        ));
+        assert!(matches!(
+            reward_recipients(&split(&[5, 0]), 5),
+            Err(ExtractRewardTokensActionError::ZeroSplitAmount(_))
+        ));
+        assert_eq!(
+            reward_recipients(&RewardsDestination::All(address.to_string()), 5)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_reward_split() {
+        assert_eq!(
+            parse_reward_split("9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r:10"),
+            Ok(RewardSplit {
+                address: "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r".to_string(),
+                amount: 10
+            })
+        );
+        assert!(parse_reward_split("9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r").is_err());
+        assert!(
+            parse_reward_split("9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r:x").is_err()
+        );
+    }
 }
@@ -0,0 +1,223 @@
+use ergo_lib::ergotree_ir::chain::ergo_box::BoxId;
+use thiserror::Error;
+
+use crate::{
+    box_kind::{OracleBox, PoolBox},
+    oracle_state::{DataSourceError, LocalDatapointBoxSource, PoolBoxSource},
+    spec_token::{RewardTokenId, TokenIdKind},
+    wallet::{WalletDataError, WalletDataSource},
+};
+
+#[derive(Debug, Error)]
+pub enum FindRewardTokensError {
+    #[error("data source error: {0}")]
+    DataSourceError(#[from] DataSourceError),
+    #[error("WalletData error: {0}")]
+    WalletData(#[from] WalletDataError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardTokenLocationKind {
+    Wallet,
+    LocalOracleBox,
+    PoolBox,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardTokenLocation {
+    pub kind: RewardTokenLocationKind,
+    pub box_id: BoxId,
+    pub amount: u64,
+}
+
+pub fn find_reward_tokens(
+    wallet: &dyn WalletDataSource,
+    local_datapoint_box_source: &dyn LocalDatapointBoxSource,
+    pool_box_source: &dyn PoolBoxSource,
+    reward_token_id: &RewardTokenId,
+) -> Result<(), anyhow::Error> {
+    let locations = find_reward_token_locations(
+        wallet,
+        local_datapoint_box_source,
+        pool_box_source,
+        reward_token_id,
+    )?;
+    println!("Reward token {}:", String::from(reward_token_id.token_id()));
+    for location in &locations {
+        let description = match location.kind {
+            RewardTokenLocationKind::Wallet => "wallet box",
+            RewardTokenLocationKind::LocalOracleBox => "oracle box",
+            RewardTokenLocationKind::PoolBox => "pool box (not owned by the oracle)",
+        };
+        println!(
+            "  {} {}: {} tokens",
+            description,
+            String::from(location.box_id),
+            location.amount
+        );
+    }
+    let owned: u64 = locations
+        .iter()
+        .filter(|l| l.kind != RewardTokenLocationKind::PoolBox)
+        .map(|l| l.amount)
+        .sum();
+    println!("Total reward tokens owned by the oracle: {}", owned);
+    Ok(())
+}
+
+fn find_reward_token_locations(
+    wallet: &dyn WalletDataSource,
+    local_datapoint_box_source: &dyn LocalDatapointBoxSource,
+    pool_box_source: &dyn PoolBoxSource,
+    reward_token_id: &RewardTokenId,
+) -> Result<Vec<RewardTokenLocation>, FindRewardTokensError> {
+    let reward_token_id = reward_token_id.token_id();
+    let mut locations: Vec<RewardTokenLocation> = wallet
+        .get_unspent_wallet_boxes()?
+        .into_iter()
+        .filter_map(|b| {
+            let amount: u64 = b
+                .tokens
+                .as_ref()
+                .map(|tokens| {
+                    tokens
+                        .iter()
+                        .filter(|t| t.token_id == reward_token_id)
+                        .map(|t| *t.amount.as_u64())
+                        .sum()
+                })
+                .unwrap_or(0);
+            (amount > 0).then(|| RewardTokenLocation {
+                kind: RewardTokenLocationKind::Wallet,
+                box_id: b.box_id(),
+                amount,
+            })
+        })
+        .collect();
+    if let Some(oracle_box) = local_datapoint_box_source.get_local_oracle_datapoint_box()? {
+        locations.push(RewardTokenLocation {
+            kind: RewardTokenLocationKind::LocalOracleBox,
+            box_id: oracle_box.get_box().box_id(),
+            amount: *oracle_box.reward_token().amount.as_u64(),
+        });
+    }
+    let pool_box = pool_box_source.get_pool_box()?;
+    locations.push(RewardTokenLocation {
+        kind: RewardTokenLocationKind::PoolBox,
+        box_id: pool_box.get_box().box_id(),
+        amount: *pool_box.reward_token().amount.as_u64(),
+    });
+    Ok(locations)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::box_kind::{OracleBoxWrapper, OracleBoxWrapperInputs};
+    use crate::contracts::oracle::OracleContractParameters;
+    use crate::contracts::pool::PoolContractParameters;
+    use crate::oracle_config::BASE_FEE;
+    use crate::oracle_types::{BlockHeight, EpochCounter};
+    use crate::pool_commands::test_utils::{
+        generate_token_ids, make_datapoint_box, make_pool_box, make_wallet_unspent_box,
+        OracleBoxMock, PoolBoxMock, WalletDataMock,
+    };
+    use ergo_lib::chain::ergo_state_context::ErgoStateContext;
+    use ergo_lib::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use ergo_lib::ergotree_ir::chain::address::AddressEncoder;
+    use ergo_lib::ergotree_ir::chain::token::{Token, TokenAmount};
+    use sigma_test_util::force_any_val;
+
+    #[test]
+    fn test_find_reward_token_locations() {
+        let ctx = force_any_val::<ErgoStateContext>();
+        let height = BlockHeight(ctx.pre_header.height);
+        let token_ids = generate_token_ids();
+        let secret = force_any_val::<DlogProverInput>();
+        let oracle_pub_key = secret.public_image().h;
+
+        let oracle_box_wrapper_inputs =
+            OracleBoxWrapperInputs::try_from((OracleContractParameters::default(), &token_ids))
+                .unwrap();
+        let oracle_box = OracleBoxWrapper::new(
+            make_datapoint_box(
+                *oracle_pub_key,
+                200,
+                EpochCounter(1),
+                &token_ids,
+                BASE_FEE.checked_mul_u32(100).unwrap(),
+                height,
+                5,
+            ),
+            &oracle_box_wrapper_inputs,
+        )
+        .unwrap();
+        let oracle_box_id = oracle_box.get_box().box_id();
+        let local_datapoint_box_source = OracleBoxMock { oracle_box };
+
+        let pool_box = make_pool_box(
+            200,
+            EpochCounter(1),
+            BASE_FEE.checked_mul_u32(100).unwrap(),
+            height,
+            &PoolContractParameters::default(),
+            &token_ids,
+        );
+        let pool_box_source = PoolBoxMock { pool_box };
+
+        let reward_box = make_wallet_unspent_box(
+            secret.public_image(),
+            BASE_FEE.checked_mul_u32(100).unwrap(),
+            Some(
+                vec![Token {
+                    token_id: token_ids.reward_token_id.token_id(),
+                    amount: TokenAmount::try_from(7).unwrap(),
+                }]
+                .try_into()
+                .unwrap(),
+            ),
+        );
+        let reward_box_id = reward_box.box_id();
+        let empty_box = make_wallet_unspent_box(
+            secret.public_image(),
+            BASE_FEE.checked_mul_u32(10000).unwrap(),
+            None,
+        );
+        let wallet_mock = WalletDataMock {
+            unspent_boxes: vec![reward_box, empty_box],
+            change_address: AddressEncoder::unchecked_parse_network_address_from_str(
+                "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r",
+            )
+            .unwrap(),
+        };
+
+        let locations = find_reward_token_locations(
+            &wallet_mock,
+            &local_datapoint_box_source,
+            &pool_box_source,
+            &token_ids.reward_token_id,
+        )
+        .unwrap();
+        assert_eq!(locations.len(), 3);
+        assert_eq!(
+            locations[0],
+            RewardTokenLocation {
+                kind: RewardTokenLocationKind::Wallet,
+                box_id: reward_box_id,
+                amount: 7,
+            }
+        );
+        assert_eq!(
+            locations[1],
+            RewardTokenLocation {
+                kind: RewardTokenLocationKind::LocalOracleBox,
+                box_id: oracle_box_id,
+                amount: 5,
+            }
+        );
+        assert_eq!(locations[2].kind, RewardTokenLocationKind::PoolBox);
+    }
+}
@@ -0,0 +1,92 @@
+use serde_json::json;
+
+use crate::metrics::MetricDef;
+use crate::metrics::METRICS;
+use crate::output::write_json_result;
+
+const PANEL_WIDTH: usize = 12;
+const PANEL_HEIGHT: usize = 8;
+
+/// Write a Grafana dashboard with a panel for each of the `/metrics` metrics to the global
+/// `--output` or print it if not given
+pub fn generate_dashboard() -> Result<(), anyhow::Error> {
+    let dashboard = serde_json::to_string_pretty(&dashboard(&METRICS))?;
+    write_json_result("Grafana dashboard", &dashboard)?;
+    Ok(())
+}
+
+/// Dashboard JSON to import into Grafana, the Prometheus data source is selected on import
+fn dashboard(metrics: &[&MetricDef]) -> serde_json::Value {
+    let panels: Vec<serde_json::Value> = metrics
+        .iter()
+        .enumerate()
+        .map(|(i, metric)| {
+            json!({
+                "id": i + 1,
+                "type": "timeseries",
+                "title": metric.name,
+                "description": metric.help,
+                "datasource": { "type": "prometheus", "uid": "${DS_PROMETHEUS}" },
+                "gridPos": {
+                    "x": (i % 2) * PANEL_WIDTH,
+                    "y": (i / 2) * PANEL_HEIGHT,
+                    "w": PANEL_WIDTH,
+                    "h": PANEL_HEIGHT,
+                },
+                "targets": [{
+                    "refId": "A",
+                    "datasource": { "type": "prometheus", "uid": "${DS_PROMETHEUS}" },
+                    "expr": format!("{}{{job=~\"$job\"}}", metric.name),
+                    "legendFormat": "{{instance}}",
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "__inputs": [{
+            "name": "DS_PROMETHEUS",
+            "label": "Prometheus",
+            "type": "datasource",
+            "pluginId": "prometheus",
+            "pluginName": "Prometheus",
+        }],
+        "title": "Oracle Core",
+        "uid": "oracle-core",
+        "tags": ["oracle-core"],
+        "schemaVersion": 36,
+        "refresh": "30s",
+        "time": { "from": "now-24h", "to": "now" },
+        "templating": {
+            "list": [{
+                "name": "job",
+                "type": "query",
+                "datasource": { "type": "prometheus", "uid": "${DS_PROMETHEUS}" },
+                "query": format!("label_values({}, job)", METRICS[0].name),
+                "includeAll": true,
+                "multi": true,
+                "refresh": 1,
+            }],
+        },
+        "panels": panels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboard() {
+        let dashboard = dashboard(&METRICS);
+        let panels = dashboard["panels"].as_array().unwrap();
+        assert_eq!(panels.len(), METRICS.len());
+        for (panel, metric) in panels.iter().zip(METRICS) {
+            assert!(panel["targets"][0]["expr"]
+                .as_str()
+                .unwrap()
+                .starts_with(metric.name));
+        }
+        assert_eq!(panels[1]["gridPos"]["x"], 12);
+        assert_eq!(panels[2]["gridPos"]["y"], 8);
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use reqwest::Url;
+use schemars::schema_for;
+
+use crate::oracle_config::OracleConfig;
+
+const HEADER: &str = "\
+# Minimal oracle config to monitor a pool with `oracle-core run --read-only --enable-rest-api`.
+# No tx is signed or submitted. Set node_api_key to the API key of your node (needed to register
+# the scans). oracle_address can be any P2PK address, e.g. your own to see your oracle's state.
+# The token ids come from the pool_config.yaml of the pool, get it from the pool operators.
+";
+
+/// Write an oracle config with only the fields the config requires (the optional ones, e.g. the
+/// wallet password and the fees, are left out) for a read-only monitoring instance
+pub fn generate_monitor_config(node_url: Option<Url>, path: &Path) -> Result<(), anyhow::Error> {
+    if path.exists() {
+        return Err(anyhow!(
+            "{} already exists. Please, remove it and run again",
+            path.display()
+        ));
+    }
+    let mut config = OracleConfig::default();
+    if let Some(node_url) = node_url {
+        config.node_url = node_url;
+    }
+    std::fs::write(path, format!("{}{}", HEADER, monitor_config_yaml(&config)?))?;
+    println!("Monitoring config written to {}", path.display());
+    Ok(())
+}
+
+/// The required fields of the oracle config (per its JSON Schema) as YAML
+fn monitor_config_yaml(config: &OracleConfig) -> Result<String, anyhow::Error> {
+    let schema = serde_json::to_value(schema_for!(OracleConfig))?;
+    let fields = serde_json::to_value(config)?;
+    let minimal: serde_json::Map<String, serde_json::Value> = schema["required"]
+        .as_array()
+        .ok_or_else(|| anyhow!("oracle config schema has no required fields"))?
+        .iter()
+        .filter_map(|name| name.as_str())
+        .filter_map(|name| Some((name.to_string(), fields.get(name)?.clone())))
+        .collect();
+    Ok(serde_yaml::to_string(&minimal)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_config_loads() {
+        let yaml = monitor_config_yaml(&OracleConfig::default()).unwrap();
+        assert!(yaml.contains("node_url"));
+        assert!(!yaml.contains("wallet_password_env_var"));
+        assert!(!yaml.contains("action_fees"));
+        let config = OracleConfig::load_from_str(&format!("{}{}", HEADER, yaml)).unwrap();
+        assert_eq!(config.node_url, OracleConfig::default().node_url);
+        assert!(config.wallet_password_env_var.is_none());
+    }
+}
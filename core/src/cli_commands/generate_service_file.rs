@@ -0,0 +1,133 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+/// What goes into the `ExecStart` of the unit, taken from the command line of
+/// `generate-service-file`
+#[derive(Debug, Clone)]
+pub struct ServiceFileParams {
+    pub binary_path: PathBuf,
+    pub oracle_config_file: PathBuf,
+    pub pool_config_file: PathBuf,
+    pub data_dir: PathBuf,
+    pub verbose: bool,
+    pub read_only: bool,
+    pub enable_rest_api: bool,
+}
+
+/// Write the systemd unit running `run` with the given paths and flags to `output_path` or
+/// print it if not given
+pub fn generate_service_file(
+    params: &ServiceFileParams,
+    output_path: Option<&Path>,
+) -> Result<(), anyhow::Error> {
+    let unit = service_unit(params);
+    match output_path {
+        Some(path) => {
+            std::fs::write(path, unit)?;
+            println!("Service unit is written to {}", path.display());
+            println!(
+                "To install it as a user unit copy it to ~/.config/systemd/user/ and run \
+                `systemctl --user enable --now {}`",
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            );
+        }
+        None => print!("{}", unit),
+    }
+    Ok(())
+}
+
+fn service_unit(params: &ServiceFileParams) -> String {
+    let mut exec_start = vec![
+        quote(&params.binary_path),
+        "--oracle-config-file".to_string(),
+        quote(&params.oracle_config_file),
+        "--pool-config-file".to_string(),
+        quote(&params.pool_config_file),
+        "--data-dir".to_string(),
+        quote(&params.data_dir),
+    ];
+    if params.verbose {
+        exec_start.push("--verbose".to_string());
+    }
+    exec_start.push("run".to_string());
+    if params.read_only {
+        exec_start.push("--read-only".to_string());
+    }
+    if params.enable_rest_api {
+        exec_start.push("--enable-rest-api".to_string());
+    }
+    format!(
+        "[Unit]
+Description=Oracle Core v{version}
+Documentation=https://github.com/ergoplatform/oracle-core
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=simple
+WorkingDirectory={working_dir}
+ExecStart={exec_start}
+Restart=on-failure
+RestartSec=5
+# SIGHUP reloads the datapoint source
+ExecReload=/bin/kill -HUP $MAINPID
+LimitNOFILE=4096
+MemoryMax=1G
+NoNewPrivileges=true
+
+[Install]
+WantedBy=default.target
+",
+        version = env!("CARGO_PKG_VERSION"),
+        working_dir = quote(&params.data_dir),
+        exec_start = exec_start.join(" ")
+    )
+}
+
+/// systemd requires absolute paths, relative ones are resolved against the current dir
+pub fn absolute_path(path: &Path) -> Result<PathBuf, std::io::Error> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// systemd splits the command line on spaces unless the argument is double-quoted
+fn quote(path: &Path) -> String {
+    let path = path.display().to_string();
+    if path.contains(char::is_whitespace) {
+        format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_unit_exec_start() {
+        let params = ServiceFileParams {
+            binary_path: PathBuf::from("/usr/local/bin/oracle-core"),
+            oracle_config_file: PathBuf::from("/home/oracle/oracle_config.yaml"),
+            pool_config_file: PathBuf::from("/home/oracle/my pool/pool_config.yaml"),
+            data_dir: PathBuf::from("/home/oracle/data"),
+            verbose: false,
+            read_only: false,
+            enable_rest_api: true,
+        };
+        let unit = service_unit(&params);
+        assert!(unit.contains(
+            "\nExecStart=/usr/local/bin/oracle-core \
+            --oracle-config-file /home/oracle/oracle_config.yaml \
+            --pool-config-file \"/home/oracle/my pool/pool_config.yaml\" \
+            --data-dir /home/oracle/data run --enable-rest-api\n"
+        ));
+        assert!(unit.contains("\nWorkingDirectory=/home/oracle/data\n"));
+        assert!(unit.contains("\nRestart=on-failure\n"));
+    }
+}
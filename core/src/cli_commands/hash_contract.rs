@@ -0,0 +1,68 @@
+use ergo_lib::{
+    ergo_chain_types::Digest32,
+    ergotree_ir::{
+        chain::address::{AddressEncoder, AddressEncoderError},
+        ergo_tree::{ErgoTree, ErgoTreeError},
+        serialization::{SigmaParsingError, SigmaSerializable, SigmaSerializationError},
+    },
+};
+use thiserror::Error;
+
+use crate::box_kind::pool_box_hash;
+
+#[derive(Debug, Error)]
+pub enum HashContractError {
+    #[error("Neither a valid address ({0}) nor a base16-encoded ergo tree")]
+    InvalidInput(AddressEncoderError),
+    #[error("Sigma parsing error: {0}")]
+    SigmaParsing(#[from] SigmaParsingError),
+    #[error("Sigma serialization error: {0}")]
+    SigmaSerialization(#[from] SigmaSerializationError),
+    #[error("ErgoTree error: {0}")]
+    ErgoTree(#[from] ErgoTreeError),
+}
+
+/// Print the base16-encoded blake2b hash of the ergo tree as expected by `vote-update-pool`
+pub fn hash_contract(address_or_tree_hex: String) -> Result<(), anyhow::Error> {
+    let hash = contract_hash(&address_or_tree_hex)?;
+    println!("{}", String::from(hash));
+    Ok(())
+}
+
+fn contract_hash(address_or_tree_hex: &str) -> Result<Digest32, HashContractError> {
+    let input = address_or_tree_hex.trim();
+    let ergo_tree = match AddressEncoder::unchecked_parse_network_address_from_str(input) {
+        Ok(network_address) => network_address.address().script()?,
+        Err(address_error) => {
+            let bytes = base16::decode(input)
+                .map_err(|_| HashContractError::InvalidInput(address_error))?;
+            ErgoTree::sigma_parse_bytes(&bytes)?
+        }
+    };
+    // hash the serialized tree, same as the output box proposition bytes checked by the contract
+    Ok(pool_box_hash(&ergo_tree)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergo_chain_types::blake2b256_hash;
+    use ergo_lib::ergotree_ir::chain::address::{Address, NetworkAddress, NetworkPrefix};
+
+    use super::*;
+    use crate::contracts::pool::PoolContractParameters;
+
+    #[test]
+    fn test_contract_hash_of_address_and_tree_match() {
+        let ergo_tree_bytes = PoolContractParameters::default().ergo_tree_bytes();
+        let ergo_tree = ErgoTree::sigma_parse_bytes(&ergo_tree_bytes).unwrap();
+        let expected = blake2b256_hash(&ergo_tree.sigma_serialize_bytes().unwrap());
+        assert_eq!(
+            contract_hash(&base16::encode_lower(&ergo_tree_bytes)).unwrap(),
+            expected
+        );
+        let address =
+            NetworkAddress::new(NetworkPrefix::Mainnet, &Address::P2S(ergo_tree_bytes)).to_base58();
+        assert_eq!(contract_hash(&address).unwrap(), expected);
+        assert!(contract_hash("not a contract").is_err());
+    }
+}
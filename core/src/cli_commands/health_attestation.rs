@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use ergo_lib::ergotree_interpreter::sigma_protocol::verifier::verify_signature;
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::AddressEncoder;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use ergo_lib::wallet::secret_key::SecretKey;
+use ergo_lib::wallet::Wallet;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::box_kind::OracleBox;
+use crate::node_interface::node_api::NodeApi;
+use crate::oracle_state::OraclePool;
+use crate::oracle_types::BlockHeight;
+use crate::output::write_json_result;
+
+/// Self-reported state of an oracle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthAttestation {
+    pub oracle_address: String,
+    /// Height of the node of the oracle
+    pub height: u32,
+    /// Creation height of the local oracle box, i.e. the last datapoint post or collection
+    pub last_post_height: Option<u32>,
+    pub version: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// `message` is the signed JSON of the [`HealthAttestation`], kept as a string so that the
+/// signature is checked against the exact signed bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedHealthAttestation {
+    pub message: String,
+    /// Base16 encoded Schnorr signature of `message` by the key of `oracle_address`
+    pub signature: String,
+}
+
+/// Sign the health attestation of this oracle with the node wallet key of `oracle_address` and
+/// print it as JSON or write it to the global `--output`
+pub fn health_attestation(
+    op: &OraclePool,
+    node_api: &NodeApi,
+    oracle_address: &NetworkAddress,
+    height: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    let last_post_height = op
+        .get_local_datapoint_box_source()
+        .get_local_oracle_datapoint_box()?
+        .map(|b| b.get_box().creation_height);
+    let attestation = HealthAttestation {
+        oracle_address: oracle_address.to_base58(),
+        height: height.0,
+        last_post_height,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    };
+    // the node has no message signing endpoint, the key is taken from the node wallet and the
+    // message signed here
+    let secret_key = node_api.wallet_secret_key(oracle_address)?;
+    let signed = serde_json::to_string_pretty(&sign(&attestation, &secret_key)?)?;
+    write_json_result("Health attestation", &signed)?;
+    Ok(())
+}
+
+/// Check the signature of a health attestation file made by `health-attestation` and print the
+/// attested state
+pub fn verify_health_attestation(file: &Path) -> Result<(), anyhow::Error> {
+    let signed: SignedHealthAttestation = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+    let attestation = verify(&signed)?;
+    println!(
+        "Valid health attestation of {} (oracle-core {}) signed at {}:",
+        attestation.oracle_address, attestation.version, attestation.timestamp
+    );
+    println!("  node height: {}", attestation.height);
+    match attestation.last_post_height {
+        Some(h) => println!("  local oracle box created at height {}", h),
+        None => println!("  no local oracle box"),
+    }
+    Ok(())
+}
+
+fn sign(
+    attestation: &HealthAttestation,
+    secret_key: &SecretKey,
+) -> Result<SignedHealthAttestation, anyhow::Error> {
+    let signer = sigma_boolean(&attestation.oracle_address)?;
+    if secret_key.public_image() != signer {
+        return Err(anyhow!(
+            "the node wallet key is not the key of {}",
+            attestation.oracle_address
+        ));
+    }
+    let message = serde_json::to_string(attestation)?;
+    let signature =
+        Wallet::from_secrets(vec![secret_key.clone()]).sign_message(signer, message.as_bytes())?;
+    Ok(SignedHealthAttestation {
+        message,
+        signature: base16::encode_lower(&signature),
+    })
+}
+
+/// The attestation if the signature is valid
+fn verify(signed: &SignedHealthAttestation) -> Result<HealthAttestation, anyhow::Error> {
+    let attestation: HealthAttestation = serde_json::from_str(&signed.message)?;
+    let signer = sigma_boolean(&attestation.oracle_address)?;
+    let signature = base16::decode(&signed.signature)?;
+    if verify_signature(signer, signed.message.as_bytes(), &signature)? {
+        Ok(attestation)
+    } else {
+        Err(anyhow!(
+            "invalid signature, the attestation was not signed by {}",
+            attestation.oracle_address
+        ))
+    }
+}
+
+fn sigma_boolean(address: &str) -> Result<SigmaBoolean, anyhow::Error> {
+    sigma_boolean_of(&AddressEncoder::unchecked_parse_network_address_from_str(address)?.address())
+}
+
+fn sigma_boolean_of(address: &Address) -> Result<SigmaBoolean, anyhow::Error> {
+    match address {
+        Address::P2Pk(prove_dlog) => Ok(prove_dlog.clone().into()),
+        _ => Err(anyhow!("oracle address is not a P2PK address")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+
+    use super::*;
+    use crate::cli_commands::address_from_mnemonic::derive_first_secret_key;
+
+    const MNEMONIC: &str = "change me do not use me change me do not use me";
+
+    #[test]
+    fn test_sign_verify() {
+        let secret_key = derive_first_secret_key(MNEMONIC, "").unwrap();
+        let address = NetworkAddress::new(
+            NetworkPrefix::Mainnet,
+            &Address::from(secret_key.public_key().unwrap()),
+        );
+        let attestation = HealthAttestation {
+            oracle_address: address.to_base58(),
+            height: 1000,
+            last_post_height: Some(995),
+            version: "2.0.0".to_string(),
+            timestamp: 1700000000,
+        };
+        let signed = sign(&attestation, &secret_key.secret_key()).unwrap();
+        assert_eq!(verify(&signed).unwrap(), attestation);
+
+        let tampered = SignedHealthAttestation {
+            message: signed.message.replace("1000", "1001"),
+            ..signed.clone()
+        };
+        assert!(verify(&tampered).is_err());
+
+        let other_key = derive_first_secret_key(MNEMONIC, "password").unwrap();
+        assert!(sign(&attestation, &other_key.secret_key()).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+
+use crate::box_kind::OracleBox;
+use crate::oracle_state::PostedDatapointBoxesSource;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::Rate;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdenticalDatapoints {
+    pub epoch: EpochCounter,
+    pub rate: Rate,
+    pub public_keys: Vec<EcPoint>,
+}
+
+/// Print the oracles that posted the same datapoint in the same epoch. Independent sources
+/// rarely agree exactly, so this hints at a shared (possibly broken) source or copied values.
+pub fn identical_datapoints(
+    datapoint_src: &dyn PostedDatapointBoxesSource,
+    network_prefix: NetworkPrefix,
+) -> Result<(), anyhow::Error> {
+    let datapoints: Vec<(EpochCounter, Rate, EcPoint)> = datapoint_src
+        .get_posted_datapoint_boxes()?
+        .iter()
+        .map(|b| (b.epoch_counter(), b.rate(), b.public_key()))
+        .collect();
+    let groups = find_identical_datapoints(datapoints);
+    if groups.is_empty() {
+        println!("No identical datapoints posted in the same epoch");
+        return Ok(());
+    }
+    for group in groups {
+        println!(
+            "Epoch {}: {} oracles posted {}",
+            group.epoch.0,
+            group.public_keys.len(),
+            group.rate
+        );
+        for pk in group.public_keys {
+            println!(
+                "  {}",
+                NetworkAddress::new(network_prefix, &Address::P2Pk(pk.into())).to_base58()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Datapoints posted by more than one oracle in the same epoch, ordered by epoch and rate
+fn find_identical_datapoints(
+    datapoints: Vec<(EpochCounter, Rate, EcPoint)>,
+) -> Vec<IdenticalDatapoints> {
+    let mut oracles_by_datapoint: BTreeMap<(EpochCounter, Rate), Vec<EcPoint>> = BTreeMap::new();
+    for (epoch, rate, public_key) in datapoints {
+        oracles_by_datapoint
+            .entry((epoch, rate))
+            .or_default()
+            .push(public_key);
+    }
+    oracles_by_datapoint
+        .into_iter()
+        .filter(|(_, public_keys)| public_keys.len() > 1)
+        .map(|((epoch, rate), public_keys)| IdenticalDatapoints {
+            epoch,
+            rate,
+            public_keys,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_lib::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use sigma_test_util::force_any_val;
+
+    #[test]
+    fn test_find_identical_datapoints() {
+        let pks: Vec<EcPoint> = (0..4)
+            .map(|_| *force_any_val::<DlogProverInput>().public_image().h)
+            .collect();
+        let datapoints = vec![
+            (EpochCounter(2), Rate::from(100), pks[0].clone()),
+            (EpochCounter(2), Rate::from(100), pks[1].clone()),
+            (EpochCounter(2), Rate::from(101), pks[2].clone()),
+            // same value in another epoch is not flagged
+            (EpochCounter(1), Rate::from(100), pks[3].clone()),
+        ];
+        assert_eq!(
+            find_identical_datapoints(datapoints),
+            vec![IdenticalDatapoints {
+                epoch: EpochCounter(2),
+                rate: Rate::from(100),
+                public_keys: vec![pks[0].clone(), pks[1].clone()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+
+use crate::box_kind::BallotBox;
+use crate::box_kind::PoolBox;
+use crate::box_kind::RefreshBox;
+use crate::oracle_state::OraclePool;
+use crate::oracle_state::PoolBoxes;
+use crate::scans::fetch_limit::FetchLimit;
+
+/// Print every box of the pool found by the scans: pool, refresh, update, oracle (posted and
+/// collected), ballot (with a vote) and buyback boxes, with their ids, values and the parsed
+/// registers
+pub fn inspect_pool(
+    op: &Arc<OraclePool>,
+    network_prefix: NetworkPrefix,
+) -> Result<(), anyhow::Error> {
+    let to_address =
+        |pk: EcPoint| NetworkAddress::new(network_prefix, &Address::P2Pk(pk.into())).to_base58();
+
+    // each source is a scan request, sent in parallel up to `scan_fetch_concurrency`
+    let PoolBoxes {
+        pool_box,
+        refresh_box,
+        update_box,
+        buyback_box,
+        posted_boxes,
+        collected_boxes,
+        ballot_boxes,
+    } = tokio::runtime::Runtime::new()?.block_on(op.fetch_all_boxes(&FetchLimit::from_config()));
+
+    let pool_box = pool_box?;
+    println!("{}", box_line("Pool box", pool_box.get_box()));
+    println!(
+        "  epoch {}, rate {}, reward tokens {}",
+        pool_box.epoch_counter().0,
+        pool_box.rate(),
+        pool_box.reward_token().amount.as_u64()
+    );
+    let refresh_box = refresh_box?;
+    println!("{}", box_line("Refresh box", refresh_box.get_box()));
+    let update_box = update_box?;
+    println!("{}", box_line("Update box", update_box.get_box()));
+    println!("  min votes {}", update_box.min_votes());
+    if let Some(buyback_box) = buyback_box {
+        match buyback_box? {
+            Some(buyback_box) => println!("{}", box_line("Buyback box", buyback_box.get_box())),
+            None => println!("Buyback box not found"),
+        }
+    }
+
+    let posted_boxes = posted_boxes?;
+    let collected_boxes = collected_boxes?;
+    println!(
+        "Oracle boxes ({} posted, {} collected):",
+        posted_boxes.len(),
+        collected_boxes.len()
+    );
+    for b in posted_boxes {
+        println!("  {}", box_line(&to_address(b.public_key()), b.get_box()));
+        println!(
+            "    posted {} in epoch {}, reward tokens {}",
+            b.rate(),
+            b.epoch_counter().0,
+            b.reward_token().amount.as_u64()
+        );
+    }
+    for b in collected_boxes {
+        println!("  {}", box_line(&to_address(b.public_key()), b.get_box()));
+        println!(
+            "    collected, reward tokens {}",
+            token_amount(b.get_box(), 1)
+        );
+    }
+
+    let ballot_boxes = ballot_boxes?;
+    println!("Ballot boxes with a vote ({}):", ballot_boxes.len());
+    for b in ballot_boxes {
+        println!(
+            "  {}",
+            box_line(&to_address(b.ballot_token_owner()), b.get_box())
+        );
+        let vote = b.vote_parameters();
+        println!(
+            "    vote for pool box address hash {}, update box height {}, reward token {}",
+            String::from(vote.pool_box_address_hash),
+            vote.update_box_creation_height,
+            vote.reward_token_opt
+                .as_ref()
+                .map(|t| format!("{} x {}", String::from(t.token_id()), t.amount.as_u64()))
+                .unwrap_or_else(|| "unchanged".to_string())
+        );
+    }
+    Ok(())
+}
+
+fn box_line(name: &str, ergo_box: &ErgoBox) -> String {
+    format!(
+        "{}: box {}, {} nanoERG, creation height {}",
+        name,
+        String::from(ergo_box.box_id()),
+        ergo_box.value.as_u64(),
+        ergo_box.creation_height
+    )
+}
+
+fn token_amount(ergo_box: &ErgoBox, index: usize) -> u64 {
+    ergo_box
+        .tokens
+        .as_ref()
+        .and_then(|tokens| tokens.get(index))
+        .map(|token| *token.amount.as_u64())
+        .unwrap_or(0)
+}
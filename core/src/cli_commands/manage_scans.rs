@@ -0,0 +1,173 @@
+use clap::ValueEnum;
+use ergo_node_interface::ScanId;
+
+use crate::node_interface::node_api::NodeApi;
+use crate::pool_config::PoolConfig;
+use crate::scans::get_scans_file_path;
+use crate::scans::wait_for_node_rescan;
+use crate::scans::NodeScanRegistry;
+
+/// A scan of the scans file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScanName {
+    Oracle,
+    Pool,
+    Ballot,
+    Refresh,
+    Update,
+    Buyback,
+}
+
+impl ScanName {
+    /// Field of the scans file, as in [`NodeScanRegistry::scans`]
+    pub fn field_name(self) -> &'static str {
+        match self {
+            ScanName::Oracle => "oracle_token_scan",
+            ScanName::Pool => "pool_token_scan",
+            ScanName::Ballot => "ballot_token_scan",
+            ScanName::Refresh => "refresh_token_scan",
+            ScanName::Update => "update_token_scan",
+            ScanName::Buyback => "buyback_token_scan",
+        }
+    }
+}
+
+/// Print the scans of the scans file and whether the node has them, then the other scans of
+/// the node
+pub fn list_scans(node_api: &NodeApi) -> Result<(), anyhow::Error> {
+    let node_scans = node_api.list_scans()?;
+    let node_scan_ids: Vec<ScanId> = node_scans.iter().map(|(id, _)| *id).collect();
+    let path = get_scans_file_path();
+    let registry_scans = if path.exists() {
+        NodeScanRegistry::load()?.scans()
+    } else {
+        println!("No scans file at {}", path.display());
+        vec![]
+    };
+    if !registry_scans.is_empty() {
+        println!("Scans of {}:", path.display());
+    }
+    for (name, scan_id) in &registry_scans {
+        println!(
+            "  {}: {} ({})",
+            name,
+            scan_id,
+            registration_status(*scan_id, &node_scan_ids)
+        );
+    }
+    let other_scans: Vec<&(ScanId, String)> = node_scans
+        .iter()
+        .filter(|(id, _)| !registry_scans.iter().any(|(_, scan_id)| scan_id == id))
+        .collect();
+    if !other_scans.is_empty() {
+        println!("Other scans in the node:");
+        for (scan_id, scan_name) in other_scans {
+            println!("  {}: {}", scan_id, scan_name);
+        }
+    }
+    Ok(())
+}
+
+/// Register the scans of the scans file that are missing in the node, or all of them if there
+/// is no scans file
+pub fn register_missing_scans(
+    node_api: &NodeApi,
+    pool_config: &PoolConfig,
+) -> Result<(), anyhow::Error> {
+    let path = get_scans_file_path();
+    if !path.exists() {
+        let registry = NodeScanRegistry::register_and_save_scans(node_api, pool_config)?;
+        wait_for_node_rescan(node_api)?;
+        println!(
+            "Registered {} scans and saved them to {}",
+            registry.scans().len(),
+            path.display()
+        );
+        return Ok(());
+    }
+    let mut registry = NodeScanRegistry::load()?;
+    registry.check_config_fingerprint(pool_config)?;
+    let node_scan_ids: Vec<ScanId> = node_api
+        .list_scans()?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    let registered = registry.register_missing_scans(node_api, pool_config, &node_scan_ids)?;
+    if registered.is_empty() {
+        println!(
+            "All the scans of {} are registered in the node",
+            path.display()
+        );
+    } else {
+        wait_for_node_rescan(node_api)?;
+        println!("Registered {}", registered.join(", "));
+    }
+    Ok(())
+}
+
+/// Deregister a single scan in the node
+pub fn deregister_scan(node_api: &NodeApi, scan: ScanName) -> Result<(), anyhow::Error> {
+    let mut registry = NodeScanRegistry::load()?;
+    let scan_id = registry.deregister_scan(node_api, scan.field_name())?;
+    println!(
+        "Deregistered {} (scan {}) in the node",
+        scan.field_name(),
+        scan_id
+    );
+    if scan != ScanName::Buyback {
+        println!("Register it again with `oracle-core scans register` before running the oracle");
+    }
+    Ok(())
+}
+
+/// Print the wallet rescan progress and for each scan whether the node has it and how many
+/// unspent boxes it found
+pub fn scans_status(node_api: &NodeApi, pool_config: &PoolConfig) -> Result<(), anyhow::Error> {
+    let registry = NodeScanRegistry::load()?;
+    let wallet_height = node_api.wallet_status()?.height;
+    let block_height = node_api.current_block_height()?;
+    println!(
+        "Wallet scanned {}/{} blocks ({})",
+        wallet_height,
+        block_height,
+        if wallet_height == block_height {
+            "synced"
+        } else {
+            "rescan in progress, the scans miss the boxes of the blocks not scanned yet"
+        }
+    );
+    if let Err(e) = registry.check_config_fingerprint(pool_config) {
+        println!("{}", e);
+    }
+    let node_scan_ids: Vec<ScanId> = node_api
+        .list_scans()?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    for (name, scan_id) in registry.scans() {
+        let boxes = if node_scan_ids.contains(&scan_id) {
+            match node_api.scan_boxes(scan_id) {
+                Ok(boxes) => format!(", {} unspent boxes", boxes.len()),
+                Err(e) => format!(", failed to get the boxes: {}", e),
+            }
+        } else {
+            String::new()
+        };
+        println!(
+            "  {}: {} ({}{})",
+            name,
+            scan_id,
+            registration_status(scan_id, &node_scan_ids),
+            boxes
+        );
+    }
+    Ok(())
+}
+
+fn registration_status(scan_id: ScanId, node_scan_ids: &[ScanId]) -> &'static str {
+    if node_scan_ids.contains(&scan_id) {
+        "registered in the node"
+    } else {
+        "missing in the node, register it with `oracle-core scans register`"
+    }
+}
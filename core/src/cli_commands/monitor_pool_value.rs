@@ -0,0 +1,138 @@
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+
+use crate::box_kind::PoolBox;
+use crate::epoch_history::read_records;
+use crate::events;
+use crate::events::OracleEvent;
+use crate::oracle_state::PoolBoxSource;
+use crate::oracle_types::EpochCounter;
+
+/// Number of the latest epochs the value trend is computed from
+const TREND_EPOCHS: usize = 10;
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Default floor, below it the pool box can't be recreated
+pub fn default_floor_nanoerg() -> u64 {
+    *BoxValue::SAFE_USER_MIN.as_u64()
+}
+
+/// Check the pool box value on every new epoch and log a warning if it's projected to drop to
+/// `floor_nanoerg` within `alert_epochs` epochs. The trend includes the pool box values recorded
+/// in the epoch history by `run`.
+pub fn monitor_pool_value(
+    pool_box_source: &dyn PoolBoxSource,
+    history_file_path: &Path,
+    alert_epochs: u32,
+    floor_nanoerg: u64,
+) -> Result<(), anyhow::Error> {
+    let mut samples: Vec<(EpochCounter, u64)> = read_records(history_file_path)?
+        .into_iter()
+        .filter_map(|r| r.pool_box_value.map(|value| (r.epoch, value)))
+        .collect();
+    log::info!(
+        "Monitoring the pool box value, {} epochs loaded from the history",
+        samples.len()
+    );
+    loop {
+        match pool_box_source.get_pool_box() {
+            Ok(pool_box) => {
+                let epoch = pool_box.epoch_counter();
+                if samples.last().map(|(e, _)| *e) != Some(epoch) {
+                    samples.push((epoch, *pool_box.get_box().value.as_u64()));
+                    check_trend(&samples, alert_epochs, floor_nanoerg);
+                }
+            }
+            Err(e) => log::error!("Failed to read the pool box: {}", e),
+        }
+        thread::sleep(Duration::from_secs(CHECK_INTERVAL_SECS));
+    }
+}
+
+fn check_trend(samples: &[(EpochCounter, u64)], alert_epochs: u32, floor_nanoerg: u64) {
+    let (epoch, value) = match samples.last() {
+        Some(last) => *last,
+        None => return,
+    };
+    match epochs_until_floor(samples, floor_nanoerg) {
+        Some(epochs) if epochs <= alert_epochs as u64 => {
+            log::warn!(
+                "Pool box value {} nanoERG at epoch {} is projected to drop to {} nanoERG in {} epoch(s), top it up to keep the pool refreshing",
+                value,
+                epoch.0,
+                floor_nanoerg,
+                epochs
+            );
+            events::emit(OracleEvent::PoolValueAlert {
+                epoch,
+                value,
+                floor: floor_nanoerg,
+                epochs_left: epochs,
+            });
+        }
+        Some(epochs) => log::info!(
+            "Pool box value {} nanoERG at epoch {}, projected to reach the floor in {} epochs",
+            value,
+            epoch.0,
+            epochs
+        ),
+        None => log::info!(
+            "Pool box value {} nanoERG at epoch {}, not decreasing",
+            value,
+            epoch.0
+        ),
+    }
+}
+
+/// Epochs until the value drops to the floor at the average decrease per epoch over the last
+/// [`TREND_EPOCHS`] samples, `None` if the value is not decreasing
+fn epochs_until_floor(samples: &[(EpochCounter, u64)], floor_nanoerg: u64) -> Option<u64> {
+    let window = &samples[samples.len().saturating_sub(TREND_EPOCHS)..];
+    let (first_epoch, first_value) = window.first()?;
+    let (last_epoch, last_value) = window.last()?;
+    if last_value <= &floor_nanoerg {
+        return Some(0);
+    }
+    let epochs = last_epoch.0.checked_sub(first_epoch.0).filter(|e| *e > 0)? as u64;
+    let decrease = first_value.checked_sub(*last_value).filter(|d| *d > 0)?;
+    // round up the decrease per epoch to not project later than the linear trend
+    let decrease_per_epoch = (decrease + epochs - 1) / epochs;
+    Some((last_value - floor_nanoerg) / decrease_per_epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(values: &[(u32, u64)]) -> Vec<(EpochCounter, u64)> {
+        values.iter().map(|(e, v)| (EpochCounter(*e), *v)).collect()
+    }
+
+    #[test]
+    fn test_epochs_until_floor() {
+        assert_eq!(epochs_until_floor(&[], 1000), None);
+        assert_eq!(epochs_until_floor(&samples(&[(1, 5000)]), 1000), None);
+        // not decreasing
+        assert_eq!(
+            epochs_until_floor(&samples(&[(1, 5000), (2, 5000), (3, 6000)]), 1000),
+            None
+        );
+        // 100 per epoch, 4000 left above the floor
+        assert_eq!(
+            epochs_until_floor(&samples(&[(1, 5200), (2, 5100), (3, 5000)]), 1000),
+            Some(40)
+        );
+        // at or below the floor already
+        assert_eq!(
+            epochs_until_floor(&samples(&[(1, 1200), (2, 900)]), 1000),
+            Some(0)
+        );
+        // only the last TREND_EPOCHS samples count
+        let mut values: Vec<(u32, u64)> = vec![(1, 1_000_000)];
+        values.extend((2..=11).map(|e| (e, 11_000 - e as u64 * 10)));
+        assert_eq!(epochs_until_floor(&samples(&values), 1000), Some(989));
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::epoch_history::read_records;
+use crate::epoch_history::EpochHistoryError;
+use crate::epoch_history::EpochHistoryRecord;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OracleParticipation {
+    /// Epochs the oracle posted a datapoint in
+    pub participated: usize,
+    /// Epochs the oracle's datapoint was collected into the pool box
+    pub collected: usize,
+}
+
+/// Print for each oracle in how many of the last `epochs` recorded epochs it posted a datapoint
+/// and how many of them were collected (in consensus). `known_oracles` are listed even if they
+/// never posted.
+pub fn participation_report(
+    history_file_path: &Path,
+    epochs: usize,
+    known_oracles: &[String],
+) -> Result<(), EpochHistoryError> {
+    let records = read_records(history_file_path)?;
+    let (num_epochs, participation) = participation(&records, epochs, known_oracles);
+    if num_epochs == 0 {
+        println!(
+            "No epochs with the oracle sets in the history at {}, they are recorded while running \
+            the oracle with `run`",
+            history_file_path.display()
+        );
+        return Ok(());
+    }
+    println!("Participation in the last {} recorded epochs:", num_epochs);
+    let mut participation: Vec<(String, OracleParticipation)> = participation.into_iter().collect();
+    participation.sort_by(|(_, a), (_, b)| b.participated.cmp(&a.participated));
+    for (address, p) in participation {
+        println!(
+            "  {}: posted in {}/{} epochs ({}), in consensus {}/{} ({})",
+            address,
+            p.participated,
+            num_epochs,
+            percent(p.participated, num_epochs),
+            p.collected,
+            p.participated,
+            percent(p.collected, p.participated)
+        );
+    }
+    Ok(())
+}
+
+/// Participation per oracle address in the last `epochs` records that have the oracle sets.
+/// Returns the number of these records as well.
+fn participation(
+    records: &[EpochHistoryRecord],
+    epochs: usize,
+    known_oracles: &[String],
+) -> (usize, BTreeMap<String, OracleParticipation>) {
+    let oracle_sets: Vec<(&Vec<String>, &Vec<String>)> = records
+        .iter()
+        .filter_map(|r| {
+            r.collected_oracles
+                .as_ref()
+                .zip(r.uncollected_oracles.as_ref())
+        })
+        .collect();
+    let last_oracle_sets = &oracle_sets[oracle_sets.len().saturating_sub(epochs)..];
+    let mut participation: BTreeMap<String, OracleParticipation> = known_oracles
+        .iter()
+        .map(|address| (address.clone(), OracleParticipation::default()))
+        .collect();
+    for (collected, uncollected) in last_oracle_sets {
+        for address in collected.iter() {
+            let p = participation.entry(address.clone()).or_default();
+            p.participated += 1;
+            p.collected += 1;
+        }
+        for address in uncollected.iter() {
+            participation
+                .entry(address.clone())
+                .or_default()
+                .participated += 1;
+        }
+    }
+    (last_oracle_sets.len(), participation)
+}
+
+fn percent(part: usize, total: usize) -> String {
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.0}%", part as f64 * 100.0 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle_types::BlockHeight;
+    use crate::oracle_types::EpochCounter;
+
+    fn record(epoch: u32, collected: &[&str], uncollected: Option<&[&str]>) -> EpochHistoryRecord {
+        let to_strings = |addresses: &[&str]| -> Vec<String> {
+            addresses.iter().map(|a| a.to_string()).collect()
+        };
+        EpochHistoryRecord {
+            height: BlockHeight(100 + epoch * 30),
+            epoch: EpochCounter(epoch),
+            posted_value: None,
+            consensus_value: 1000.into(),
+            reward_tokens: 0,
+            pool_box_value: None,
+            collected_oracles: uncollected.map(|_| to_strings(collected)),
+            uncollected_oracles: uncollected.map(to_strings),
+        }
+    }
+
+    #[test]
+    fn test_participation() {
+        let records = vec![
+            // recorded by an older version, skipped
+            record(1, &[], None),
+            record(2, &["a", "b"], Some(&["c"])),
+            record(3, &["a", "c"], Some(&[])),
+            record(4, &["a"], Some(&["b"])),
+        ];
+        let (num_epochs, participation) =
+            participation(&records, 2, &["a".to_string(), "d".to_string()]);
+        assert_eq!(num_epochs, 2);
+        assert_eq!(
+            participation.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    "a".to_string(),
+                    OracleParticipation {
+                        participated: 2,
+                        collected: 2
+                    }
+                ),
+                (
+                    "b".to_string(),
+                    OracleParticipation {
+                        participated: 1,
+                        collected: 0
+                    }
+                ),
+                (
+                    "c".to_string(),
+                    OracleParticipation {
+                        participated: 1,
+                        collected: 1
+                    }
+                ),
+                ("d".to_string(), OracleParticipation::default()),
+            ]
+        );
+        assert_eq!(percent(1, 3), "33%");
+        assert_eq!(percent(0, 0), "-");
+    }
+}
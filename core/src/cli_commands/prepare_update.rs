@@ -130,7 +130,7 @@ pub fn prepare_update(
         blake2b_pool_ergo_tree
     );
     print_hints_for_voting(height)?;
-    wait_for_txs_confirmation(submitted_tx_ids);
+    wait_for_txs_confirmation(submitted_tx_ids)?;
     Ok(())
 }
 
@@ -0,0 +1,202 @@
+use ergo_lib::ergotree_ir::chain::ergo_box::BoxId;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBoxCandidate;
+use ergo_lib::ergotree_ir::ergo_tree::ErgoTree;
+
+use crate::actions::PoolAction;
+use crate::datapoint_source::RuntimeDataPointSource;
+use crate::oracle_config::action_fee;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_state::OraclePool;
+use crate::oracle_types::BlockHeight;
+use crate::pool_commands::build_action;
+use crate::pool_commands::PoolCommand;
+use crate::pool_commands::DEFAULT_ACTION_PRIORITY;
+use crate::pool_config::POOL_CONFIG;
+use crate::state::process;
+use crate::state::PoolState;
+use crate::wallet::WalletDataSource;
+
+/// Wallet boxes spent by an action tx and the change sent back to the wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Funding {
+    /// Box ids and values (nanoERG) of the spent wallet boxes
+    pub wallet_inputs: Vec<(BoxId, u64)>,
+    /// Values (nanoERG) of the outputs to the change address
+    pub change: Vec<u64>,
+}
+
+impl Funding {
+    pub fn wallet_inputs_value(&self) -> u64 {
+        self.wallet_inputs.iter().map(|(_, value)| value).sum()
+    }
+}
+
+/// Build the next action tx (the publish datapoint one if no action is due) with the box
+/// selection of the main loop and print the wallet boxes it spends and the change it returns
+pub fn preview_funding(
+    op: &OraclePool,
+    wallet: &dyn WalletDataSource,
+    height: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    let datapoint_source = RuntimeDataPointSource::new(
+        POOL_CONFIG.data_point_source,
+        ORACLE_CONFIG.data_point_source_custom_script.clone(),
+        ORACLE_CONFIG.data_point_source_json.clone(),
+    )?;
+    let change_address = wallet.get_change_address()?;
+    let has_oracle_box = op
+        .get_local_datapoint_box_source()
+        .get_local_oracle_datapoint_box()?
+        .is_some();
+    let due_cmd = process(
+        PoolState::LiveEpoch(op.get_live_epoch_state()?),
+        POOL_CONFIG
+            .refresh_box_wrapper_inputs
+            .contract_inputs
+            .contract_parameters()
+            .epoch_length(),
+        height,
+        ORACLE_CONFIG
+            .action_priority
+            .as_deref()
+            .unwrap_or(&DEFAULT_ACTION_PRIORITY),
+        ORACLE_CONFIG.publish_grace_blocks.unwrap_or(0),
+    );
+    let cmd = match due_cmd {
+        Some(cmd) => {
+            println!("Next action at height {}: {:?}", height, cmd);
+            cmd
+        }
+        None => {
+            println!(
+                "No action is due at height {}, previewing the next datapoint publish",
+                height
+            );
+            if has_oracle_box {
+                PoolCommand::PublishSubsequentDataPoint { republish: false }
+            } else {
+                PoolCommand::PublishFirstDataPoint
+            }
+        }
+    };
+    let tx_fee = action_fee(cmd.kind().into());
+    let (action, _) = build_action(
+        cmd,
+        op,
+        wallet,
+        height,
+        change_address.address(),
+        &datapoint_source,
+        tx_fee,
+    )?;
+    let tx = match action {
+        PoolAction::Refresh(action) => action.tx,
+        PoolAction::PublishDatapoint(action) => action.tx,
+    };
+    let input_ids: Vec<BoxId> = tx.inputs.iter().map(|input| input.box_id).collect();
+    let funding = funding(
+        &input_ids,
+        &tx.output_candidates,
+        &wallet.get_unspent_wallet_boxes()?,
+        &change_address.address().script()?,
+    );
+    println!("Wallet boxes spent:");
+    for (box_id, value) in &funding.wallet_inputs {
+        println!("  {}: {} nanoERG", String::from(*box_id), value);
+    }
+    println!("Fee: {} nanoERG", tx_fee.as_u64());
+    println!(
+        "Change to {}: {} nanoERG",
+        change_address.to_base58(),
+        funding.change.iter().sum::<u64>()
+    );
+    println!(
+        "{} nanoERG of the wallet is locked in the tx until it's confirmed",
+        funding.wallet_inputs_value()
+    );
+    Ok(())
+}
+
+fn funding(
+    input_ids: &[BoxId],
+    outputs: &[ErgoBoxCandidate],
+    wallet_boxes: &[ErgoBox],
+    change_tree: &ErgoTree,
+) -> Funding {
+    let wallet_inputs = input_ids
+        .iter()
+        .filter_map(|id| {
+            wallet_boxes
+                .iter()
+                .find(|b| b.box_id() == *id)
+                .map(|b| (*id, b.value.as_u64()))
+        })
+        .collect();
+    let change = outputs
+        .iter()
+        .filter(|candidate| candidate.ergo_tree == *change_tree)
+        .map(|candidate| candidate.value.as_u64())
+        .collect();
+    Funding {
+        wallet_inputs,
+        change,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+    use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisters;
+    use sigma_test_util::force_any_val;
+
+    use super::*;
+    use crate::pool_commands::test_utils::make_wallet_unspent_box;
+
+    #[test]
+    fn test_funding() {
+        let wallet_pk = force_any_val::<DlogProverInput>().public_image();
+        let wallet_boxes: Vec<ErgoBox> = [5_000_000_000u64, 2_000_000]
+            .iter()
+            .map(|value| {
+                make_wallet_unspent_box(wallet_pk.clone(), BoxValue::new(*value).unwrap(), None)
+            })
+            .collect();
+        let change_tree = wallet_boxes[0].ergo_tree.clone();
+        // e.g. the oracle box
+        let other_box = make_wallet_unspent_box(
+            force_any_val::<DlogProverInput>().public_image(),
+            BoxValue::SAFE_USER_MIN,
+            None,
+        );
+        let change = ErgoBoxCandidate {
+            value: BoxValue::new(4_998_000_000).unwrap(),
+            ergo_tree: change_tree.clone(),
+            tokens: None,
+            additional_registers: NonMandatoryRegisters::empty(),
+            creation_height: 1,
+        };
+        let outputs = vec![
+            ErgoBoxCandidate {
+                ergo_tree: other_box.ergo_tree.clone(),
+                ..change.clone()
+            },
+            change,
+        ];
+        let funding = funding(
+            &[other_box.box_id(), wallet_boxes[0].box_id()],
+            &outputs,
+            &wallet_boxes,
+            &change_tree,
+        );
+        assert_eq!(
+            funding,
+            Funding {
+                wallet_inputs: vec![(wallet_boxes[0].box_id(), 5_000_000_000)],
+                change: vec![4_998_000_000],
+            }
+        );
+        assert_eq!(funding.wallet_inputs_value(), 5_000_000_000);
+    }
+}
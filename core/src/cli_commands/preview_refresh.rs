@@ -0,0 +1,122 @@
+use ergo_lib::{
+    ergo_chain_types::EcPoint,
+    ergotree_ir::chain::address::{Address, NetworkAddress},
+};
+
+use crate::{
+    box_kind::{PoolBox, RefreshBox},
+    oracle_config::{action_fee, FeeKind, ORACLE_CONFIG},
+    oracle_state::OraclePool,
+    oracle_types::BlockHeight,
+    output::{output_path, write_json_result},
+    pool_commands::refresh::build_refresh_action,
+    pool_config::POOL_CONFIG,
+    value_format::{describe_rate, DEFAULT_DISPLAY_DECIMALS},
+    wallet::WalletDataSource,
+};
+
+/// Build the refresh tx against the current state and print it without submitting, the unsigned
+/// tx JSON is written to the global `--output` if given
+pub fn preview_refresh(
+    op: &OraclePool,
+    wallet: &dyn WalletDataSource,
+    my_oracle_pk: &EcPoint,
+    height: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    let change_network_address = wallet.get_change_address()?;
+    let network_prefix = change_network_address.network();
+    let refresh_parameters = POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let tx_fee = action_fee(FeeKind::Refresh);
+    let (action, report) = build_refresh_action(
+        op.get_pool_box_source(),
+        op.get_refresh_box_source(),
+        op.get_posted_datapoint_boxes_source(),
+        refresh_parameters.max_deviation_percent() as u32,
+        refresh_parameters.min_data_points(),
+        wallet,
+        height,
+        change_network_address.address(),
+        my_oracle_pk,
+        op.get_buyback_box_source(),
+        tx_fee,
+    )?;
+    let tx = action.tx;
+
+    let pool_box = op.get_pool_box_source().get_pool_box()?;
+    let refresh_box = op.get_refresh_box_source().get_refresh_box()?;
+    let posted_oracle_boxes = op
+        .get_posted_datapoint_boxes_source()
+        .get_posted_datapoint_boxes()?;
+    let decimals = ORACLE_CONFIG
+        .value_display_decimals
+        .unwrap_or(DEFAULT_DISPLAY_DECIMALS);
+    let describe_address =
+        |pk: EcPoint| NetworkAddress::new(network_prefix, &Address::P2Pk(pk.into())).to_base58();
+
+    println!("Refresh tx preview at height {} (NOT submitted)", height);
+    println!("Inputs:");
+    for (idx, input) in tx.inputs.iter().enumerate() {
+        let description = if input.box_id == pool_box.get_box().box_id() {
+            "pool box".to_string()
+        } else if input.box_id == refresh_box.get_box().box_id() {
+            "refresh box".to_string()
+        } else if let Some(oracle_box) = posted_oracle_boxes
+            .iter()
+            .find(|b| b.get_box().box_id() == input.box_id)
+        {
+            format!(
+                "oracle box of {} with datapoint {}",
+                describe_address(oracle_box.public_key()),
+                describe_rate(oracle_box.rate(), POOL_CONFIG.data_point_source, decimals)
+            )
+        } else {
+            "wallet or buyback box".to_string()
+        };
+        println!("  #{} {}: {}", idx, String::from(input.box_id), description);
+    }
+    println!("Outputs:");
+    for (idx, candidate) in tx.output_candidates.iter().enumerate() {
+        let description = if candidate.ergo_tree == pool_box.get_box().ergo_tree {
+            "pool box"
+        } else if candidate.ergo_tree == refresh_box.get_box().ergo_tree {
+            "refresh box"
+        } else if posted_oracle_boxes
+            .iter()
+            .any(|b| b.get_box().ergo_tree == candidate.ergo_tree)
+        {
+            "oracle box"
+        } else {
+            "other (change, fee or buyback box)"
+        };
+        let num_tokens = candidate.tokens.as_ref().map(|t| t.len()).unwrap_or(0);
+        println!(
+            "  #{} {}: {} nanoERG, {} token(s)",
+            idx,
+            description,
+            candidate.value.as_u64(),
+            num_tokens
+        );
+    }
+    println!(
+        "Collected datapoints from: {}",
+        report
+            .oracle_boxes_collected
+            .into_iter()
+            .map(describe_address)
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    println!(
+        "New pool rate: {}",
+        describe_rate(report.pool_rate, POOL_CONFIG.data_point_source, decimals)
+    );
+    println!("Fee: {} nanoERG", tx_fee.as_u64());
+    if output_path().is_none() {
+        println!("Unsigned tx:");
+    }
+    write_json_result("Unsigned tx", &serde_json::to_string_pretty(&tx)?)?;
+    Ok(())
+}
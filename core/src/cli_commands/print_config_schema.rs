@@ -0,0 +1,48 @@
+use clap::ValueEnum;
+use schemars::schema_for;
+
+use crate::oracle_config::OracleConfig;
+use crate::output::write_json_result;
+use crate::serde::PoolConfigSerde;
+
+/// Config file to print the JSON Schema of
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ConfigSchema {
+    /// oracle_config.yaml
+    Oracle,
+    /// pool_config.yaml
+    Pool,
+}
+
+/// Print the JSON Schema of the config file, generated from the types it's deserialized into,
+/// to validate the YAML configs with editors or in CI
+pub fn print_config_schema(config: ConfigSchema) -> Result<(), anyhow::Error> {
+    let schema = match config {
+        ConfigSchema::Oracle => schema_for!(OracleConfig),
+        ConfigSchema::Pool => schema_for!(PoolConfigSerde),
+    };
+    let schema = serde_json::to_string_pretty(&schema)?;
+    write_json_result("Config schema", &schema)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle_config_schema() {
+        let schema = serde_json::to_value(schema_for!(OracleConfig)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(properties["node_url"]["type"], "string");
+        assert_eq!(properties["oracle_address"]["type"], "string");
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert!(required.contains(&"node_api_key"));
+        assert!(!required.contains(&"datapoint_transform"));
+    }
+}
@@ -0,0 +1,219 @@
+use ergo_lib::ergo_chain_types::EcPoint;
+
+use crate::box_kind::PoolBox;
+use crate::box_kind::RefreshBox;
+use crate::oracle_config::action_fee;
+use crate::oracle_config::FeeKind;
+use crate::oracle_state::OraclePool;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::MinDatapoints;
+use crate::oracle_types::Rate;
+use crate::pool_commands::refresh::build_refresh_action;
+use crate::pool_commands::refresh::filtered_oracle_boxes_by_rate;
+use crate::pool_config::POOL_CONFIG;
+use crate::wallet::check_funds;
+use crate::wallet::WalletDataSource;
+
+/// Outcome of one of the refresh preconditions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadinessCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl ReadinessCheck {
+    fn new(name: &'static str, passed: bool, detail: String) -> Self {
+        ReadinessCheck {
+            name,
+            passed,
+            detail,
+        }
+    }
+}
+
+/// Run the refresh preconditions against the current state without submitting anything and
+/// print pass/fail for each: epoch end, datapoints of the epoch, their consensus, this oracle's
+/// datapoint among them, pool box reward tokens, wallet funds and the tx build itself
+pub fn refresh_readiness(
+    op: &OraclePool,
+    wallet: &dyn WalletDataSource,
+    my_oracle_pk: &EcPoint,
+    height: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    let refresh_parameters = POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters();
+    let max_deviation_percent = refresh_parameters.max_deviation_percent() as u32;
+    let min_data_points = refresh_parameters.min_data_points();
+    let pool_box = op.get_pool_box_source().get_pool_box()?;
+    let refresh_box = op.get_refresh_box_source().get_refresh_box()?;
+    let epoch_length = refresh_box.contract().epoch_length();
+    let min_start_height = height - epoch_length;
+    let pool_box_height = pool_box.get_box().creation_height;
+    let mut checks = vec![];
+
+    let epoch_end = pool_box_height + epoch_length.0 as u32;
+    checks.push(ReadinessCheck::new(
+        "epoch ended",
+        pool_box_height < min_start_height.0,
+        format!(
+            "pool box of epoch {} created at height {}, refresh possible after height {} \
+            (current {})",
+            pool_box.epoch_counter().0,
+            pool_box_height,
+            epoch_end,
+            height.0
+        ),
+    ));
+
+    let epoch_boxes: Vec<_> = op
+        .get_posted_datapoint_boxes_source()
+        .get_posted_datapoint_boxes()?
+        .into_iter()
+        .filter(|b| {
+            b.get_box().creation_height > min_start_height.0
+                && b.epoch_counter() == pool_box.epoch_counter()
+        })
+        .collect();
+    let rates: Vec<Rate> = epoch_boxes.iter().map(|b| b.rate()).collect();
+    let consensus = consensus_rates(rates.clone(), max_deviation_percent);
+    checks.extend(datapoint_checks(
+        &rates,
+        &consensus,
+        max_deviation_percent,
+        min_data_points,
+    ));
+    let my_rate = epoch_boxes
+        .iter()
+        .find(|b| b.public_key() == *my_oracle_pk)
+        .map(|b| b.rate());
+    checks.push(match my_rate {
+        Some(rate) => ReadinessCheck::new(
+            "own datapoint in consensus",
+            consensus.contains(&rate),
+            format!("this oracle posted {}", rate),
+        ),
+        None => ReadinessCheck::new(
+            "own datapoint in consensus",
+            false,
+            "this oracle has no datapoint of the epoch, only an oracle with one can refresh"
+                .to_string(),
+        ),
+    });
+
+    let reward_tokens = *pool_box.reward_token().amount.as_u64();
+    let reward_needed = consensus.len().max(min_data_points.0 as usize) as u64 * 2;
+    checks.push(ReadinessCheck::new(
+        "pool box reward tokens",
+        reward_tokens >= reward_needed,
+        format!(
+            "{} in the pool box, {} paid out by the refresh",
+            reward_tokens, reward_needed
+        ),
+    ));
+
+    let tx_fee = action_fee(FeeKind::Refresh);
+    let wallet_boxes = wallet.get_unspent_wallet_boxes()?;
+    checks.push(match check_funds(&wallet_boxes, tx_fee) {
+        Ok(()) => ReadinessCheck::new(
+            "wallet funds",
+            true,
+            format!("the {} nanoERG fee is covered", tx_fee.as_u64()),
+        ),
+        Err(e) => ReadinessCheck::new("wallet funds", false, e.to_string()),
+    });
+
+    let change_address = wallet.get_change_address()?;
+    let build_res = build_refresh_action(
+        op.get_pool_box_source(),
+        op.get_refresh_box_source(),
+        op.get_posted_datapoint_boxes_source(),
+        max_deviation_percent,
+        min_data_points,
+        wallet,
+        height,
+        change_address.address(),
+        my_oracle_pk,
+        op.get_buyback_box_source(),
+        tx_fee,
+    );
+    checks.push(match build_res {
+        Ok((_, report)) => ReadinessCheck::new(
+            "refresh tx builds",
+            true,
+            format!("new pool rate {}", report.pool_rate),
+        ),
+        Err(e) => ReadinessCheck::new("refresh tx builds", false, e.to_string()),
+    });
+
+    println!("Refresh readiness at height {}:", height.0);
+    for check in &checks {
+        println!(
+            "  [{}] {}: {}",
+            if check.passed { "PASS" } else { "FAIL" },
+            check.name,
+            check.detail
+        );
+    }
+    let failed = checks.iter().filter(|c| !c.passed).count();
+    if failed == 0 {
+        println!("The refresh is possible now");
+    } else {
+        println!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Datapoints left after removing the outliers, none if too few are left
+fn consensus_rates(rates: Vec<Rate>, max_deviation_percent: u32) -> Vec<Rate> {
+    let mut rates = rates;
+    rates.sort();
+    filtered_oracle_boxes_by_rate(rates, max_deviation_percent).unwrap_or_default()
+}
+
+fn datapoint_checks(
+    rates: &[Rate],
+    consensus: &[Rate],
+    max_deviation_percent: u32,
+    min_data_points: MinDatapoints,
+) -> Vec<ReadinessCheck> {
+    let required = min_data_points.0.max(0) as usize;
+    vec![
+        ReadinessCheck::new(
+            "datapoints of the epoch",
+            rates.len() >= required,
+            format!("{} posted, at least {} needed", rates.len(), required),
+        ),
+        ReadinessCheck::new(
+            "datapoints in consensus",
+            consensus.len() >= required,
+            format!(
+                "{} of {} within {}% of each other, at least {} needed",
+                consensus.len(),
+                rates.len(),
+                max_deviation_percent,
+                required
+            ),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_datapoint_checks() {
+        let rates: Vec<Rate> = vec![100.into(), 101.into(), 102.into(), 150.into()];
+        let consensus = consensus_rates(rates.clone(), 5);
+        assert_eq!(consensus.len(), 3);
+        let checks = datapoint_checks(&rates, &consensus, 5, MinDatapoints(4));
+        assert!(checks[0].passed);
+        assert!(!checks[1].passed);
+        let checks = datapoint_checks(&rates, &consensus, 5, MinDatapoints(3));
+        assert!(checks.iter().all(|c| c.passed));
+        assert!(consensus_rates(vec![], 5).is_empty());
+    }
+}
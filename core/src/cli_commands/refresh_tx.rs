@@ -0,0 +1,65 @@
+//! The boxes spent by the refresh tx that created the pool box, fetched from the explorer (the
+//! node doesn't serve the spent boxes without the extra indexer). The oracle boxes the pool value
+//! was computed from are only there, the oracles post new ones after the refresh.
+
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+
+use crate::box_kind::OracleBoxWrapper;
+use crate::box_kind::PostedOracleBox;
+use crate::explorer_api::ExplorerApi;
+use crate::explorer_api::ExplorerApiError;
+use crate::pool_config::PoolConfig;
+use crate::spec_token::TokenIdKind;
+
+pub struct RefreshTxInputs {
+    /// Pool box of the previous epoch
+    pub previous_pool_box: ErgoBox,
+    /// Oracle boxes whose datapoints were collected
+    pub oracle_boxes: Vec<PostedOracleBox>,
+}
+
+/// Inputs of the tx that created `pool_box`, `None` if it wasn't a refresh (e.g. `bootstrap` or
+/// `update-pool`)
+pub fn refresh_tx_inputs(
+    pool_box: &ErgoBox,
+    pool_config: &PoolConfig,
+) -> Result<Option<RefreshTxInputs>, ExplorerApiError> {
+    let explorer_api = ExplorerApi::from_config();
+    let tx = explorer_api.get_transaction_v1(pool_box.transaction_id)?;
+    let mut inputs = Vec::new();
+    for input in tx.inputs.iter() {
+        inputs.push(explorer_api.get_box_v1(input.box_id)?);
+    }
+    let token_ids = &pool_config.token_ids;
+    let first_token_is = |b: &ErgoBox, token_id| {
+        b.tokens
+            .as_ref()
+            .map_or(false, |tokens| tokens.first().token_id == token_id)
+    };
+    if !inputs
+        .iter()
+        .any(|b| first_token_is(b, token_ids.refresh_nft_token_id.token_id()))
+    {
+        return Ok(None);
+    }
+    let previous_pool_box = match inputs
+        .iter()
+        .find(|b| first_token_is(b, token_ids.pool_nft_token_id.token_id()))
+    {
+        Some(previous_pool_box) => previous_pool_box.clone(),
+        None => return Ok(None),
+    };
+    let oracle_boxes = inputs
+        .into_iter()
+        .filter_map(
+            |b| match OracleBoxWrapper::new(b, &pool_config.oracle_box_wrapper_inputs) {
+                Ok(OracleBoxWrapper::Posted(posted)) => Some(posted),
+                Ok(OracleBoxWrapper::Collected(_)) | Err(_) => None,
+            },
+        )
+        .collect();
+    Ok(Some(RefreshTxInputs {
+        previous_pool_box,
+        oracle_boxes,
+    }))
+}
@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use crate::epoch_history::read_records;
+use crate::epoch_history::EpochHistoryError;
+use crate::epoch_history::EpochHistoryRecord;
+
+/// Outcome of comparing the oracle box reward tokens of two consecutive epochs against the
+/// refresh contract rules: every collected oracle gets 1 reward token and the collector gets
+/// another one per collected datapoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewardAudit {
+    /// Reward tokens changed as expected
+    Ok { earned: u64 },
+    /// Reward tokens changed by something else than the contract allows
+    Discrepancy { delta: u64, expected: Vec<u64> },
+    /// Collected less datapoints than `min_data_points` of the refresh contract
+    TooFewCollected { collected: usize },
+    /// Reward tokens decreased, i.e. were extracted, so the delta can't be checked
+    Extracted,
+    /// Recorded by an older version without the oracle sets
+    NoOracleSets,
+}
+
+/// Check the reward tokens earned in the last recorded epoch
+pub fn reward_audit(
+    history_file_path: &Path,
+    oracle_address: &str,
+    min_data_points: usize,
+) -> Result<(), EpochHistoryError> {
+    let records = read_records(history_file_path)?;
+    let (prev, last) = match records.as_slice() {
+        [.., prev, last] if last.epoch.0 == prev.epoch.0 + 1 => (prev, last),
+        _ => {
+            println!(
+                "No two consecutive epochs recorded in the history at {}, they are recorded while \
+                running the oracle with `run`",
+                history_file_path.display()
+            );
+            return Ok(());
+        }
+    };
+    println!(
+        "Reward tokens in epoch {}: {} -> {}",
+        last.epoch.0, prev.reward_tokens, last.reward_tokens
+    );
+    match audit(prev, last, oracle_address, min_data_points) {
+        RewardAudit::Ok { earned } => println!("OK: earned {} as expected", earned),
+        RewardAudit::Discrepancy { delta, expected } => println!(
+            "DISCREPANCY: reward tokens changed by {}, expected one of {:?}",
+            delta, expected
+        ),
+        RewardAudit::TooFewCollected { collected } => println!(
+            "DISCREPANCY: {} datapoints collected, the refresh contract requires at least {}",
+            collected, min_data_points
+        ),
+        RewardAudit::Extracted => {
+            println!("Reward tokens were extracted in this epoch, nothing to check")
+        }
+        RewardAudit::NoOracleSets => {
+            println!("The epoch was recorded without the oracle sets, nothing to check")
+        }
+    }
+    Ok(())
+}
+
+fn audit(
+    prev: &EpochHistoryRecord,
+    last: &EpochHistoryRecord,
+    oracle_address: &str,
+    min_data_points: usize,
+) -> RewardAudit {
+    let collected = match &last.collected_oracles {
+        Some(collected) => collected,
+        None => return RewardAudit::NoOracleSets,
+    };
+    if collected.len() < min_data_points {
+        return RewardAudit::TooFewCollected {
+            collected: collected.len(),
+        };
+    }
+    if last.reward_tokens < prev.reward_tokens {
+        return RewardAudit::Extracted;
+    }
+    let delta = last.reward_tokens - prev.reward_tokens;
+    let expected = if collected.iter().any(|a| a == oracle_address) {
+        // 1 as a collected oracle, or 1 + 1 per collected datapoint as the collector
+        vec![1, 1 + collected.len() as u64]
+    } else {
+        vec![0]
+    };
+    if expected.contains(&delta) {
+        RewardAudit::Ok { earned: delta }
+    } else {
+        RewardAudit::Discrepancy { delta, expected }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle_types::BlockHeight;
+    use crate::oracle_types::EpochCounter;
+
+    fn record(epoch: u32, reward_tokens: u64, collected: Option<&[&str]>) -> EpochHistoryRecord {
+        EpochHistoryRecord {
+            height: BlockHeight(100 + epoch * 30),
+            epoch: EpochCounter(epoch),
+            posted_value: None,
+            consensus_value: 1000.into(),
+            reward_tokens,
+            pool_box_value: None,
+            collected_oracles: collected.map(|c| c.iter().map(|a| a.to_string()).collect()),
+            uncollected_oracles: collected.map(|_| Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_audit() {
+        let prev = record(1, 10, Some(&["a", "b", "c"]));
+        let collected = Some(&["a", "b", "c"][..]);
+        assert_eq!(
+            audit(&prev, &record(2, 11, collected), "a", 2),
+            RewardAudit::Ok { earned: 1 }
+        );
+        // collector
+        assert_eq!(
+            audit(&prev, &record(2, 14, collected), "a", 2),
+            RewardAudit::Ok { earned: 4 }
+        );
+        assert_eq!(
+            audit(&prev, &record(2, 12, collected), "a", 2),
+            RewardAudit::Discrepancy {
+                delta: 2,
+                expected: vec![1, 4]
+            }
+        );
+        assert_eq!(
+            audit(&prev, &record(2, 11, collected), "d", 2),
+            RewardAudit::Discrepancy {
+                delta: 1,
+                expected: vec![0]
+            }
+        );
+        assert_eq!(
+            audit(&prev, &record(2, 1, collected), "a", 2),
+            RewardAudit::Extracted
+        );
+        assert_eq!(
+            audit(&prev, &record(2, 11, Some(&["a"])), "a", 2),
+            RewardAudit::TooFewCollected { collected: 1 }
+        );
+        assert_eq!(
+            audit(&prev, &record(2, 11, None), "a", 2),
+            RewardAudit::NoOracleSets
+        );
+    }
+}
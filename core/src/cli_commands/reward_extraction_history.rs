@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use ergo_lib::ergotree_ir::chain::address::AddressEncoder;
+use ergo_lib::ergotree_ir::serialization::SigmaSerializable;
+
+use crate::node_interface::node_api::NodeApi;
+use crate::node_interface::node_api::WalletTransaction;
+use crate::reward_extraction_history::read_records;
+use crate::reward_extraction_history::RewardExtractionRecord;
+
+/// On-chain state of a recorded extraction tx
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractionStatus {
+    Confirmed {
+        inclusion_height: u32,
+        confirmations: u32,
+    },
+    /// In the mempool or the node wallet hasn't seen it included yet
+    Unconfirmed,
+    /// The node wallet doesn't know the tx, e.g. it was dropped from the mempool
+    NotFound,
+    /// The tx doesn't send the recorded amounts, listed as `amount to address`
+    Mismatch { missing: Vec<String> },
+}
+
+/// List the reward tokens extractions recorded by `extract-reward-tokens` and check each of
+/// them against the tx in the node wallet
+pub fn reward_extraction_history(
+    history_file_path: &Path,
+    node_api: &NodeApi,
+) -> Result<(), anyhow::Error> {
+    let records = read_records(history_file_path)?;
+    if records.is_empty() {
+        println!(
+            "No reward tokens extractions recorded in {}, they are recorded by \
+            `extract-reward-tokens`",
+            history_file_path.display()
+        );
+        return Ok(());
+    }
+    let mut totals: Vec<(String, u64)> = Vec::new();
+    for record in &records {
+        let tx = node_api.get_wallet_transaction(&record.tx_id)?;
+        let expected_ergo_trees = record
+            .transfers
+            .iter()
+            .map(|transfer| address_ergo_tree(&transfer.address))
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let status = extraction_status(record, &expected_ergo_trees, tx.as_ref());
+        println!("Height {}, tx {}:", record.height.0, record.tx_id);
+        for transfer in &record.transfers {
+            println!(
+                "  {} reward tokens to {}",
+                transfer.amount, transfer.address
+            );
+            if matches!(status, ExtractionStatus::Confirmed { .. }) {
+                match totals.iter_mut().find(|(a, _)| *a == transfer.address) {
+                    Some((_, total)) => *total += transfer.amount,
+                    None => totals.push((transfer.address.clone(), transfer.amount)),
+                }
+            }
+        }
+        match status {
+            ExtractionStatus::Confirmed {
+                inclusion_height,
+                confirmations,
+            } => println!(
+                "  confirmed at height {} ({} confirmations)",
+                inclusion_height, confirmations
+            ),
+            ExtractionStatus::Unconfirmed => println!("  not confirmed yet"),
+            ExtractionStatus::NotFound => println!("  NOT FOUND in the node wallet"),
+            ExtractionStatus::Mismatch { missing } => {
+                println!("  MISMATCH: the tx doesn't send {}", missing.join(", "))
+            }
+        }
+    }
+    println!("Total sent (confirmed txs only):");
+    for (address, total) in totals {
+        println!("  {} reward tokens to {}", total, address);
+    }
+    Ok(())
+}
+
+fn address_ergo_tree(address: &str) -> Result<String, anyhow::Error> {
+    let ergo_tree = AddressEncoder::unchecked_parse_network_address_from_str(address)?
+        .address()
+        .script()?;
+    Ok(base16::encode_lower(&ergo_tree.sigma_serialize_bytes()?))
+}
+
+/// `expected_ergo_trees` are the base16 encoded ergo trees of the addresses of
+/// `record.transfers`, in the same order
+fn extraction_status(
+    record: &RewardExtractionRecord,
+    expected_ergo_trees: &[String],
+    tx: Option<&WalletTransaction>,
+) -> ExtractionStatus {
+    let tx = match tx {
+        Some(tx) => tx,
+        None => return ExtractionStatus::NotFound,
+    };
+    let missing: Vec<String> = record
+        .transfers
+        .iter()
+        .zip(expected_ergo_trees)
+        .filter(|(transfer, ergo_tree)| {
+            !tx.outputs.iter().any(|output| {
+                output.ergo_tree == **ergo_tree
+                    && output.tokens.iter().any(|(token_id, amount)| {
+                        *token_id == record.reward_token_id && *amount == transfer.amount
+                    })
+            })
+        })
+        .map(|(transfer, _)| format!("{} to {}", transfer.amount, transfer.address))
+        .collect();
+    if !missing.is_empty() {
+        return ExtractionStatus::Mismatch { missing };
+    }
+    match tx.inclusion_height {
+        Some(inclusion_height) if tx.num_confirmations > 0 => ExtractionStatus::Confirmed {
+            inclusion_height,
+            confirmations: tx.num_confirmations,
+        },
+        _ => ExtractionStatus::Unconfirmed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_interface::node_api::WalletTransactionOutput;
+    use crate::oracle_types::BlockHeight;
+    use crate::reward_extraction_history::RewardTransfer;
+
+    #[test]
+    fn test_extraction_status() {
+        let token_id = "ab".repeat(32);
+        let record = RewardExtractionRecord {
+            tx_id: "cd".repeat(32),
+            height: BlockHeight(1000),
+            reward_token_id: token_id.clone(),
+            transfers: vec![
+                RewardTransfer {
+                    address: "address1".to_string(),
+                    amount: 30,
+                },
+                RewardTransfer {
+                    address: "address2".to_string(),
+                    amount: 12,
+                },
+            ],
+        };
+        let ergo_trees = vec!["tree1".to_string(), "tree2".to_string()];
+        let output = |ergo_tree: &str, amount: u64| WalletTransactionOutput {
+            ergo_tree: ergo_tree.to_string(),
+            tokens: vec![(token_id.clone(), amount)],
+        };
+        let tx = WalletTransaction {
+            inclusion_height: Some(1001),
+            num_confirmations: 5,
+            input_box_ids: vec![],
+            outputs: vec![
+                output("oracle", 1),
+                output("tree1", 30),
+                output("tree2", 12),
+            ],
+        };
+        assert_eq!(
+            extraction_status(&record, &ergo_trees, Some(&tx)),
+            ExtractionStatus::Confirmed {
+                inclusion_height: 1001,
+                confirmations: 5
+            }
+        );
+        assert_eq!(
+            extraction_status(&record, &ergo_trees, None),
+            ExtractionStatus::NotFound
+        );
+        let unconfirmed = WalletTransaction {
+            inclusion_height: None,
+            num_confirmations: 0,
+            ..tx.clone()
+        };
+        assert_eq!(
+            extraction_status(&record, &ergo_trees, Some(&unconfirmed)),
+            ExtractionStatus::Unconfirmed
+        );
+        let wrong_amount = WalletTransaction {
+            outputs: vec![output("tree1", 30), output("tree2", 11)],
+            ..tx
+        };
+        assert_eq!(
+            extraction_status(&record, &ergo_trees, Some(&wrong_amount)),
+            ExtractionStatus::Mismatch {
+                missing: vec!["12 to address2".to_string()]
+            }
+        );
+    }
+}
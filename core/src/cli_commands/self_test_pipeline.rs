@@ -0,0 +1,455 @@
+//! End-to-end smoke test of the posting pipeline on testnet: bootstrap a throwaway pool, publish a
+//! datapoint to it, refresh it and burn the leftover throwaway tokens. Neither a pool config file
+//! nor scans are created for the throwaway pool, its boxes are taken from the submitted txs.
+
+use std::cell::RefCell;
+use std::convert::TryInto;
+use std::fmt::Display;
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use ergo_lib::chain::ergo_box::box_builder::ErgoBoxCandidateBuilder;
+use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+use ergo_lib::chain::transaction::Transaction;
+use ergo_lib::chain::transaction::TxId;
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::token::Token;
+use ergo_lib::ergotree_ir::chain::token::TokenId;
+use ergo_lib::wallet::box_selector::BoxSelector;
+use ergo_lib::wallet::box_selector::SimpleBoxSelector;
+use ergo_lib::wallet::tx_builder::TxBuilder;
+use thiserror::Error;
+
+use crate::box_kind::PoolBox;
+use crate::box_kind::PoolBoxWrapper;
+use crate::box_kind::PostedOracleBox;
+use crate::box_kind::RefreshBox;
+use crate::box_kind::RefreshBoxWrapper;
+use crate::cli_commands::bootstrap::perform_bootstrap_chained_transaction;
+use crate::cli_commands::bootstrap::BootstrapConfig;
+use crate::cli_commands::bootstrap::BootstrapInput;
+use crate::contracts::refresh::RefreshContractParameters;
+use crate::contracts::refresh::RefreshContractParametersInputs;
+use crate::datapoint_source::DataPointSource;
+use crate::datapoint_source::DataPointSourceError;
+use crate::explorer_api::wait_for_tx_confirmation;
+use crate::explorer_api::wait_for_txs_confirmation;
+use crate::node_interface::node_api::NodeApi;
+use crate::node_interface::SignTransactionWithInputs;
+use crate::node_interface::SubmitTransaction;
+use crate::oracle_config::BASE_FEE;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_state::PoolBoxSource;
+use crate::oracle_state::PostedDatapointBoxesSource;
+use crate::oracle_state::RefreshBoxSource;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::EpochLength;
+use crate::oracle_types::MinDatapoints;
+use crate::oracle_types::Rate;
+use crate::pool_commands::publish_datapoint::build_publish_first_datapoint_action;
+use crate::pool_commands::refresh::build_refresh_action;
+use crate::pool_config::PoolConfig;
+use crate::spec_token::TokenIdKind;
+use crate::wallet::WalletDataSource;
+
+/// Short epoch so that the refresh can be done a few blocks after the datapoint is posted
+const SELF_TEST_EPOCH_LENGTH: EpochLength = EpochLength(10);
+const SELF_TEST_DATAPOINT: i64 = 1_000_000;
+const HEIGHT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestStep {
+    Bootstrap,
+    PublishDatapoint,
+    Refresh,
+    Teardown,
+}
+
+impl Display for SelfTestStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SelfTestStep::Bootstrap => "bootstrap",
+            SelfTestStep::PublishDatapoint => "publish datapoint",
+            SelfTestStep::Refresh => "refresh",
+            SelfTestStep::Teardown => "teardown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("self-test failed at the {step} step: {error:?}")]
+pub struct SelfTestPipelineError {
+    pub step: SelfTestStep,
+    pub error: anyhow::Error,
+}
+
+/// Run the whole pipeline against the node of the oracle config, which must be on testnet. The
+/// ERG in the throwaway pool, refresh, update and oracle boxes stays locked by their contracts.
+pub fn self_test_pipeline(node_api: &NodeApi) -> Result<(), anyhow::Error> {
+    if ORACLE_CONFIG.oracle_address.network() != NetworkPrefix::Testnet {
+        return Err(anyhow!(
+            "self-test-pipeline mints tokens and creates boxes, it runs on testnet only"
+        ));
+    }
+    let change_address = node_api.get_change_address()?.address();
+    let (pool_config, pool_box, refresh_box) = step(SelfTestStep::Bootstrap, || {
+        bootstrap_throwaway_pool(node_api)
+    })?;
+    let pool_box_height = BlockHeight(pool_box.get_box().creation_height);
+    let oracle_box = step(SelfTestStep::PublishDatapoint, || {
+        wait_for_height(node_api, publish_height(pool_box_height))?;
+        publish_datapoint(node_api, &pool_config, change_address.clone())
+    })?;
+    step(SelfTestStep::Refresh, || {
+        wait_for_height(node_api, refresh_height(pool_box_height))?;
+        refresh(
+            node_api,
+            pool_box,
+            refresh_box,
+            oracle_box,
+            change_address.clone(),
+        )
+    })?;
+    step(SelfTestStep::Teardown, || {
+        burn_wallet_tokens(
+            node_api,
+            &[
+                pool_config.token_ids.ballot_token_id.token_id(),
+                pool_config.token_ids.oracle_token_id.token_id(),
+                pool_config.token_ids.reward_token_id.token_id(),
+            ],
+            change_address.clone(),
+        )
+    })?;
+    println!("Self-test passed: bootstrapped a pool, published a datapoint and refreshed the pool");
+    Ok(())
+}
+
+fn step<T>(
+    step: SelfTestStep,
+    f: impl FnOnce() -> Result<T, anyhow::Error>,
+) -> Result<T, SelfTestPipelineError> {
+    println!("Self-test step: {}", step);
+    f().map_err(|error| SelfTestPipelineError { step, error })
+}
+
+/// The datapoint is posted in the second half of the first epoch so that it's still within the
+/// epoch length when the epoch ends and the pool can be refreshed
+fn publish_height(pool_box_height: BlockHeight) -> BlockHeight {
+    BlockHeight(pool_box_height.0 + SELF_TEST_EPOCH_LENGTH.0 as u32 / 2)
+}
+
+fn refresh_height(pool_box_height: BlockHeight) -> BlockHeight {
+    BlockHeight(pool_box_height.0 + SELF_TEST_EPOCH_LENGTH.0 as u32 + 1)
+}
+
+/// Default bootstrap config with a single oracle token, a refresh with a single datapoint and a
+/// short epoch
+fn self_test_bootstrap_config() -> Result<BootstrapConfig, anyhow::Error> {
+    let mut config = BootstrapConfig::default();
+    let p = config.refresh_contract_parameters.clone();
+    config.refresh_contract_parameters =
+        RefreshContractParameters::build_with(RefreshContractParametersInputs {
+            ergo_tree_bytes: p.ergo_tree_bytes(),
+            pool_nft_index: p.pool_nft_index(),
+            oracle_token_id_index: p.oracle_token_id_index(),
+            min_data_points_index: p.min_data_points_index(),
+            min_data_points: MinDatapoints(1),
+            buffer_length_index: p.buffer_length_index(),
+            buffer_length: p.buffer_length(),
+            max_deviation_percent_index: p.max_deviation_percent_index(),
+            max_deviation_percent: p.max_deviation_percent(),
+            epoch_length_index: p.epoch_length_index(),
+            epoch_length: SELF_TEST_EPOCH_LENGTH,
+        })?;
+    let tokens = &mut config.tokens_to_mint;
+    tokens.oracle_tokens.name = "self-test oracle token".into();
+    tokens.oracle_tokens.quantity = 1;
+    tokens.ballot_tokens.name = "self-test ballot token".into();
+    tokens.ballot_tokens.quantity = 1;
+    tokens.reward_tokens.name = "self-test reward token".into();
+    tokens.reward_tokens.quantity = 100;
+    tokens.pool_nft.name = "self-test pool NFT".into();
+    tokens.refresh_nft.name = "self-test refresh NFT".into();
+    tokens.update_nft.name = "self-test update NFT".into();
+    Ok(config)
+}
+
+/// Submits the txs to the node and keeps them to find the created boxes
+struct RecordingSubmitTx<'a> {
+    submit_tx: &'a dyn SubmitTransaction,
+    transactions: RefCell<Vec<Transaction>>,
+}
+
+impl SubmitTransaction for RecordingSubmitTx<'_> {
+    fn submit_transaction(&self, tx: &Transaction) -> crate::node_interface::Result<TxId> {
+        let tx_id = self.submit_tx.submit_transaction(tx)?;
+        self.transactions.borrow_mut().push(tx.clone());
+        Ok(tx_id)
+    }
+}
+
+fn bootstrap_throwaway_pool(
+    node_api: &NodeApi,
+) -> Result<(PoolConfig, PoolBoxWrapper, RefreshBoxWrapper), anyhow::Error> {
+    let config = self_test_bootstrap_config()?;
+    let erg_value_per_box = config.oracle_contract_parameters.min_storage_rent;
+    let submit_tx = RecordingSubmitTx {
+        submit_tx: &node_api.node,
+        transactions: RefCell::new(Vec::new()),
+    };
+    let input = BootstrapInput {
+        oracle_address: ORACLE_CONFIG.oracle_address.clone(),
+        config,
+        wallet: node_api as &dyn WalletDataSource,
+        tx_signer: &node_api.node as &dyn SignTransactionWithInputs,
+        submit_tx: &submit_tx,
+        tx_fee: *BASE_FEE,
+        erg_value_per_box,
+        change_address: node_api.get_change_address()?.address(),
+        height: BlockHeight(node_api.current_block_height()? as u32),
+    };
+    let (pool_config, submitted_tx_ids) = perform_bootstrap_chained_transaction(input)?;
+    wait_for_txs_confirmation(submitted_tx_ids)?;
+    let transactions = submit_tx.transactions.into_inner();
+    let pool_box = PoolBoxWrapper::new(
+        find_box_with_token(
+            &transactions,
+            &pool_config.token_ids.pool_nft_token_id.token_id(),
+        )?,
+        &pool_config.pool_box_wrapper_inputs,
+    )?;
+    let refresh_box = RefreshBoxWrapper::new(
+        find_box_with_token(
+            &transactions,
+            &pool_config.token_ids.refresh_nft_token_id.token_id(),
+        )?,
+        &pool_config.refresh_box_wrapper_inputs,
+    )?;
+    println!(
+        "Bootstrapped the throwaway pool, pool NFT {}",
+        String::from(pool_config.token_ids.pool_nft_token_id.token_id())
+    );
+    Ok((pool_config, pool_box, refresh_box))
+}
+
+/// The last created box holding the token (the NFT boxes are spent into the pool and refresh
+/// boxes by the later txs)
+fn find_box_with_token(
+    transactions: &[Transaction],
+    token_id: &TokenId,
+) -> Result<ErgoBox, anyhow::Error> {
+    transactions
+        .iter()
+        .flat_map(|tx| tx.outputs.iter())
+        .filter(|b| {
+            b.tokens
+                .as_ref()
+                .map(|tokens| tokens.iter().any(|t| &t.token_id == token_id))
+                .unwrap_or(false)
+        })
+        .last()
+        .cloned()
+        .ok_or_else(|| anyhow!("no box with token {}", String::from(*token_id)))
+}
+
+struct FixedDatapoint(Rate);
+
+impl DataPointSource for FixedDatapoint {
+    fn get_datapoint(&self) -> Result<Rate, DataPointSourceError> {
+        Ok(self.0)
+    }
+}
+
+fn publish_datapoint(
+    node_api: &NodeApi,
+    pool_config: &PoolConfig,
+    change_address: Address,
+) -> Result<PostedOracleBox, anyhow::Error> {
+    let public_key = *ORACLE_CONFIG.oracle_address_p2pk()?.h;
+    let height = BlockHeight(node_api.current_block_height()? as u32);
+    let (action, _) = build_publish_first_datapoint_action(
+        node_api,
+        height,
+        change_address,
+        public_key,
+        pool_config.oracle_box_wrapper_inputs.clone(),
+        &FixedDatapoint(SELF_TEST_DATAPOINT.into()),
+        EpochCounter(1),
+        false,
+        *BASE_FEE,
+    )?;
+    let oracle_box = submit_and_confirm(node_api, &action.tx)?
+        .into_iter()
+        .find_map(|b| PostedOracleBox::new(b, &pool_config.oracle_box_wrapper_inputs).ok())
+        .ok_or_else(|| anyhow!("no oracle box in the publish datapoint tx"))?;
+    println!("Published datapoint {}", SELF_TEST_DATAPOINT);
+    Ok(oracle_box)
+}
+
+struct SelfTestPoolBoxes {
+    pool_box: PoolBoxWrapper,
+    refresh_box: RefreshBoxWrapper,
+    oracle_box: PostedOracleBox,
+}
+
+impl PoolBoxSource for SelfTestPoolBoxes {
+    fn get_pool_box(&self) -> crate::oracle_state::Result<PoolBoxWrapper> {
+        Ok(self.pool_box.clone())
+    }
+}
+
+impl RefreshBoxSource for SelfTestPoolBoxes {
+    fn get_refresh_box(&self) -> crate::oracle_state::Result<RefreshBoxWrapper> {
+        Ok(self.refresh_box.clone())
+    }
+}
+
+impl PostedDatapointBoxesSource for SelfTestPoolBoxes {
+    fn get_posted_datapoint_boxes(&self) -> crate::oracle_state::Result<Vec<PostedOracleBox>> {
+        Ok(vec![self.oracle_box.clone()])
+    }
+}
+
+fn refresh(
+    node_api: &NodeApi,
+    pool_box: PoolBoxWrapper,
+    refresh_box: RefreshBoxWrapper,
+    oracle_box: PostedOracleBox,
+    change_address: Address,
+) -> Result<(), anyhow::Error> {
+    let public_key = *ORACLE_CONFIG.oracle_address_p2pk()?.h;
+    let max_deviation_percent = refresh_box.contract().max_deviation_percent() as u32;
+    let min_data_points = refresh_box.contract().min_data_points();
+    let boxes = SelfTestPoolBoxes {
+        pool_box,
+        refresh_box,
+        oracle_box,
+    };
+    let height = BlockHeight(node_api.current_block_height()? as u32);
+    let (action, report) = build_refresh_action(
+        &boxes,
+        &boxes,
+        &boxes,
+        max_deviation_percent,
+        min_data_points,
+        node_api,
+        height,
+        change_address,
+        &public_key,
+        None,
+        *BASE_FEE,
+    )?;
+    submit_and_confirm(node_api, &action.tx)?;
+    println!("Refreshed the pool, rate {}", report.pool_rate);
+    Ok(())
+}
+
+/// Burn the throwaway tokens left in the wallet
+fn burn_wallet_tokens(
+    node_api: &NodeApi,
+    token_ids: &[TokenId],
+    change_address: Address,
+) -> Result<(), anyhow::Error> {
+    let unspent_boxes = node_api.get_unspent_wallet_boxes()?;
+    let tokens: Vec<Token> = token_ids
+        .iter()
+        .filter_map(|token_id| {
+            let amount: u64 = unspent_boxes
+                .iter()
+                .flat_map(|b| b.tokens.iter().flat_map(|tokens| tokens.iter()))
+                .filter(|t| &t.token_id == token_id)
+                .map(|t| *t.amount.as_u64())
+                .sum();
+            Some(Token {
+                token_id: *token_id,
+                amount: amount.try_into().ok()?,
+            })
+        })
+        .collect();
+    if tokens.is_empty() {
+        println!("No throwaway tokens left in the wallet");
+        return Ok(());
+    }
+    let height = node_api.current_block_height()? as u32;
+    let output_value = BoxValue::SAFE_USER_MIN;
+    let selection = SimpleBoxSelector::new().select(
+        unspent_boxes,
+        output_value.checked_add(&BASE_FEE)?,
+        &tokens,
+    )?;
+    let output =
+        ErgoBoxCandidateBuilder::new(output_value, change_address.script()?, height).build()?;
+    let mut tx_builder = TxBuilder::new(selection, vec![output], height, *BASE_FEE, change_address);
+    tx_builder.set_token_burn_permit(tokens.clone());
+    submit_and_confirm(node_api, &tx_builder.build()?)?;
+    println!(
+        "Burned {} throwaway token kinds left in the wallet",
+        tokens.len()
+    );
+    Ok(())
+}
+
+/// The outputs of the tx once it's confirmed
+fn submit_and_confirm(
+    node_api: &NodeApi,
+    tx: &UnsignedTransaction,
+) -> Result<Vec<ErgoBox>, anyhow::Error> {
+    let tx_id = node_api.sign_and_submit_transaction(tx)?;
+    wait_for_tx_confirmation(tx_id)?;
+    tx.output_candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            ErgoBox::from_box_candidate(candidate, tx_id, index as u16).map_err(Into::into)
+        })
+        .collect()
+}
+
+fn wait_for_height(node_api: &NodeApi, height: BlockHeight) -> Result<(), anyhow::Error> {
+    loop {
+        let current_height = node_api.current_block_height()? as u32;
+        if current_height >= height.0 {
+            return Ok(());
+        }
+        println!(
+            "Waiting for height {} (current height {})",
+            height.0, current_height
+        );
+        sleep(HEIGHT_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_bootstrap_config() {
+        let config = self_test_bootstrap_config().unwrap();
+        let p = &config.refresh_contract_parameters;
+        assert_eq!(p.min_data_points(), MinDatapoints(1));
+        assert_eq!(p.epoch_length(), SELF_TEST_EPOCH_LENGTH);
+        assert_eq!(
+            p.max_deviation_percent(),
+            RefreshContractParameters::default().max_deviation_percent()
+        );
+        assert_eq!(config.tokens_to_mint.oracle_tokens.quantity, 1);
+    }
+
+    #[test]
+    fn test_publish_within_epoch_at_refresh() {
+        let pool_box_height = BlockHeight(1000);
+        let publish = publish_height(pool_box_height);
+        let refresh = refresh_height(pool_box_height);
+        // the refresh takes the datapoints created later than `height - epoch_length`
+        assert!(publish.0 > refresh.0 - SELF_TEST_EPOCH_LENGTH.0 as u32);
+        assert!(refresh.0 > pool_box_height.0 + SELF_TEST_EPOCH_LENGTH.0 as u32);
+    }
+}
@@ -0,0 +1,260 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::box_kind::BallotBox;
+use crate::box_kind::PoolBox;
+use crate::oracle_state::OraclePool;
+use crate::oracle_state::PoolBoxes;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::Rate;
+use crate::scans::fetch_limit::FetchLimit;
+
+/// Pool state saved by `snapshot-state` and compared by `diff-state`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub height: BlockHeight,
+    pub pool: PoolBoxSnapshot,
+    pub oracles: Vec<OracleSnapshot>,
+    /// Ballot token owner addresses and the pool box address hash they voted for
+    pub votes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolBoxSnapshot {
+    pub box_id: String,
+    pub epoch_counter: EpochCounter,
+    pub rate: Rate,
+    pub reward_tokens: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleSnapshot {
+    pub address: String,
+    pub box_id: String,
+    /// Datapoint and epoch of a posted (not yet collected) oracle box
+    pub posted: Option<(Rate, EpochCounter)>,
+    pub reward_tokens: u64,
+}
+
+/// Save the pool box, the oracle boxes and the ballot votes to `output`, or to
+/// `pool-snapshot-<height>-<timestamp>.json` in the current directory if not given
+pub fn snapshot_state(
+    op: &Arc<OraclePool>,
+    height: BlockHeight,
+    network_prefix: NetworkPrefix,
+    output: Option<PathBuf>,
+) -> Result<(), anyhow::Error> {
+    let to_address =
+        |pk: EcPoint| NetworkAddress::new(network_prefix, &Address::P2Pk(pk.into())).to_base58();
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let PoolBoxes {
+        pool_box,
+        posted_boxes,
+        collected_boxes,
+        ballot_boxes,
+        ..
+    } = tokio::runtime::Runtime::new()?.block_on(op.fetch_all_boxes(&FetchLimit::from_config()));
+    let pool_box = pool_box?;
+    let mut oracles: Vec<OracleSnapshot> = posted_boxes?
+        .into_iter()
+        .map(|b| OracleSnapshot {
+            address: to_address(b.public_key()),
+            box_id: String::from(b.get_box().box_id()),
+            posted: Some((b.rate(), b.epoch_counter())),
+            reward_tokens: *b.reward_token().amount.as_u64(),
+        })
+        .collect();
+    oracles.extend(collected_boxes?.into_iter().map(|b| OracleSnapshot {
+        address: to_address(b.public_key()),
+        box_id: String::from(b.get_box().box_id()),
+        posted: None,
+        reward_tokens: token_amount(b.get_box(), 1),
+    }));
+    oracles.sort_by(|a, b| a.address.cmp(&b.address));
+    let mut votes: Vec<(String, String)> = ballot_boxes?
+        .into_iter()
+        .map(|b| {
+            (
+                to_address(b.ballot_token_owner()),
+                String::from(b.vote_parameters().pool_box_address_hash),
+            )
+        })
+        .collect();
+    votes.sort();
+    let snapshot = PoolSnapshot {
+        created_at,
+        height,
+        pool: PoolBoxSnapshot {
+            box_id: String::from(pool_box.get_box().box_id()),
+            epoch_counter: pool_box.epoch_counter(),
+            rate: pool_box.rate(),
+            reward_tokens: *pool_box.reward_token().amount.as_u64(),
+        },
+        oracles,
+        votes,
+    };
+    let path = output.unwrap_or_else(|| {
+        PathBuf::from(format!("pool-snapshot-{}-{}.json", height.0, created_at))
+    });
+    std::fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+    println!(
+        "Pool state at height {} ({} oracle boxes, {} votes) written to {}",
+        height.0,
+        snapshot.oracles.len(),
+        snapshot.votes.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Print what changed from the snapshot `a` to the snapshot `b`
+pub fn diff_state(a: &Path, b: &Path) -> Result<(), anyhow::Error> {
+    let from = read_snapshot(a)?;
+    let to = read_snapshot(b)?;
+    println!(
+        "From height {} ({}) to height {} ({}):",
+        from.height.0,
+        a.display(),
+        to.height.0,
+        b.display()
+    );
+    let changes = snapshot_changes(&from, &to);
+    if changes.is_empty() {
+        println!("  no changes");
+    }
+    for change in changes {
+        println!("  {}", change);
+    }
+    Ok(())
+}
+
+fn read_snapshot(path: &Path) -> Result<PoolSnapshot, anyhow::Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn snapshot_changes(from: &PoolSnapshot, to: &PoolSnapshot) -> Vec<String> {
+    let mut changes = vec![];
+    if from.pool.epoch_counter != to.pool.epoch_counter {
+        changes.push(format!(
+            "pool epoch {} -> {} ({} refreshes)",
+            from.pool.epoch_counter.0,
+            to.pool.epoch_counter.0,
+            to.pool
+                .epoch_counter
+                .0
+                .saturating_sub(from.pool.epoch_counter.0)
+        ));
+    }
+    if from.pool.rate != to.pool.rate {
+        changes.push(format!("pool rate {} -> {}", from.pool.rate, to.pool.rate));
+    }
+    if from.pool.reward_tokens != to.pool.reward_tokens {
+        changes.push(format!(
+            "pool box reward tokens {} -> {}",
+            from.pool.reward_tokens, to.pool.reward_tokens
+        ));
+    }
+    for oracle in &to.oracles {
+        match from.oracles.iter().find(|o| o.address == oracle.address) {
+            None => changes.push(format!(
+                "oracle {} joined with {} reward tokens",
+                oracle.address, oracle.reward_tokens
+            )),
+            Some(before) if before.reward_tokens != oracle.reward_tokens => changes.push(format!(
+                "oracle {} reward tokens {} -> {} ({:+})",
+                oracle.address,
+                before.reward_tokens,
+                oracle.reward_tokens,
+                oracle.reward_tokens as i64 - before.reward_tokens as i64
+            )),
+            Some(_) => (),
+        }
+    }
+    for oracle in &from.oracles {
+        if !to.oracles.iter().any(|o| o.address == oracle.address) {
+            changes.push(format!(
+                "oracle {} left ({} reward tokens in its last box)",
+                oracle.address, oracle.reward_tokens
+            ));
+        }
+    }
+    for vote in &to.votes {
+        if !from.votes.contains(vote) {
+            changes.push(format!("{} voted for pool box hash {}", vote.0, vote.1));
+        }
+    }
+    for vote in &from.votes {
+        if !to.votes.iter().any(|v| v.0 == vote.0) {
+            changes.push(format!("{} withdrew the vote for {}", vote.0, vote.1));
+        }
+    }
+    changes
+}
+
+fn token_amount(ergo_box: &ErgoBox, index: usize) -> u64 {
+    ergo_box
+        .tokens
+        .as_ref()
+        .and_then(|tokens| tokens.get(index))
+        .map(|token| *token.amount.as_u64())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oracle(address: &str, reward_tokens: u64) -> OracleSnapshot {
+        OracleSnapshot {
+            address: address.to_string(),
+            box_id: "00".repeat(32),
+            posted: None,
+            reward_tokens,
+        }
+    }
+
+    fn snapshot(epoch: u32, oracles: Vec<OracleSnapshot>) -> PoolSnapshot {
+        PoolSnapshot {
+            created_at: 1700000000,
+            height: BlockHeight(900000 + epoch * 30),
+            pool: PoolBoxSnapshot {
+                box_id: "11".repeat(32),
+                epoch_counter: EpochCounter(epoch),
+                rate: Rate::from(200),
+                reward_tokens: 1000,
+            },
+            oracles,
+            votes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let from = snapshot(10, vec![oracle("a", 5), oracle("b", 3)]);
+        assert!(snapshot_changes(&from, &from).is_empty());
+        let to = snapshot(12, vec![oracle("a", 9), oracle("c", 1)]);
+        assert_eq!(
+            snapshot_changes(&from, &to),
+            vec![
+                "pool epoch 10 -> 12 (2 refreshes)",
+                "oracle a reward tokens 5 -> 9 (+4)",
+                "oracle c joined with 1 reward tokens",
+                "oracle b left (3 reward tokens in its last box)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::convert::TryFrom;
+
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+
+use crate::node_interface::node_api::NodeApi;
+use crate::node_interface::node_api::NodeApiError;
+use crate::oracle_config::action_fee;
+use crate::oracle_config::FeeKind;
+use crate::oracle_config::MIN_TX_FEE;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_types::BLOCK_TIME_MINUTES;
+
+/// Rough size in bytes of the signed tx of each kind. The refresh tx grows with the number of
+/// collected datapoints, its size is taken for ~10 oracle boxes.
+fn estimated_tx_size(kind: FeeKind) -> usize {
+    match kind {
+        FeeKind::PublishDatapoint => 700,
+        FeeKind::Refresh => 3000,
+        FeeKind::ExtractRewardTokens => 700,
+        FeeKind::TransferOracleToken => 700,
+    }
+}
+
+/// Print the fees the node recommends for the oracle txs to be included within `target_blocks`
+/// along with the configured ones
+pub fn suggest_fee(node_api: &NodeApi, target_blocks: u32) -> Result<(), NodeApiError> {
+    println!(
+        "Fees recommended by the node for inclusion within {} blocks:",
+        target_blocks
+    );
+    for (name, kind) in [
+        ("publish datapoint", FeeKind::PublishDatapoint),
+        ("refresh", FeeKind::Refresh),
+        ("extract reward tokens", FeeKind::ExtractRewardTokens),
+        ("transfer oracle token", FeeKind::TransferOracleToken),
+    ] {
+        let size = estimated_tx_size(kind);
+        let recommended = recommended_fee(node_api, kind, target_blocks)?;
+        println!(
+            "  {}: {} nanoERG ({} nanoERG per byte for ~{} bytes), configured {} nanoERG",
+            name,
+            recommended,
+            recommended / size as u64,
+            size,
+            action_fee(kind).as_u64()
+        );
+    }
+    Ok(())
+}
+
+/// The configured fee raised to the one recommended by the node for inclusion within
+/// `target_blocks` (`fee_target_blocks` in the oracle config), the recommended one capped at
+/// `max_tx_fee`
+pub fn suggested_tx_fee(
+    node_api: &NodeApi,
+    kind: FeeKind,
+    target_blocks: u32,
+) -> Result<BoxValue, NodeApiError> {
+    let recommended = recommended_fee(node_api, kind, target_blocks)?;
+    let max_tx_fee = ORACLE_CONFIG.max_tx_fee;
+    if let Some(max_tx_fee) = max_tx_fee.filter(|max_tx_fee| recommended > *max_tx_fee) {
+        log::warn!(
+            "The node recommends a fee of {} nanoERG for the {:?} tx, capped at max_tx_fee {} \
+             nanoERG",
+            recommended,
+            kind,
+            max_tx_fee
+        );
+    }
+    let fee = tx_fee(*action_fee(kind).as_u64(), recommended, max_tx_fee);
+    BoxValue::try_from(fee).map_err(|e| {
+        NodeApiError::UnexpectedResponse(format!(
+            "recommended fee {} nanoERG is not a valid box value: {}",
+            fee, e
+        ))
+    })
+}
+
+fn recommended_fee(
+    node_api: &NodeApi,
+    kind: FeeKind,
+    target_blocks: u32,
+) -> Result<u64, NodeApiError> {
+    node_api.recommended_fee(
+        target_blocks.max(1) * BLOCK_TIME_MINUTES,
+        estimated_tx_size(kind),
+    )
+}
+
+/// Never less than the configured fee (or the minimal fee the nodes accept), so that calm
+/// mempool periods don't lower the fee below what the operator set, and the recommended fee is
+/// never paid above `max_fee` so that a misbehaving or congested node doesn't make the oracle
+/// overpay
+fn tx_fee(configured_fee: u64, recommended_fee: u64, max_fee: Option<u64>) -> u64 {
+    let recommended_fee = max_fee.map_or(recommended_fee, |max_fee| recommended_fee.min(max_fee));
+    configured_fee.max(recommended_fee).max(MIN_TX_FEE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_fee() {
+        assert_eq!(tx_fee(1_100_000, 5_000_000, None), 5_000_000);
+        assert_eq!(tx_fee(1_100_000, 0, None), 1_100_000);
+        assert_eq!(tx_fee(0, 10, None), MIN_TX_FEE);
+        assert_eq!(tx_fee(1_100_000, 5_000_000, Some(2_000_000)), 2_000_000);
+        // the cap doesn't lower the configured fee
+        assert_eq!(tx_fee(3_000_000, 5_000_000, Some(2_000_000)), 3_000_000);
+        assert_eq!(tx_fee(0, u64::MAX, Some(2_000_000)), 2_000_000);
+    }
+}
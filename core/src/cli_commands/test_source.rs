@@ -0,0 +1,72 @@
+use crate::datapoint_source::RuntimeDataPointSource;
+use crate::oracle_config::OracleConfig;
+use crate::pool_config::PoolConfig;
+use crate::value_format::describe_rate;
+use crate::value_format::DEFAULT_DISPLAY_DECIMALS;
+
+/// Longest part of a response body that is printed
+const MAX_RESPONSE_SNIPPET_CHARS: usize = 300;
+
+/// Fetch once from the named source and print the responses, the fetched rate and the datapoint
+/// that would be posted
+pub fn test_source(
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+    name: &str,
+) -> Result<(), anyhow::Error> {
+    let datapoint_source = RuntimeDataPointSource::new(
+        pool_config.data_point_source,
+        oracle_config.data_point_source_custom_script.clone(),
+        oracle_config.data_point_source_json.clone(),
+    )?;
+    let result = datapoint_source.test_source(name)?;
+    println!(
+        "Fetched from {} in {}ms",
+        result.name,
+        result.latency.as_millis()
+    );
+    for (url, body) in &result.responses {
+        println!("Response from {}:", url);
+        println!("  {}", response_snippet(body, MAX_RESPONSE_SNIPPET_CHARS));
+    }
+    match result.rate {
+        Ok(rate) => {
+            println!("Parsed rate: {}", rate);
+            println!(
+                "Datapoint to post: {}",
+                describe_rate(
+                    (rate as i64).into(),
+                    pool_config.data_point_source,
+                    oracle_config
+                        .value_display_decimals
+                        .unwrap_or(DEFAULT_DISPLAY_DECIMALS)
+                )
+            );
+        }
+        Err(e) => println!("Fetch failed: {}", e),
+    }
+    Ok(())
+}
+
+fn response_snippet(body: &str, max_chars: usize) -> String {
+    let body = body.trim();
+    if body.chars().count() > max_chars {
+        format!("{}...", body.chars().take(max_chars).collect::<String>())
+    } else {
+        body.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_snippet() {
+        assert_eq!(
+            response_snippet(" {\"ergo\":{\"usd\":1.5}}\n", 300),
+            "{\"ergo\":{\"usd\":1.5}}"
+        );
+        assert_eq!(response_snippet("aébcd", 2), "aé...");
+    }
+}
@@ -0,0 +1,125 @@
+use crate::node_interface::node_api::BoxSpending;
+use crate::node_interface::node_api::NodeApi;
+use crate::node_interface::node_api::WalletTransaction;
+
+/// Where the node sees the tx
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStatus {
+    InMempool,
+    Confirmed {
+        inclusion_height: u32,
+        confirmations: u32,
+    },
+    /// Known to the node wallet but neither included nor in the mempool
+    Dropped {
+        input_box_ids: Vec<String>,
+    },
+    /// Neither in the mempool nor in the node wallet
+    Unknown,
+}
+
+/// Print the status of the tx (in the mempool, confirmed or dropped) and, if it was dropped,
+/// which of its inputs were spent by another tx
+pub fn trace_tx(node_api: &NodeApi, tx_id: &str) -> Result<(), anyhow::Error> {
+    let in_mempool = node_api.is_transaction_in_mempool(tx_id)?;
+    let wallet_tx = node_api.get_wallet_transaction(tx_id)?;
+    match tx_status(in_mempool, wallet_tx.as_ref()) {
+        TxStatus::InMempool => println!("Tx {} is in the mempool, not included yet", tx_id),
+        TxStatus::Confirmed {
+            inclusion_height,
+            confirmations,
+        } => println!(
+            "Tx {} is confirmed at height {} ({} confirmations)",
+            tx_id, inclusion_height, confirmations
+        ),
+        TxStatus::Dropped { input_box_ids } => {
+            println!(
+                "Tx {} was dropped: the node wallet knows it but it's neither included nor in \
+                the mempool",
+                tx_id
+            );
+            for box_id in input_box_ids {
+                match node_api.get_box_spending(&box_id)? {
+                    BoxSpending::Unspent => println!(
+                        "  input {} is unspent, the tx was evicted (e.g. low fee or the mempool \
+                        was cleared) and can be submitted again",
+                        box_id
+                    ),
+                    BoxSpending::Spent {
+                        spent_by: Some(spending_tx),
+                    } if spending_tx != tx_id => {
+                        println!("  input {} was double-spent by tx {}", box_id, spending_tx)
+                    }
+                    BoxSpending::Spent { spent_by: Some(_) } => {
+                        println!("  input {} is spent by this tx", box_id)
+                    }
+                    BoxSpending::Spent { spent_by: None } => println!(
+                        "  input {} was spent by another tx (run the node with \
+                        `extraIndex = true` to see which one)",
+                        box_id
+                    ),
+                }
+            }
+        }
+        TxStatus::Unknown => println!(
+            "Tx {} is unknown to the node: not in the mempool and not a tx of the node wallet. \
+            It was dropped before the wallet saw it or it's not a tx of this oracle",
+            tx_id
+        ),
+    }
+    Ok(())
+}
+
+pub fn tx_status(in_mempool: bool, wallet_tx: Option<&WalletTransaction>) -> TxStatus {
+    match wallet_tx {
+        Some(WalletTransaction {
+            inclusion_height: Some(inclusion_height),
+            num_confirmations,
+            ..
+        }) => TxStatus::Confirmed {
+            inclusion_height: *inclusion_height,
+            confirmations: *num_confirmations,
+        },
+        _ if in_mempool => TxStatus::InMempool,
+        Some(tx) => TxStatus::Dropped {
+            input_box_ids: tx.input_box_ids.clone(),
+        },
+        None => TxStatus::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_status() {
+        let tx = WalletTransaction {
+            inclusion_height: Some(1000),
+            num_confirmations: 3,
+            input_box_ids: vec!["ab".repeat(32)],
+            outputs: vec![],
+        };
+        assert_eq!(
+            tx_status(false, Some(&tx)),
+            TxStatus::Confirmed {
+                inclusion_height: 1000,
+                confirmations: 3
+            }
+        );
+        let unconfirmed = WalletTransaction {
+            inclusion_height: None,
+            num_confirmations: 0,
+            ..tx
+        };
+        assert_eq!(tx_status(true, Some(&unconfirmed)), TxStatus::InMempool);
+        assert_eq!(tx_status(true, None), TxStatus::InMempool);
+        assert_eq!(
+            tx_status(false, Some(&unconfirmed)),
+            TxStatus::Dropped {
+                input_box_ids: vec!["ab".repeat(32)]
+            }
+        );
+        assert_eq!(tx_status(false, None), TxStatus::Unknown);
+    }
+}
@@ -24,7 +24,7 @@ use crate::{
     },
     explorer_api::ergo_explorer_transaction_link,
     node_interface::{SignTransaction, SubmitTransaction},
-    oracle_config::BASE_FEE,
+    oracle_config::{action_fee, FeeKind},
     oracle_state::{DataSourceError, LocalDatapointBoxSource},
     oracle_types::BlockHeight,
     wallet::{WalletDataError, WalletDataSource},
@@ -94,11 +94,12 @@ pub fn transfer_oracle_token(
     if input.trim() == "YES" {
         let signed_tx = tx_signer.sign_transaction(&unsigned_tx)?;
         let tx_id = tx_submit.submit_transaction(&signed_tx)?;
-        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id());
         println!(
-            "Transaction made. Check status here: {}",
+            "Transaction submitted. Check status here: {}",
             ergo_explorer_transaction_link(tx_id, network_prefix)
         );
+        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id())?;
+        println!("Transaction confirmed");
     } else {
         println!("Aborting the transaction.")
     }
@@ -148,7 +149,7 @@ fn build_transfer_oracle_token_tx(
 
         let unspent_boxes = wallet.get_unspent_wallet_boxes()?;
 
-        let target_balance = *BASE_FEE;
+        let target_balance = action_fee(FeeKind::TransferOracleToken);
 
         let box_selector = SimpleBoxSelector::new();
         let selection = box_selector.select(unspent_boxes, target_balance, &[])?;
@@ -185,6 +186,7 @@ mod tests {
     use super::*;
     use crate::box_kind::{OracleBoxWrapper, OracleBoxWrapperInputs};
     use crate::contracts::oracle::OracleContractParameters;
+    use crate::oracle_config::BASE_FEE;
     use crate::oracle_types::EpochCounter;
     use crate::pool_commands::test_utils::{
         find_input_boxes, generate_token_ids, make_datapoint_box, make_wallet_unspent_box,
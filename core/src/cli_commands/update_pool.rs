@@ -4,13 +4,11 @@ use ergo_lib::{
         ergo_box::box_builder::ErgoBoxCandidateBuilderError,
         transaction::unsigned::UnsignedTransaction,
     },
-    ergo_chain_types::blake2b256_hash,
     ergotree_interpreter::sigma_protocol::prover::ContextExtension,
     ergotree_ir::chain::{
         address::Address,
         ergo_box::{ErgoBox, NonMandatoryRegisterId},
     },
-    ergotree_ir::serialization::SigmaSerializable,
     wallet::{
         box_selector::{BoxSelection, BoxSelector, BoxSelectorError, SimpleBoxSelector},
         signing::{TransactionContext, TxSigningError},
@@ -23,8 +21,8 @@ use std::convert::TryInto;
 
 use crate::{
     box_kind::{
-        make_pool_box_candidate_unchecked, BallotBox, CastBallotBoxVoteParameters, PoolBox,
-        PoolBoxWrapper, VoteBallotBoxWrapper,
+        make_pool_box_candidate_unchecked, pool_box_hash, BallotBox, CastBallotBoxVoteParameters,
+        PoolBox, PoolBoxWrapper, VoteBallotBoxWrapper,
     },
     contracts::pool::PoolContract,
     explorer_api::ergo_explorer_transaction_link,
@@ -97,12 +95,7 @@ pub fn update_pool(
 
     let new_pool_contract =
         PoolContract::checked_load(&new_pool_config.pool_box_wrapper_inputs.contract_inputs)?;
-    let new_pool_box_hash = blake2b256_hash(
-        &new_pool_contract
-            .ergo_tree()
-            .sigma_serialize_bytes()
-            .unwrap(),
-    );
+    let new_pool_box_hash = pool_box_hash(&new_pool_contract.ergo_tree()).unwrap();
 
     display_update_diff(
         &POOL_CONFIG,
@@ -143,11 +136,12 @@ pub fn update_pool(
     std::io::stdin().read_line(&mut input)?;
     if input.trim_end() == "YES" {
         let tx_id_str = tx_submit.submit_transaction(&signed_tx)?;
-        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id());
         println!(
             "Update pool box transaction submitted: view here, {}",
             ergo_explorer_transaction_link(tx_id_str, network_prefix)
         );
+        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id())?;
+        println!("Update pool box transaction confirmed");
         println!("Send the new pool_config_updated.yaml to the oracle operators.");
         println!("The operators should import it with `import-pool-update` command.");
         remind_send_minted_tokens_to_oracles(&POOL_CONFIG, &new_pool_config);
@@ -167,12 +161,7 @@ fn display_update_diff(
         PoolContract::checked_load(&new_pool_config.pool_box_wrapper_inputs.contract_inputs)
             .unwrap();
     println!("Pool Parameters: ");
-    let pool_box_hash = blake2b256_hash(
-        &new_pool_contract
-            .ergo_tree()
-            .sigma_serialize_bytes()
-            .unwrap(),
-    );
+    let pool_box_hash = pool_box_hash(&new_pool_contract.ergo_tree()).unwrap();
     println!("Pool Box Hash (new): {}", String::from(pool_box_hash));
     if old_pool_config.token_ids.reward_token_id != new_pool_config.token_ids.reward_token_id {
         println!(
@@ -277,12 +266,7 @@ fn build_update_pool_box_tx(
     let update_box = update_box.get_update_box()?;
     let min_votes = update_box.min_votes();
     let old_pool_box = pool_box_source.get_pool_box()?;
-    let pool_box_hash = blake2b256_hash(
-        &new_pool_contract
-            .ergo_tree()
-            .sigma_serialize_bytes()
-            .unwrap(),
-    );
+    let pool_box_hash = pool_box_hash(&new_pool_contract.ergo_tree()).unwrap();
     let vote_parameters = CastBallotBoxVoteParameters {
         pool_box_address_hash: pool_box_hash,
         reward_token_opt: new_reward_tokens.clone(),
@@ -429,15 +413,11 @@ mod tests {
             ergo_box::box_builder::ErgoBoxCandidateBuilder, ergo_state_context::ErgoStateContext,
             transaction::TxId,
         },
-        ergo_chain_types::blake2b256_hash,
         ergotree_interpreter::sigma_protocol::private_input::DlogProverInput,
-        ergotree_ir::{
-            chain::{
-                address::AddressEncoder,
-                ergo_box::ErgoBox,
-                token::{Token, TokenId},
-            },
-            serialization::SigmaSerializable,
+        ergotree_ir::chain::{
+            address::AddressEncoder,
+            ergo_box::ErgoBox,
+            token::{Token, TokenId},
         },
         wallet::Wallet,
     };
@@ -552,11 +532,7 @@ mod tests {
             RefreshTokenId::from_token_id_unchecked(new_refresh_token_id);
         let new_pool_contract = PoolContract::build_with(&new_pool_contract_inputs).unwrap();
 
-        let pool_box_bytes = new_pool_contract
-            .ergo_tree()
-            .sigma_serialize_bytes()
-            .unwrap();
-        let pool_box_hash = blake2b256_hash(&pool_box_bytes);
+        let pool_box_hash = pool_box_hash(&new_pool_contract.ergo_tree()).unwrap();
 
         let ballot_contract_parameters = BallotContractParameters::default();
         let ballot_contract_inputs = BallotContractInputs::build_with(
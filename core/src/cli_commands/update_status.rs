@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use ergo_lib::ergo_chain_types::{Digest32, EcPoint};
+
+use crate::{
+    box_kind::{pool_box_hash, BallotBox, VoteBallotBoxWrapper},
+    contracts::pool::PoolContract,
+    oracle_state::OraclePool,
+    pool_config::PoolConfig,
+};
+
+const POOL_CONFIG_UPDATED_FILE_NAME: &str = "pool_config_updated.yaml";
+
+/// Print where the pool update process stands: updated config, update box, votes
+pub fn update_status(op: &OraclePool, my_oracle_pk: &EcPoint) -> Result<(), anyhow::Error> {
+    let new_pool_box_hash = if Path::new(POOL_CONFIG_UPDATED_FILE_NAME).exists() {
+        let new_pool_config =
+            PoolConfig::load_from_str(&std::fs::read_to_string(POOL_CONFIG_UPDATED_FILE_NAME)?)?;
+        let new_pool_contract =
+            PoolContract::checked_load(&new_pool_config.pool_box_wrapper_inputs.contract_inputs)?;
+        let hash = pool_box_hash(&new_pool_contract.ergo_tree())?;
+        println!(
+            "[x] Updated config {} found, new pool box hash: {}",
+            POOL_CONFIG_UPDATED_FILE_NAME,
+            String::from(hash)
+        );
+        Some(hash)
+    } else {
+        println!(
+            "[ ] Updated config {} not found. Create it with `prepare-update` or get it from the pool operator",
+            POOL_CONFIG_UPDATED_FILE_NAME
+        );
+        None
+    };
+
+    let update_box = match op.get_update_box_source().get_update_box() {
+        Ok(update_box) => {
+            println!(
+                "[x] Update box found, creation height: {}, votes required: {}",
+                update_box.get_box().creation_height,
+                update_box.min_votes()
+            );
+            update_box
+        }
+        Err(e) => {
+            println!("[ ] Update box not found: {}", e);
+            return Ok(());
+        }
+    };
+
+    let ballot_boxes = op.get_ballot_boxes_source().get_ballot_boxes()?;
+    let update_box_creation_height = update_box.get_box().creation_height as i32;
+    if let Some(new_pool_box_hash) = new_pool_box_hash {
+        let votes = count_votes(
+            &ballot_boxes,
+            &new_pool_box_hash,
+            update_box_creation_height,
+        );
+        let min_votes = update_box.min_votes() as u64;
+        println!(
+            "[{}] Votes for the new pool box: {} of {} required",
+            if votes >= min_votes { "x" } else { " " },
+            votes,
+            min_votes
+        );
+        let my_vote = ballot_boxes.iter().find(|b| {
+            b.ballot_token_owner() == *my_oracle_pk
+                && b.vote_parameters().pool_box_address_hash == new_pool_box_hash
+                && b.vote_parameters().update_box_creation_height == update_box_creation_height
+        });
+        if my_vote.is_some() {
+            println!("[x] You voted for the new pool box");
+        } else {
+            println!("[ ] You haven't voted for the new pool box yet. Use `vote-update-pool`");
+        }
+        if votes >= min_votes {
+            println!("Enough votes are cast, the pool can be updated with `update-pool`");
+        }
+    } else {
+        println!(
+            "Ballot boxes voting for the current update box: {}",
+            ballot_boxes
+                .iter()
+                .filter(|b| b.vote_parameters().update_box_creation_height
+                    == update_box_creation_height)
+                .count()
+        );
+    }
+    Ok(())
+}
+
+/// Sum of ballot tokens in the ballot boxes voting for the given pool box hash
+fn count_votes(
+    ballot_boxes: &[VoteBallotBoxWrapper],
+    pool_box_hash: &Digest32,
+    update_box_creation_height: i32,
+) -> u64 {
+    ballot_boxes
+        .iter()
+        .filter(|b| {
+            b.vote_parameters().pool_box_address_hash == *pool_box_hash
+                && b.vote_parameters().update_box_creation_height == update_box_creation_height
+        })
+        .map(|b| *b.ballot_token().amount.as_u64())
+        .sum()
+}
@@ -0,0 +1,237 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use anyhow::anyhow;
+
+use crate::cli_commands::prepare_update::UpdateBootstrapConfig;
+use crate::oracle_config::OracleConfig;
+use crate::pool_config::PoolConfig;
+use crate::serde::UpdateBootstrapConfigSerde;
+
+/// Contract parameters of a pool update, the ones not in the update file are the current ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractParams {
+    pub epoch_length: i32,
+    pub buffer_length: i32,
+    pub max_deviation_percent: i32,
+    pub min_data_points: i32,
+    pub min_votes: u64,
+    /// Oracle tokens minted by the update, or the number of `known_oracle_addresses`
+    pub total_oracles: Option<u64>,
+    /// Ballot tokens minted by the update
+    pub total_ballot_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamViolation {
+    pub parameter: &'static str,
+    pub value: String,
+    pub reason: String,
+}
+
+impl Display for ParamViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}: {}", self.parameter, self.value, self.reason)
+    }
+}
+
+/// Check the contract parameters of the `prepare-update` file against their sensible bounds and
+/// print the violations
+pub fn validate_contract_params(
+    update_file: &str,
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+) -> Result<(), anyhow::Error> {
+    let s = std::fs::read_to_string(update_file)?;
+    let config_serde: UpdateBootstrapConfigSerde = serde_yaml::from_str(&s)?;
+    let config = UpdateBootstrapConfig::try_from(config_serde)?;
+    let params = contract_params(&config, oracle_config, pool_config);
+    let violations = find_violations(&params);
+    if violations.is_empty() {
+        println!("All contract parameters are within their bounds");
+        if params.total_oracles.is_none() {
+            println!(
+                "min_data_points wasn't checked against the number of oracles: no oracle tokens \
+                are minted by the update and known_oracle_addresses is not set"
+            );
+        }
+        return Ok(());
+    }
+    for violation in &violations {
+        println!("{}", violation);
+    }
+    Err(anyhow!(
+        "{} contract parameter(s) out of bounds",
+        violations.len()
+    ))
+}
+
+fn contract_params(
+    config: &UpdateBootstrapConfig,
+    oracle_config: &OracleConfig,
+    pool_config: &PoolConfig,
+) -> ContractParams {
+    let refresh_params = config
+        .refresh_contract_parameters
+        .clone()
+        .unwrap_or_else(|| {
+            pool_config
+                .refresh_box_wrapper_inputs
+                .contract_inputs
+                .contract_parameters()
+                .clone()
+        });
+    let min_votes = config
+        .update_contract_parameters
+        .as_ref()
+        .map(|p| p.min_votes())
+        .unwrap_or_else(|| {
+            pool_config
+                .update_box_wrapper_inputs
+                .contract_inputs
+                .contract_parameters()
+                .min_votes()
+        });
+    ContractParams {
+        epoch_length: refresh_params.epoch_length().0,
+        buffer_length: refresh_params.buffer_length(),
+        max_deviation_percent: refresh_params.max_deviation_percent(),
+        min_data_points: refresh_params.min_data_points().0,
+        min_votes,
+        total_oracles: config
+            .tokens_to_mint
+            .oracle_tokens
+            .as_ref()
+            .map(|t| t.quantity)
+            .or_else(|| {
+                oracle_config
+                    .known_oracle_addresses
+                    .as_ref()
+                    .map(|addresses| addresses.len() as u64)
+            }),
+        total_ballot_tokens: config
+            .tokens_to_mint
+            .ballot_tokens
+            .as_ref()
+            .map(|t| t.quantity),
+    }
+}
+
+fn find_violations(params: &ContractParams) -> Vec<ParamViolation> {
+    let mut violations = Vec::new();
+    let mut check = |ok: bool, parameter: &'static str, value: String, reason: String| {
+        if !ok {
+            violations.push(ParamViolation {
+                parameter,
+                value,
+                reason,
+            });
+        }
+    };
+    check(
+        params.epoch_length > 0,
+        "epoch_length",
+        params.epoch_length.to_string(),
+        "must be positive".to_string(),
+    );
+    check(
+        params.buffer_length >= 0,
+        "buffer_length",
+        params.buffer_length.to_string(),
+        "must not be negative".to_string(),
+    );
+    check(
+        (0..=100).contains(&params.max_deviation_percent),
+        "max_deviation_percent",
+        params.max_deviation_percent.to_string(),
+        "must be between 0 and 100".to_string(),
+    );
+    check(
+        params.min_data_points > 0,
+        "min_data_points",
+        params.min_data_points.to_string(),
+        "must be positive".to_string(),
+    );
+    if let Some(total_oracles) = params.total_oracles {
+        check(
+            params.min_data_points <= 0 || params.min_data_points as u64 <= total_oracles,
+            "min_data_points",
+            params.min_data_points.to_string(),
+            format!("must not exceed the number of oracles ({})", total_oracles),
+        );
+    }
+    check(
+        params.min_votes > 0,
+        "min_votes",
+        params.min_votes.to_string(),
+        "must be positive".to_string(),
+    );
+    if let Some(total_ballot_tokens) = params.total_ballot_tokens {
+        check(
+            params.min_votes <= total_ballot_tokens,
+            "min_votes",
+            params.min_votes.to_string(),
+            format!(
+                "must not exceed the number of ballot tokens ({})",
+                total_ballot_tokens
+            ),
+        );
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_params() -> ContractParams {
+        ContractParams {
+            epoch_length: 30,
+            buffer_length: 4,
+            max_deviation_percent: 5,
+            min_data_points: 4,
+            min_votes: 6,
+            total_oracles: Some(10),
+            total_ballot_tokens: Some(10),
+        }
+    }
+
+    #[test]
+    fn test_find_violations() {
+        assert_eq!(find_violations(&valid_params()), vec![]);
+        assert_eq!(
+            find_violations(&ContractParams {
+                total_oracles: None,
+                total_ballot_tokens: None,
+                ..valid_params()
+            }),
+            vec![]
+        );
+        let violations = find_violations(&ContractParams {
+            epoch_length: 0,
+            max_deviation_percent: 101,
+            min_data_points: 11,
+            min_votes: 11,
+            ..valid_params()
+        });
+        assert_eq!(
+            violations.iter().map(|v| v.parameter).collect::<Vec<_>>(),
+            vec![
+                "epoch_length",
+                "max_deviation_percent",
+                "min_data_points",
+                "min_votes"
+            ]
+        );
+        let violations = find_violations(&ContractParams {
+            buffer_length: -1,
+            min_data_points: 0,
+            min_votes: 0,
+            ..valid_params()
+        });
+        assert_eq!(
+            violations.iter().map(|v| v.parameter).collect::<Vec<_>>(),
+            vec!["buffer_length", "min_data_points", "min_votes"]
+        );
+    }
+}
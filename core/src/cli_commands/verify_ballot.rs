@@ -0,0 +1,189 @@
+use anyhow::anyhow;
+use ergo_lib::ergo_chain_types::EcPoint;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisterId;
+use ergo_lib::ergotree_ir::mir::constant::TryExtractInto;
+
+use crate::box_kind::BallotBox;
+use crate::box_kind::BallotBoxWrapper;
+use crate::box_kind::BallotBoxWrapperInputs;
+use crate::oracle_state::OraclePool;
+use crate::scans::NodeScanRegistry;
+use crate::scans::ScanGetBoxes;
+use crate::spec_token::TokenIdKind;
+use crate::wallet::WalletDataSource;
+
+/// Check that the ballot boxes owned by the oracle (R4) hold the pool's ballot token and are
+/// accepted by the ballot contract of the pool config, then that the local ballot box source
+/// (which silently skips malformed boxes) finds one of them
+pub fn verify_ballot(
+    node_scan_registry: &NodeScanRegistry,
+    op: &OraclePool,
+    wallet: &dyn WalletDataSource,
+    inputs: &BallotBoxWrapperInputs,
+    oracle_pk: &EcPoint,
+) -> Result<(), anyhow::Error> {
+    let expected_token_id = inputs.ballot_token_id.token_id();
+    println!("Expected ballot token: {}", String::from(expected_token_id));
+    let owned: Vec<ErgoBox> = node_scan_registry
+        .ballot_token_scan
+        .get_boxes()?
+        .into_iter()
+        .filter(|b| ballot_token_owner(b).as_ref() == Some(oracle_pk))
+        .collect();
+    if owned.is_empty() {
+        let in_wallet: u64 = wallet
+            .get_unspent_wallet_boxes()?
+            .iter()
+            .flat_map(|b| b.tokens.iter().flat_map(|tokens| tokens.iter()))
+            .filter(|t| t.token_id == expected_token_id)
+            .map(|t| *t.amount.as_u64())
+            .sum();
+        if in_wallet == 0 {
+            return Err(anyhow!(
+                "no ballot box owned by this oracle and no ballot token in the wallet"
+            ));
+        }
+        println!(
+            "No ballot box owned by this oracle yet, {} ballot token(s) are in the wallet. \
+            `vote-update-pool` creates the ballot box from them",
+            in_wallet
+        );
+        return Ok(());
+    }
+    for ballot_box in &owned {
+        println!("Ballot box {}", String::from(ballot_box.box_id()));
+        let problems = ballot_box_problems(ballot_box, inputs);
+        if problems.is_empty() {
+            println!("  accepted by the ballot contract");
+        }
+        for problem in problems {
+            println!("  {}", problem);
+        }
+    }
+    match op.get_local_ballot_box_source().get_ballot_box()? {
+        Some(ballot_box) => {
+            println!(
+                "Ballot box {} is recognized with {} ballot token(s)",
+                String::from(ballot_box.get_box().box_id()),
+                ballot_box.ballot_token().amount.as_u64()
+            );
+            Ok(())
+        }
+        None => Err(anyhow!(
+            "none of the ballot boxes owned by this oracle is recognized, votes won't be counted"
+        )),
+    }
+}
+
+fn ballot_token_owner(ergo_box: &ErgoBox) -> Option<EcPoint> {
+    ergo_box
+        .get_register(NonMandatoryRegisterId::R4.into())?
+        .try_extract_into::<EcPoint>()
+        .ok()
+}
+
+/// Why the ballot contract of the pool config wouldn't accept the box as a ballot box
+fn ballot_box_problems(ergo_box: &ErgoBox, inputs: &BallotBoxWrapperInputs) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(token) = ergo_box.tokens.as_ref().map(|tokens| tokens.first()) {
+        if token.token_id != inputs.ballot_token_id.token_id() {
+            problems.push(format!(
+                "TOKENS(0) is {}, not the pool's ballot token",
+                String::from(token.token_id)
+            ));
+        }
+    }
+    match BallotBoxWrapper::new(ergo_box.clone(), inputs) {
+        Ok(ballot_box) => {
+            if ergo_box.value < ballot_box.min_storage_rent() {
+                problems.push(format!(
+                    "value {} is below the contract's min storage rent {}",
+                    ergo_box.value.as_u64(),
+                    ballot_box.min_storage_rent().as_u64()
+                ));
+            }
+        }
+        // the token id mismatch is reported above
+        Err(e) if problems.is_empty() => problems.push(e.to_string()),
+        Err(_) => (),
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::chain::transaction::TxId;
+    use ergo_lib::ergo_chain_types::Digest32;
+    use ergo_lib::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+    use sigma_test_util::force_any_val;
+
+    use super::*;
+    use crate::box_kind::make_local_ballot_box_candidate;
+    use crate::contracts::ballot::BallotContract;
+    use crate::contracts::ballot::BallotContractInputs;
+    use crate::contracts::ballot::BallotContractParameters;
+    use crate::oracle_types::BlockHeight;
+    use crate::pool_commands::test_utils::generate_token_ids;
+    use crate::spec_token::BallotTokenId;
+    use crate::spec_token::SpecToken;
+    use crate::spec_token::UpdateTokenId;
+
+    #[test]
+    fn test_ballot_box_problems() {
+        let token_ids = generate_token_ids();
+        let inputs = BallotBoxWrapperInputs {
+            contract_inputs: BallotContractInputs::build_with(
+                BallotContractParameters::default(),
+                token_ids.update_nft_token_id.clone(),
+            )
+            .unwrap(),
+            ballot_token_id: token_ids.ballot_token_id.clone(),
+        };
+        let contract = BallotContract::checked_load(&inputs.contract_inputs).unwrap();
+        let owner = *force_any_val::<DlogProverInput>().public_image().h;
+        let make_box = |token_id: BallotTokenId, value: BoxValue| {
+            let candidate = make_local_ballot_box_candidate(
+                &contract,
+                owner.clone(),
+                BlockHeight(1),
+                SpecToken {
+                    token_id,
+                    amount: 1.try_into().unwrap(),
+                },
+                force_any_val::<Digest32>(),
+                None,
+                value,
+                BlockHeight(1),
+            )
+            .unwrap();
+            ErgoBox::from_box_candidate(&candidate, force_any_val::<TxId>(), 0).unwrap()
+        };
+        let ballot_box = make_box(
+            token_ids.ballot_token_id.clone(),
+            contract.min_storage_rent(),
+        );
+        assert_eq!(ballot_token_owner(&ballot_box), Some(owner));
+        assert!(ballot_box_problems(&ballot_box, &inputs).is_empty());
+
+        let other_token = make_box(
+            BallotTokenId::from_token_id_unchecked(token_ids.oracle_token_id.token_id()),
+            contract.min_storage_rent(),
+        );
+        assert_eq!(ballot_box_problems(&other_token, &inputs).len(), 1);
+
+        let wrong_contract_inputs = BallotBoxWrapperInputs {
+            contract_inputs: BallotContractInputs::build_with(
+                BallotContractParameters::default(),
+                UpdateTokenId::from_token_id_unchecked(token_ids.refresh_nft_token_id.token_id()),
+            )
+            .unwrap(),
+            ..inputs.clone()
+        };
+        assert_eq!(
+            ballot_box_problems(&ballot_box, &wrong_contract_inputs).len(),
+            1
+        );
+    }
+}
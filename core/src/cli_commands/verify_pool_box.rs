@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use ergo_lib::chain::transaction::TxId;
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::ergo_box::NonMandatoryRegisterId;
+use ergo_lib::ergotree_ir::mir::constant::TryExtractInto;
+use ergo_lib::ergotree_ir::serialization::SigmaSerializable;
+
+use crate::box_kind::make_pool_box_candidate;
+use crate::cli_commands::refresh_tx::refresh_tx_inputs;
+use crate::contracts::pool::PoolContract;
+use crate::epoch_history::read_records;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::pool_config::PoolConfig;
+use crate::scans::NodeScanRegistry;
+use crate::scans::ScanGetBoxes;
+use crate::spec_token::SpecToken;
+
+const REGISTER_IDS: [NonMandatoryRegisterId; 6] = [
+    NonMandatoryRegisterId::R4,
+    NonMandatoryRegisterId::R5,
+    NonMandatoryRegisterId::R6,
+    NonMandatoryRegisterId::R7,
+    NonMandatoryRegisterId::R8,
+    NonMandatoryRegisterId::R9,
+];
+
+/// Build the pool box the refresh should have made (the contract and tokens of the pool config,
+/// the epoch and value following the previous pool box spent by the refresh tx) with the same box
+/// construction as the refresh action, and print every difference from the on-chain pool box.
+/// The rate and the reward tokens amount can't be recomputed, the on-chain ones are used. Without
+/// the explorer the previous epoch in the epoch history is used for the value, if the history has
+/// it.
+pub fn verify_pool_box(
+    node_scan_registry: &NodeScanRegistry,
+    pool_config: &PoolConfig,
+    epoch_history_file_path: &Path,
+) -> Result<(), anyhow::Error> {
+    let actual = node_scan_registry
+        .pool_token_scan
+        .get_box()?
+        .ok_or_else(|| anyhow!("pool box not found"))?;
+    println!(
+        "Pool box {} (creation height {})",
+        String::from(actual.box_id()),
+        actual.creation_height
+    );
+    let rate = actual
+        .get_register(NonMandatoryRegisterId::R4.into())
+        .and_then(|c| c.try_extract_into::<i64>().ok());
+    let epoch_counter = register_epoch_counter(&actual);
+    let reward_amount = actual
+        .tokens
+        .as_ref()
+        .and_then(|tokens| tokens.get(1))
+        .map(|t| t.amount)
+        .ok_or_else(|| anyhow!("pool box has no reward tokens"))?;
+
+    let mut discrepancies = Vec::new();
+    if let Some(rate) = rate.filter(|rate| *rate <= 0) {
+        discrepancies.push(format!("R4 rate {} is not positive", rate));
+    }
+    let mut expected_epoch_counter = EpochCounter(epoch_counter.unwrap_or(0) as u32);
+    let mut expected_value = actual.value;
+    match refresh_tx_inputs(&actual, pool_config) {
+        Ok(Some(inputs)) => {
+            if let Some(previous_epoch_counter) = register_epoch_counter(&inputs.previous_pool_box)
+            {
+                expected_epoch_counter = EpochCounter(previous_epoch_counter as u32 + 1);
+            }
+            expected_value = inputs.previous_pool_box.value;
+            let min_data_points = pool_config
+                .refresh_box_wrapper_inputs
+                .contract_inputs
+                .contract_parameters()
+                .min_data_points();
+            if (inputs.oracle_boxes.len() as i32) < min_data_points.0 {
+                discrepancies.push(format!(
+                    "{} datapoints collected by the refresh tx, min_data_points is {}",
+                    inputs.oracle_boxes.len(),
+                    min_data_points.0
+                ));
+            }
+        }
+        Ok(None) => println!(
+            "The pool box wasn't created by a refresh tx (bootstrap or pool update), the epoch, \
+            value and datapoints are not checked"
+        ),
+        Err(e) => {
+            println!(
+                "Failed to get the refresh tx from the explorer ({}), the epoch and datapoints \
+                are not checked",
+                e
+            );
+            let previous_epoch = read_records(epoch_history_file_path)?
+                .into_iter()
+                .filter(|r| r.height.0 < actual.creation_height)
+                .max_by_key(|r| r.height);
+            // with a gap in the history the last record isn't the previous epoch
+            match previous_epoch.filter(|r| Some(r.epoch.0 as i32 + 1) == epoch_counter) {
+                Some(previous) => {
+                    if let Some(value) = previous.pool_box_value {
+                        expected_value = BoxValue::new(value)?;
+                    }
+                }
+                None => println!(
+                    "The epoch history doesn't have the previous epoch, the value is not checked"
+                ),
+            }
+        }
+    }
+
+    let token_ids = &pool_config.token_ids;
+    let candidate = make_pool_box_candidate(
+        &PoolContract::checked_load(&pool_config.pool_box_wrapper_inputs.contract_inputs)?,
+        rate.unwrap_or_default(),
+        expected_epoch_counter,
+        SpecToken {
+            token_id: token_ids.pool_nft_token_id.clone(),
+            amount: 1u64.try_into()?,
+        },
+        SpecToken {
+            token_id: token_ids.reward_token_id.clone(),
+            amount: reward_amount,
+        },
+        expected_value,
+        BlockHeight(actual.creation_height),
+    )?;
+    let expected = ErgoBox::from_box_candidate(&candidate, TxId::zero(), 0)?;
+    discrepancies.extend(box_discrepancies(&expected, &actual)?);
+    if discrepancies.is_empty() {
+        println!("Pool box matches the expected contents");
+    } else {
+        println!("Found {} discrepancy(ies):", discrepancies.len());
+        for discrepancy in discrepancies {
+            println!("  {}", discrepancy);
+        }
+    }
+    Ok(())
+}
+
+fn register_epoch_counter(pool_box: &ErgoBox) -> Option<i32> {
+    pool_box
+        .get_register(NonMandatoryRegisterId::R5.into())
+        .and_then(|c| c.try_extract_into::<i32>().ok())
+}
+
+/// Differences of the value, contract, tokens and registers of `actual` from `expected`
+fn box_discrepancies(expected: &ErgoBox, actual: &ErgoBox) -> Result<Vec<String>, anyhow::Error> {
+    let mut discrepancies = Vec::new();
+    let mut check = |what: &str, expected: String, actual: String| {
+        if expected != actual {
+            discrepancies.push(format!("{}: {} (expected {})", what, actual, expected));
+        }
+    };
+    check(
+        "value",
+        expected.value.as_u64().to_string(),
+        actual.value.as_u64().to_string(),
+    );
+    check(
+        "contract",
+        expected.ergo_tree.to_base16_bytes()?,
+        actual.ergo_tree.to_base16_bytes()?,
+    );
+    let tokens = |b: &ErgoBox| {
+        b.tokens
+            .as_ref()
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .map(|t| format!("{}:{}", String::from(t.token_id), t.amount.as_u64()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    };
+    check("tokens", tokens(expected), tokens(actual));
+    for id in REGISTER_IDS {
+        let register = |b: &ErgoBox| match b.get_register(id.into()) {
+            Some(constant) => format!("{:?}", constant.v),
+            None => "empty".to_string(),
+        };
+        check(&format!("{:?}", id), register(expected), register(actual));
+    }
+    Ok(discrepancies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::box_kind::PoolBox;
+    use crate::contracts::pool::PoolContractParameters;
+    use crate::pool_commands::test_utils::generate_token_ids;
+    use crate::pool_commands::test_utils::make_pool_box;
+
+    #[test]
+    fn test_box_discrepancies() {
+        let token_ids = generate_token_ids();
+        let pool_box = |datapoint: i64, epoch: u32| {
+            make_pool_box(
+                datapoint,
+                EpochCounter(epoch),
+                BoxValue::SAFE_USER_MIN,
+                BlockHeight(100),
+                &PoolContractParameters::default(),
+                &token_ids,
+            )
+            .get_box()
+            .clone()
+        };
+        assert!(box_discrepancies(&pool_box(200, 2), &pool_box(200, 2))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            box_discrepancies(&pool_box(200, 2), &pool_box(200, 3)).unwrap(),
+            vec!["R5: Int(3) (expected Int(2))".to_string()]
+        );
+    }
+}
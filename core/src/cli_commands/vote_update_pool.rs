@@ -23,7 +23,7 @@ use crate::{
     explorer_api::ergo_explorer_transaction_link,
     node_interface::{SignTransaction, SubmitTransaction},
     oracle_config::{BASE_FEE, ORACLE_CONFIG},
-    oracle_state::{DataSourceError, LocalBallotBoxSource},
+    oracle_state::{DataSourceError, LocalBallotBoxSource, UpdateBoxSource},
     oracle_types::BlockHeight,
     pool_config::{TokenIds, POOL_CONFIG},
     spec_token::{RewardTokenId, SpecToken, TokenIdKind},
@@ -55,6 +55,13 @@ pub enum VoteUpdatePoolError {
     BallotContract(#[from] BallotContractError),
     #[error("WalletData error: {0}")]
     WalletData(#[from] WalletDataError),
+    #[error(
+        "Vote update pool: the update box creation height is {actual}, not the supplied {supplied}"
+    )]
+    UpdateBoxCreationHeightMismatch {
+        supplied: BlockHeight,
+        actual: BlockHeight,
+    },
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -63,11 +70,21 @@ pub fn vote_update_pool(
     tx_signer: &dyn SignTransaction,
     tx_submit: &dyn SubmitTransaction,
     local_ballot_box_source: &dyn LocalBallotBoxSource,
+    update_box_source: &dyn UpdateBoxSource,
     new_pool_box_address_hash_str: String,
     reward_token_opt: Option<SpecToken<RewardTokenId>>,
-    update_box_creation_height: BlockHeight,
+    update_box_creation_height: Option<BlockHeight>,
     height: BlockHeight,
 ) -> Result<(), anyhow::Error> {
+    let update_box_creation_height = check_update_box_creation_height(
+        update_box_creation_height,
+        BlockHeight(
+            update_box_source
+                .get_update_box()?
+                .get_box()
+                .creation_height,
+        ),
+    )?;
     let change_network_address = wallet.get_change_address()?;
     let network_prefix = change_network_address.network();
     let new_pool_box_address_hash = Digest32::try_from(new_pool_box_address_hash_str)?;
@@ -119,17 +136,31 @@ pub fn vote_update_pool(
     if input.trim_end() == "YES" {
         let signed_tx = tx_signer.sign_transaction(&unsigned_tx)?;
         let tx_id_str = tx_submit.submit_transaction(&signed_tx)?;
-        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id());
         println!(
-            "Transaction made. Check status here: {}",
+            "Transaction submitted. Check status here: {}",
             ergo_explorer_transaction_link(tx_id_str, network_prefix)
         );
+        crate::explorer_api::wait_for_tx_confirmation(signed_tx.id())?;
+        println!("Transaction confirmed");
     } else {
         println!("Aborting the transaction.")
     }
     Ok(())
 }
 
+/// The on-chain update box creation height, `supplied` must match it if given
+fn check_update_box_creation_height(
+    supplied: Option<BlockHeight>,
+    actual: BlockHeight,
+) -> Result<BlockHeight, VoteUpdatePoolError> {
+    match supplied {
+        Some(supplied) if supplied != actual => {
+            Err(VoteUpdatePoolError::UpdateBoxCreationHeightMismatch { supplied, actual })
+        }
+        _ => Ok(actual),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn build_tx_with_existing_ballot_box(
     in_ballot_box: BallotBoxWrapper,
@@ -268,7 +299,27 @@ mod tests {
         wallet::WalletDataSource,
     };
 
-    use super::{build_tx_for_first_ballot_box, build_tx_with_existing_ballot_box};
+    use super::{
+        build_tx_for_first_ballot_box, build_tx_with_existing_ballot_box,
+        check_update_box_creation_height, VoteUpdatePoolError,
+    };
+
+    #[test]
+    fn test_check_update_box_creation_height() {
+        let actual = BlockHeight(1000);
+        assert_eq!(
+            check_update_box_creation_height(None, actual).unwrap(),
+            actual
+        );
+        assert_eq!(
+            check_update_box_creation_height(Some(actual), actual).unwrap(),
+            actual
+        );
+        assert!(matches!(
+            check_update_box_creation_height(Some(BlockHeight(999)), actual),
+            Err(VoteUpdatePoolError::UpdateBoxCreationHeightMismatch { .. })
+        ));
+    }
 
     #[test]
     fn test_vote_update_pool_no_existing_ballot_box() {
@@ -1,20 +1,50 @@
 //! Datapoint sources for oracle-core
+//!
+//! HTTP requests are sent with `Accept-Encoding: gzip, deflate` and compressed responses are
+//! decoded by reqwest. A response that fails to decompress is returned as
+//! [`DataPointSourceError::Reqwest`] like any other fetch error.
 mod ada_usd;
 mod aggregator;
 mod assets_exchange_rate;
+pub mod benchmark;
 mod bitpanda;
 mod coincap;
 mod coingecko;
+pub mod credentials;
 mod custom_ext_script;
 mod erg_usd;
 mod erg_xau;
+pub mod historical;
+pub mod json_source;
+pub mod persisted;
 mod predef;
+pub mod reference_feed;
+pub mod source_health;
+pub mod transform;
 
+use std::time::Instant;
+
+use crate::loop_timing;
+use crate::loop_timing::LoopPhase;
+use crate::oracle_config::OracleConfig;
+use crate::oracle_config::ORACLE_CONFIG_OPT;
 use crate::oracle_types::Rate;
+use crate::pool_config::PoolConfig;
 use crate::pool_config::PredefinedDataPointSource;
 
+use self::benchmark::benchmark_external_script;
+use self::benchmark::benchmark_json_source;
+use self::benchmark::benchmark_predef_sources;
+use self::benchmark::test_external_script;
+use self::benchmark::test_json_source;
+use self::benchmark::test_predef_source;
+use self::benchmark::SourceBenchmark;
+use self::benchmark::SourceTestResult;
+use self::credentials::CredentialError;
 use self::custom_ext_script::ExternalScript;
 use self::custom_ext_script::ExternalScriptError;
+use self::json_source::JsonSource;
+pub use self::predef::predef_source_names;
 use self::predef::sync_fetch_predef_source_aggregated;
 
 use anyhow::anyhow;
@@ -34,42 +64,166 @@ pub enum DataPointSourceError {
     JsonParse(#[from] json::Error),
     #[error("Missing JSON field {field} in {json}")]
     JsonMissingField { field: String, json: String },
+    #[error("Not a finite number: {0}")]
+    InvalidValue(String),
     #[error("No datapoints from any source")]
     NoDataPoints,
+    #[error("source credential error: {0}")]
+    Credential(#[from] CredentialError),
+    #[error("Sources spread {spread_percent:.2}% is above {max_percent}%, market in turmoil")]
+    SourceSpreadTooHigh {
+        spread_percent: f64,
+        max_percent: f64,
+    },
 }
 
+#[derive(Debug)]
 pub enum RuntimeDataPointSource {
     Predefined(PredefinedDataPointSource),
     ExternalScript(ExternalScript),
+    Json(JsonSource),
 }
 
 impl RuntimeDataPointSource {
     pub fn new(
         predef_datapoint_source: Option<PredefinedDataPointSource>,
         custom_datapoint_source_shell_cmd: Option<String>,
+        json_datapoint_source: Option<JsonSource>,
     ) -> Result<RuntimeDataPointSource, anyhow::Error> {
-        if let Some(external_script_name) = custom_datapoint_source_shell_cmd.clone() {
-            Ok(RuntimeDataPointSource::ExternalScript(ExternalScript::new(
+        let source = if let Some(external_script_name) = custom_datapoint_source_shell_cmd.clone() {
+            RuntimeDataPointSource::ExternalScript(ExternalScript::new(
                 external_script_name.clone(),
-            )))
+            ))
+        } else if let Some(json_source) = json_datapoint_source {
+            RuntimeDataPointSource::Json(json_source)
         } else {
             match predef_datapoint_source {
-                Some(predef_datasource) => Ok(RuntimeDataPointSource::Predefined(predef_datasource)),
-                _ => Err(anyhow!(
-                    "pool config data_point_source is empty along with data_point_source_custom_script in the oracle config"
+                Some(predef_datasource) => RuntimeDataPointSource::Predefined(predef_datasource),
+                _ => return Err(anyhow!(
+                    "pool config data_point_source is empty along with data_point_source_custom_script and data_point_source_json in the oracle config"
                 )),
             }
+        };
+        if let Some(min_sources) = ORACLE_CONFIG_OPT.as_ref().ok().and_then(|c| c.min_sources) {
+            let configured = source.source_names().len();
+            if min_sources > configured {
+                return Err(anyhow!(
+                    "min_sources {} in the oracle config is above the {} configured sources ({})",
+                    min_sources,
+                    configured,
+                    source.source_names().join(", ")
+                ));
+            }
         }
+        Ok(source)
     }
-}
 
-impl DataPointSource for RuntimeDataPointSource {
-    fn get_datapoint(&self) -> Result<Rate, DataPointSourceError> {
+    /// Load the datapoint source settings from the oracle and pool config files and check that
+    /// the new source returns a datapoint
+    pub fn reload_from_config_files() -> Result<RuntimeDataPointSource, anyhow::Error> {
+        let oracle_config = OracleConfig::load()?;
+        let pool_config = PoolConfig::load()?;
+        let datapoint_source = RuntimeDataPointSource::new(
+            pool_config.data_point_source,
+            oracle_config.data_point_source_custom_script,
+            oracle_config.data_point_source_json,
+        )?;
+        let rate = datapoint_source.fetch_datapoint()?;
+        log::debug!("Reloaded datapoint source returned datapoint {}", rate);
+        Ok(datapoint_source)
+    }
+
+    /// Fetch from each source `samples` times and collect latencies and values
+    pub fn benchmark(&self, samples: usize) -> Vec<SourceBenchmark> {
+        match self {
+            RuntimeDataPointSource::Predefined(predef) => benchmark_predef_sources(predef, samples),
+            RuntimeDataPointSource::ExternalScript(script) => {
+                vec![benchmark_external_script(script, samples)]
+            }
+            RuntimeDataPointSource::Json(source) => vec![benchmark_json_source(source, samples)],
+        }
+    }
+
+    /// Fetch once from the source with the given name (one of [`Self::source_names`])
+    pub fn test_source(&self, name: &str) -> Result<SourceTestResult, anyhow::Error> {
+        let res = match self {
+            RuntimeDataPointSource::Predefined(predef) => test_predef_source(predef, name),
+            RuntimeDataPointSource::ExternalScript(script) => {
+                if self.source_names().iter().any(|n| n == name) || script.script_name() == name {
+                    Some(test_external_script(script))
+                } else {
+                    None
+                }
+            }
+            RuntimeDataPointSource::Json(source) => {
+                if source.name() == name || name == "json" {
+                    Some(test_json_source(source))
+                } else {
+                    None
+                }
+            }
+        };
+        res.ok_or_else(|| {
+            anyhow!(
+                "unknown source {}, configured sources: {}",
+                name,
+                self.source_names().join(", ")
+            )
+        })
+    }
+
+    /// Human-readable names of the sources the datapoint is fetched from
+    pub fn source_names(&self) -> Vec<String> {
+        match self {
+            RuntimeDataPointSource::Predefined(predef) => predef_source_names(predef)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            RuntimeDataPointSource::ExternalScript(script) => {
+                vec![format!("external script {}", script.script_name())]
+            }
+            RuntimeDataPointSource::Json(source) => vec![source.name()],
+        }
+    }
+
+    /// Identifies the source of a persisted datapoint
+    fn persisted_source_id(&self) -> String {
         match self {
+            RuntimeDataPointSource::Predefined(predef) => format!("{:?}", predef),
+            RuntimeDataPointSource::ExternalScript(script) => {
+                format!("external script {}", script.script_name())
+            }
+            RuntimeDataPointSource::Json(source) => source.name(),
+        }
+    }
+
+    fn fetch_datapoint(&self) -> Result<Rate, DataPointSourceError> {
+        let started = Instant::now();
+        let res = match self {
             RuntimeDataPointSource::Predefined(predef) => {
-                sync_fetch_predef_source_aggregated(predef)
+                sync_fetch_predef_source_aggregated(predef).map(|(rate, quorum)| {
+                    log::info!("Datapoint {} {}", rate, quorum);
+                    rate
+                })
             }
             RuntimeDataPointSource::ExternalScript(script) => script.get_datapoint(),
+            RuntimeDataPointSource::Json(source) => source.get_datapoint(),
+        };
+        loop_timing::record(LoopPhase::DatapointFetch, started.elapsed());
+        res
+    }
+}
+
+impl DataPointSource for RuntimeDataPointSource {
+    /// The datapoint persisted before the restart if still fresh, otherwise a newly fetched one
+    fn get_datapoint(&self) -> Result<Rate, DataPointSourceError> {
+        let source_id = self.persisted_source_id();
+        if let Some(rate) = persisted::take_restored(&source_id) {
+            log::info!("Reusing datapoint {} fetched before the restart", rate);
+            return Ok(rate);
         }
+        let rate = self.fetch_datapoint()?;
+        persisted::save(rate, &source_id);
+        Ok(rate)
     }
 }
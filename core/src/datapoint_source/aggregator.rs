@@ -1,34 +1,210 @@
+use std::fmt::Display;
 use std::pin::Pin;
 
 use futures::Future;
 
+use crate::oracle_config::SourceSpreadPolicy;
+use crate::oracle_config::ORACLE_CONFIG_OPT;
+
 use super::assets_exchange_rate::Asset;
 use super::assets_exchange_rate::AssetsExchangeRate;
+use super::transform::DatapointTransform;
 use super::DataPointSourceError;
 
+/// Minimum number of sources that must return a rate for the aggregated rate to be used
+pub const MIN_SOURCES_QUORUM: usize = 1;
+
+/// How many of the sources returned a rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcesQuorum {
+    pub ok: usize,
+    pub total: usize,
+    pub required: usize,
+}
+
+impl SourcesQuorum {
+    pub fn is_met(&self) -> bool {
+        self.ok >= self.required && self.ok > 0
+    }
+}
+
+impl Display for SourcesQuorum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "aggregated from {} of {} sources (quorum {})",
+            self.ok, self.total, self.required
+        )
+    }
+}
+
 pub fn aggregate<PER1: Asset, GET: Asset>(
     rates: Vec<AssetsExchangeRate<PER1, GET>>,
 ) -> AssetsExchangeRate<PER1, GET> {
-    // TODO: filter out outliers if > 2 datapoints?
-    let average = rates.iter().map(|r| r.rate).sum::<f64>() / rates.len() as f64;
+    let values: Vec<f64> = rates.iter().map(|r| r.rate).collect();
     AssetsExchangeRate {
-        rate: average,
+        rate: aggregate_values(&values),
         ..rates[0]
     }
 }
 
+/// The aggregated rate of the source rates, also used for the historical rates of `backtest-source`
+pub fn aggregate_values(values: &[f64]) -> f64 {
+    // TODO: filter out outliers if > 2 datapoints?
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Aggregated rate of the inner sources of a combined source, e.g. the USD rates of
+/// `bitpanda+coincap+coingecko`. The `datapoint_transform`, the quorum and the
+/// `max_source_spread_percent` check are left to the aggregation of the combined rate, so that
+/// they are applied once, to the final rate.
 #[allow(clippy::type_complexity)]
 pub async fn fetch_aggregated<PER1: Asset, GET: Asset>(
     sources: Vec<
         Pin<Box<dyn Future<Output = Result<AssetsExchangeRate<PER1, GET>, DataPointSourceError>>>>,
     >,
 ) -> Result<AssetsExchangeRate<PER1, GET>, DataPointSourceError> {
-    let ok_results: Vec<AssetsExchangeRate<PER1, GET>> = fetch(sources).await?;
+    let ok_results = fetch(sources).await?;
     if ok_results.is_empty() {
         return Err(DataPointSourceError::NoDataPoints);
     }
+    Ok(aggregate(ok_results))
+}
+
+#[allow(clippy::type_complexity)]
+pub async fn fetch_aggregated_with_quorum<PER1: Asset, GET: Asset>(
+    sources: Vec<
+        Pin<Box<dyn Future<Output = Result<AssetsExchangeRate<PER1, GET>, DataPointSourceError>>>>,
+    >,
+) -> Result<(AssetsExchangeRate<PER1, GET>, SourcesQuorum), DataPointSourceError> {
+    let total = sources.len();
+    let transform = ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.datapoint_transform.as_ref());
+    let ok_results = apply_transform(fetch(sources).await?, transform);
+    let quorum = SourcesQuorum {
+        ok: ok_results.len(),
+        total,
+        required: MIN_SOURCES_QUORUM,
+    };
+    if !quorum.is_met() {
+        log::warn!("Datapoint quorum not met: {}", quorum);
+        return Err(DataPointSourceError::NoDataPoints);
+    }
+    check_configured_spread(&ok_results)?;
     let rate = aggregate(ok_results);
-    Ok(rate)
+    Ok((rate, quorum))
+}
+
+/// [`check_spread`] with `max_source_spread_percent` and `source_spread_policy` of the oracle
+/// config, nothing to check if not set
+fn check_configured_spread<PER1: Asset, GET: Asset>(
+    rates: &[AssetsExchangeRate<PER1, GET>],
+) -> Result<(), DataPointSourceError> {
+    let config = match ORACLE_CONFIG_OPT.as_ref() {
+        Ok(config) => config,
+        Err(_) => return Ok(()),
+    };
+    match config.max_source_spread_percent {
+        Some(max_percent) => check_spread(
+            rates,
+            max_percent,
+            config
+                .source_spread_policy
+                .unwrap_or(SourceSpreadPolicy::Refuse),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Guard against silently degrading to a single point of truth: `min_sources` (oracle config)
+/// of the configured sources must have returned a rate
+pub fn check_min_sources(
+    quorum: &SourcesQuorum,
+    min_sources: usize,
+) -> Result<(), DataPointSourceError> {
+    if quorum.ok >= min_sources {
+        return Ok(());
+    }
+    log::error!(
+        "Only {} of {} sources responding, min_sources is {}",
+        quorum.ok,
+        quorum.total,
+        min_sources
+    );
+    Err(DataPointSourceError::NoDataPoints)
+}
+
+/// Difference of the highest and the lowest rate relative to their average, in percent
+fn spread_percent<PER1: Asset, GET: Asset>(rates: &[AssetsExchangeRate<PER1, GET>]) -> f64 {
+    let min = rates.iter().map(|r| r.rate).fold(f64::INFINITY, f64::min);
+    let max = rates
+        .iter()
+        .map(|r| r.rate)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let average = rates.iter().map(|r| r.rate).sum::<f64>() / rates.len() as f64;
+    if rates.len() < 2 || average == 0.0 {
+        return 0.0;
+    }
+    (max - min) / average.abs() * 100.0
+}
+
+/// Guard against posting in a market in turmoil, on the total dispersion of the sources
+fn check_spread<PER1: Asset, GET: Asset>(
+    rates: &[AssetsExchangeRate<PER1, GET>],
+    max_percent: f64,
+    policy: SourceSpreadPolicy,
+) -> Result<(), DataPointSourceError> {
+    let spread_percent = spread_percent(rates);
+    if spread_percent <= max_percent {
+        return Ok(());
+    }
+    match policy {
+        SourceSpreadPolicy::Warn => {
+            log::warn!(
+                "Sources spread {:.2}% is above max_source_spread_percent {}%, posting anyway",
+                spread_percent,
+                max_percent
+            );
+            Ok(())
+        }
+        SourceSpreadPolicy::Refuse => {
+            let e = DataPointSourceError::SourceSpreadTooHigh {
+                spread_percent,
+                max_percent,
+            };
+            log::error!("{}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Apply the transform to each source rate, dropping the rates it turns into NaN or infinity
+fn apply_transform<PER1: Asset, GET: Asset>(
+    rates: Vec<AssetsExchangeRate<PER1, GET>>,
+    transform: Option<&DatapointTransform>,
+) -> Vec<AssetsExchangeRate<PER1, GET>> {
+    let transform = match transform {
+        Some(transform) => transform,
+        None => return rates,
+    };
+    rates
+        .into_iter()
+        .filter_map(|r| {
+            let rate = transform.apply(r.rate);
+            if rate.is_finite() {
+                Some(AssetsExchangeRate { rate, ..r })
+            } else {
+                log::warn!(
+                    "Datapoint transform {} of {} is not a number, dropping it",
+                    transform,
+                    r.rate
+                );
+                None
+            }
+        })
+        .collect()
 }
 
 #[allow(clippy::type_complexity)]
@@ -42,3 +218,132 @@ pub async fn fetch<PER1: Asset, GET: Asset>(
         results.into_iter().flat_map(|res| res.ok()).collect();
     Ok(ok_results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::assets_exchange_rate::NanoErg;
+    use super::super::assets_exchange_rate::Usd;
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[allow(clippy::type_complexity)]
+    fn make_source(
+        rate: Option<f64>,
+    ) -> Pin<Box<dyn Future<Output = Result<AssetsExchangeRate<Usd, NanoErg>, DataPointSourceError>>>>
+    {
+        Box::pin(async move {
+            rate.map(|rate| AssetsExchangeRate {
+                per1: Usd {},
+                get: NanoErg {},
+                rate,
+            })
+            .ok_or(DataPointSourceError::NoDataPoints)
+        })
+    }
+
+    #[test]
+    fn test_fetch_aggregated_with_quorum() {
+        let sources = vec![
+            make_source(Some(1.0)),
+            make_source(None),
+            make_source(Some(3.0)),
+        ];
+        let (rate, quorum) = tokio_test::block_on(fetch_aggregated_with_quorum(sources)).unwrap();
+        assert_eq!(rate.rate, 2.0);
+        assert_eq!(
+            quorum,
+            SourcesQuorum {
+                ok: 2,
+                total: 3,
+                required: MIN_SOURCES_QUORUM
+            }
+        );
+        assert_eq!(
+            quorum.to_string(),
+            "aggregated from 2 of 3 sources (quorum 1)"
+        );
+    }
+
+    #[test]
+    fn test_fetch_aggregated() {
+        let sources = vec![
+            make_source(Some(1.0)),
+            make_source(None),
+            make_source(Some(3.0)),
+        ];
+        assert_eq!(
+            tokio_test::block_on(fetch_aggregated(sources))
+                .unwrap()
+                .rate,
+            2.0
+        );
+        assert!(tokio_test::block_on(fetch_aggregated(vec![make_source(None)])).is_err());
+        // no spread check on the inner sources
+        let spread_sources = vec![make_source(Some(1.0)), make_source(Some(100.0))];
+        assert_eq!(
+            tokio_test::block_on(fetch_aggregated(spread_sources))
+                .unwrap()
+                .rate,
+            50.5
+        );
+    }
+
+    #[test]
+    fn test_check_min_sources() {
+        let quorum = SourcesQuorum {
+            ok: 2,
+            total: 3,
+            required: MIN_SOURCES_QUORUM,
+        };
+        assert!(check_min_sources(&quorum, 2).is_ok());
+        assert!(matches!(
+            check_min_sources(&quorum, 3),
+            Err(DataPointSourceError::NoDataPoints)
+        ));
+    }
+
+    #[test]
+    fn test_check_spread() {
+        let rates: Vec<AssetsExchangeRate<Usd, NanoErg>> = [90.0, 100.0, 110.0]
+            .iter()
+            .map(|rate| AssetsExchangeRate {
+                per1: Usd {},
+                get: NanoErg {},
+                rate: *rate,
+            })
+            .collect();
+        assert_eq!(spread_percent(&rates), 20.0);
+        assert_eq!(spread_percent(&rates[..1]), 0.0);
+        assert!(check_spread(&rates, 20.0, SourceSpreadPolicy::Refuse).is_ok());
+        assert!(matches!(
+            check_spread(&rates, 10.0, SourceSpreadPolicy::Refuse),
+            Err(DataPointSourceError::SourceSpreadTooHigh { .. })
+        ));
+        assert!(check_spread(&rates, 10.0, SourceSpreadPolicy::Warn).is_ok());
+    }
+
+    #[test]
+    fn test_fetch_aggregated_with_quorum_all_failed() {
+        let sources = vec![make_source(None), make_source(None)];
+        assert!(tokio_test::block_on(fetch_aggregated_with_quorum(sources)).is_err());
+    }
+
+    #[test]
+    fn test_apply_transform() {
+        let rates = [1.0, 0.0, 4.0]
+            .into_iter()
+            .map(|rate| AssetsExchangeRate {
+                per1: Usd {},
+                get: NanoErg {},
+                rate,
+            })
+            .collect::<Vec<_>>();
+        let transform = DatapointTransform::try_from("1 / x".to_string()).unwrap();
+        let transformed: Vec<f64> = apply_transform(rates.clone(), Some(&transform))
+            .iter()
+            .map(|r| r.rate)
+            .collect();
+        assert_eq!(transformed, vec![1.0, 0.25]);
+        assert_eq!(apply_transform(rates, None).len(), 3);
+    }
+}
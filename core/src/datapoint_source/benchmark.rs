@@ -0,0 +1,269 @@
+//! Measures latency and reliability of the datapoint sources
+
+use std::pin::Pin;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures::Future;
+
+use crate::http_client::start_capturing_responses;
+use crate::http_client::take_captured_responses;
+use crate::http_client::DATAPOINT_FETCH_RUNTIME;
+
+use super::ada_usd::usd_lovelace_sources;
+use super::assets_exchange_rate::Asset;
+use super::assets_exchange_rate::AssetsExchangeRate;
+use super::custom_ext_script::ExternalScript;
+use super::erg_usd::nanoerg_usd_sources;
+use super::erg_xau::nanoerg_kgau_sources;
+use super::json_source::JsonSource;
+use super::predef::predef_source_names;
+use super::DataPointSource;
+use super::DataPointSourceError;
+use super::PredefinedDataPointSource;
+
+#[derive(Debug, Clone)]
+pub struct SourceBenchmark {
+    pub name: String,
+    pub samples: usize,
+    /// Latencies of the successful fetches
+    pub latencies: Vec<Duration>,
+    /// Values of the successful fetches
+    pub values: Vec<f64>,
+}
+
+impl SourceBenchmark {
+    fn new(name: String) -> Self {
+        SourceBenchmark {
+            name,
+            samples: 0,
+            latencies: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    fn add_sample(&mut self, latency: Duration, value: Option<f64>) {
+        self.samples += 1;
+        if let Some(value) = value {
+            self.latencies.push(latency);
+            self.values.push(value);
+        }
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.values.len() as f64 / self.samples as f64
+        }
+    }
+
+    pub fn min_latency(&self) -> Option<Duration> {
+        self.latencies.iter().min().copied()
+    }
+
+    pub fn max_latency(&self) -> Option<Duration> {
+        self.latencies.iter().max().copied()
+    }
+
+    pub fn median_latency(&self) -> Option<Duration> {
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        let len = sorted.len();
+        match len {
+            0 => None,
+            _ if len % 2 == 0 => Some((sorted[len / 2 - 1] + sorted[len / 2]) / 2),
+            _ => Some(sorted[len / 2]),
+        }
+    }
+
+    /// Population variance of the fetched values
+    pub fn value_variance(&self) -> Option<f64> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let len = self.values.len() as f64;
+        let mean = self.values.iter().sum::<f64>() / len;
+        Some(self.values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / len)
+    }
+}
+
+/// Result of a single fetch from one source
+#[derive(Debug)]
+pub struct SourceTestResult {
+    pub name: String,
+    pub latency: Duration,
+    pub rate: Result<f64, DataPointSourceError>,
+    /// URLs and bodies of the HTTP responses received during the fetch
+    pub responses: Vec<(String, String)>,
+}
+
+/// Fetch once from the predefined source with the given name, `None` if there is no such source
+pub fn test_predef_source(
+    predef_datasource: &PredefinedDataPointSource,
+    name: &str,
+) -> Option<SourceTestResult> {
+    let index = predef_source_names(predef_datasource)
+        .iter()
+        .position(|n| *n == name)?;
+    start_capturing_responses();
+    let start = Instant::now();
+    let rate = match predef_datasource {
+        PredefinedDataPointSource::NanoErgUsd => {
+            DATAPOINT_FETCH_RUNTIME.block_on(fetch_nth(nanoerg_usd_sources(), index))
+        }
+        PredefinedDataPointSource::NanoErgXau => {
+            DATAPOINT_FETCH_RUNTIME.block_on(fetch_nth(nanoerg_kgau_sources(), index))
+        }
+        PredefinedDataPointSource::NanoAdaUsd => {
+            DATAPOINT_FETCH_RUNTIME.block_on(fetch_nth(usd_lovelace_sources(), index))
+        }
+    };
+    Some(SourceTestResult {
+        name: name.to_string(),
+        latency: start.elapsed(),
+        rate,
+        responses: take_captured_responses(),
+    })
+}
+
+pub fn test_external_script(script: &ExternalScript) -> SourceTestResult {
+    let start = Instant::now();
+    let rate = script.get_datapoint().map(|r| i64::from(r) as f64);
+    SourceTestResult {
+        name: format!("external script {}", script.script_name()),
+        latency: start.elapsed(),
+        rate,
+        responses: Vec::new(),
+    }
+}
+
+pub fn test_json_source(source: &JsonSource) -> SourceTestResult {
+    start_capturing_responses();
+    let start = Instant::now();
+    let rate = source.fetch_value();
+    SourceTestResult {
+        name: source.name(),
+        latency: start.elapsed(),
+        rate,
+        responses: take_captured_responses(),
+    }
+}
+
+pub fn benchmark_predef_sources(
+    predef_datasource: &PredefinedDataPointSource,
+    samples: usize,
+) -> Vec<SourceBenchmark> {
+    let mut benchmarks: Vec<SourceBenchmark> = predef_source_names(predef_datasource)
+        .into_iter()
+        .map(|name| SourceBenchmark::new(name.to_string()))
+        .collect();
+    for _ in 0..samples {
+        match predef_datasource {
+            PredefinedDataPointSource::NanoErgUsd => {
+                DATAPOINT_FETCH_RUNTIME.block_on(sample(nanoerg_usd_sources(), &mut benchmarks))
+            }
+            PredefinedDataPointSource::NanoErgXau => {
+                DATAPOINT_FETCH_RUNTIME.block_on(sample(nanoerg_kgau_sources(), &mut benchmarks))
+            }
+            PredefinedDataPointSource::NanoAdaUsd => {
+                DATAPOINT_FETCH_RUNTIME.block_on(sample(usd_lovelace_sources(), &mut benchmarks))
+            }
+        }
+    }
+    benchmarks
+}
+
+pub fn benchmark_external_script(script: &ExternalScript, samples: usize) -> SourceBenchmark {
+    let mut benchmark = SourceBenchmark::new(format!("external script {}", script.script_name()));
+    for _ in 0..samples {
+        let start = Instant::now();
+        let value = script.get_datapoint().ok().map(|r| i64::from(r) as f64);
+        benchmark.add_sample(start.elapsed(), value);
+    }
+    benchmark
+}
+
+pub fn benchmark_json_source(source: &JsonSource, samples: usize) -> SourceBenchmark {
+    let mut benchmark = SourceBenchmark::new(source.name());
+    for _ in 0..samples {
+        let start = Instant::now();
+        let value = source.fetch_value().ok();
+        benchmark.add_sample(start.elapsed(), value);
+    }
+    benchmark
+}
+
+/// Fetch from each source one by one so that the latencies don't affect each other
+#[allow(clippy::type_complexity)]
+async fn sample<PER1: Asset, GET: Asset>(
+    sources: Vec<
+        Pin<Box<dyn Future<Output = Result<AssetsExchangeRate<PER1, GET>, DataPointSourceError>>>>,
+    >,
+    benchmarks: &mut [SourceBenchmark],
+) {
+    for (source, benchmark) in sources.into_iter().zip(benchmarks.iter_mut()) {
+        let start = Instant::now();
+        let res = source.await;
+        if let Err(e) = &res {
+            log::debug!("{} fetch failed: {}", benchmark.name, e);
+        }
+        benchmark.add_sample(start.elapsed(), res.ok().map(|r| r.rate));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+async fn fetch_nth<PER1: Asset, GET: Asset>(
+    sources: Vec<
+        Pin<Box<dyn Future<Output = Result<AssetsExchangeRate<PER1, GET>, DataPointSourceError>>>>,
+    >,
+    index: usize,
+) -> Result<f64, DataPointSourceError> {
+    match sources.into_iter().nth(index) {
+        Some(source) => source.await.map(|r| r.rate),
+        None => Err(DataPointSourceError::NoDataPoints),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_benchmark(
+        latencies_ms: Vec<u64>,
+        values: Vec<f64>,
+        failures: usize,
+    ) -> SourceBenchmark {
+        let mut benchmark = SourceBenchmark::new("test".to_string());
+        for (latency, value) in latencies_ms.into_iter().zip(values) {
+            benchmark.add_sample(Duration::from_millis(latency), Some(value));
+        }
+        for _ in 0..failures {
+            benchmark.add_sample(Duration::from_millis(1000), None);
+        }
+        benchmark
+    }
+
+    #[test]
+    fn test_benchmark_stats() {
+        let benchmark = make_benchmark(vec![30, 10, 20], vec![1.0, 2.0, 3.0], 1);
+        assert_eq!(benchmark.samples, 4);
+        assert_eq!(benchmark.success_rate(), 0.75);
+        assert_eq!(benchmark.min_latency(), Some(Duration::from_millis(10)));
+        assert_eq!(benchmark.max_latency(), Some(Duration::from_millis(30)));
+        assert_eq!(benchmark.median_latency(), Some(Duration::from_millis(20)));
+        assert_eq!(benchmark.value_variance(), Some(2.0 / 3.0));
+    }
+
+    #[test]
+    fn test_benchmark_stats_even_and_empty() {
+        let benchmark = make_benchmark(vec![10, 40], vec![5.0, 5.0], 0);
+        assert_eq!(benchmark.median_latency(), Some(Duration::from_millis(25)));
+        assert_eq!(benchmark.value_variance(), Some(0.0));
+
+        let benchmark = make_benchmark(vec![], vec![], 2);
+        assert_eq!(benchmark.success_rate(), 0.0);
+        assert_eq!(benchmark.median_latency(), None);
+        assert_eq!(benchmark.value_variance(), None);
+    }
+}
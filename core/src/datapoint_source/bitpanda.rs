@@ -1,3 +1,5 @@
+use crate::http_client::http_get_text;
+
 use super::assets_exchange_rate::AssetsExchangeRate;
 use super::assets_exchange_rate::Usd;
 use super::erg_xau::KgAu;
@@ -8,8 +10,7 @@ pub struct BitPanda {}
 
 pub async fn get_kgau_usd() -> Result<AssetsExchangeRate<KgAu, Usd>, DataPointSourceError> {
     let url = "https://api.bitpanda.com/v1/ticker";
-    let resp = reqwest::get(url).await?;
-    let json = json::parse(&resp.text().await?)?;
+    let json = json::parse(&http_get_text(url).await?)?;
     if let Some(p) = json["XAU"]["USD"].as_str() {
         // USD price of 1 gram of gold
         let p_float = p
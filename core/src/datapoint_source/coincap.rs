@@ -1,6 +1,9 @@
+use crate::http_client::http_get_text_with_headers;
+
 use super::assets_exchange_rate::AssetsExchangeRate;
 use super::assets_exchange_rate::NanoErg;
 use super::assets_exchange_rate::Usd;
+use super::credentials::source_credential;
 use super::DataPointSourceError;
 
 #[derive(Debug, Clone)]
@@ -9,8 +12,12 @@ pub struct CoinCap;
 pub async fn get_usd_nanoerg() -> Result<AssetsExchangeRate<Usd, NanoErg>, DataPointSourceError> {
     // see https://coincap.io/assets/ergo
     let url = "https://api.coincap.io/v2/assets/ergo";
-    let resp = reqwest::get(url).await?;
-    let price_json = json::parse(&resp.text().await?)?;
+    let headers: Vec<(&str, String)> = source_credential("coincap")
+        .await?
+        .map(|key| ("Authorization", format!("Bearer {}", key)))
+        .into_iter()
+        .collect();
+    let price_json = json::parse(&http_get_text_with_headers(url, &headers).await?)?;
     if let Some(p) = price_json["data"]["priceUsd"].as_str() {
         let p_float = p
             .parse::<f64>()
@@ -1,6 +1,8 @@
 use crate::datapoint_source::assets_exchange_rate::AssetsExchangeRate;
 use crate::datapoint_source::assets_exchange_rate::NanoErg;
+use crate::datapoint_source::credentials::source_credential;
 use crate::datapoint_source::DataPointSourceError;
+use crate::http_client::http_get_text_with_headers;
 
 use super::ada_usd::Lovelace;
 use super::assets_exchange_rate::Usd;
@@ -8,8 +10,7 @@ use super::erg_xau::KgAu;
 
 pub async fn get_kgau_nanoerg() -> Result<AssetsExchangeRate<KgAu, NanoErg>, DataPointSourceError> {
     let url = "https://api.coingecko.com/api/v3/simple/price?ids=ergo&vs_currencies=XAU";
-    let resp = reqwest::get(url).await?;
-    let price_json = json::parse(&resp.text().await?)?;
+    let price_json = json::parse(&get(url).await?)?;
     if let Some(p) = price_json["ergo"]["xau"].as_f64() {
         // Convert from price Erg/XAU to nanoErgs per 1 XAU
         let nanoerg_per_troy_ounce = NanoErg::from_erg(1.0 / p);
@@ -30,8 +31,7 @@ pub async fn get_kgau_nanoerg() -> Result<AssetsExchangeRate<KgAu, NanoErg>, Dat
 
 pub async fn get_usd_nanoerg() -> Result<AssetsExchangeRate<Usd, NanoErg>, DataPointSourceError> {
     let url = "https://api.coingecko.com/api/v3/simple/price?ids=ergo&vs_currencies=USD";
-    let resp = reqwest::get(url).await?;
-    let price_json = json::parse(&resp.text().await?)?;
+    let price_json = json::parse(&get(url).await?)?;
     if let Some(p) = price_json["ergo"]["usd"].as_f64() {
         // Convert from price Erg/USD to nanoErgs per 1 USD
         let nanoerg_per_usd = NanoErg::from_erg(1.0 / p);
@@ -51,8 +51,7 @@ pub async fn get_usd_nanoerg() -> Result<AssetsExchangeRate<Usd, NanoErg>, DataP
 
 pub async fn get_usd_lovelace() -> Result<AssetsExchangeRate<Usd, Lovelace>, DataPointSourceError> {
     let url = "https://api.coingecko.com/api/v3/simple/price?ids=cardano&vs_currencies=USD";
-    let resp = reqwest::get(url).await?;
-    let price_json = json::parse(&resp.text().await?)?;
+    let price_json = json::parse(&get(url).await?)?;
     if let Some(p) = price_json["cardano"]["usd"].as_f64() {
         // Convert from price Erg/USD to nanoErgs per 1 USD
         let lovelace_price = Lovelace::from_ada(1.0 / p);
@@ -70,6 +69,41 @@ pub async fn get_usd_lovelace() -> Result<AssetsExchangeRate<Usd, Lovelace>, Dat
     }
 }
 
+/// (timestamp in ms, price) points of the coin in `vs_currency` between the timestamps in
+/// seconds, hourly for ranges of up to 90 days and daily above
+pub async fn get_price_history(
+    coin_id: &str,
+    vs_currency: &str,
+    from_secs: u64,
+    to_secs: u64,
+) -> Result<Vec<(u64, f64)>, DataPointSourceError> {
+    let url = format!(
+        "https://api.coingecko.com/api/v3/coins/{}/market_chart/range?vs_currency={}&from={}&to={}",
+        coin_id, vs_currency, from_secs, to_secs
+    );
+    let history_json = json::parse(&get(&url).await?)?;
+    if !history_json["prices"].is_array() {
+        return Err(DataPointSourceError::JsonMissingField {
+            field: "prices as array".to_string(),
+            json: history_json.dump(),
+        });
+    }
+    Ok(history_json["prices"]
+        .members()
+        .filter_map(|point| Some((point[0].as_f64()? as u64, point[1].as_f64()?)))
+        .collect())
+}
+
+/// GET with the `coingecko` API key of `datapoint_source_credentials`, if set
+async fn get(url: &str) -> Result<String, DataPointSourceError> {
+    let headers: Vec<(&str, String)> = source_credential("coingecko")
+        .await?
+        .map(|key| ("x-cg-demo-api-key", key))
+        .into_iter()
+        .collect();
+    Ok(http_get_text_with_headers(url, &headers).await?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
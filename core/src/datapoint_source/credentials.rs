@@ -0,0 +1,152 @@
+//! API keys of the predefined datapoint sources (`datapoint_source_credentials` in the oracle
+//! config), read from the OS keyring, an environment variable or the config itself. Resolved off
+//! the async runtime on the first fetch and kept in memory once all of them are read.
+
+use std::collections::BTreeMap;
+
+use once_cell::sync::OnceCell;
+use thiserror::Error;
+
+use crate::oracle_config::SourceCredential;
+use crate::oracle_config::ORACLE_CONFIG_OPT;
+
+/// OS keyring service of the `keyring_entry` accounts
+pub const KEYRING_SERVICE: &str = "oracle-core";
+
+/// The API keys by source name, set once every configured credential is read
+static RESOLVED: OnceCell<BTreeMap<String, String>> = OnceCell::new();
+
+#[derive(Debug, Clone, Error)]
+pub enum CredentialError {
+    #[error(
+        "failed to read the keyring entry {entry} of the datapoint source {source_name}: {error}"
+    )]
+    Keyring {
+        source_name: String,
+        entry: String,
+        error: String,
+    },
+    #[error("failed to resolve the datapoint source credentials: {0}")]
+    Join(String),
+}
+
+/// The API key of the source, if one is configured and found. Fails if the configured keyring
+/// entry can't be read.
+pub async fn source_credential(source: &str) -> Result<Option<String>, CredentialError> {
+    if let Some(resolved) = RESOLVED.get() {
+        return Ok(resolved.get(source).cloned());
+    }
+    let resolved = tokio::task::spawn_blocking(resolve_configured)
+        .await
+        .map_err(|e| CredentialError::Join(e.to_string()))??;
+    Ok(RESOLVED.get_or_init(|| resolved).get(source).cloned())
+}
+
+fn resolve_configured() -> Result<BTreeMap<String, String>, CredentialError> {
+    let credentials = match ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.datapoint_source_credentials.as_ref())
+    {
+        Some(credentials) => credentials,
+        None => return Ok(BTreeMap::new()),
+    };
+    let mut resolved = BTreeMap::new();
+    for (source, credential) in credentials {
+        match resolve(source, credential, read_keyring, |name| {
+            std::env::var(name).ok()
+        })? {
+            Some(key) => {
+                resolved.insert(source.clone(), key);
+            }
+            None => log::warn!("No credential found for the datapoint source {}", source),
+        }
+    }
+    Ok(resolved)
+}
+
+fn resolve(
+    source: &str,
+    credential: &SourceCredential,
+    keyring: impl Fn(&str) -> Result<String, String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Option<String>, CredentialError> {
+    if let Some(entry) = credential.keyring_entry.as_deref() {
+        let key = keyring(entry).map_err(|error| CredentialError::Keyring {
+            source_name: source.to_string(),
+            entry: entry.to_string(),
+            error,
+        })?;
+        return Ok(Some(key).filter(|key| !key.is_empty()));
+    }
+    Ok(credential
+        .env_var
+        .as_deref()
+        .and_then(env)
+        .or_else(|| credential.value.clone())
+        .filter(|key| !key.is_empty()))
+}
+
+fn read_keyring(entry: &str) -> Result<String, String> {
+    keyring::Entry::new(KEYRING_SERVICE, entry)
+        .and_then(|entry| entry.get_password())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_order() {
+        let credential = SourceCredential {
+            keyring_entry: None,
+            env_var: Some("COINGECKO_API_KEY".to_string()),
+            value: Some("inline".to_string()),
+        };
+        let env = |name: &str| (name == "COINGECKO_API_KEY").then(|| "from-env".to_string());
+        let no_keyring = |_: &str| -> Result<String, String> { unreachable!() };
+        assert_eq!(
+            resolve("coingecko", &credential, no_keyring, env).unwrap(),
+            Some("from-env".to_string())
+        );
+        assert_eq!(
+            resolve("coingecko", &credential, no_keyring, |_| None).unwrap(),
+            Some("inline".to_string())
+        );
+        let with_keyring = SourceCredential {
+            keyring_entry: Some("coingecko".to_string()),
+            ..credential
+        };
+        assert_eq!(
+            resolve(
+                "coingecko",
+                &with_keyring,
+                |_| Ok("from-keyring".to_string()),
+                env
+            )
+            .unwrap(),
+            Some("from-keyring".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_keyring_error() {
+        let credential = SourceCredential {
+            keyring_entry: Some("coingecko".to_string()),
+            env_var: Some("COINGECKO_API_KEY".to_string()),
+            value: Some("inline".to_string()),
+        };
+        let err = resolve(
+            "coingecko",
+            &credential,
+            |_| Err("locked".to_string()),
+            |_| Some("from-env".to_string()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to read the keyring entry coingecko of the datapoint source coingecko: locked"
+        );
+    }
+}
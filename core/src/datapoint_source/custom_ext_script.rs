@@ -22,6 +22,10 @@ impl ExternalScript {
     pub fn new(script_name: String) -> Self {
         ExternalScript(script_name)
     }
+
+    pub fn script_name(&self) -> &str {
+        &self.0
+    }
 }
 
 impl DataPointSource for ExternalScript {
@@ -11,8 +11,13 @@ use super::assets_exchange_rate::NanoErg;
 use super::bitpanda;
 use super::coingecko;
 use super::erg_usd::nanoerg_usd_sources;
+use super::predef::tracked_sources;
 use super::DataPointSourceError;
 
+/// Health names of the USD sources of the combined source, distinct from the direct coingecko
+/// source of this pair and from the NanoErgUsd ones
+const COMBINED_USD_SOURCE_NAMES: [&str; 2] = ["coincap (usd)", "coingecko (usd)"];
+
 #[derive(Debug, Clone, Copy)]
 pub struct KgAu {}
 
@@ -47,7 +52,12 @@ pub fn nanoerg_kgau_sources() -> Vec<
 pub async fn combined_kgau_nanoerg(
 ) -> Result<AssetsExchangeRate<KgAu, NanoErg>, DataPointSourceError> {
     let kgau_usd_rate = bitpanda::get_kgau_usd().await?;
-    let aggregated_usd_nanoerg_rate = fetch_aggregated(nanoerg_usd_sources()).await?;
+    // the USD sources are excluded after failing and record their failures like in NanoErgUsd
+    let aggregated_usd_nanoerg_rate = fetch_aggregated(tracked_sources(
+        COMBINED_USD_SOURCE_NAMES.to_vec(),
+        nanoerg_usd_sources(),
+    ))
+    .await?;
     let rate = kgau_usd_rate.rate * aggregated_usd_nanoerg_rate.rate;
     Ok(AssetsExchangeRate {
         per1: KgAu {},
@@ -0,0 +1,160 @@
+//! Historical datapoints of the predefined datapoint sources whose API serves past prices, used
+//! by `backtest-source` to compare a source configuration against what the pool posted.
+
+use crate::http_client::DATAPOINT_FETCH_RUNTIME;
+use crate::pool_config::PredefinedDataPointSource;
+
+use super::ada_usd::Lovelace;
+use super::aggregator::aggregate_values;
+use super::assets_exchange_rate::NanoErg;
+use super::coingecko;
+use super::erg_xau::KgAu;
+use super::predef::predef_source_names;
+use super::transform::DatapointTransform;
+use super::DataPointSourceError;
+
+/// A historical price further than this from the timestamp isn't used for it
+pub const MAX_PRICE_GAP_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Datapoints of one source over time
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoricalSeries {
+    pub source: &'static str,
+    /// (timestamp in ms, datapoint before `datapoint_transform`), sorted by timestamp
+    pub points: Vec<(u64, f64)>,
+}
+
+impl HistoricalSeries {
+    /// The datapoint closest in time to the timestamp, none if it's more than
+    /// [`MAX_PRICE_GAP_MS`] away
+    pub fn datapoint_at(&self, timestamp_ms: u64) -> Option<f64> {
+        self.points
+            .iter()
+            .min_by_key(|(t, _)| t.abs_diff(timestamp_ms))
+            .filter(|(t, _)| t.abs_diff(timestamp_ms) <= MAX_PRICE_GAP_MS)
+            .map(|(_, v)| *v)
+    }
+}
+
+/// Sources of the predefined datapoint source that have no history and are left out of the
+/// backtest
+pub fn sources_without_history(predef: &PredefinedDataPointSource) -> Vec<&'static str> {
+    predef_source_names(predef)
+        .into_iter()
+        .filter(|name| *name != "coingecko")
+        .collect()
+}
+
+/// Fetch the datapoints of the sources with history between the timestamps in seconds
+pub fn fetch_historical_series(
+    predef: &PredefinedDataPointSource,
+    from_secs: u64,
+    to_secs: u64,
+) -> Result<Vec<HistoricalSeries>, DataPointSourceError> {
+    let (coin_id, vs_currency) = match predef {
+        PredefinedDataPointSource::NanoErgUsd => ("ergo", "usd"),
+        PredefinedDataPointSource::NanoErgXau => ("ergo", "xau"),
+        PredefinedDataPointSource::NanoAdaUsd => ("cardano", "usd"),
+    };
+    let prices = DATAPOINT_FETCH_RUNTIME.block_on(coingecko::get_price_history(
+        coin_id,
+        vs_currency,
+        from_secs,
+        to_secs,
+    ))?;
+    let mut points: Vec<(u64, f64)> = prices
+        .into_iter()
+        .filter(|(_, price)| *price > 0.0)
+        .map(|(t, price)| (t, to_datapoint_units(predef, price)))
+        .collect();
+    points.sort_by_key(|(t, _)| *t);
+    Ok(vec![HistoricalSeries {
+        source: "coingecko",
+        points,
+    }])
+}
+
+/// Convert the price of 1 ERG (1 ADA) to the datapoint units, as the live sources do
+fn to_datapoint_units(predef: &PredefinedDataPointSource, price: f64) -> f64 {
+    match predef {
+        PredefinedDataPointSource::NanoErgUsd => NanoErg::from_erg(1.0 / price),
+        PredefinedDataPointSource::NanoErgXau => {
+            KgAu::from_troy_ounce(NanoErg::from_erg(1.0 / price))
+        }
+        PredefinedDataPointSource::NanoAdaUsd => Lovelace::from_ada(1.0 / price),
+    }
+}
+
+/// The datapoint the sources would have given at the timestamp: the transform is applied to each
+/// source before the aggregation, as for the live datapoint. None if no source has a price close
+/// enough.
+pub fn historical_datapoint(
+    series: &[HistoricalSeries],
+    timestamp_ms: u64,
+    transform: Option<&DatapointTransform>,
+) -> Option<f64> {
+    let values: Vec<f64> = series
+        .iter()
+        .filter_map(|s| s.datapoint_at(timestamp_ms))
+        .map(|v| match transform {
+            Some(transform) => transform.apply(v),
+            None => v,
+        })
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(aggregate_values(&values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_historical_datapoint() {
+        let hour = 60 * 60 * 1000;
+        let series = vec![HistoricalSeries {
+            source: "coingecko",
+            points: vec![(0, 100.0), (hour, 200.0), (2 * hour, 300.0)],
+        }];
+        assert_eq!(
+            historical_datapoint(&series, hour + 1000, None),
+            Some(200.0)
+        );
+        assert_eq!(
+            historical_datapoint(&series, 2 * hour + MAX_PRICE_GAP_MS, None),
+            Some(300.0)
+        );
+        assert_eq!(
+            historical_datapoint(&series, 2 * hour + MAX_PRICE_GAP_MS + 1, None),
+            None
+        );
+        let transform = DatapointTransform::try_from("2 * x".to_string()).unwrap();
+        assert_eq!(
+            historical_datapoint(&series, 0, Some(&transform)),
+            Some(200.0)
+        );
+        assert_eq!(historical_datapoint(&[], 0, None), None);
+    }
+
+    #[test]
+    fn test_to_datapoint_units() {
+        assert_eq!(
+            to_datapoint_units(&PredefinedDataPointSource::NanoErgUsd, 2.0),
+            500_000_000.0
+        );
+        assert_eq!(
+            to_datapoint_units(&PredefinedDataPointSource::NanoAdaUsd, 0.5),
+            2_000_000.0
+        );
+        assert_eq!(
+            sources_without_history(&PredefinedDataPointSource::NanoErgXau),
+            vec!["bitpanda+coincap+coingecko"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Generic datapoint source (`data_point_source_json` in the oracle config) reading a number at
+//! a JSON path of the response of any REST API, for the feeds without a predefined source.
+
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::http_client::http_get_text;
+use crate::http_client::DATAPOINT_FETCH_RUNTIME;
+use crate::oracle_types::Rate;
+
+use super::transform::DatapointTransform;
+use super::DataPointSource;
+use super::DataPointSourceError;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Invalid JSON path \"{path}\": {reason}")]
+pub struct JsonPathError {
+    path: String,
+    reason: String,
+}
+
+/// Dot-separated keys of the value, array elements are given by their index, e.g. `data.0.price`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct JsonPath(String);
+
+impl JsonPath {
+    /// Number (or a string holding one) at the path
+    pub fn extract(&self, json: &json::JsonValue) -> Option<f64> {
+        json_value(json, &self.0)
+    }
+}
+
+impl TryFrom<String> for JsonPath {
+    type Error = JsonPathError;
+
+    fn try_from(path: String) -> Result<Self, Self::Error> {
+        let reason = if path.trim().is_empty() {
+            Some("the path is empty")
+        } else if path.split('.').any(|key| key.trim().is_empty()) {
+            Some("empty key, expected keys separated by single dots like data.0.price")
+        } else if path.chars().any(char::is_whitespace) {
+            Some("keys can't contain whitespace")
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => Err(JsonPathError {
+                path,
+                reason: reason.to_string(),
+            }),
+            None => Ok(JsonPath(path)),
+        }
+    }
+}
+
+impl From<JsonPath> for String {
+    fn from(path: JsonPath) -> Self {
+        path.0
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// `data_point_source_json` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct JsonSource {
+    /// URL returning JSON
+    pub url: String,
+    /// Dot-separated path of the value in the response, e.g. `data.priceUsd` or `data.0.price`
+    #[schemars(with = "String")]
+    pub json_path: JsonPath,
+    /// Expression converting the value to the datapoint units, e.g. `1000000000 / x`
+    #[schemars(with = "Option<String>")]
+    pub transform: Option<DatapointTransform>,
+}
+
+impl JsonSource {
+    pub fn name(&self) -> String {
+        format!("json {}", self.url)
+    }
+
+    /// The value at `json_path` of the response, transformed
+    pub fn fetch_value(&self) -> Result<f64, DataPointSourceError> {
+        let body = DATAPOINT_FETCH_RUNTIME.block_on(http_get_text(&self.url))?;
+        let json = json::parse(&body)?;
+        let value = self.json_path.extract(&json).ok_or_else(|| {
+            DataPointSourceError::JsonMissingField {
+                field: self.json_path.to_string(),
+                json: json.dump(),
+            }
+        })?;
+        Ok(match &self.transform {
+            Some(transform) => transform.apply(value),
+            None => value,
+        })
+    }
+}
+
+impl DataPointSource for JsonSource {
+    fn get_datapoint(&self) -> Result<Rate, DataPointSourceError> {
+        let value = self.fetch_value()?;
+        if !value.is_finite() {
+            return Err(DataPointSourceError::InvalidValue(format!(
+                "{} from {} at {}",
+                value, self.url, self.json_path
+            )));
+        }
+        Ok((value as i64).into())
+    }
+}
+
+/// Number (or a string holding one) at the dot-separated `path`, array elements are given by
+/// their index, e.g. `data.0.price`
+pub(crate) fn json_value(json: &json::JsonValue, path: &str) -> Option<f64> {
+    let value = path
+        .split('.')
+        .fold(json, |value, key| match key.parse::<usize>() {
+            Ok(index) if value.is_array() => &value[index],
+            _ => &value[key],
+        });
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_value() {
+        let json =
+            json::parse(r#"{"data": {"priceUsd": "1.25", "rates": [{"value": 2.5}]}, "price": 3}"#)
+                .unwrap();
+        assert_eq!(json_value(&json, "data.priceUsd"), Some(1.25));
+        assert_eq!(json_value(&json, "data.rates.0.value"), Some(2.5));
+        assert_eq!(json_value(&json, "price"), Some(3.0));
+        assert_eq!(json_value(&json, "data.missing"), None);
+        assert_eq!(json_value(&json, "data"), None);
+    }
+
+    #[test]
+    fn test_json_path() {
+        assert!(JsonPath::try_from("data.0.price".to_string()).is_ok());
+        assert!(JsonPath::try_from("".to_string()).is_err());
+        assert!(JsonPath::try_from("data..price".to_string()).is_err());
+        assert!(JsonPath::try_from("data.".to_string()).is_err());
+        assert!(JsonPath::try_from("data. price".to_string()).is_err());
+        assert!(serde_yaml::from_str::<JsonPath>("\"data..price\"").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! The last fetched datapoint saved to the data dir (`datapoint_reuse_window_secs` in the oracle
+//! config), so that a restarted oracle reuses it instead of fetching from the sources again
+//! right away. Only the first datapoint after the start is taken from the file and only if it's
+//! younger than the window and was fetched from the same source for the same pool with the same
+//! `datapoint_transform` and `min_sources`.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use once_cell::sync;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::oracle_types::Rate;
+use crate::output::write_atomically;
+use crate::scans::SCANS_DIR_PATH;
+
+static PERSISTED_DATAPOINT_FILE_PATH: sync::OnceCell<PathBuf> = sync::OnceCell::new();
+static REUSE_WINDOW_SECS: AtomicU64 = AtomicU64::new(0);
+static REUSE_KEY: sync::OnceCell<ReuseKey> = sync::OnceCell::new();
+
+lazy_static! {
+    /// Loaded from the file on start, taken by the first datapoint fetch
+    static ref RESTORED: Mutex<Option<PersistedDatapoint>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedDatapoint {
+    pub rate: Rate,
+    /// When the datapoint was fetched, seconds since the Unix epoch
+    pub timestamp: u64,
+    /// The datapoint source it was fetched from
+    pub source: String,
+    #[serde(flatten)]
+    pub key: ReuseKey,
+}
+
+/// What the datapoint was fetched with besides the source, a persisted datapoint is reused only
+/// if all of them are the same
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReuseKey {
+    /// The pool NFT token id of the pool it was fetched for
+    pub pool_nft_token_id: String,
+    /// `datapoint_transform` in the oracle config
+    #[serde(default)]
+    pub datapoint_transform: Option<String>,
+    /// `min_sources` in the oracle config
+    #[serde(default)]
+    pub min_sources: Option<usize>,
+}
+
+impl PersistedDatapoint {
+    fn is_fresh(&self, now: u64, reuse_window_secs: u64) -> bool {
+        self.timestamp <= now && now - self.timestamp <= reuse_window_secs
+    }
+
+    fn is_for(&self, source: &str, key: &ReuseKey) -> bool {
+        self.source == source && self.key == *key
+    }
+}
+
+pub fn get_persisted_datapoint_file_path() -> PathBuf {
+    SCANS_DIR_PATH.get().unwrap().join("last_datapoint.json")
+}
+
+/// Save every datapoint fetched with `key` to `path` and load the one saved by the previous run
+pub fn enable(path: PathBuf, reuse_window_secs: u64, key: ReuseKey) {
+    match read(&path) {
+        Ok(persisted) => *RESTORED.lock().unwrap() = Some(persisted),
+        Err(e) => log::debug!(
+            "No persisted datapoint loaded from {}: {}",
+            path.display(),
+            e
+        ),
+    }
+    REUSE_WINDOW_SECS.store(reuse_window_secs, Ordering::SeqCst);
+    let _ = REUSE_KEY.set(key);
+    let _ = PERSISTED_DATAPOINT_FILE_PATH.set(path);
+}
+
+/// The datapoint saved by the previous run if it's still fresh and was fetched from `source` for
+/// this pool with the current config. Returns `None` after the first call.
+pub fn take_restored(source: &str) -> Option<Rate> {
+    let persisted = RESTORED.lock().unwrap().take()?;
+    let key = REUSE_KEY.get()?;
+    if !persisted.is_for(source, key) {
+        log::info!(
+            "Ignoring the persisted datapoint fetched from {} with {:?}, the current source is {} with {:?}",
+            persisted.source,
+            persisted.key,
+            source,
+            key
+        );
+        None
+    } else if persisted.is_fresh(now(), REUSE_WINDOW_SECS.load(Ordering::SeqCst)) {
+        Some(persisted.rate)
+    } else {
+        None
+    }
+}
+
+/// Save the datapoint freshly fetched from `source` if enabled
+pub fn save(rate: Rate, source: &str) {
+    if let (Some(path), Some(key)) = (PERSISTED_DATAPOINT_FILE_PATH.get(), REUSE_KEY.get()) {
+        let persisted = PersistedDatapoint {
+            rate,
+            timestamp: now(),
+            source: source.to_string(),
+            key: key.clone(),
+        };
+        if let Err(e) = write(path, &persisted) {
+            log::warn!("Failed to save the datapoint to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<PersistedDatapoint, std::io::Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn write(path: &Path, persisted: &PersistedDatapoint) -> Result<(), std::io::Error> {
+    write_atomically(path, serde_json::to_string(persisted)?.as_bytes())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(pool_nft_token_id: &str) -> ReuseKey {
+        ReuseKey {
+            pool_nft_token_id: pool_nft_token_id.to_string(),
+            datapoint_transform: Some("x * 2".to_string()),
+            min_sources: Some(2),
+        }
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let persisted = PersistedDatapoint {
+            rate: 100.into(),
+            timestamp: 1000,
+            source: "NanoErgUsd".to_string(),
+            key: key("pool"),
+        };
+        assert!(persisted.is_fresh(1000, 60));
+        assert!(persisted.is_fresh(1060, 60));
+        assert!(!persisted.is_fresh(1061, 60));
+        // clock moved back
+        assert!(!persisted.is_fresh(999, 60));
+    }
+
+    #[test]
+    fn test_is_for() {
+        let persisted = PersistedDatapoint {
+            rate: 100.into(),
+            timestamp: 1000,
+            source: "NanoErgUsd".to_string(),
+            key: key("pool"),
+        };
+        assert!(persisted.is_for("NanoErgUsd", &key("pool")));
+        assert!(!persisted.is_for("NanoErgXau", &key("pool")));
+        assert!(!persisted.is_for("NanoErgUsd", &key("other pool")));
+        let other_transform = ReuseKey {
+            datapoint_transform: None,
+            ..key("pool")
+        };
+        assert!(!persisted.is_for("NanoErgUsd", &other_transform));
+        let other_min_sources = ReuseKey {
+            min_sources: Some(3),
+            ..key("pool")
+        };
+        assert!(!persisted.is_for("NanoErgUsd", &other_min_sources));
+    }
+
+    #[test]
+    fn test_write_read() {
+        let path = std::env::temp_dir().join("oracle_core_test_last_datapoint.json");
+        let persisted = PersistedDatapoint {
+            rate: 769230769.into(),
+            timestamp: 1700000000,
+            source: "NanoErgUsd".to_string(),
+            key: key("pool"),
+        };
+        write(&path, &persisted).unwrap();
+        assert_eq!(read(&path).unwrap(), persisted);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
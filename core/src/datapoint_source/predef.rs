@@ -1,33 +1,105 @@
+use std::pin::Pin;
+
+use futures::Future;
+
+use crate::http_client::DATAPOINT_FETCH_RUNTIME;
+use crate::oracle_config::ORACLE_CONFIG_OPT;
 use crate::oracle_types::Rate;
 
 use super::ada_usd::usd_lovelace_sources;
-use super::aggregator::fetch_aggregated;
+use super::aggregator::check_min_sources;
+use super::aggregator::fetch_aggregated_with_quorum;
+use super::aggregator::SourcesQuorum;
+use super::assets_exchange_rate::Asset;
+use super::assets_exchange_rate::AssetsExchangeRate;
 use super::erg_usd::nanoerg_usd_sources;
 use super::erg_xau::nanoerg_kgau_sources;
+use super::source_health;
 use super::DataPointSourceError;
 use super::PredefinedDataPointSource;
 
 pub fn sync_fetch_predef_source_aggregated(
     predef_datasource: &PredefinedDataPointSource,
-) -> Result<Rate, DataPointSourceError> {
-    let tokio_runtime = tokio::runtime::Runtime::new().unwrap();
-    let rate = tokio_runtime.block_on(fetch_predef_source_aggregated(predef_datasource))?;
-    Ok(rate)
+) -> Result<(Rate, SourcesQuorum), DataPointSourceError> {
+    DATAPOINT_FETCH_RUNTIME.block_on(fetch_predef_source_aggregated(predef_datasource))
+}
+
+/// Names of the services the datapoint is fetched from
+pub fn predef_source_names(predef_datasource: &PredefinedDataPointSource) -> Vec<&'static str> {
+    match predef_datasource {
+        PredefinedDataPointSource::NanoErgUsd => vec!["coincap", "coingecko"],
+        PredefinedDataPointSource::NanoErgXau => vec!["coingecko", "bitpanda+coincap+coingecko"],
+        PredefinedDataPointSource::NanoAdaUsd => vec!["coingecko"],
+    }
 }
 
 async fn fetch_predef_source_aggregated(
     predef_datasource: &PredefinedDataPointSource,
-) -> Result<Rate, DataPointSourceError> {
-    let rate_float = match predef_datasource {
+) -> Result<(Rate, SourcesQuorum), DataPointSourceError> {
+    let (rate_float, quorum) = match predef_datasource {
         PredefinedDataPointSource::NanoErgUsd => {
-            fetch_aggregated(nanoerg_usd_sources()).await?.rate
+            let (rate, quorum) = fetch_aggregated_with_quorum(tracked_sources(
+                predef_source_names(predef_datasource),
+                nanoerg_usd_sources(),
+            ))
+            .await?;
+            (rate.rate, quorum)
         }
         PredefinedDataPointSource::NanoErgXau => {
-            fetch_aggregated(nanoerg_kgau_sources()).await?.rate
+            let (rate, quorum) = fetch_aggregated_with_quorum(tracked_sources(
+                predef_source_names(predef_datasource),
+                nanoerg_kgau_sources(),
+            ))
+            .await?;
+            (rate.rate, quorum)
         }
         PredefinedDataPointSource::NanoAdaUsd => {
-            fetch_aggregated(usd_lovelace_sources()).await?.rate
+            let (rate, quorum) = fetch_aggregated_with_quorum(tracked_sources(
+                predef_source_names(predef_datasource),
+                usd_lovelace_sources(),
+            ))
+            .await?;
+            (rate.rate, quorum)
         }
     };
-    Ok((rate_float as i64).into())
+    if let Some(min_sources) = ORACLE_CONFIG_OPT.as_ref().ok().and_then(|c| c.min_sources) {
+        check_min_sources(&quorum, min_sources)?;
+    }
+    Ok(((rate_float as i64).into(), quorum))
+}
+
+/// Future fetching the rate from one source
+pub type SourceFuture<PER1, GET> =
+    Pin<Box<dyn Future<Output = Result<AssetsExchangeRate<PER1, GET>, DataPointSourceError>>>>;
+
+/// Leave out the sources excluded by `source_failure_exclusion` (all of them are fetched if all
+/// are excluded) and record the failures of the others. The names are in the order of the sources.
+pub fn tracked_sources<PER1: Asset + 'static, GET: Asset + 'static>(
+    names: Vec<&'static str>,
+    sources: Vec<SourceFuture<PER1, GET>>,
+) -> Vec<SourceFuture<PER1, GET>> {
+    let included: Vec<bool> = names
+        .iter()
+        .map(|&name| !source_health::is_excluded(name))
+        .collect();
+    let all_excluded = !included.contains(&true);
+    if all_excluded && !names.is_empty() {
+        log::warn!("All datapoint sources are excluded after failing, fetching from all of them");
+    }
+    names
+        .into_iter()
+        .zip(sources)
+        .zip(included)
+        .filter(|(_, included)| *included || all_excluded)
+        .map(|((name, source), _)| {
+            let tracked: SourceFuture<PER1, GET> = Box::pin(async move {
+                let res = source.await;
+                if res.is_err() {
+                    source_health::record_failure(name);
+                }
+                res
+            });
+            tracked
+        })
+        .collect()
 }
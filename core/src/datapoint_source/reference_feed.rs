@@ -0,0 +1,75 @@
+//! Independent reference feed (`reference_feed` in the oracle config) the datapoints posted by
+//! this oracle are compared against, to catch systematic errors of the configured sources. The
+//! reference is never posted.
+
+use crate::http_client::http_get_text;
+use crate::http_client::DATAPOINT_FETCH_RUNTIME;
+use crate::oracle_config::ReferenceFeed;
+use crate::oracle_types::Rate;
+
+use super::json_source::json_value;
+use super::DataPointSourceError;
+
+pub const DEFAULT_REFERENCE_MAX_DEVIATION_PERCENT: f64 = 5.0;
+
+/// Datapoint according to the reference feed, in the units of the posted datapoints
+pub fn fetch_reference(feed: &ReferenceFeed) -> Result<f64, DataPointSourceError> {
+    let body = DATAPOINT_FETCH_RUNTIME.block_on(http_get_text(&feed.url))?;
+    let json = json::parse(&body)?;
+    let value = json_value(&json, &feed.json_path).ok_or_else(|| {
+        DataPointSourceError::JsonMissingField {
+            field: feed.json_path.clone(),
+            json: json.dump(),
+        }
+    })?;
+    Ok(match &feed.transform {
+        Some(transform) => transform.apply(value),
+        None => value,
+    })
+}
+
+/// Fetch the reference and log a warning if `posted` deviates from it by more than
+/// `max_deviation_percent`. Returns the reference value and the deviation in percent.
+pub fn compare_with_reference(
+    posted: Rate,
+    feed: &ReferenceFeed,
+) -> Result<(f64, f64), DataPointSourceError> {
+    let reference = fetch_reference(feed)?;
+    let deviation = deviation_percent(posted, reference);
+    let max_deviation = feed
+        .max_deviation_percent
+        .unwrap_or(DEFAULT_REFERENCE_MAX_DEVIATION_PERCENT);
+    if deviation.abs() > max_deviation {
+        log::warn!(
+            "Datapoint {} deviates by {:.2}% from the reference feed value {:.0}, more than {}%",
+            posted,
+            deviation,
+            reference,
+            max_deviation
+        );
+    } else {
+        log::info!(
+            "Datapoint {} deviates by {:.2}% from the reference feed value {:.0}",
+            posted,
+            deviation,
+            reference
+        );
+    }
+    Ok((reference, deviation))
+}
+
+/// Relative difference of the datapoint from the reference in percent
+pub fn deviation_percent(posted: Rate, reference: f64) -> f64 {
+    (i64::from(posted) as f64 - reference) / reference.abs() * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deviation_percent() {
+        assert_eq!(deviation_percent(105.into(), 100.0), 5.0);
+        assert_eq!(deviation_percent(95.into(), 100.0), -5.0);
+    }
+}
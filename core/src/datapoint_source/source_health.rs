@@ -0,0 +1,160 @@
+//! Failures of the predefined datapoint sources. A source failing more than
+//! `source_failure_exclusion.max_failures` times within the window is left out of the fetches
+//! until the cooldown is over, so that a known-bad source doesn't slow down every fetch.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::oracle_config::SourceFailureExclusion;
+use crate::oracle_config::ORACLE_CONFIG_OPT;
+
+const DEFAULT_WINDOW_SECS: u64 = 600;
+const DEFAULT_COOLDOWN_SECS: u64 = 300;
+
+lazy_static! {
+    static ref SOURCE_HEALTH: Mutex<SourceHealth> = Mutex::new(SourceHealth::default());
+}
+
+#[derive(Debug, Default)]
+struct SourceState {
+    /// Failures within the window, oldest first
+    failures: VecDeque<Instant>,
+    excluded_until: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+pub struct SourceHealth {
+    sources: HashMap<&'static str, SourceState>,
+}
+
+impl SourceHealth {
+    /// Whether the source is excluded now. A source whose cooldown is over is included again
+    /// with its failures forgotten.
+    pub fn is_excluded(&mut self, source: &'static str, now: Instant) -> bool {
+        let state = match self.sources.get_mut(source) {
+            Some(state) => state,
+            None => return false,
+        };
+        match state.excluded_until {
+            Some(until) if now < until => true,
+            Some(_) => {
+                log::info!("Datapoint source {} is included again", source);
+                *state = SourceState::default();
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record the failed fetch, true if it got the source excluded
+    pub fn record_failure(
+        &mut self,
+        source: &'static str,
+        now: Instant,
+        settings: &SourceFailureExclusion,
+    ) -> bool {
+        let window = Duration::from_secs(settings.window_secs.unwrap_or(DEFAULT_WINDOW_SECS));
+        let state = self.sources.entry(source).or_default();
+        match state.excluded_until {
+            Some(until) if now < until => return false,
+            Some(_) => *state = SourceState::default(),
+            None => (),
+        }
+        state.failures.push_back(now);
+        while let Some(oldest) = state.failures.front() {
+            if now.duration_since(*oldest) > window {
+                state.failures.pop_front();
+            } else {
+                break;
+            }
+        }
+        if state.failures.len() <= settings.max_failures as usize {
+            return false;
+        }
+        let cooldown = settings.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS);
+        state.excluded_until = Some(now + Duration::from_secs(cooldown));
+        log::warn!(
+            "Datapoint source {} failed {} times within {} seconds, excluding it for {} seconds",
+            source,
+            state.failures.len(),
+            window.as_secs(),
+            cooldown
+        );
+        true
+    }
+
+    /// Names of the sources excluded now
+    pub fn excluded(&self, now: Instant) -> Vec<&'static str> {
+        let mut excluded: Vec<&'static str> = self
+            .sources
+            .iter()
+            .filter(|(_, state)| state.excluded_until.map_or(false, |until| now < until))
+            .map(|(source, _)| *source)
+            .collect();
+        excluded.sort_unstable();
+        excluded
+    }
+}
+
+fn settings() -> Option<SourceFailureExclusion> {
+    ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.source_failure_exclusion)
+}
+
+/// Whether to skip the source in this fetch, never if the exclusion is off
+pub fn is_excluded(source: &'static str) -> bool {
+    settings().is_some()
+        && SOURCE_HEALTH
+            .lock()
+            .unwrap()
+            .is_excluded(source, Instant::now())
+}
+
+pub fn record_failure(source: &'static str) {
+    if let Some(settings) = settings() {
+        SOURCE_HEALTH
+            .lock()
+            .unwrap()
+            .record_failure(source, Instant::now(), &settings);
+    }
+}
+
+/// Names of the sources excluded now, for the `/metrics` endpoint
+pub fn excluded_sources() -> Vec<&'static str> {
+    SOURCE_HEALTH.lock().unwrap().excluded(Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_exclusion() {
+        let settings = SourceFailureExclusion {
+            max_failures: 2,
+            window_secs: Some(60),
+            cooldown_secs: Some(300),
+        };
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut health = SourceHealth::default();
+        assert!(!health.record_failure("coincap", at(0), &settings));
+        assert!(!health.record_failure("coincap", at(10), &settings));
+        // the first failure is out of the window
+        assert!(!health.record_failure("coincap", at(65), &settings));
+        assert!(!health.is_excluded("coincap", at(65)));
+        assert!(health.record_failure("coincap", at(66), &settings));
+        assert!(health.is_excluded("coincap", at(100)));
+        assert!(!health.is_excluded("coingecko", at(100)));
+        assert_eq!(health.excluded(at(100)), vec!["coincap"]);
+        assert!(!health.is_excluded("coincap", at(366)));
+        assert!(health.excluded(at(366)).is_empty());
+        // failures before the exclusion are forgotten
+        assert!(!health.record_failure("coincap", at(367), &settings));
+    }
+}
@@ -0,0 +1,215 @@
+//! Arithmetic applied to the value of each predefined datapoint source before aggregation
+//! (`datapoint_transform` in the oracle config), e.g. `x / 1000` to post per gram a price in
+//! nanoERG per kilogram or `x * 1.005 + 10` for a conversion factor and an offset. `x` is the
+//! source value; numbers (with an optional exponent like `1e-3`), `+`, `-`, `*`, `/` and
+//! parentheses are supported.
+
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Invalid datapoint transform \"{expression}\": {reason}")]
+pub struct DatapointTransformError {
+    expression: String,
+    reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DatapointTransform {
+    expression: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    X,
+    Num(f64),
+    Neg(Box<Expr>),
+    Bin(char, Box<Expr>, Box<Expr>),
+}
+
+impl DatapointTransform {
+    pub fn apply(&self, x: f64) -> f64 {
+        eval(&self.expr, x)
+    }
+}
+
+impl TryFrom<String> for DatapointTransform {
+    type Error = DatapointTransformError;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        let expr = Parser::new(&expression)
+            .parse()
+            .map_err(|reason| DatapointTransformError {
+                expression: expression.clone(),
+                reason,
+            })?;
+        Ok(DatapointTransform { expression, expr })
+    }
+}
+
+impl From<DatapointTransform> for String {
+    fn from(transform: DatapointTransform) -> Self {
+        transform.expression
+    }
+}
+
+impl Display for DatapointTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+fn eval(expr: &Expr, x: f64) -> f64 {
+    match expr {
+        Expr::X => x,
+        Expr::Num(n) => *n,
+        Expr::Neg(e) => -eval(e, x),
+        Expr::Bin(op, l, r) => {
+            let (l, r) = (eval(l, x), eval(r, x));
+            match op {
+                '+' => l + r,
+                '-' => l - r,
+                '*' => l * r,
+                _ => l / r,
+            }
+        }
+    }
+}
+
+/// Recursive descent over `expr = term (('+' | '-') term)*`,
+/// `term = factor (('*' | '/') factor)*`, `factor = '-' factor | number | 'x' | '(' expr ')'`
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(expression: &'a str) -> Self {
+        Parser {
+            chars: expression.chars().peekable(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.expr()?;
+        match self.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("unexpected '{}'", c)),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.chars.next();
+            expr = Expr::Bin(op, Box::new(expr), Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.chars.next();
+            expr = Expr::Bin(op, Box::new(expr), Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expr::Neg(Box::new(self.factor()?)))
+            }
+            Some('x') => {
+                self.chars.next();
+                Ok(Expr::X)
+            }
+            Some('(') => {
+                self.chars.next();
+                let expr = self.expr()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.chars.next();
+                        Ok(expr)
+                    }
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(c) = self.chars.next_if(|c| {
+                    c.is_ascii_digit()
+                        || *c == '.'
+                        || *c == 'e'
+                        || ((*c == '-' || *c == '+') && number.ends_with('e'))
+                }) {
+                    number.push(c);
+                }
+                number
+                    .parse()
+                    .map(Expr::Num)
+                    .map_err(|_| format!("invalid number {}", number))
+            }
+            Some(c) => Err(format!("unexpected '{}'", c)),
+            None => Err("unexpected end".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(expression: &str) -> DatapointTransform {
+        DatapointTransform::try_from(expression.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(transform("x").apply(2.0), 2.0);
+        assert_eq!(transform("1 / x").apply(4.0), 0.25);
+        assert_eq!(transform("x * 2 + 10").apply(3.0), 16.0);
+        assert_eq!(transform("x*(2+10)").apply(3.0), 36.0);
+        assert_eq!(transform("-x + 1e3").apply(1.0), 999.0);
+        assert_eq!(transform("x * 1e-3").apply(2000.0), 2.0);
+        assert_eq!(transform("x * 2e+3 - 1").apply(1.0), 1999.0);
+        assert_eq!(transform("x-1").apply(3.0), 2.0);
+        assert_eq!(transform("x - 1 - 1").apply(3.0), 1.0);
+        assert_eq!(transform("x / 2 / 2").apply(8.0), 2.0);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for expression in ["", "x +", "2 ** x", "(x * 2", "y", "1..2 * x", "x x"] {
+            assert!(
+                DatapointTransform::try_from(expression.to_string()).is_err(),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let transform: DatapointTransform = serde_yaml::from_str("\"1 / x\"").unwrap();
+        assert_eq!(transform.apply(2.0), 0.5);
+        let yaml = serde_yaml::to_string(&transform).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<DatapointTransform>(&yaml).unwrap(),
+            transform
+        );
+        assert!(serde_yaml::from_str::<DatapointTransform>("\"1 / \"").is_err());
+    }
+}
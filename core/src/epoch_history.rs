@@ -0,0 +1,243 @@
+//! Per-epoch record of the pool rate and the oracle's datapoints and rewards. Stored as JSON
+//! lines in the data dir, one record per observed pool box epoch.
+
+use std::io::BufRead;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::jsonl;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::Rate;
+use crate::scans::SCANS_DIR_PATH;
+use crate::serde::discard_unparsable_file;
+
+pub fn get_epoch_history_file_path() -> PathBuf {
+    SCANS_DIR_PATH.get().unwrap().join("epoch_history.jsonl")
+}
+
+#[derive(Debug, Error)]
+pub enum EpochHistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochHistoryRecord {
+    /// Pool box creation height
+    pub height: BlockHeight,
+    pub epoch: EpochCounter,
+    /// Last datapoint posted by this oracle before the pool box was created
+    pub posted_value: Option<Rate>,
+    /// Pool box rate
+    pub consensus_value: Rate,
+    /// Reward tokens in the oracle box
+    pub reward_tokens: u64,
+    /// Pool box value in nanoERG (not recorded by older versions)
+    #[serde(default)]
+    pub pool_box_value: Option<u64>,
+    /// Oracles whose datapoints were collected into the pool box (not recorded by older versions)
+    #[serde(default)]
+    pub collected_oracles: Option<Vec<String>>,
+    /// Oracles that posted a datapoint in the previous epoch that wasn't collected, i.e. outside
+    /// of the deviation range or too late (not recorded by older versions)
+    #[serde(default)]
+    pub uncollected_oracles: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub struct EpochHistory {
+    file_path: PathBuf,
+    last_epoch: Option<EpochCounter>,
+}
+
+impl EpochHistory {
+    /// The lines of the history file that can't be parsed (e.g. the last one truncated by a
+    /// crash) are dropped from it, see [`discard_unparsable_lines`]
+    pub fn new(file_path: PathBuf) -> Result<Self, EpochHistoryError> {
+        discard_unparsable_lines(&file_path)?;
+        let last_epoch = read_records(&file_path)?.last().map(|record| record.epoch);
+        Ok(EpochHistory {
+            file_path,
+            last_epoch,
+        })
+    }
+
+    pub fn is_recorded(&self, epoch: EpochCounter) -> bool {
+        self.last_epoch == Some(epoch)
+    }
+
+    /// Append the record unless its epoch is already recorded
+    pub fn add_if_new_epoch(
+        &mut self,
+        record: EpochHistoryRecord,
+    ) -> Result<(), EpochHistoryError> {
+        if self.is_recorded(record.epoch) {
+            return Ok(());
+        }
+        jsonl::append_record::<_, EpochHistoryError>(&self.file_path, &record)?;
+        self.last_epoch = Some(record.epoch);
+        Ok(())
+    }
+}
+
+/// The records of the history file, the lines that can't be parsed are skipped with a warning
+pub fn read_records(file_path: &Path) -> Result<Vec<EpochHistoryRecord>, EpochHistoryError> {
+    Ok(read_records_and_unparsable_count(file_path)?.0)
+}
+
+fn read_records_and_unparsable_count(
+    file_path: &Path,
+) -> Result<(Vec<EpochHistoryRecord>, usize), EpochHistoryError> {
+    jsonl::read_records(file_path)
+}
+
+/// Move the history file with unparsable lines to a timestamped `.discarded` file and write the
+/// parsed records back, so that the next records aren't appended to a truncated line. Returns
+/// the discarded file if there was one.
+fn discard_unparsable_lines(file_path: &Path) -> Result<Option<PathBuf>, EpochHistoryError> {
+    let (records, unparsable) = read_records_and_unparsable_count(file_path)?;
+    if unparsable == 0 {
+        return Ok(None);
+    }
+    let discarded_path =
+        discard_unparsable_file(file_path, &format!("{} unparsable line(s)", unparsable))?;
+    jsonl::write_records::<_, EpochHistoryError>(file_path, &records)?;
+    Ok(Some(discarded_path))
+}
+
+/// Convert the history to CSV line by line. Reward tokens earned are the increase of the oracle
+/// box reward tokens since the previous record (zero after the tokens were extracted). The lines
+/// that can't be parsed are skipped with a warning.
+pub fn write_csv(reader: impl BufRead, mut writer: impl Write) -> Result<(), EpochHistoryError> {
+    writeln!(
+        writer,
+        "height,epoch,posted_value,consensus_value,reward_tokens_earned"
+    )?;
+    let mut prev_reward_tokens: Option<u64> = None;
+    for line in reader.lines() {
+        let line = line?;
+        let record: EpochHistoryRecord = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(e) => {
+                log::warn!("Skipping unparsable epoch history line {}: {}", line, e);
+                continue;
+            }
+        };
+        let earned = prev_reward_tokens
+            .map(|prev| record.reward_tokens.saturating_sub(prev))
+            .unwrap_or(0);
+        writeln!(
+            writer,
+            "{},{},{},{},{}",
+            record.height,
+            record.epoch.0,
+            record
+                .posted_value
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            record.consensus_value,
+            earned
+        )?;
+        prev_reward_tokens = Some(record.reward_tokens);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(epoch: u32, posted_value: Option<i64>, reward_tokens: u64) -> EpochHistoryRecord {
+        EpochHistoryRecord {
+            height: BlockHeight(100 + epoch * 30),
+            epoch: EpochCounter(epoch),
+            posted_value: posted_value.map(Into::into),
+            consensus_value: 1000.into(),
+            reward_tokens,
+            pool_box_value: None,
+            collected_oracles: None,
+            uncollected_oracles: None,
+        }
+    }
+
+    #[test]
+    fn test_add_if_new_epoch() {
+        let file_path = std::env::temp_dir().join("oracle_core_test_epoch_history.jsonl");
+        let _ = std::fs::remove_file(&file_path);
+        let mut history = EpochHistory::new(file_path.clone()).unwrap();
+        history.add_if_new_epoch(record(1, Some(990), 10)).unwrap();
+        history.add_if_new_epoch(record(1, Some(990), 10)).unwrap();
+        history.add_if_new_epoch(record(2, None, 12)).unwrap();
+        let mut history = EpochHistory::new(file_path.clone()).unwrap();
+        assert_eq!(history.last_epoch, Some(EpochCounter(2)));
+        history.add_if_new_epoch(record(2, None, 12)).unwrap();
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert_eq!(read_records(&file_path).unwrap()[1], record(2, None, 12));
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_discard_unparsable_lines() {
+        let file_path =
+            std::env::temp_dir().join("oracle_core_test_epoch_history_unparsable.jsonl");
+        let first = serde_json::to_string(&record(1, Some(990), 10)).unwrap();
+        // a bad line and a last line truncated by a crash
+        std::fs::write(
+            &file_path,
+            format!(
+                "{}\n{{\"height\":130,\"epoch\":\"one\"}}\n{{\"height\":1",
+                first
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            read_records(&file_path).unwrap(),
+            vec![record(1, Some(990), 10)]
+        );
+        let discarded_path = discard_unparsable_lines(&file_path).unwrap().unwrap();
+        assert!(std::fs::read_to_string(&discarded_path)
+            .unwrap()
+            .ends_with("{\"height\":1"));
+        assert_eq!(discard_unparsable_lines(&file_path).unwrap(), None);
+        let mut history = EpochHistory::new(file_path.clone()).unwrap();
+        assert_eq!(history.last_epoch, Some(EpochCounter(1)));
+        history.add_if_new_epoch(record(2, None, 12)).unwrap();
+        assert_eq!(
+            read_records(&file_path).unwrap(),
+            vec![record(1, Some(990), 10), record(2, None, 12)]
+        );
+        std::fs::remove_file(&file_path).unwrap();
+        std::fs::remove_file(&discarded_path).unwrap();
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let input = [
+            record(1, Some(990), 10),
+            record(2, None, 12),
+            record(3, Some(1010), 1),
+        ]
+        .iter()
+        .map(|r| serde_json::to_string(r).unwrap())
+        .collect::<Vec<String>>()
+        .join("\n");
+        let mut output = Vec::new();
+        write_csv(input.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "height,epoch,posted_value,consensus_value,reward_tokens_earned\n\
+            130,1,990,1000,0\n\
+            160,2,,1000,2\n\
+            190,3,1010,1000,0\n"
+        );
+    }
+}
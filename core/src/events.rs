@@ -0,0 +1,232 @@
+//! Oracle events (datapoint posted, refresh completed, main loop error, epoch counter
+//! regression, insufficient funds, persistent consensus failure) published as JSON to the
+//! message broker of `event_sink` in the oracle config, for the event driven automation around
+//! the oracle. Publishing is best effort, a failure is only logged.
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::action_report::PoolActionReport;
+use crate::oracle_config::EventSink;
+use crate::oracle_config::EventSinkKind;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::Rate;
+
+const BROKER_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum OracleEvent {
+    DatapointPosted {
+        height: BlockHeight,
+        datapoint: Rate,
+    },
+    RefreshCompleted {
+        height: BlockHeight,
+        pool_rate: Rate,
+        collected_oracles: usize,
+    },
+    Error {
+        message: String,
+    },
+    /// The pool box epoch counter went below the highest one seen
+    EpochCounterRegression {
+        height: BlockHeight,
+        highest: EpochCounter,
+        current: EpochCounter,
+    },
+    /// The wallet can't pay for an action, `missing` nanoERG have to be sent to `address`
+    InsufficientFunds {
+        height: BlockHeight,
+        address: String,
+        needed: u64,
+        available: u64,
+        missing: u64,
+    },
+    /// The refresh failed to reach consensus for `consensus_failure_escalation_epochs` epochs
+    ConsensusFailureEscalated {
+        height: BlockHeight,
+        failed_epochs: u32,
+        found: i32,
+        expected: i32,
+    },
+    /// The pool box value is projected to drop to `floor` within `epochs_left` epochs
+    /// (`monitor-pool-value`)
+    PoolValueAlert {
+        epoch: EpochCounter,
+        value: u64,
+        floor: u64,
+        epochs_left: u64,
+    },
+    /// The pool box has fewer than the `required` reward tokens for a refresh
+    RewardDepletion {
+        height: BlockHeight,
+        available: u64,
+        required: u64,
+    },
+}
+
+impl OracleEvent {
+    pub fn from_report(report: &PoolActionReport, height: BlockHeight) -> Self {
+        match report {
+            PoolActionReport::PublishDatapoint(report) => OracleEvent::DatapointPosted {
+                height,
+                datapoint: report.posted_datapoint,
+            },
+            PoolActionReport::Refresh(report) => OracleEvent::RefreshCompleted {
+                height,
+                pool_rate: report.pool_rate,
+                collected_oracles: report.oracle_boxes_collected.len(),
+            },
+        }
+    }
+}
+
+/// Published JSON, the event fields and the event name in `event`
+#[derive(Debug, Serialize)]
+struct EventMessage<'a> {
+    /// Seconds since the Unix epoch
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a OracleEvent,
+}
+
+#[derive(Debug, Error)]
+pub enum EventSinkError {
+    #[error("event sink IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("event sink JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("event sink address {0} doesn't resolve")]
+    UnresolvedAddress(String),
+    #[error("event sink broker error: {0}")]
+    Broker(String),
+}
+
+/// Publish the event to the configured `event_sink`, if any
+pub fn emit(event: OracleEvent) {
+    if let Some(sink) = &ORACLE_CONFIG.event_sink {
+        if let Err(e) = publish(sink, &event) {
+            log::warn!("Failed to publish {:?} to the event sink: {}", event, e);
+        }
+    }
+}
+
+fn publish(sink: &EventSink, event: &OracleEvent) -> Result<(), EventSinkError> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let payload = serde_json::to_string(&EventMessage { timestamp, event })?;
+    let address = sink
+        .address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| EventSinkError::UnresolvedAddress(sink.address.clone()))?;
+    let mut stream = TcpStream::connect_timeout(&address, BROKER_TIMEOUT)?;
+    stream.set_read_timeout(Some(BROKER_TIMEOUT))?;
+    stream.set_write_timeout(Some(BROKER_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    match sink.kind {
+        EventSinkKind::Redis => {
+            stream.write_all(&redis_publish_command(&sink.channel, &payload))?;
+            reader.read_line(&mut line)?;
+        }
+        EventSinkKind::Nats => {
+            // the server greets with its INFO line
+            reader.read_line(&mut line)?;
+            stream.write_all(&nats_publish_command(&sink.channel, &payload))?;
+            line.clear();
+            reader.read_line(&mut line)?;
+        }
+    }
+    check_broker_reply(&line)
+}
+
+/// Redis `PUBLISH channel payload` in the RESP protocol
+fn redis_publish_command(channel: &str, payload: &str) -> Vec<u8> {
+    let mut command = b"*3\r\n$7\r\nPUBLISH\r\n".to_vec();
+    for arg in [channel, payload] {
+        command.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    command
+}
+
+/// NATS `PUB` of the payload on the subject, followed by a `PING` so that the `PONG` reply
+/// confirms the server processed it
+fn nats_publish_command(subject: &str, payload: &str) -> Vec<u8> {
+    format!(
+        "CONNECT {{\"verbose\":false,\"pedantic\":false}}\r\nPUB {} {}\r\n{}\r\nPING\r\n",
+        subject,
+        payload.len(),
+        payload
+    )
+    .into_bytes()
+}
+
+/// Redis replies with `:<subscribers>`, NATS with `PONG`, both send errors as `-ERR ...`
+fn check_broker_reply(line: &str) -> Result<(), EventSinkError> {
+    let line = line.trim_end();
+    if line.starts_with(':') || line == "PONG" {
+        Ok(())
+    } else if line.is_empty() {
+        Err(EventSinkError::Broker("no reply".to_string()))
+    } else {
+        Err(EventSinkError::Broker(line.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_message() {
+        let event = OracleEvent::DatapointPosted {
+            height: BlockHeight(1000),
+            datapoint: 200.into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&EventMessage {
+                timestamp: 1700000000,
+                event: &event
+            })
+            .unwrap(),
+            r#"{"timestamp":1700000000,"event":"datapoint_posted","height":1000,"datapoint":200}"#
+        );
+    }
+
+    #[test]
+    fn test_publish_commands() {
+        assert_eq!(
+            redis_publish_command("oracle", "{}"),
+            b"*3\r\n$7\r\nPUBLISH\r\n$6\r\noracle\r\n$2\r\n{}\r\n".to_vec()
+        );
+        assert!(String::from_utf8(nats_publish_command("oracle", "{}"))
+            .unwrap()
+            .ends_with("\r\nPUB oracle 2\r\n{}\r\nPING\r\n"));
+    }
+
+    #[test]
+    fn test_check_broker_reply() {
+        assert!(check_broker_reply(":1\r\n").is_ok());
+        assert!(check_broker_reply("PONG\r\n").is_ok());
+        assert!(matches!(
+            check_broker_reply("-ERR 'Authorization Violation'\r\n"),
+            Err(EventSinkError::Broker(_))
+        ));
+        assert!(check_broker_reply("").is_err());
+    }
+}
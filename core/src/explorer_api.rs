@@ -1,8 +1,11 @@
 use std::time::Duration;
+use std::time::Instant;
 
 use ergo_lib::chain::transaction::Transaction;
 use ergo_lib::chain::transaction::TxId;
 use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
+use ergo_lib::ergotree_ir::chain::ergo_box::BoxId;
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
 use reqwest::blocking::RequestBuilder;
 use reqwest::blocking::Response;
 use reqwest::header::CONTENT_TYPE;
@@ -10,6 +13,9 @@ use reqwest::Url;
 use thiserror::Error;
 use url::ParseError;
 
+use crate::http_client::response_status;
+use crate::http_client::BLOCKING_HTTP_CLIENT;
+use crate::http_trace;
 use crate::oracle_config::ORACLE_CONFIG;
 
 use self::explorer_url::default_explorer_api_url;
@@ -25,6 +31,14 @@ pub enum ExplorerApiError {
     SerdeError(#[from] serde_json::Error),
     #[error("invalid explorer url: {0}")]
     InvalidExplorerUrl(#[from] ParseError),
+    #[error("no numConfirmations in the explorer response")]
+    NoConfirmations,
+    #[error("txs {tx_ids:?} don't have {depth} confirmation(s) after {timeout_secs} seconds, they may still be confirmed later or dropped")]
+    NotConfirmed {
+        tx_ids: Vec<TxId>,
+        depth: u32,
+        timeout_secs: u64,
+    },
 }
 
 pub struct ExplorerApi {
@@ -36,6 +50,15 @@ impl ExplorerApi {
         Self { url }
     }
 
+    /// The `explorer_url` of the oracle config, the public explorer of the network if not set
+    pub fn from_config() -> Self {
+        Self::new(
+            ORACLE_CONFIG.explorer_url.clone().unwrap_or_else(|| {
+                default_explorer_api_url(ORACLE_CONFIG.oracle_address.network())
+            }),
+        )
+    }
+
     /// Sets required headers for a request
     fn set_req_headers(&self, rb: RequestBuilder) -> RequestBuilder {
         rb.header("accept", "application/json")
@@ -45,8 +68,18 @@ impl ExplorerApi {
     /// Sends a GET request to the Ergo node
     fn send_get_req(&self, endpoint: &str) -> Result<Response, ExplorerApiError> {
         let url = self.url.join(endpoint)?;
-        let client = reqwest::blocking::Client::new().get(url);
-        let response = self.set_req_headers(client).send()?;
+        let client = BLOCKING_HTTP_CLIENT.get(url.clone());
+        let started = Instant::now();
+        let res = self.set_req_headers(client).send();
+        if http_trace::is_enabled() {
+            http_trace::log_request(
+                "GET",
+                url.as_str(),
+                &response_status(res.as_ref().map(|r| r.status())),
+                started,
+            );
+        }
+        let response = res?;
         if response.status().is_success() {
             Ok(response)
         } else {
@@ -64,6 +97,28 @@ impl ExplorerApi {
         log::debug!("get_transaction_v1 response: {}", text);
         Ok(serde_json::from_str(&text)?)
     }
+
+    /// GET /api/v1/boxes/{id}, also finds the spent boxes
+    pub fn get_box_v1(&self, box_id: BoxId) -> Result<ErgoBox, ExplorerApiError> {
+        let endpoint = "/api/v1/boxes/".to_owned() + &String::from(box_id);
+        let response = self.send_get_req(&endpoint)?;
+        Ok(serde_json::from_str(&response.text()?)?)
+    }
+
+    /// Number of confirmations of the tx, from GET /api/v1/transactions/{id}
+    pub fn get_transaction_confirmations(&self, tx_id: TxId) -> Result<u32, ExplorerApiError> {
+        let endpoint = "/api/v1/transactions/".to_owned() + &tx_id.to_string();
+        let response = self.send_get_req(&endpoint)?;
+        num_confirmations(&response.text()?)
+    }
+}
+
+fn num_confirmations(text: &str) -> Result<u32, ExplorerApiError> {
+    let tx: serde_json::Value = serde_json::from_str(text)?;
+    tx["numConfirmations"]
+        .as_u64()
+        .map(|n| n as u32)
+        .ok_or(ExplorerApiError::NoConfirmations)
 }
 
 pub(crate) fn ergo_explorer_transaction_link(tx_id: TxId, prefix: NetworkPrefix) -> String {
@@ -79,45 +134,68 @@ pub(crate) fn ergo_explorer_transaction_link(tx_id: TxId, prefix: NetworkPrefix)
         .to_string()
 }
 
-pub fn wait_for_tx_confirmation(tx_id: TxId) {
-    wait_for_txs_confirmation(vec![tx_id]);
+pub fn wait_for_tx_confirmation(tx_id: TxId) -> Result<(), ExplorerApiError> {
+    wait_for_txs_confirmation(vec![tx_id])
 }
 
-pub fn wait_for_txs_confirmation(tx_ids: Vec<TxId>) {
-    let network = ORACLE_CONFIG.oracle_address.network();
-    let timeout = Duration::from_secs(1200);
-    let explorer_url = ORACLE_CONFIG
-        .explorer_url
-        .clone()
-        .unwrap_or_else(|| default_explorer_api_url(network));
-    let explorer_api = ExplorerApi::new(explorer_url);
+/// Wait until the txs have `confirmation_depth` (oracle config) confirmations. Fails with the
+/// txs that are still unconfirmed on the timeout.
+pub fn wait_for_txs_confirmation(tx_ids: Vec<TxId>) -> Result<(), ExplorerApiError> {
+    let depth = ORACLE_CONFIG.confirmation_depth.unwrap_or(1).max(1);
+    let timeout = confirmation_timeout(depth);
+    let explorer_api = ExplorerApi::from_config();
     let start_time = std::time::Instant::now();
-    println!("Waiting for block confirmation from ExplorerApi for tx ids: {tx_ids:?} ...");
+    println!(
+        "Waiting for {depth} block confirmation(s) from ExplorerApi for tx ids: {tx_ids:?} ..."
+    );
     let mut remaining_txs = tx_ids.clone();
     loop {
         for tx_id in remaining_txs.clone() {
-            match explorer_api.get_transaction_v1(tx_id) {
+            let found = match explorer_api.get_transaction_v1(tx_id) {
                 Ok(tx) => {
                     assert_eq!(tx.id(), tx_id);
                     log::info!("Transaction found: {tx_id}");
-                    remaining_txs.retain(|id| *id != tx_id);
+                    true
                 }
                 Err(ExplorerApiError::SerdeError(_)) => {
                     // remove after https://github.com/ergoplatform/explorer-backend/issues/249 is fixed
                     log::info!("Transaction found, but failed to parse: {tx_id}");
+                    true
+                }
+                Err(_e) => {
+                    log::debug!("ExplorerApi error: {_e}");
+                    false
+                }
+            };
+            if !found {
+                continue;
+            }
+            if depth == 1 {
+                remaining_txs.retain(|id| *id != tx_id);
+                continue;
+            }
+            match explorer_api.get_transaction_confirmations(tx_id) {
+                Ok(confirmations) if confirmations >= depth => {
+                    log::info!("Transaction {tx_id} has {confirmations} confirmations");
                     remaining_txs.retain(|id| *id != tx_id);
                 }
+                Ok(confirmations) => {
+                    println!("Transaction {tx_id}: {confirmations} of {depth} confirmations");
+                }
                 Err(_e) => {
                     log::debug!("ExplorerApi error: {_e}");
                 }
             }
         }
         if remaining_txs.is_empty() {
-            break;
+            return Ok(());
         }
         if start_time.elapsed() > timeout {
-            println!("Timeout waiting for transactions");
-            break;
+            return Err(ExplorerApiError::NotConfirmed {
+                tx_ids: remaining_txs,
+                depth,
+                timeout_secs: timeout.as_secs(),
+            });
         }
         println!(
             "Elapsed: {}s out of {}s (timeout)",
@@ -127,3 +205,32 @@ pub fn wait_for_txs_confirmation(tx_ids: Vec<TxId>) {
         std::thread::sleep(std::time::Duration::from_secs(30));
     }
 }
+
+/// 20 minutes for the first confirmation and 4 minutes (2 blocks) for each further one
+fn confirmation_timeout(depth: u32) -> Duration {
+    Duration::from_secs(1200 + 240 * (depth.max(1) as u64 - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_num_confirmations() {
+        assert_eq!(
+            num_confirmations(r#"{"id":"ab","inclusionHeight":900000,"numConfirmations":3}"#)
+                .unwrap(),
+            3
+        );
+        assert!(matches!(
+            num_confirmations(r#"{"id":"ab"}"#),
+            Err(ExplorerApiError::NoConfirmations)
+        ));
+    }
+
+    #[test]
+    fn test_confirmation_timeout() {
+        assert_eq!(confirmation_timeout(1), Duration::from_secs(1200));
+        assert_eq!(confirmation_timeout(6), Duration::from_secs(2400));
+    }
+}
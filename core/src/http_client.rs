@@ -0,0 +1,230 @@
+//! HTTP clients with configurable connection pooling, shared by the node API, the datapoint
+//! sources and the explorer API. `ergo-node-interface` builds a client for every request, so
+//! [`NodeApi`](crate::node_interface::node_api::NodeApi) sends the node requests with
+//! [`BLOCKING_HTTP_CLIENT`] except for the few it leaves to `ergo-node-interface` (the scan
+//! registration and the transaction signing and submission).
+
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::http_trace;
+use crate::oracle_config::OracleConfig;
+use crate::oracle_config::ORACLE_CONFIG_OPT;
+
+/// reqwest defaults
+const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpPoolSettings {
+    pub max_idle_per_host: usize,
+    pub idle_timeout: Option<Duration>,
+    pub keep_alive: bool,
+}
+
+impl Default for HttpPoolSettings {
+    fn default() -> Self {
+        HttpPoolSettings {
+            max_idle_per_host: usize::MAX,
+            idle_timeout: Some(Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT_SECS)),
+            keep_alive: true,
+        }
+    }
+}
+
+impl HttpPoolSettings {
+    pub fn from_config(config: &OracleConfig) -> Self {
+        let default = HttpPoolSettings::default();
+        HttpPoolSettings {
+            max_idle_per_host: config
+                .http_pool_max_idle_per_host
+                .unwrap_or(default.max_idle_per_host),
+            idle_timeout: config
+                .http_pool_idle_timeout_secs
+                .map(Duration::from_secs)
+                .or(default.idle_timeout),
+            keep_alive: config.http_keep_alive.unwrap_or(default.keep_alive),
+        }
+    }
+
+    /// Idle connections are not kept at all when keep-alive is off
+    fn effective_max_idle_per_host(&self) -> usize {
+        if self.keep_alive {
+            self.max_idle_per_host
+        } else {
+            0
+        }
+    }
+
+    pub fn async_client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .pool_max_idle_per_host(self.effective_max_idle_per_host())
+            .pool_idle_timeout(self.idle_timeout)
+            .build()
+            .unwrap()
+    }
+
+    pub fn blocking_client(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::builder()
+            .pool_max_idle_per_host(self.effective_max_idle_per_host())
+            .pool_idle_timeout(self.idle_timeout)
+            .build()
+            .unwrap()
+    }
+}
+
+lazy_static! {
+    pub static ref HTTP_POOL_SETTINGS: HttpPoolSettings = ORACLE_CONFIG_OPT
+        .as_ref()
+        .map(HttpPoolSettings::from_config)
+        .unwrap_or_default();
+    /// Pooled connections are bound to the tokio runtime they were opened on, so use it with
+    /// [`DATAPOINT_FETCH_RUNTIME`]
+    pub static ref ASYNC_HTTP_CLIENT: reqwest::Client = HTTP_POOL_SETTINGS.async_client();
+    /// For the node and the explorer requests
+    pub static ref BLOCKING_HTTP_CLIENT: reqwest::blocking::Client =
+        HTTP_POOL_SETTINGS.blocking_client();
+    /// Runtime for the datapoint source fetches, kept alive so that the pooled connections are
+    /// reused between the fetches
+    pub static ref DATAPOINT_FETCH_RUNTIME: tokio::runtime::Runtime =
+        tokio::runtime::Runtime::new().unwrap();
+    /// URLs and bodies of the responses received with [`http_get_text`], recorded only after
+    /// [`start_capturing_responses`]
+    static ref CAPTURED_RESPONSES: Mutex<Option<Vec<(String, String)>>> = Mutex::new(None);
+    /// URLs and bodies returned by [`http_get_text`] instead of sending the requests, set by
+    /// [`replay_responses`]
+    static ref REPLAYED_RESPONSES: Mutex<Option<Vec<(String, String)>>> = Mutex::new(None);
+}
+
+/// GET request with the shared async client, traced with `--trace-http`
+pub async fn http_get(url: &str) -> reqwest::Result<reqwest::Response> {
+    http_get_with_headers(url, &[]).await
+}
+
+/// [`http_get`] with extra request headers, e.g. an API key. The headers are not traced.
+pub async fn http_get_with_headers(
+    url: &str,
+    headers: &[(&str, String)],
+) -> reqwest::Result<reqwest::Response> {
+    let started = Instant::now();
+    let mut request = ASYNC_HTTP_CLIENT.get(url);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    let res = request.send().await;
+    if http_trace::is_enabled() {
+        http_trace::log_request(
+            "GET",
+            url,
+            &response_status(res.as_ref().map(|r| r.status())),
+            started,
+        );
+    }
+    res
+}
+
+/// GET request with the shared async client, returns the response body
+pub async fn http_get_text(url: &str) -> reqwest::Result<String> {
+    http_get_text_with_headers(url, &[]).await
+}
+
+/// [`http_get_text`] with extra request headers
+pub async fn http_get_text_with_headers(
+    url: &str,
+    headers: &[(&str, String)],
+) -> reqwest::Result<String> {
+    let replayed = REPLAYED_RESPONSES
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|responses| take_replayed_response(responses, url));
+    let body = match replayed {
+        Some(body) => body,
+        None => http_get_with_headers(url, headers).await?.text().await?,
+    };
+    if let Some(captured) = CAPTURED_RESPONSES.lock().unwrap().as_mut() {
+        captured.push((url.to_string(), body.clone()));
+    }
+    Ok(body)
+}
+
+/// Record the responses of the following [`http_get_text`] calls until
+/// [`take_captured_responses`] is called
+pub fn start_capturing_responses() {
+    *CAPTURED_RESPONSES.lock().unwrap() = Some(Vec::new());
+}
+
+/// Stop capturing and return the captured URLs and response bodies
+pub fn take_captured_responses() -> Vec<(String, String)> {
+    CAPTURED_RESPONSES
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap_or_default()
+}
+
+/// Answer the following [`http_get_text`] calls with the saved responses instead of sending the
+/// requests, e.g. to run the source parsers offline on a captured response. A URL is answered
+/// with its saved responses in order, a URL without one gets an empty body.
+pub fn replay_responses(responses: Vec<(String, String)>) {
+    *REPLAYED_RESPONSES.lock().unwrap() = Some(responses);
+}
+
+fn take_replayed_response(responses: &mut Vec<(String, String)>, url: &str) -> String {
+    match responses.iter().position(|(saved_url, _)| saved_url == url) {
+        Some(i) => responses.remove(i).1,
+        None => {
+            log::error!("No saved response for {}", url);
+            String::new()
+        }
+    }
+}
+
+pub fn response_status(res: Result<reqwest::StatusCode, &reqwest::Error>) -> String {
+    match res {
+        Ok(status) => status.to_string(),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_settings_from_config() {
+        let config = OracleConfig {
+            http_pool_max_idle_per_host: Some(4),
+            http_pool_idle_timeout_secs: Some(10),
+            http_keep_alive: Some(false),
+            ..OracleConfig::default()
+        };
+        let settings = HttpPoolSettings::from_config(&config);
+        assert_eq!(
+            settings,
+            HttpPoolSettings {
+                max_idle_per_host: 4,
+                idle_timeout: Some(Duration::from_secs(10)),
+                keep_alive: false,
+            }
+        );
+        assert_eq!(settings.effective_max_idle_per_host(), 0);
+        assert_eq!(
+            HttpPoolSettings::from_config(&OracleConfig::default()),
+            HttpPoolSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_take_replayed_response() {
+        let mut responses = vec![
+            ("https://a".to_string(), "1".to_string()),
+            ("https://b".to_string(), "2".to_string()),
+            ("https://a".to_string(), "3".to_string()),
+        ];
+        assert_eq!(take_replayed_response(&mut responses, "https://a"), "1");
+        assert_eq!(take_replayed_response(&mut responses, "https://a"), "3");
+        assert_eq!(take_replayed_response(&mut responses, "https://a"), "");
+        assert_eq!(responses.len(), 1);
+    }
+}
@@ -0,0 +1,90 @@
+//! Records kept in the data dir as JSON lines files (the epoch history, the reward tokens
+//! extractions), appended one line per record.
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub fn append_record<T: Serialize, E: From<std::io::Error> + From<serde_json::Error>>(
+    file_path: &Path,
+    record: &T,
+) -> Result<(), E> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Replace the file with the records
+pub fn write_records<T: Serialize, E: From<std::io::Error> + From<serde_json::Error>>(
+    file_path: &Path,
+    records: &[T],
+) -> Result<(), E> {
+    let mut file = File::create(file_path)?;
+    for record in records {
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+    }
+    Ok(())
+}
+
+/// The records of the file (none if it doesn't exist) and the number of the lines that can't be
+/// parsed, these are skipped with a warning
+pub fn read_records<T: DeserializeOwned, E: From<std::io::Error>>(
+    file_path: &Path,
+) -> Result<(Vec<T>, usize), E> {
+    if !file_path.exists() {
+        return Ok((Vec::new(), 0));
+    }
+    let mut records = Vec::new();
+    let mut unparsable = 0;
+    for (idx, line) in BufReader::new(File::open(file_path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => {
+                log::warn!(
+                    "Skipping unparsable line {} of {}: {}",
+                    idx + 1,
+                    file_path.display(),
+                    e
+                );
+                unparsable += 1;
+            }
+        }
+    }
+    Ok((records, unparsable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_records_skips_unparsable_lines() {
+        let path = std::env::temp_dir().join("oracle_core_test_jsonl.jsonl");
+        let _ = std::fs::remove_file(&path);
+        append_record::<_, std::io::Error>(&path, &1u32).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"trunc\n\n")
+            .unwrap();
+        append_record::<_, std::io::Error>(&path, &2u32).unwrap();
+        let (records, unparsable) = read_records::<u32, std::io::Error>(&path).unwrap();
+        assert_eq!(records, vec![1, 2]);
+        assert_eq!(unparsable, 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
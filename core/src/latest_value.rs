@@ -0,0 +1,73 @@
+//! Latest pool rate written to a file (`latest_value_file` in the oracle config) on every main
+//! loop iteration, for the consumers that would rather read a file than the REST API.
+
+use std::path::Path;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::oracle_state::LiveEpochState;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochCounter;
+use crate::oracle_types::Rate;
+use crate::output::write_atomically;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatestValue {
+    /// Pool box rate
+    pub rate: Rate,
+    pub epoch: EpochCounter,
+    /// Pool box creation height
+    pub height: BlockHeight,
+    /// When the pool box was read, seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl LatestValue {
+    pub fn from_live_epoch_state(live_epoch_state: &LiveEpochState) -> Self {
+        LatestValue {
+            rate: (live_epoch_state.latest_pool_datapoint as i64).into(),
+            epoch: live_epoch_state.pool_box_epoch_id,
+            height: live_epoch_state.latest_pool_box_height,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Write the value as JSON atomically, so that the readers never see a partially written file
+pub fn write_latest_value(path: &Path, value: &LatestValue) -> Result<(), std::io::Error> {
+    write_atomically(path, serde_json::to_string(value)?.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::tmp_file_path;
+
+    #[test]
+    fn test_write_latest_value() {
+        let path = std::env::temp_dir().join("oracle_core_test_latest_value.json");
+        let value = LatestValue {
+            rate: 769230769.into(),
+            epoch: EpochCounter(12),
+            height: BlockHeight(900000),
+            timestamp: 1700000000,
+        };
+        write_latest_value(&path, &value).unwrap();
+        let newer_value = LatestValue {
+            epoch: EpochCounter(13),
+            ..value
+        };
+        write_latest_value(&path, &newer_value).unwrap();
+        let read_value: LatestValue =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read_value, newer_value);
+        assert!(!tmp_file_path(&path).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,4 +1,7 @@
 use std::path::Path;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use log::LevelFilter;
 use log4rs::append::console::ConsoleAppender;
@@ -9,14 +12,53 @@ use log4rs::append::rolling_file::RollingFileAppender;
 use log4rs::config::Appender;
 use log4rs::config::Logger;
 use log4rs::config::Root;
+use log4rs::encode::pattern::PatternEncoder;
 use log4rs::Config;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Timezone of the log timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogTimezone {
+    Local,
+    Utc,
+}
+
+/// Timestamp format and timezone of the log lines. The log4rs default (ISO 8601 in local time)
+/// is used for the unset ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogTimestampSettings {
+    /// chrono format string, e.g. `%Y-%m-%dT%H:%M:%S%.3fZ`
+    pub format: Option<String>,
+    pub timezone: Option<LogTimezone>,
+}
+
+/// ISO 8601 with milliseconds and the UTC offset, the log4rs default
+const DEFAULT_TIMESTAMP_FORMAT: &str = "%+";
+
+impl LogTimestampSettings {
+    /// log4rs pattern of the log lines, same as the log4rs default apart from the timestamp
+    fn pattern(&self) -> String {
+        let format = self.format.as_deref().unwrap_or(DEFAULT_TIMESTAMP_FORMAT);
+        let timezone = match self.timezone.unwrap_or(LogTimezone::Local) {
+            LogTimezone::Local => "local",
+            LogTimezone::Utc => "utc",
+        };
+        format!("{{d({})({})}} {{l}} {{t}} - {{m}}{{n}}", format, timezone)
+    }
+}
 
 pub fn setup_log(
     cmdline_log_level: Option<LevelFilter>,
     config_log_level: Option<LevelFilter>,
+    timestamp_settings: &LogTimestampSettings,
     data_dir: &Path,
 ) {
-    let stdout = ConsoleAppender::builder().build();
+    let stdout = ConsoleAppender::builder()
+        .encoder(Box::new(PatternEncoder::new(&timestamp_settings.pattern())))
+        .build();
 
     // via https://stackoverflow.com/questions/56345288/how-do-i-use-log4rs-rollingfileappender-to-incorporate-rolling-logging#
     let window_size = 3; // log0, log1, log2
@@ -51,6 +93,7 @@ pub fn setup_log(
                 "logfile",
                 Box::new(
                     RollingFileAppender::builder()
+                        .encoder(Box::new(PatternEncoder::new(&timestamp_settings.pattern())))
                         .build(data_dir.join("oracle-core.log"), Box::new(compound_policy))
                         .unwrap(),
                 ),
@@ -75,3 +118,77 @@ pub fn setup_log(
 
     log_panics::init();
 }
+
+/// `log_sample_rate` of the oracle config, 1 logs every occurrence
+static LOG_SAMPLE_RATE: AtomicU32 = AtomicU32::new(1);
+
+pub fn set_log_sample_rate(rate: u32) {
+    LOG_SAMPLE_RATE.store(rate.max(1), Ordering::SeqCst);
+}
+
+/// Occurrence counter of a sampled log call site, see [`sampled_debug`]
+#[derive(Default)]
+pub struct LogSampler {
+    occurrences: AtomicU64,
+}
+
+impl LogSampler {
+    pub const fn new() -> Self {
+        LogSampler {
+            occurrences: AtomicU64::new(0),
+        }
+    }
+
+    /// True for the first occurrence and then every `log_sample_rate`-th one
+    pub fn sample(&self) -> bool {
+        is_sampled(
+            self.occurrences.fetch_add(1, Ordering::Relaxed),
+            LOG_SAMPLE_RATE.load(Ordering::Relaxed),
+        )
+    }
+}
+
+fn is_sampled(occurrence: u64, rate: u32) -> bool {
+    occurrence % u64::from(rate.max(1)) == 0
+}
+
+/// `log::debug!` for the messages logged every main loop iteration, only 1-in-`log_sample_rate`
+/// of them are logged. The arguments aren't evaluated for the skipped ones.
+macro_rules! sampled_debug {
+    ($($arg:tt)+) => {{
+        static SAMPLER: $crate::logging::LogSampler = $crate::logging::LogSampler::new();
+        if SAMPLER.sample() {
+            log::debug!($($arg)+);
+        }
+    }};
+}
+pub(crate) use sampled_debug;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_pattern() {
+        assert_eq!(
+            LogTimestampSettings::default().pattern(),
+            "{d(%+)(local)} {l} {t} - {m}{n}"
+        );
+        assert_eq!(
+            LogTimestampSettings {
+                format: Some("%Y-%m-%dT%H:%M:%S%.3fZ".to_string()),
+                timezone: Some(LogTimezone::Utc),
+            }
+            .pattern(),
+            "{d(%Y-%m-%dT%H:%M:%S%.3fZ)(utc)} {l} {t} - {m}{n}"
+        );
+    }
+
+    #[test]
+    fn test_is_sampled() {
+        let sampled: Vec<u64> = (0..10).filter(|i| is_sampled(*i, 4)).collect();
+        assert_eq!(sampled, vec![0, 4, 8]);
+        assert!((0..10).all(|i| is_sampled(i, 1)));
+        assert!((0..10).all(|i| is_sampled(i, 0)));
+    }
+}
@@ -0,0 +1,96 @@
+//! Durations of the phases of the last main loop iteration, served by the REST API
+//! `/loopTiming` endpoint and logged at the debug level after every iteration.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopPhase {
+    /// Wallet unlock check and the current height
+    HeightFetch,
+    /// Pool state from the scanned boxes
+    StateEval,
+    /// Datapoint from the source (part of the publish datapoint action build)
+    DatapointFetch,
+    ActionBuild,
+    Submit,
+}
+
+/// Milliseconds spent in each phase, `None` for the phases the iteration didn't reach
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IterationTiming {
+    pub height_fetch_ms: Option<u64>,
+    pub state_eval_ms: Option<u64>,
+    pub datapoint_fetch_ms: Option<u64>,
+    pub action_build_ms: Option<u64>,
+    pub submit_ms: Option<u64>,
+    pub total_ms: u64,
+}
+
+impl IterationTiming {
+    fn record(&mut self, phase: LoopPhase, duration: Duration) {
+        let ms = Some(duration.as_millis() as u64);
+        match phase {
+            LoopPhase::HeightFetch => self.height_fetch_ms = ms,
+            LoopPhase::StateEval => self.state_eval_ms = ms,
+            LoopPhase::DatapointFetch => self.datapoint_fetch_ms = ms,
+            LoopPhase::ActionBuild => self.action_build_ms = ms,
+            LoopPhase::Submit => self.submit_ms = ms,
+        }
+    }
+}
+
+static CURRENT_ITERATION: Mutex<Option<IterationTiming>> = Mutex::new(None);
+static LAST_ITERATION: Mutex<Option<IterationTiming>> = Mutex::new(None);
+
+pub fn start_iteration() {
+    *CURRENT_ITERATION.lock().unwrap() = Some(IterationTiming::default());
+}
+
+/// Only recorded between `start_iteration` and `finish_iteration`, so that the datapoint
+/// fetches of the other commands are ignored
+pub fn record(phase: LoopPhase, duration: Duration) {
+    if let Some(timing) = CURRENT_ITERATION.lock().unwrap().as_mut() {
+        timing.record(phase, duration);
+    }
+}
+
+pub fn finish_iteration(total: Duration) -> IterationTiming {
+    let mut timing = CURRENT_ITERATION.lock().unwrap().take().unwrap_or_default();
+    timing.total_ms = total.as_millis() as u64;
+    *LAST_ITERATION.lock().unwrap() = Some(timing.clone());
+    timing
+}
+
+pub fn last_iteration() -> Option<IterationTiming> {
+    LAST_ITERATION.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iteration_timing() {
+        record(LoopPhase::Submit, Duration::from_millis(5));
+        start_iteration();
+        record(LoopPhase::HeightFetch, Duration::from_millis(40));
+        record(LoopPhase::StateEval, Duration::from_millis(1200));
+        let timing = finish_iteration(Duration::from_millis(1250));
+        assert_eq!(
+            timing,
+            IterationTiming {
+                height_fetch_ms: Some(40),
+                state_eval_ms: Some(1200),
+                total_ms: 1250,
+                ..IterationTiming::default()
+            }
+        );
+        assert_eq!(last_iteration(), Some(timing));
+        // not in an iteration
+        record(LoopPhase::Submit, Duration::from_millis(5));
+        assert_eq!(last_iteration().unwrap().submit_ms, None);
+    }
+}
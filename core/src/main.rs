@@ -27,20 +27,36 @@ mod cli_commands;
 mod contracts;
 mod datapoint_source;
 mod default_parameters;
+mod epoch_history;
+mod events;
 mod explorer_api;
+mod http_client;
+mod http_trace;
+mod jsonl;
+mod latest_value;
 mod logging;
+mod loop_timing;
+mod metrics;
 mod migrate;
 mod node_interface;
 mod oracle_config;
 mod oracle_state;
 mod oracle_types;
+mod output;
+mod pause;
+mod pending_action;
 mod pool_commands;
 mod pool_config;
+mod reward_depletion;
+mod reward_extraction_history;
 mod scans;
 mod serde;
+mod shutdown;
 mod spec_token;
 mod state;
 mod templates;
+mod value_format;
+mod version_check;
 mod wallet;
 
 #[cfg(test)]
@@ -49,30 +65,61 @@ mod tests;
 use action_report::ActionReportStorage;
 use action_report::PoolActionReport;
 use actions::PoolAction;
-use anyhow::anyhow;
 use anyhow::Context;
-use clap::{Parser, Subcommand};
+use box_kind::OracleBox;
+use box_kind::PoolBox;
+use box_kind::RefreshBox;
+use clap::{Parser, Subcommand, ValueEnum};
 use crossbeam::channel::bounded;
+use crossbeam::channel::RecvTimeoutError;
+use crossbeam::channel::Sender;
+use datapoint_source::persisted;
+use datapoint_source::persisted::get_persisted_datapoint_file_path;
+use datapoint_source::persisted::ReuseKey;
+use datapoint_source::reference_feed;
 use datapoint_source::RuntimeDataPointSource;
+use epoch_history::get_epoch_history_file_path;
+use epoch_history::EpochHistory;
+use epoch_history::EpochHistoryRecord;
 use ergo_lib::ergo_chain_types::Digest32;
+use ergo_lib::ergo_chain_types::EcPoint;
 use ergo_lib::ergotree_ir::chain::address::Address;
 use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
 use ergo_lib::ergotree_ir::chain::address::NetworkPrefix;
 use ergo_lib::ergotree_ir::chain::token::TokenAmount;
 use ergo_lib::ergotree_ir::chain::token::TokenId;
+use events::OracleEvent;
 use log::error;
 use log::LevelFilter;
 use node_interface::assert_wallet_unlocked;
+use node_interface::change_address;
+use node_interface::ensure_wallet_unlocked;
+use node_interface::fault_injection;
+use node_interface::fault_injection::parse_failure_rate;
 use node_interface::node_api::NodeApi;
+use node_interface::node_network_matches;
+use node_interface::request_cache;
+use node_interface::run_startup_steps;
+use node_interface::StartupStep;
 use oracle_config::ORACLE_CONFIG;
+use oracle_state::ConsensusFailureTracker;
+use oracle_state::EpochCounterGuard;
+use oracle_state::LiveEpochState;
 use oracle_state::OraclePool;
 use oracle_types::BlockHeight;
+use oracle_types::EpochCounter;
+use oracle_types::EpochLength;
+use pending_action::get_pending_action_file_path;
+use pending_action::PendingAction;
 use pool_commands::build_action;
 use pool_commands::publish_datapoint::PublishDatapointActionError;
 use pool_commands::refresh::RefreshActionError;
 use pool_commands::PoolCommandError;
+use pool_commands::DEFAULT_ACTION_PRIORITY;
+use pool_config::TokenIds;
 use pool_config::DEFAULT_POOL_CONFIG_FILE_NAME;
 use pool_config::POOL_CONFIG;
+use reqwest::Url;
 use scans::get_scans_file_path;
 use scans::wait_for_node_rescan;
 use spec_token::RewardTokenId;
@@ -85,33 +132,59 @@ use std::env;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::RwLock;
-use std::thread;
 use std::time::Duration;
+use std::time::Instant;
 
-use crate::actions::execute_action;
-use crate::api::start_rest_server;
+use crate::actions::execute_action_with_retries;
+use crate::api::supervise_rest_server;
+use crate::cli_commands::extract_reward_tokens::parse_reward_split;
+use crate::cli_commands::extract_reward_tokens::RewardSplit;
+use crate::cli_commands::extract_reward_tokens::RewardsDestination;
+use crate::cli_commands::generate_service_file::absolute_path;
+use crate::cli_commands::manage_scans::ScanName;
+use crate::cli_commands::print_config_schema::ConfigSchema;
+use crate::cli_commands::suggest_fee::suggested_tx_fee;
 use crate::default_parameters::print_contract_hashes;
+use crate::latest_value::LatestValue;
+use crate::logging::sampled_debug;
+use crate::loop_timing::LoopPhase;
 use crate::migrate::check_migration_to_split_config;
+use crate::oracle_config::action_fee;
+use crate::oracle_config::BuildFailurePolicy;
+use crate::oracle_config::EpochRegressionPolicy;
+use crate::oracle_config::FeeKind;
+use crate::oracle_config::NetworkMismatchPolicy;
 use crate::oracle_config::OracleConfig;
+use crate::oracle_config::PendingActionPolicy;
 use crate::oracle_config::DEFAULT_ORACLE_CONFIG_FILE_NAME;
+use crate::oracle_config::DEFAULT_STARTUP_RETRY_ATTEMPTS;
 use crate::oracle_config::ORACLE_CONFIG_FILE_PATH;
 use crate::oracle_config::ORACLE_CONFIG_OPT;
 use crate::pool_config::POOL_CONFIG_FILE_PATH;
+use crate::reward_extraction_history::get_reward_extraction_history_file_path;
 use crate::scans::NodeScanRegistry;
+use crate::wallet::InsufficientFunds;
 
-const APP_VERSION: &str = concat!(
-    "v",
-    env!("CARGO_PKG_VERSION"),
-    "+",
-    env!("GIT_COMMIT_HASH"),
-    " ",
-    env!("GIT_COMMIT_DATE")
-);
+/// Delay between the main loop iterations
+const MAIN_LOOP_INTERVAL: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    /// Git commit info is set by build.rs and might be missing (e.g. when building from a source
+    /// tarball without git metadata)
+    static ref APP_VERSION: String = format!(
+        "v{}+{} {}",
+        env!("CARGO_PKG_VERSION"),
+        option_env!("GIT_COMMIT_HASH").unwrap_or("unknown"),
+        option_env!("GIT_COMMIT_DATE").unwrap_or("unknown")
+    );
+}
 
 #[derive(Debug, Parser)]
-#[clap(author, version = APP_VERSION, about, long_about = None)]
+#[clap(author, version = APP_VERSION.as_str(), about, long_about = None)]
 struct Args {
     #[clap(subcommand)]
     command: Command,
@@ -127,12 +200,39 @@ struct Args {
     /// Set folder path for the data files (scanIDs.json, logs). Default is the current folder.
     #[clap(short, long)]
     data_dir: Option<String>,
+    /// Write the result of the command to this file instead of printing it. The JSON results
+    /// (`consensus-value`, `print-config-schema`, `diagnostics`, `generate-dashboard`,
+    /// `health-attestation` and the unsigned tx of `preview-refresh` and
+    /// `extract-reward-tokens --preview`) and the `export-history` CSV replace the file
+    /// atomically, `snapshot-state` and `generate-service-file` write their file there. An error
+    /// for the other commands.
+    #[clap(long, global = true)]
+    output: Option<PathBuf>,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
     /// Generate oracle_config.yaml with default settings.
     GenerateOracleConfig,
+    /// Generate a minimal oracle_config.yaml with only the required fields, to monitor a pool
+    /// with `run --read-only --enable-rest-api` without the operator settings
+    GenerateMonitorConfig {
+        /// URL of the node, http://127.0.0.1:9053 if not set
+        #[clap(long)]
+        node_url: Option<Url>,
+    },
+    /// Write the oracle and pool configs derived from a seed file (oracle address, node URL,
+    /// token ids and optionally a bootstrap config), the same files for the same seed
+    DeriveConfig {
+        /// YAML file with the seed parameters
+        seed: PathBuf,
+    },
+    /// Check that the pool config and the oracle address and node URL of the oracle config
+    /// match the ones derived from the seed file
+    VerifyDerivedConfig {
+        /// YAML file with the seed parameters
+        seed: PathBuf,
+    },
     /// Bootstrap a new oracle-pool or generate a bootstrap config template file using default
     /// contract scripts and parameters.
     Bootstrap {
@@ -142,6 +242,10 @@ enum Command {
         /// Set this flag to output a bootstrap config template file to the given filename. If
         /// filename already exists, return error.
         generate_config_template: bool,
+        /// Build and sign the bootstrap transactions and print the ERG cost without submitting
+        /// them
+        #[clap(long)]
+        dry_run: bool,
     },
 
     /// Run the oracle-pool
@@ -152,17 +256,51 @@ enum Command {
         #[clap(long)]
         /// Set this flag to enable the REST API. NOTE: SSL is not used!
         enable_rest_api: bool,
+        /// Log every HTTP request to the node and the datapoint sources made in the first main
+        /// loop iteration (method, URL, status and latency)
+        #[clap(long)]
+        trace_http: bool,
+        /// Make this share (0 to 1) of the node API calls fail as if the node was unreachable,
+        /// to test the failure handling
+        #[clap(long, hide = true, value_parser = parse_failure_rate)]
+        simulate_node_failure_rate: Option<f64>,
     },
 
     /// Send reward tokens accumulated in the oracle box to a chosen address
     ExtractRewardTokens {
         /// Base58 encoded address to send reward tokens to
-        rewards_address: String,
+        #[clap(required_unless_present = "split", conflicts_with = "split")]
+        rewards_address: Option<String>,
+        /// Send AMOUNT of the reward tokens to ADDRESS instead, repeated for each recipient. The
+        /// amounts must add up to all the extractable reward tokens.
+        #[clap(long, value_name = "ADDRESS:AMOUNT", value_parser = parse_reward_split)]
+        split: Vec<RewardSplit>,
+        /// Print the extraction transaction without submitting it
+        #[clap(long)]
+        preview: bool,
     },
 
     /// Print the number of reward tokens earned by the oracle (in the last posted/collected oracle box)
     PrintRewardTokens,
 
+    /// Find all wallet and oracle boxes holding reward tokens and print their amounts
+    FindRewardTokens,
+
+    /// Keep checking the pool box value every epoch and warn in the log if it's projected to
+    /// drop to the floor soon
+    MonitorPoolValue {
+        /// Warn if the floor is projected to be reached within this many epochs
+        #[clap(long, default_value_t = 10)]
+        alert_epochs: u32,
+        /// Pool box value floor in nanoERG (the minimal box value by default)
+        #[clap(long)]
+        floor_nanoerg: Option<u64>,
+    },
+
+    /// Print registers R4-R9 of the local oracle box and the pool box as serialized hex and
+    /// decoded values
+    DumpRegisters,
+
     /// Transfer an oracle token to a chosen address.
     TransferOracleToken {
         /// Base58 encoded address to send oracle token to
@@ -173,13 +311,20 @@ enum Command {
     VoteUpdatePool {
         /// The base16-encoded blake2b hash of the serialized pool box contract for the new pool box.
         new_pool_box_address_hash_str: String,
-        /// The creation height of the existing update box.
-        update_box_creation_height: u32,
+        /// The creation height of the existing update box, read from the chain if not given.
+        /// Must match the on-chain one if given.
+        update_box_creation_height: Option<u32>,
         /// The base16-encoded reward token id of the new pool box (if minted)
         reward_token_id_str: Option<String>,
         /// The reward token amount in the pool box at the time of update transaction is committed (if minted).
         reward_token_amount: Option<u64>,
     },
+    /// Check that this oracle's ballot box holds the pool's ballot token and is accepted by the
+    /// ballot contract of the pool config, so its votes are counted
+    VerifyBallot,
+    /// Print the pool's oracles and contract parameters, whether there is room for a new oracle,
+    /// the tokens needed and the remaining steps for this oracle to join
+    CheckJoinEligibility,
     /// Initiate the Update Pool transaction.
     /// Updated config file `pool_config_updated.yaml` is expected to be in the current directory
     /// and must be created using --prepare-update command first
@@ -189,6 +334,32 @@ enum Command {
         /// New reward token amount (only if minted)
         reward_token_amount: Option<u64>,
     },
+    /// Print the state of the pool update: updated config file, update box, votes cast and
+    /// whether this oracle has voted
+    UpdateStatus,
+    /// Check that the pool box and the refresh box reference each other's tokens and match the
+    /// pool config (token ids, epoch length and other refresh parameters)
+    CheckPoolIntegrity,
+    /// Rebuild the pool box the last refresh should have made from the pool config and the
+    /// epoch history and print every difference from the on-chain pool box
+    VerifyPoolBox,
+    /// Detect a pool update left half-way (votes passed but the pool box not updated, or the
+    /// pool box updated but not the local pool config) and print the next step
+    CheckStuckUpdate,
+    /// Print the datapoints posted for the next refresh in the order the refresh spends them,
+    /// this oracle's position and whether it can submit the refresh
+    CollectionPriority,
+    /// Compare the local oracle datapoint and the pool box rate against the `reference_feed` of
+    /// the oracle config
+    CompareReference,
+    /// Print the pool value as JSON with how many oracles contributed to it, the spread of the
+    /// datapoints posted since and its age
+    ConsensusValue,
+    /// Report the oracles that posted the same datapoint in the same epoch
+    IdenticalDatapoints,
+    /// Print all the boxes of the pool (pool, refresh, update, oracle, ballot and buyback boxes)
+    /// with their ids, values and registers
+    InspectPool,
     /// Prepare updating oracle pool with new contracts/parameters.
     /// Creates new refresh box and pool box if needed (e.g. if new reward tokens are minted)
     PrepareUpdate {
@@ -196,17 +367,234 @@ enum Command {
         update_file: String,
     },
 
+    /// Check the contract parameters of the `prepare-update` parameters file against their
+    /// sensible bounds (positive epoch length, deviation within 0-100%, quorum not above the
+    /// number of oracles, etc.)
+    ValidateContractParams {
+        /// Name of the parameters file (.yaml) with new contract parameters
+        update_file: String,
+    },
+
     /// Print base 64 encodings of the blake2b hash of ergo-tree bytes of each contract
     PrintContractHashes,
 
+    /// Print the JSON Schema of the oracle or pool config file. Doesn't need the config files or
+    /// the node.
+    PrintConfigSchema {
+        #[clap(value_enum)]
+        config: ConfigSchema,
+    },
+
+    /// Generate a Grafana dashboard (JSON to import) with a panel for each metric of the REST
+    /// API `/metrics` endpoint. Doesn't need the config files or the node.
+    GenerateDashboard,
+
     ImportPoolUpdate {
         /// Name of the pool config file (.yaml) with new contract parameters
         pool_config_file: String,
     },
+
+    /// Build the refresh transaction against the current pool state and print it without
+    /// submitting
+    PreviewRefresh,
+
+    /// Check the refresh preconditions (epoch end, datapoints in consensus, own datapoint, pool
+    /// box reward tokens, wallet funds) against the current state and print pass/fail for each
+    RefreshReadiness,
+
+    /// Build the next action tx and print the wallet boxes it would spend, their values and the
+    /// change, without submitting it
+    PreviewFunding,
+
+    /// Fetch from each configured datapoint source several times and print success rate,
+    /// latency and value variance for each source
+    BenchmarkSources {
+        /// Number of fetches from each source
+        #[clap(long, default_value_t = 10)]
+        samples: usize,
+    },
+
+    /// Fetch once from a single configured datapoint source and print the response, the parsed
+    /// rate, the datapoint that would be posted and the latency
+    TestSource {
+        /// Source name as listed by `benchmark-sources` (e.g. coingecko)
+        name: String,
+    },
+
+    /// Fetch once from a single configured datapoint source and print the full responses and
+    /// the parsed rate or the parse error. The responses can be saved and parsed again offline.
+    DebugSource {
+        /// Source name as listed by `benchmark-sources` (e.g. coingecko)
+        name: String,
+        /// Save the raw HTTP responses to this file
+        #[clap(long)]
+        dump: Option<PathBuf>,
+        /// Parse the responses saved with `--dump` instead of fetching
+        #[clap(long)]
+        replay: Option<PathBuf>,
+    },
+
+    /// Print a human-readable explanation of what the current oracle and pool config do
+    ExplainConfig,
+
+    /// Print the base16-encoded blake2b hash of the contract, as expected by `vote-update-pool`
+    HashContract {
+        /// P2S address or base16-encoded ergo tree of the contract
+        address_or_tree_hex: String,
+    },
+
+    /// Derive the first address from the wallet mnemonic and compare it with `oracle_address`.
+    /// The mnemonic is read from stdin unless `--mnemonic-env-var` is given, and is never logged.
+    AddressFromMnemonic {
+        /// Environment variable holding the mnemonic
+        #[clap(long)]
+        mnemonic_env_var: Option<String>,
+        /// Environment variable holding the optional mnemonic password
+        #[clap(long)]
+        mnemonic_password_env_var: Option<String>,
+    },
+
+    /// Print (or write to a file) a health attestation of this oracle (node height, last post
+    /// height, version) signed with the node wallet key of the oracle address, for other oracles
+    /// to check with `verify-health-attestation`
+    HealthAttestation,
+
+    /// Check the signature of a health attestation made by `health-attestation` and print the
+    /// attested state. Doesn't need the config files or the node.
+    VerifyHealthAttestation {
+        /// Health attestation JSON file
+        file: PathBuf,
+    },
+
+    /// Report oracle and pool config fields that differ from the defaults of this binary, and
+    /// defaults that are not set in the oracle config
+    CheckDefaults,
+
+    /// Generate a systemd unit running `run` with this binary and the config files and data dir
+    /// given to this command
+    GenerateServiceFile {
+        /// Run in read-only mode
+        #[clap(long)]
+        read_only: bool,
+        /// Enable the REST API
+        #[clap(long)]
+        enable_rest_api: bool,
+    },
+
+    /// Print the recorded per-epoch history (posted and consensus values, earned reward tokens)
+    ExportHistory {
+        #[clap(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+    },
+
+    /// Report for each oracle how many of the last epochs in the epoch history it posted in and
+    /// how many of its datapoints were in consensus (collected into the pool box)
+    ParticipationReport {
+        /// Number of the latest recorded epochs to report on
+        #[clap(long, default_value_t = 30)]
+        epochs: usize,
+    },
+    /// Check the reward tokens earned by this oracle in the last recorded epoch against the
+    /// refresh contract rules
+    RewardAudit,
+    /// Compare the pool rates of the epochs in the epoch history between the heights with the
+    /// datapoints the configured source would have posted, from its historical prices
+    BacktestSource {
+        /// First block height of the range
+        #[clap(long)]
+        from: u32,
+        /// Last block height of the range
+        #[clap(long)]
+        to: u32,
+    },
+    /// List the reward tokens extractions made with `extract-reward-tokens` (height, amounts
+    /// and destination addresses) and check them against the txs in the node wallet
+    RewardExtractionHistory,
+    /// Print the fees the node recommends, based on its mempool, for the oracle txs to be
+    /// included within the target number of blocks
+    SuggestFee {
+        #[clap(long, default_value_t = 1)]
+        target_blocks: u32,
+    },
+    /// Testnet only. Bootstrap a throwaway pool, publish a datapoint to it, refresh it and burn
+    /// the leftover throwaway tokens, reporting the step that failed if any
+    SelfTestPipeline,
+    /// Print whether the tx is in the mempool, confirmed (at which height) or dropped and, if
+    /// dropped, which of its inputs were spent by another tx
+    TraceTx { tx_id: String },
+    /// Gather the version, the config (secrets redacted), the scans, the node status, the pool,
+    /// oracle and ballot boxes and the end of the log into one JSON file to attach to a support
+    /// request
+    Diagnostics,
+    /// Save the pool box, the oracle boxes and the ballot votes to a JSON file
+    /// (`pool-snapshot-<height>-<timestamp>.json` if not given with `--output`), compared later
+    /// with `diff-state`
+    SnapshotState,
+    /// Print the oracles that joined or left, the pool value changes and the reward token
+    /// movements between two `snapshot-state` files
+    DiffState { a: PathBuf, b: PathBuf },
+    /// Manage the node scans of the pool boxes one by one
+    Scans {
+        #[clap(subcommand)]
+        action: ScansCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ScansCommand {
+    /// List the scans of the scans file, whether the node has them, and the other scans of the
+    /// node
+    List,
+    /// Register the scans missing in the node, all of them if there is no scans file
+    Register,
+    /// Deregister a single scan in the node
+    Deregister {
+        #[clap(value_enum)]
+        scan: ScanName,
+    },
+    /// Print the wallet rescan progress and, for each scan, whether the node has it and its
+    /// unspent boxes count
+    Status,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
 }
 
 fn main() {
     let args = Args::parse();
+    if let Some(output) = &args.output {
+        if !supports_output(&args.command) {
+            eprintln!("--output is not supported by this command");
+            std::process::exit(exitcode::USAGE);
+        }
+        output::OUTPUT_PATH.set(output.clone()).unwrap();
+    }
+
+    if let Command::PrintConfigSchema { config } = args.command {
+        if let Err(e) = cli_commands::print_config_schema::print_config_schema(config) {
+            eprintln!("Fatal print-config-schema error: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
+
+    if matches!(args.command, Command::GenerateDashboard) {
+        if let Err(e) = cli_commands::generate_dashboard::generate_dashboard() {
+            eprintln!("Fatal generate-dashboard error: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
+
+    if let Command::VerifyHealthAttestation { file } = &args.command {
+        if let Err(e) = cli_commands::health_attestation::verify_health_attestation(file) {
+            eprintln!("Fatal verify-health-attestation error: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
 
     ORACLE_CONFIG_FILE_PATH
         .set(
@@ -238,6 +626,39 @@ fn main() {
         }
     }
 
+    if let Command::GenerateMonitorConfig { node_url } = &args.command {
+        if let Err(e) = cli_commands::generate_monitor_config::generate_monitor_config(
+            node_url.clone(),
+            oracle_config_path,
+        ) {
+            eprintln!("Fatal generate-monitor-config error: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
+
+    if let Command::DeriveConfig { seed } = &args.command {
+        if let Err(e) =
+            cli_commands::derive_config::derive_config(seed, oracle_config_path, pool_config_path)
+        {
+            eprintln!("Fatal derive-config error: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
+
+    if let Command::VerifyDerivedConfig { seed } = &args.command {
+        if let Err(e) = cli_commands::derive_config::verify_derived_config(
+            seed,
+            oracle_config_path,
+            pool_config_path,
+        ) {
+            eprintln!("Fatal verify-derived-config error: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
+
     if !oracle_config_path.exists() {
         OracleConfig::write_default_config_file(oracle_config_path);
         println!(
@@ -266,7 +687,26 @@ fn main() {
         .map(|c| c.log_level)
         .ok()
         .flatten();
-    logging::setup_log(cmdline_log_level, config_log_level, &data_dir_path);
+    let log_timestamp_settings = ORACLE_CONFIG_OPT
+        .as_ref()
+        .map(|c| logging::LogTimestampSettings {
+            format: c.log_timestamp_format.clone(),
+            timezone: c.log_timezone,
+        })
+        .unwrap_or_default();
+    if let Some(rate) = ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.log_sample_rate)
+    {
+        logging::set_log_sample_rate(rate);
+    }
+    logging::setup_log(
+        cmdline_log_level,
+        config_log_level,
+        &log_timestamp_settings,
+        &data_dir_path,
+    );
 
     scans::SCANS_DIR_PATH.set(data_dir_path).unwrap();
 
@@ -275,8 +715,13 @@ fn main() {
 
     log_on_launch();
     let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
-    assert_wallet_unlocked(&node_api.node);
-    wait_for_node_rescan(&node_api).unwrap();
+    let startup_retry_attempts = ORACLE_CONFIG
+        .startup_retry_attempts
+        .unwrap_or(DEFAULT_STARTUP_RETRY_ATTEMPTS);
+    if !matches!(args.command, Command::Run { .. }) {
+        assert_wallet_unlocked(&node_api);
+        wait_for_node_rescan(&node_api).unwrap();
+    }
 
     let pool_config = &POOL_CONFIG;
 
@@ -294,12 +739,13 @@ fn main() {
         Command::Bootstrap {
             yaml_config_name,
             generate_config_template,
+            dry_run,
         } => {
             if let Err(e) = (|| -> Result<(), anyhow::Error> {
                 if generate_config_template {
                     cli_commands::bootstrap::generate_bootstrap_config_template(yaml_config_name)?;
                 } else {
-                    cli_commands::bootstrap::bootstrap(yaml_config_name)?;
+                    cli_commands::bootstrap::bootstrap(yaml_config_name, dry_run)?;
                 }
                 Ok(())
             })() {
@@ -312,65 +758,525 @@ fn main() {
         Command::PrintContractHashes => {
             print_contract_hashes();
         }
+        Command::BenchmarkSources { samples } => {
+            if let Err(e) = cli_commands::benchmark_sources::benchmark_sources(
+                &ORACLE_CONFIG,
+                pool_config,
+                samples,
+            ) {
+                error!("Fatal benchmark-sources error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::TestSource { name } => {
+            if let Err(e) =
+                cli_commands::test_source::test_source(&ORACLE_CONFIG, pool_config, &name)
+            {
+                error!("Fatal test-source error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::DebugSource { name, dump, replay } => {
+            if let Err(e) = cli_commands::debug_source::debug_source(
+                &ORACLE_CONFIG,
+                pool_config,
+                &name,
+                dump.as_deref(),
+                replay.as_deref(),
+            ) {
+                error!("Fatal debug-source error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::ExplainConfig => {
+            if let Err(e) =
+                cli_commands::explain_config::explain_config(&ORACLE_CONFIG, pool_config)
+            {
+                error!("Fatal explain-config error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::ValidateContractParams { update_file } => {
+            if let Err(e) = cli_commands::validate_contract_params::validate_contract_params(
+                &update_file,
+                &ORACLE_CONFIG,
+                pool_config,
+            ) {
+                error!("Fatal validate-contract-params error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::HashContract {
+            address_or_tree_hex,
+        } => {
+            if let Err(e) = cli_commands::hash_contract::hash_contract(address_or_tree_hex) {
+                error!("Fatal hash-contract error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::AddressFromMnemonic {
+            mnemonic_env_var,
+            mnemonic_password_env_var,
+        } => {
+            if let Err(e) = cli_commands::address_from_mnemonic::address_from_mnemonic(
+                &ORACLE_CONFIG.oracle_address,
+                mnemonic_env_var,
+                mnemonic_password_env_var,
+            ) {
+                error!("Fatal address-from-mnemonic error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::GenerateServiceFile {
+            read_only,
+            enable_rest_api,
+        } => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let params = cli_commands::generate_service_file::ServiceFileParams {
+                    binary_path: env::current_exe()?,
+                    oracle_config_file: absolute_path(oracle_config_path)?,
+                    pool_config_file: absolute_path(pool_config_path)?,
+                    data_dir: absolute_path(scans::SCANS_DIR_PATH.get().unwrap())?,
+                    verbose: args.verbose,
+                    read_only,
+                    enable_rest_api,
+                };
+                cli_commands::generate_service_file::generate_service_file(
+                    &params,
+                    output::output_path(),
+                )
+            })() {
+                error!("Fatal generate-service-file error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::CheckDefaults => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_config_str =
+                    std::fs::read_to_string(ORACLE_CONFIG_FILE_PATH.get().unwrap())?;
+                cli_commands::check_defaults::check_defaults(&oracle_config_str, pool_config)
+            })() {
+                error!("Fatal check-defaults error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::ExportHistory { format } => {
+            let res = match format {
+                ExportFormat::Csv => {
+                    cli_commands::export_history::export_history_csv(&get_epoch_history_file_path())
+                }
+            };
+            if let Err(e) = res {
+                error!("Fatal export-history error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::ParticipationReport { epochs } => {
+            let known_oracles: Vec<String> = ORACLE_CONFIG
+                .known_oracle_addresses
+                .iter()
+                .flatten()
+                .map(|a| a.to_base58())
+                .collect();
+            if let Err(e) = cli_commands::participation_report::participation_report(
+                &get_epoch_history_file_path(),
+                epochs,
+                &known_oracles,
+            ) {
+                error!("Fatal participation-report error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::BacktestSource { from, to } => {
+            if let Err(e) = cli_commands::backtest_source::backtest_source(
+                &ORACLE_CONFIG,
+                pool_config,
+                &node_api,
+                &get_epoch_history_file_path(),
+                BlockHeight(from),
+                BlockHeight(to),
+            ) {
+                error!("Fatal backtest-source error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::SuggestFee { target_blocks } => {
+            if let Err(e) = cli_commands::suggest_fee::suggest_fee(&node_api, target_blocks) {
+                error!("Fatal suggest-fee error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::SelfTestPipeline => {
+            if let Err(e) = cli_commands::self_test_pipeline::self_test_pipeline(&node_api) {
+                error!("Fatal self-test-pipeline error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::TraceTx { tx_id } => {
+            if let Err(e) = cli_commands::trace_tx::trace_tx(&node_api, &tx_id) {
+                error!("Fatal trace-tx error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::Diagnostics => {
+            if let Err(e) = cli_commands::diagnostics::diagnostics(
+                &node_api,
+                scans::SCANS_DIR_PATH.get().unwrap(),
+            ) {
+                error!("Fatal diagnostics error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::DiffState { a, b } => {
+            if let Err(e) = cli_commands::snapshot_state::diff_state(&a, &b) {
+                error!("Fatal diff-state error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::Scans { action } => {
+            let res = match action {
+                ScansCommand::List => cli_commands::manage_scans::list_scans(&node_api),
+                ScansCommand::Register => {
+                    cli_commands::manage_scans::register_missing_scans(&node_api, pool_config)
+                }
+                ScansCommand::Deregister { scan } => {
+                    cli_commands::manage_scans::deregister_scan(&node_api, scan)
+                }
+                ScansCommand::Status => {
+                    cli_commands::manage_scans::scans_status(&node_api, pool_config)
+                }
+            };
+            if let Err(e) = res {
+                error!("Fatal scans error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::RewardExtractionHistory => {
+            if let Err(e) = cli_commands::reward_extraction_history::reward_extraction_history(
+                &get_reward_extraction_history_file_path(),
+                &node_api,
+            ) {
+                error!("Fatal reward-extraction-history error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::RewardAudit => {
+            if let Err(e) = cli_commands::reward_audit::reward_audit(
+                &get_epoch_history_file_path(),
+                &ORACLE_CONFIG.oracle_address.to_base58(),
+                POOL_CONFIG
+                    .refresh_box_wrapper_inputs
+                    .contract_inputs
+                    .contract_parameters()
+                    .min_data_points()
+                    .0 as usize,
+            ) {
+                error!("Fatal reward-audit error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
         Command::Run {
             read_only,
             enable_rest_api,
+            trace_http,
+            simulate_node_failure_rate,
         } => {
             let tokio_runtime = tokio::runtime::Runtime::new().unwrap();
             let (_, repost_receiver) = bounded::<bool>(1);
 
-            let node_scan_registry =
-                NodeScanRegistry::ensure_node_registered_scans(&node_api, pool_config).unwrap();
+            let node_scan_registry = run_startup(&node_api, startup_retry_attempts);
+            if shutdown::is_requested() {
+                log::info!("Oracle stopped");
+                return;
+            }
             let oracle_pool = Arc::new(OraclePool::new(&node_scan_registry).unwrap());
-            let datapoint_source = RuntimeDataPointSource::new(
+            if let Err(e) = check_epoch_length(&oracle_pool) {
+                error!("Fatal startup error: {}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+            let mut datapoint_source = RuntimeDataPointSource::new(
                 POOL_CONFIG.data_point_source,
                 ORACLE_CONFIG.data_point_source_custom_script.clone(),
+                ORACLE_CONFIG.data_point_source_json.clone(),
             )
             .unwrap();
+            if let Some(reuse_window_secs) = ORACLE_CONFIG.datapoint_reuse_window_secs {
+                persisted::enable(
+                    get_persisted_datapoint_file_path(),
+                    reuse_window_secs,
+                    ReuseKey {
+                        pool_nft_token_id: String::from(
+                            POOL_CONFIG.token_ids.pool_nft_token_id.token_id(),
+                        ),
+                        datapoint_transform: ORACLE_CONFIG
+                            .datapoint_transform
+                            .as_ref()
+                            .map(|t| t.to_string()),
+                        min_sources: ORACLE_CONFIG.min_sources,
+                    },
+                );
+            }
+            let mut epoch_history = EpochHistory::new(get_epoch_history_file_path()).unwrap();
+            let mut epoch_counter_guard = EpochCounterGuard::default();
+            let mut consensus_failures = ConsensusFailureTracker::default();
+            let mut pending_action = match ORACLE_CONFIG
+                .pending_action_policy
+                .unwrap_or(PendingActionPolicy::Wait)
+            {
+                PendingActionPolicy::Wait => pending_action::load(&get_pending_action_file_path()),
+                PendingActionPolicy::Rebuild => None,
+            };
+            let datapoint_source_reload_requested = Arc::new(AtomicBool::new(false));
+            spawn_sighup_listener(&tokio_runtime, datapoint_source_reload_requested.clone());
 
             // Start Oracle Core GET API Server
             if enable_rest_api {
-                let op_clone = oracle_pool.clone();
-                tokio_runtime.spawn(async {
-                    if let Err(e) = start_rest_server(repost_receiver, op_clone).await {
-                        error!("An error occurred while starting the REST server: {}", e);
-                        std::process::exit(exitcode::SOFTWARE);
-                    }
-                });
+                tokio_runtime.spawn(supervise_rest_server(repost_receiver, oracle_pool.clone()));
+            }
+            http_trace::set_enabled(trace_http);
+            if let Some(rate) = simulate_node_failure_rate {
+                log::warn!("Simulating the failure of {} of the node API calls", rate);
+                fault_injection::set_failure_rate(rate);
             }
             loop {
+                if datapoint_source_reload_requested.swap(false, Ordering::SeqCst) {
+                    reload_datapoint_source(&mut datapoint_source);
+                }
+                loop_timing::start_iteration();
+                request_cache::start_iteration();
+                let iteration_started = Instant::now();
                 if let Err(e) = main_loop_iteration(
                     &oracle_pool,
                     read_only,
                     &datapoint_source,
                     &node_api,
                     action_report_storage.clone(),
+                    &mut epoch_history,
+                    &mut epoch_counter_guard,
+                    &mut consensus_failures,
+                    &mut pending_action,
                 ) {
                     error!("error: {:?}", e);
+                    events::emit(OracleEvent::Error {
+                        message: format!("{:?}", e),
+                    });
+                }
+                let iteration_timing = loop_timing::finish_iteration(iteration_started.elapsed());
+                sampled_debug!("Main loop iteration timing: {:?}", iteration_timing);
+                if http_trace::is_enabled() {
+                    log::info!(
+                        "{} HTTP requests made in the main loop iteration",
+                        http_trace::take_traced_requests_count()
+                    );
+                    http_trace::set_enabled(false);
+                }
+                // Delay loop restart, a shutdown signal ends the wait right away
+                match shutdown_receiver.recv_timeout(MAIN_LOOP_INTERVAL) {
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                        log::info!("Oracle stopped");
+                        break;
+                    }
                 }
-                // Delay loop restart
-                thread::sleep(Duration::new(30, 0));
             }
         }
         oracle_command => handle_pool_command(oracle_command, &node_api),
     }
 }
 
+/// Commands that write their result to the global `--output`
+fn supports_output(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::ConsensusValue
+            | Command::PrintConfigSchema { .. }
+            | Command::Diagnostics
+            | Command::GenerateDashboard
+            | Command::HealthAttestation
+            | Command::GenerateServiceFile { .. }
+            | Command::SnapshotState
+            | Command::ExportHistory { .. }
+            | Command::PreviewRefresh
+            | Command::ExtractRewardTokens { preview: true, .. }
+    )
+}
+
+/// Wait for the node wallet (unlocked and rescanned) and register the scans. The node might be
+/// starting up along with the oracle, so each step is retried on its own until it succeeds.
+fn run_startup(node_api: &NodeApi, startup_retry_attempts: u32) -> NodeScanRegistry {
+    let mut node_scan_registry = None;
+    let not_ready = run_startup_steps(
+        vec![
+            StartupStep {
+                name: "network",
+                after: None,
+                run: Box::new(|| {
+                    let node_network = node_api.get_network().map_err(|e| e.to_string())?;
+                    check_node_network(&node_network);
+                    Ok(())
+                }),
+            },
+            StartupStep {
+                name: "wallet",
+                after: None,
+                run: Box::new(|| {
+                    let unlocked = ensure_wallet_unlocked(
+                        node_api,
+                        ORACLE_CONFIG.wallet_password_env_var.as_deref(),
+                    )
+                    .map_err(|e| e.to_string())?;
+                    if !unlocked {
+                        return Err("wallet is locked".to_string());
+                    }
+                    wait_for_node_rescan(node_api).map_err(|e| e.to_string())
+                }),
+            },
+            StartupStep {
+                name: "scans",
+                // not registered on a node of the wrong network
+                after: Some("network"),
+                run: Box::new(|| {
+                    node_scan_registry = Some(
+                        NodeScanRegistry::ensure_node_registered_scans(node_api, &POOL_CONFIG)
+                            .map_err(|e| e.to_string())?,
+                    );
+                    Ok(())
+                }),
+            },
+        ],
+        startup_retry_attempts,
+    );
+    if !not_ready.is_empty() {
+        error!(
+            "Startup failed after {} attempts, not ready: {}",
+            startup_retry_attempts,
+            not_ready.join(", ")
+        );
+        std::process::exit(exitcode::SOFTWARE);
+    }
+    check_token_ids_exist(node_api, &POOL_CONFIG.token_ids);
+    node_scan_registry.unwrap()
+}
+
+/// Exit if the node runs on another network than the one of `oracle_address` (unless
+/// `network_mismatch_policy` is `warn`), before the scans are registered on the wrong node
+fn check_node_network(node_network: &str) {
+    let configured = ORACLE_CONFIG.oracle_address.network();
+    if node_network_matches(node_network, configured) {
+        return;
+    }
+    let configured_name = match configured {
+        NetworkPrefix::Mainnet => "mainnet",
+        NetworkPrefix::Testnet => "testnet",
+    };
+    let msg = format!(
+        "The node at {} runs on {} but oracle_address in the oracle config is a {} address. \
+        Point node_url to a {} node or fix oracle_address",
+        http_trace::redact_url(ORACLE_CONFIG.node_url.as_str()),
+        node_network,
+        configured_name,
+        configured_name
+    );
+    match ORACLE_CONFIG
+        .network_mismatch_policy
+        .unwrap_or(NetworkMismatchPolicy::Refuse)
+    {
+        NetworkMismatchPolicy::Warn => log::warn!("{}, running anyway", msg),
+        NetworkMismatchPolicy::Refuse => {
+            error!("{}", msg);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+    }
+}
+
+/// The main loop decides when to refresh with the epoch length from the pool config while the
+/// refresh contract enforces its own, so fail if they differ (e.g. the pool config wasn't
+/// replaced after a pool update)
+fn check_epoch_length(oracle_pool: &OraclePool) -> std::result::Result<(), anyhow::Error> {
+    let config_epoch_length = POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters()
+        .epoch_length();
+    let refresh_box = match oracle_pool.get_refresh_box_source().get_refresh_box() {
+        Ok(refresh_box) => refresh_box,
+        Err(e) => {
+            log::warn!(
+                "Skipping the epoch length check, failed to get the refresh box: {}",
+                e
+            );
+            return Ok(());
+        }
+    };
+    let contract_epoch_length = refresh_box.contract().epoch_length();
+    if config_epoch_length != contract_epoch_length {
+        return Err(anyhow::anyhow!(
+            "epoch_length {} in the pool config doesn't match the epoch length {} of the refresh \
+            box contract. Update the pool config (see `import-pool-update`)",
+            config_epoch_length.0,
+            contract_epoch_length.0
+        ));
+    }
+    Ok(())
+}
+
+/// Log the configured token ids that don't exist on-chain. Otherwise a mistyped token id only
+/// shows as the oracle not finding any pool boxes.
+fn check_token_ids_exist(node_api: &NodeApi, token_ids: &TokenIds) {
+    for (name, token_id) in token_ids.named_token_ids() {
+        match node_api.token_exists(&token_id) {
+            Ok(Some(true)) => log::debug!("{} {} found on-chain", name, String::from(token_id)),
+            Ok(Some(false)) => error!(
+                "{} {} from the pool config doesn't exist on-chain",
+                name,
+                String::from(token_id)
+            ),
+            Ok(None) => {
+                log::info!(
+                    "Skipping the check of the pool config token ids, the node doesn't have the \
+                    extra indexer (extraIndex) enabled"
+                );
+                return;
+            }
+            Err(e) => log::warn!("Failed to check {} on-chain: {}", name, e),
+        }
+    }
+}
+
 /// Handle all other commands
 fn handle_pool_command(command: Command, node_api: &NodeApi) {
-    let height = BlockHeight(node_api.node.current_block_height().unwrap() as u32);
-    let node_scan_registry = NodeScanRegistry::load().unwrap();
-    let op = OraclePool::new(&node_scan_registry).unwrap();
+    let height = BlockHeight(node_api.current_block_height().unwrap() as u32);
+    let node_scan_registry = match NodeScanRegistry::load() {
+        Ok(registry) => registry,
+        Err(e) => {
+            error!("Failed to load the scans: {:?}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+    };
+    if let Err(e) = node_scan_registry.check_config_fingerprint(&POOL_CONFIG) {
+        error!("{}", e);
+        std::process::exit(exitcode::SOFTWARE);
+    }
+    let op = Arc::new(OraclePool::new(&node_scan_registry).unwrap());
     match command {
-        Command::ExtractRewardTokens { rewards_address } => {
+        Command::ExtractRewardTokens {
+            rewards_address,
+            split,
+            preview,
+        } => {
+            let rewards_destination = match rewards_address {
+                Some(address) => RewardsDestination::All(address),
+                None => RewardsDestination::Split(split),
+            };
             if let Err(e) = cli_commands::extract_reward_tokens::extract_reward_tokens(
                 // TODO: pass the NodeApi instance instead of these three
                 node_api,
                 &node_api.node,
                 &node_api.node,
                 op.get_local_datapoint_box_source(),
-                rewards_address,
+                rewards_destination,
                 height,
+                preview,
             ) {
                 error!("Fatal extract-rewards-token error: {:?}", e);
                 std::process::exit(exitcode::SOFTWARE);
@@ -386,6 +1292,44 @@ fn handle_pool_command(command: Command, node_api: &NodeApi) {
             }
         }
 
+        Command::MonitorPoolValue {
+            alert_epochs,
+            floor_nanoerg,
+        } => {
+            if let Err(e) = cli_commands::monitor_pool_value::monitor_pool_value(
+                op.get_pool_box_source(),
+                &get_epoch_history_file_path(),
+                alert_epochs,
+                floor_nanoerg
+                    .unwrap_or_else(cli_commands::monitor_pool_value::default_floor_nanoerg),
+            ) {
+                error!("Fatal monitor-pool-value error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+
+        Command::DumpRegisters => {
+            if let Err(e) = cli_commands::dump_registers::dump_registers(
+                op.get_local_datapoint_box_source(),
+                op.get_pool_box_source(),
+            ) {
+                error!("Fatal dump-registers error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+
+        Command::FindRewardTokens => {
+            if let Err(e) = cli_commands::find_reward_tokens::find_reward_tokens(
+                node_api,
+                op.get_local_datapoint_box_source(),
+                op.get_pool_box_source(),
+                &POOL_CONFIG.token_ids.reward_token_id,
+            ) {
+                error!("Fatal find-reward-tokens error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+
         Command::TransferOracleToken {
             oracle_token_address,
         } => {
@@ -414,15 +1358,45 @@ fn handle_pool_command(command: Command, node_api: &NodeApi) {
                 &node_api.node,
                 &node_api.node,
                 op.get_local_ballot_box_source(),
+                op.get_update_box_source(),
                 new_pool_box_address_hash_str,
                 reward_token_opt,
-                BlockHeight(update_box_creation_height),
+                update_box_creation_height.map(BlockHeight),
                 height,
             ) {
                 error!("Fatal vote-update-pool error: {:?}", e);
                 std::process::exit(exitcode::SOFTWARE);
             }
         }
+        Command::VerifyBallot => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_pk = ORACLE_CONFIG.oracle_address_p2pk()?;
+                cli_commands::verify_ballot::verify_ballot(
+                    &node_scan_registry,
+                    &op,
+                    node_api,
+                    &POOL_CONFIG.ballot_box_wrapper_inputs,
+                    &oracle_pk.h,
+                )
+            })() {
+                error!("Fatal verify-ballot error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::CheckJoinEligibility => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_pk = ORACLE_CONFIG.oracle_address_p2pk()?;
+                cli_commands::check_join_eligibility::check_join_eligibility(
+                    &node_scan_registry,
+                    node_api,
+                    &POOL_CONFIG,
+                    &oracle_pk.h,
+                )
+            })() {
+                error!("Fatal check-join-eligibility error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
         Command::UpdatePool {
             reward_token_id,
             reward_token_amount,
@@ -440,6 +1414,106 @@ fn handle_pool_command(command: Command, node_api: &NodeApi) {
                 std::process::exit(exitcode::SOFTWARE);
             }
         }
+        Command::CheckPoolIntegrity => {
+            if let Err(e) = cli_commands::check_pool_integrity::check_pool_integrity(
+                &node_scan_registry,
+                &POOL_CONFIG,
+            ) {
+                error!("Fatal check-pool-integrity error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::IdenticalDatapoints => {
+            if let Err(e) = cli_commands::identical_datapoints::identical_datapoints(
+                op.get_posted_datapoint_boxes_source(),
+                ORACLE_CONFIG.oracle_address.network(),
+            ) {
+                error!("Fatal identical-datapoints error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::InspectPool => {
+            if let Err(e) = cli_commands::inspect_pool::inspect_pool(
+                &op,
+                ORACLE_CONFIG.oracle_address.network(),
+            ) {
+                error!("Fatal inspect-pool error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::SnapshotState => {
+            if let Err(e) = cli_commands::snapshot_state::snapshot_state(
+                &op,
+                height,
+                ORACLE_CONFIG.oracle_address.network(),
+                output::output_path().map(Path::to_path_buf),
+            ) {
+                error!("Fatal snapshot-state error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::VerifyPoolBox => {
+            if let Err(e) = cli_commands::verify_pool_box::verify_pool_box(
+                &node_scan_registry,
+                &POOL_CONFIG,
+                &get_epoch_history_file_path(),
+            ) {
+                error!("Fatal verify-pool-box error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::CheckStuckUpdate => {
+            if let Err(e) = cli_commands::check_stuck_update::check_stuck_update(&op) {
+                error!("Fatal check-stuck-update error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::HealthAttestation => {
+            if let Err(e) = cli_commands::health_attestation::health_attestation(
+                &op,
+                node_api,
+                &ORACLE_CONFIG.oracle_address,
+                height,
+            ) {
+                error!("Fatal health-attestation error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::CollectionPriority => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_pk = ORACLE_CONFIG.oracle_address_p2pk()?;
+                cli_commands::collection_priority::collection_priority(
+                    &op,
+                    &oracle_pk.h,
+                    ORACLE_CONFIG.oracle_address.network(),
+                    height,
+                )
+            })() {
+                error!("Fatal collection-priority error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::CompareReference => {
+            if let Err(e) = cli_commands::compare_reference::compare_reference(&op) {
+                error!("Fatal compare-reference error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::ConsensusValue => {
+            if let Err(e) = cli_commands::consensus_value::consensus_value(&op, height) {
+                error!("Fatal consensus-value error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::UpdateStatus => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_pk = ORACLE_CONFIG.oracle_address_p2pk()?;
+                cli_commands::update_status::update_status(&op, &oracle_pk.h)
+            })() {
+                error!("Fatal update-status error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
         Command::PrepareUpdate { update_file } => {
             if let Err(e) =
                 cli_commands::prepare_update::prepare_update(update_file, node_api, height)
@@ -448,6 +1522,35 @@ fn handle_pool_command(command: Command, node_api: &NodeApi) {
                 std::process::exit(exitcode::SOFTWARE);
             }
         }
+        Command::PreviewRefresh => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_pk = ORACLE_CONFIG.oracle_address_p2pk()?;
+                cli_commands::preview_refresh::preview_refresh(&op, node_api, &oracle_pk.h, height)
+            })() {
+                error!("Fatal preview-refresh error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::RefreshReadiness => {
+            if let Err(e) = (|| -> Result<(), anyhow::Error> {
+                let oracle_pk = ORACLE_CONFIG.oracle_address_p2pk()?;
+                cli_commands::refresh_readiness::refresh_readiness(
+                    &op,
+                    node_api,
+                    &oracle_pk.h,
+                    height,
+                )
+            })() {
+                error!("Fatal refresh-readiness error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+        Command::PreviewFunding => {
+            if let Err(e) = cli_commands::preview_funding::preview_funding(&op, node_api, height) {
+                error!("Fatal preview-funding error: {:?}", e);
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
         Command::ImportPoolUpdate { pool_config_file } => {
             if let Err(e) = cli_commands::import_pool_update::import_pool_update(
                 pool_config_file,
@@ -468,7 +1571,33 @@ fn handle_pool_command(command: Command, node_api: &NodeApi) {
         }
         Command::Bootstrap { .. }
         | Command::PrintContractHashes
+        | Command::PrintConfigSchema { .. }
+        | Command::GenerateDashboard
+        | Command::VerifyHealthAttestation { .. }
+        | Command::ExplainConfig
+        | Command::BenchmarkSources { .. }
+        | Command::TestSource { .. }
+        | Command::DebugSource { .. }
+        | Command::CheckDefaults
+        | Command::HashContract { .. }
+        | Command::ValidateContractParams { .. }
+        | Command::AddressFromMnemonic { .. }
+        | Command::GenerateServiceFile { .. }
+        | Command::ExportHistory { .. }
+        | Command::ParticipationReport { .. }
+        | Command::RewardAudit
+        | Command::BacktestSource { .. }
+        | Command::RewardExtractionHistory
+        | Command::SuggestFee { .. }
+        | Command::SelfTestPipeline
+        | Command::TraceTx { .. }
+        | Command::Diagnostics
+        | Command::DiffState { .. }
+        | Command::Scans { .. }
         | Command::GenerateOracleConfig
+        | Command::GenerateMonitorConfig { .. }
+        | Command::DeriveConfig { .. }
+        | Command::VerifyDerivedConfig { .. }
         | Command::Run { .. } => unreachable!(),
     }
 }
@@ -479,31 +1608,120 @@ fn main_loop_iteration(
     datapoint_source: &RuntimeDataPointSource,
     node_api: &NodeApi,
     report_storage: Arc<RwLock<ActionReportStorage>>,
+    epoch_history: &mut EpochHistory,
+    epoch_counter_guard: &mut EpochCounterGuard,
+    consensus_failures: &mut ConsensusFailureTracker,
+    pending_action: &mut Option<PendingAction>,
 ) -> std::result::Result<(), anyhow::Error> {
-    if !node_api.node.wallet_status()?.unlocked {
-        return Err(anyhow!("Wallet is locked!"));
+    let phase_started = Instant::now();
+    if !ensure_wallet_unlocked(node_api, ORACLE_CONFIG.wallet_password_env_var.as_deref())? {
+        log::warn!("Skipping main loop iteration, wallet is locked");
+        return Ok(());
     }
     let height = BlockHeight(
-        node_api
-            .node
-            .current_block_height()
-            .context("Failed to get the current height")? as u32,
+        request_cache::current_height(node_api).context("Failed to get the current height")?,
     );
-    let network_change_address = node_api.get_change_address()?;
+    let network_change_address = change_address::get_change_address(node_api)?;
+    loop_timing::record(LoopPhase::HeightFetch, phase_started.elapsed());
+    let epoch_length = POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters()
+        .epoch_length();
+    if pending_action::should_wait(
+        node_api,
+        &get_pending_action_file_path(),
+        pending_action,
+        height,
+        epoch_length,
+    )? {
+        return Ok(());
+    }
+    let phase_started = Instant::now();
     let pool_state = match oracle_pool.get_live_epoch_state() {
-        Ok(live_epoch_state) => PoolState::LiveEpoch(live_epoch_state),
+        Ok(live_epoch_state) => {
+            if let Err(regression) =
+                epoch_counter_guard.check(live_epoch_state.pool_box_epoch_id, height, epoch_length)
+            {
+                events::emit(OracleEvent::EpochCounterRegression {
+                    height,
+                    highest: regression.highest,
+                    current: regression.current,
+                });
+                match ORACLE_CONFIG
+                    .epoch_regression_policy
+                    .unwrap_or(EpochRegressionPolicy::Refuse)
+                {
+                    EpochRegressionPolicy::Warn => log::warn!("{}, acting anyway", regression),
+                    EpochRegressionPolicy::Refuse => {
+                        log::error!("{}, not acting until it clears", regression);
+                        return Ok(());
+                    }
+                }
+            }
+            consensus_failures.observe_epoch(live_epoch_state.pool_box_epoch_id);
+            if !epoch_history.is_recorded(live_epoch_state.pool_box_epoch_id) {
+                if let Err(e) = reward_depletion::check_reward_supply(oracle_pool, height) {
+                    log::warn!("Failed to check the pool box reward tokens: {:?}", e);
+                }
+            } else if let Err(e) = reward_depletion::check_replenished(oracle_pool) {
+                log::warn!("Failed to check the pool box reward tokens: {:?}", e);
+            }
+            if let Err(e) = record_epoch_history(
+                oracle_pool,
+                &live_epoch_state,
+                &report_storage.read().unwrap(),
+                epoch_history,
+            ) {
+                log::warn!("Failed to record epoch history: {:?}", e);
+            }
+            if let Some(path) = &ORACLE_CONFIG.latest_value_file {
+                if let Err(e) = latest_value::write_latest_value(
+                    path,
+                    &LatestValue::from_live_epoch_state(&live_epoch_state),
+                ) {
+                    log::warn!(
+                        "Failed to write the latest value to {}: {}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+            PoolState::LiveEpoch(live_epoch_state)
+        }
         Err(error) => {
             log::error!("error getting live epoch state: {:?}", error);
             PoolState::NeedsBootstrap
         }
     };
-    let epoch_length = POOL_CONFIG
-        .refresh_box_wrapper_inputs
-        .contract_inputs
-        .contract_parameters()
-        .epoch_length();
-    if let Some(cmd) = process(pool_state, epoch_length, height) {
+    let action_priority = ORACLE_CONFIG
+        .action_priority
+        .as_deref()
+        .unwrap_or(&DEFAULT_ACTION_PRIORITY);
+    let cmd = process(
+        pool_state,
+        epoch_length,
+        height,
+        action_priority,
+        ORACLE_CONFIG.publish_grace_blocks.unwrap_or(0),
+    );
+    loop_timing::record(LoopPhase::StateEval, phase_started.elapsed());
+    if let Some(cmd) = cmd {
         log::debug!("Height {height}. Building action for command: {:?}", cmd);
+        let phase_started = Instant::now();
+        let action_kind = cmd.kind();
+        let fee_kind: FeeKind = action_kind.into();
+        let tx_fee = match ORACLE_CONFIG.fee_target_blocks {
+            Some(target_blocks) => suggested_tx_fee(node_api, fee_kind, target_blocks)
+                .unwrap_or_else(|e| {
+                    log::warn!(
+                        "Failed to get the recommended fee from the node, using the configured one: {}",
+                        e
+                    );
+                    action_fee(fee_kind)
+                }),
+            None => action_fee(fee_kind),
+        };
         let build_action_tuple_res = build_action(
             cmd,
             oracle_pool,
@@ -511,12 +1729,60 @@ fn main_loop_iteration(
             height,
             network_change_address.address(),
             datapoint_source,
+            tx_fee,
         );
-        if let Some((action, report)) =
-            log_and_continue_if_non_fatal(network_change_address.network(), build_action_tuple_res)?
-        {
-            if !read_only {
-                execute_action(action, node_api)?;
+        let action_tuple = log_and_continue_if_non_fatal(
+            network_change_address.network(),
+            height,
+            epoch_length,
+            consensus_failures,
+            build_action_tuple_res,
+        )
+        .map_err(on_build_failure)?;
+        loop_timing::record(LoopPhase::ActionBuild, phase_started.elapsed());
+        if let Some((action, report)) = action_tuple {
+            if pause::is_paused() {
+                log::info!("Oracle is paused, not submitting the action");
+            } else if !read_only {
+                let phase_started = Instant::now();
+                let tx_id = action.tx_id();
+                let submit_res = execute_action_with_retries(
+                    action,
+                    node_api,
+                    ORACLE_CONFIG.submit_retry_attempts.unwrap_or(0),
+                );
+                loop_timing::record(LoopPhase::Submit, phase_started.elapsed());
+                if let Err(e) = submit_res {
+                    // the node could have relocked the wallet since the check above
+                    if !ensure_wallet_unlocked(
+                        node_api,
+                        ORACLE_CONFIG.wallet_password_env_var.as_deref(),
+                    )? {
+                        log::warn!("Skipping action, wallet is locked. Error: {:?}", e);
+                        return Ok(());
+                    }
+                    return Err(e);
+                }
+                pending_action::save(
+                    &get_pending_action_file_path(),
+                    &PendingAction {
+                        tx_id: tx_id.to_string(),
+                        kind: action_kind,
+                        height,
+                    },
+                );
+                events::emit(OracleEvent::from_report(&report, height));
+                if let PoolActionReport::PublishDatapoint(ref publish_report) = report {
+                    metrics::record_post();
+                    if let Some(feed) = &ORACLE_CONFIG.reference_feed {
+                        if let Err(e) = reference_feed::compare_with_reference(
+                            publish_report.posted_datapoint,
+                            feed,
+                        ) {
+                            log::warn!("Failed to check the reference feed: {:?}", e);
+                        }
+                    }
+                }
                 report_storage.write().unwrap().add(report);
             }
         };
@@ -524,11 +1790,82 @@ fn main_loop_iteration(
     Ok(())
 }
 
+/// Append the pool box epoch to the history if it wasn't recorded yet
+fn record_epoch_history(
+    oracle_pool: &OraclePool,
+    live_epoch_state: &LiveEpochState,
+    report_storage: &ActionReportStorage,
+    epoch_history: &mut EpochHistory,
+) -> std::result::Result<(), anyhow::Error> {
+    let epoch = live_epoch_state.pool_box_epoch_id;
+    if epoch_history.is_recorded(epoch) {
+        return Ok(());
+    }
+    let network_prefix = ORACLE_CONFIG.oracle_address.network();
+    let to_address =
+        |pk: EcPoint| NetworkAddress::new(network_prefix, &Address::P2Pk(pk.into())).to_base58();
+    let collected_oracles = oracle_pool
+        .get_collected_datapoint_boxes_source()
+        .get_collected_datapoint_boxes()?
+        .into_iter()
+        .filter(|b| b.get_box().creation_height == live_epoch_state.latest_pool_box_height.0)
+        .map(|b| to_address(b.public_key()))
+        .collect();
+    let previous_epoch = EpochCounter(epoch.0.saturating_sub(1));
+    let uncollected_oracles = oracle_pool
+        .get_posted_datapoint_boxes_source()
+        .get_posted_datapoint_boxes()?
+        .into_iter()
+        .filter(|b| b.epoch_counter() == previous_epoch)
+        .map(|b| to_address(b.public_key()))
+        .collect();
+    let reward_tokens = oracle_pool
+        .get_local_datapoint_box_source()
+        .get_local_oracle_datapoint_box()?
+        .map(|b| *b.reward_token().amount.as_u64())
+        .unwrap_or(0);
+    epoch_history.add_if_new_epoch(EpochHistoryRecord {
+        height: live_epoch_state.latest_pool_box_height,
+        epoch,
+        posted_value: report_storage
+            .get_last_publish_datapoint_report()
+            .map(|r| r.posted_datapoint),
+        consensus_value: (live_epoch_state.latest_pool_datapoint as i64).into(),
+        reward_tokens,
+        pool_box_value: Some(
+            *oracle_pool
+                .get_pool_box_source()
+                .get_pool_box()?
+                .get_box()
+                .value
+                .as_u64(),
+        ),
+        collected_oracles: Some(collected_oracles),
+        uncollected_oracles: Some(uncollected_oracles),
+    })?;
+    Ok(())
+}
+
 fn log_and_continue_if_non_fatal(
     network_prefix: NetworkPrefix,
+    height: BlockHeight,
+    epoch_length: EpochLength,
+    consensus_failures: &mut ConsensusFailureTracker,
     res: Result<(PoolAction, PoolActionReport), PoolCommandError>,
 ) -> Result<Option<(PoolAction, PoolActionReport)>, anyhow::Error> {
     match res {
+        Ok((action, PoolActionReport::Refresh(report))) => {
+            for pk in &report.oracle_boxes_collected {
+                if !ORACLE_CONFIG.is_known_oracle(pk) {
+                    log::warn!(
+                        "Refresh collects a datapoint from unknown oracle {}",
+                        NetworkAddress::new(network_prefix, &Address::P2Pk(pk.clone().into()))
+                            .to_base58()
+                    );
+                }
+            }
+            Ok(Some((action, PoolActionReport::Refresh(report))))
+        }
         Ok(tuple) => Ok(Some(tuple)),
         Err(PoolCommandError::RefreshActionError(RefreshActionError::FailedToReachConsensus {
             expected,
@@ -538,11 +1875,24 @@ fn log_and_continue_if_non_fatal(
             let found_oracle_addresses: String = found_public_keys
                 .into_iter()
                 .map(|pk| {
-                    NetworkAddress::new(network_prefix, &Address::P2Pk(pk.into())).to_base58()
+                    let address =
+                        NetworkAddress::new(network_prefix, &Address::P2Pk(pk.clone().into()))
+                            .to_base58();
+                    if ORACLE_CONFIG.is_known_oracle(&pk) {
+                        address
+                    } else {
+                        format!("{address} (unknown)")
+                    }
                 })
                 .collect::<Vec<String>>()
                 .join(", ");
             log::error!("Refresh failed, not enough datapoints. The minimum number of datapoints within the deviation range: required minumum {expected}, found {found_num} from addresses {found_oracle_addresses},");
+            let failed_epochs = consensus_failures.record_failure(height, epoch_length);
+            if let Some(threshold) = ORACLE_CONFIG.consensus_failure_escalation_epochs {
+                if consensus_failures.should_escalate(failed_epochs, threshold) {
+                    on_persistent_consensus_failure(height, failed_epochs, found_num, expected);
+                }
+            }
             Ok(None)
         }
         Err(PoolCommandError::PublishDatapointActionError(
@@ -551,15 +1901,209 @@ fn log_and_continue_if_non_fatal(
             log::error!("Failed to get datapoint with error: {}", e);
             Ok(None)
         }
+        Err(PoolCommandError::PublishDatapointActionError(
+            e @ PublishDatapointActionError::NonPositiveDatapoint(_),
+        )) => {
+            log::error!("Skipping datapoint publishing: {}", e);
+            Ok(None)
+        }
+        Err(PoolCommandError::PublishDatapointActionError(
+            e @ (PublishDatapointActionError::NoOracleTokenInWallet
+            | PublishDatapointActionError::NoRewardTokenInWallet),
+        )) => {
+            log::error!(
+                "Oracle box not found and cannot be recreated: {}. Send the oracle token and at \
+                least one reward token to the oracle address to rejoin the pool",
+                e
+            );
+            Ok(None)
+        }
+        Err(PoolCommandError::PublishDatapointActionError(
+            PublishDatapointActionError::InsufficientFunds(funds),
+        ))
+        | Err(PoolCommandError::RefreshActionError(RefreshActionError::InsufficientFunds(funds))) =>
+        {
+            on_insufficient_funds(funds, height);
+            Ok(None)
+        }
         Err(e) => Err(e.into()),
     }
 }
 
+fn on_build_failure(e: anyhow::Error) -> anyhow::Error {
+    match ORACLE_CONFIG
+        .build_failure_policy
+        .unwrap_or(BuildFailurePolicy::Continue)
+    {
+        BuildFailurePolicy::Continue => (),
+        BuildFailurePolicy::Pause => {
+            pause::set_paused(true);
+            log::error!(
+                "Failed to build the tx, pausing the oracle (build_failure_policy: pause). \
+                Resume it with the REST API /resume endpoint or restart it"
+            );
+        }
+    }
+    e.context("Failed to build the tx")
+}
+
+fn on_persistent_consensus_failure(
+    height: BlockHeight,
+    failed_epochs: u32,
+    found: i32,
+    expected: i32,
+) {
+    log::error!(
+        "!!! The refresh failed to reach consensus for {} epochs in a row ({} of {} datapoints \
+        in consensus), the pool needs attention: check the oracles' sources and \
+        max_deviation_percent !!!",
+        failed_epochs,
+        found,
+        expected
+    );
+    events::emit(OracleEvent::ConsensusFailureEscalated {
+        height,
+        failed_epochs,
+        found,
+        expected,
+    });
+    match ORACLE_CONFIG
+        .consensus_failure_policy
+        .unwrap_or(BuildFailurePolicy::Continue)
+    {
+        BuildFailurePolicy::Continue => (),
+        BuildFailurePolicy::Pause => {
+            pause::set_paused(true);
+            log::error!(
+                "Pausing the oracle (consensus_failure_policy: pause). Resume it with the REST \
+                API /resume endpoint or restart it"
+            );
+        }
+    }
+}
+
+fn on_insufficient_funds(funds: InsufficientFunds, height: BlockHeight) {
+    let address = ORACLE_CONFIG.oracle_address.to_base58();
+    log::error!(
+        "Not enough ERG in the wallet for the tx: {} nanoERG needed, {} available. Add {} ERG to \
+        {}",
+        funds.needed,
+        funds.available,
+        funds.missing() as f64 / 1_000_000_000.0,
+        address
+    );
+    events::emit(OracleEvent::InsufficientFunds {
+        height,
+        address,
+        needed: funds.needed,
+        available: funds.available,
+        missing: funds.missing(),
+    });
+    match ORACLE_CONFIG
+        .insufficient_funds_policy
+        .or(ORACLE_CONFIG.build_failure_policy)
+        .unwrap_or(BuildFailurePolicy::Continue)
+    {
+        BuildFailurePolicy::Continue => (),
+        BuildFailurePolicy::Pause => {
+            pause::set_paused(true);
+            log::error!(
+                "Pausing the oracle (insufficient_funds_policy: pause). Resume it with the REST \
+                API /resume endpoint once the wallet is topped up"
+            );
+        }
+    }
+}
+
+/// Stop the main loop on SIGINT or SIGTERM, exit right away on the second one (e.g. an iteration
+/// stuck on an unresponsive node)
+fn spawn_shutdown_listener(tokio_runtime: &tokio::runtime::Runtime, shutdown_sender: Sender<()>) {
+    tokio_runtime.spawn(async move {
+        shutdown_signal().await;
+        log::info!(
+            "Shutdown signal received, stopping after the current main loop iteration. Send it \
+             again to exit right away"
+        );
+        shutdown::request();
+        let _ = shutdown_sender.send(());
+        shutdown_signal().await;
+        error!("Second shutdown signal received, exiting");
+        std::process::exit(exitcode::SOFTWARE);
+    });
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to set up SIGTERM handler: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => (),
+        _ = terminate => (),
+    }
+}
+
+/// Request the datapoint source config reload on SIGHUP
+#[cfg(unix)]
+fn spawn_sighup_listener(
+    tokio_runtime: &tokio::runtime::Runtime,
+    reload_requested: Arc<AtomicBool>,
+) {
+    tokio_runtime.spawn(async move {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+            Ok(mut sighup) => {
+                while sighup.recv().await.is_some() {
+                    log::info!("SIGHUP received, datapoint source config will be reloaded");
+                    reload_requested.store(true, Ordering::SeqCst);
+                }
+            }
+            Err(e) => error!("Failed to set up SIGHUP handler: {}", e),
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_sighup_listener(
+    _tokio_runtime: &tokio::runtime::Runtime,
+    _reload_requested: Arc<AtomicBool>,
+) {
+}
+
+/// Replace the datapoint source with the one from the config files. Keep the current one if the
+/// new config is invalid.
+fn reload_datapoint_source(datapoint_source: &mut RuntimeDataPointSource) {
+    match RuntimeDataPointSource::reload_from_config_files() {
+        Ok(new_datapoint_source) => {
+            log::info!(
+                "Datapoint source reloaded. Before: {:?}, after: {:?}",
+                datapoint_source,
+                new_datapoint_source
+            );
+            *datapoint_source = new_datapoint_source;
+        }
+        Err(e) => error!(
+            "Invalid datapoint source config, keeping the current one ({:?}). Error: {:?}",
+            datapoint_source, e
+        ),
+    }
+}
+
 fn log_on_launch() {
-    log::info!("{}", APP_VERSION);
+    log::info!("{}", *APP_VERSION);
     if let Ok(config) = ORACLE_CONFIG_OPT.clone() {
         // log::info!("Token ids: {:?}", config.token_ids);
         log::info!("Oracle address: {}", config.oracle_address.to_base58());
+        version_check::warn_if_outdated(config.min_recommended_version.as_deref());
     }
 }
 
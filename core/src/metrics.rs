@@ -0,0 +1,136 @@
+//! Prometheus metrics served by the REST API `/metrics` endpoint. [`METRICS`] is the single list
+//! of the exposed metrics, the Grafana dashboard of `generate-dashboard` is built from it too.
+
+use std::fmt::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+pub struct MetricDef {
+    pub name: &'static str,
+    pub help: &'static str,
+}
+
+pub const BLOCK_HEIGHT: MetricDef = MetricDef {
+    name: "oracle_core_block_height",
+    help: "Current block height of the node",
+};
+pub const POOL_EPOCH: MetricDef = MetricDef {
+    name: "oracle_core_pool_epoch",
+    help: "Epoch counter of the pool box",
+};
+pub const POOL_RATE: MetricDef = MetricDef {
+    name: "oracle_core_pool_rate",
+    help: "Rate (datapoint) of the pool box",
+};
+pub const CONSENSUS_ORACLES: MetricDef = MetricDef {
+    name: "oracle_core_consensus_oracles",
+    help: "Number of oracles whose datapoints were collected into the pool box",
+};
+pub const SECONDS_SINCE_LAST_POST: MetricDef = MetricDef {
+    name: "oracle_core_seconds_since_last_post",
+    help: "Seconds since this oracle submitted its last datapoint (not set until the first one \
+        after the start)",
+};
+pub const EXCLUDED_SOURCES: MetricDef = MetricDef {
+    name: "oracle_core_excluded_sources",
+    help: "Number of the datapoint sources excluded from the fetches after failing too often \
+        (see source_failure_exclusion)",
+};
+
+pub const METRICS: [&MetricDef; 6] = [
+    &BLOCK_HEIGHT,
+    &POOL_EPOCH,
+    &POOL_RATE,
+    &CONSENSUS_ORACLES,
+    &SECONDS_SINCE_LAST_POST,
+    &EXCLUDED_SOURCES,
+];
+
+/// Seconds since the Unix epoch of the last submitted publish datapoint tx, 0 if none yet
+static LAST_POST_TIMESTAMP: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_post() {
+    LAST_POST_TIMESTAMP.store(now(), Ordering::SeqCst);
+}
+
+pub fn seconds_since_last_post() -> Option<u64> {
+    match LAST_POST_TIMESTAMP.load(Ordering::SeqCst) {
+        0 => None,
+        timestamp => Some(now().saturating_sub(timestamp)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricValues {
+    pub block_height: u32,
+    pub pool_epoch: u32,
+    pub pool_rate: i64,
+    pub consensus_oracles: usize,
+    pub seconds_since_last_post: Option<u64>,
+    pub excluded_sources: usize,
+}
+
+/// Prometheus text exposition format, the metrics without a value are left out
+pub fn render(values: &MetricValues) -> String {
+    let mut out = String::new();
+    let mut gauge = |metric: &MetricDef, value: Option<String>| {
+        if let Some(value) = value {
+            let _ = writeln!(out, "# HELP {} {}", metric.name, metric.help);
+            let _ = writeln!(out, "# TYPE {} gauge", metric.name);
+            let _ = writeln!(out, "{} {}", metric.name, value);
+        }
+    };
+    gauge(&BLOCK_HEIGHT, Some(values.block_height.to_string()));
+    gauge(&POOL_EPOCH, Some(values.pool_epoch.to_string()));
+    gauge(&POOL_RATE, Some(values.pool_rate.to_string()));
+    gauge(
+        &CONSENSUS_ORACLES,
+        Some(values.consensus_oracles.to_string()),
+    );
+    gauge(
+        &SECONDS_SINCE_LAST_POST,
+        values.seconds_since_last_post.map(|s| s.to_string()),
+    );
+    gauge(&EXCLUDED_SOURCES, Some(values.excluded_sources.to_string()));
+    out
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let values = MetricValues {
+            block_height: 1000,
+            pool_epoch: 12,
+            pool_rate: 769230769,
+            consensus_oracles: 4,
+            seconds_since_last_post: None,
+            excluded_sources: 0,
+        };
+        let out = render(&values);
+        assert!(
+            out.contains("# TYPE oracle_core_block_height gauge\noracle_core_block_height 1000\n")
+        );
+        assert!(out.contains("\noracle_core_pool_rate 769230769\n"));
+        assert!(!out.contains(SECONDS_SINCE_LAST_POST.name));
+        let out = render(&MetricValues {
+            seconds_since_last_post: Some(30),
+            ..values
+        });
+        assert!(out.contains("\noracle_core_seconds_since_last_post 30\n"));
+        for metric in METRICS {
+            assert!(out.contains(metric.name), "{}", metric.name);
+        }
+    }
+}
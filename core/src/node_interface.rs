@@ -1,15 +1,46 @@
 use ergo_lib::{
     chain::transaction::{unsigned::UnsignedTransaction, Transaction, TxId, TxIoVec},
-    ergotree_ir::chain::ergo_box::ErgoBox,
+    ergotree_ir::chain::{address::NetworkPrefix, ergo_box::ErgoBox},
 };
 use ergo_node_interface::node_interface::{NodeError, NodeInterface};
 use log::debug;
 use log::error;
 
+use crate::http_trace::trace_node;
+use crate::shutdown;
+
+use self::fault_injection::inject_failure;
+use self::node_api::NodeApi;
+use self::node_api::NodeApiError;
+use self::rate_limiter::wait_for_node_request_slot;
+
+pub mod change_address;
+pub mod fault_injection;
 pub mod node_api;
+pub mod rate_limiter;
+pub mod request_cache;
 
 pub type Result<T> = std::result::Result<T, NodeError>;
 
+const RETRY_INITIAL_DELAY_SECS: u64 = 2;
+const RETRY_MAX_DELAY_SECS: u64 = 60;
+
+/// Node API call, waits for the `node_max_requests_per_sec` limit and is traced with
+/// `--trace-http`. Fails without reaching the node when a failure is injected with
+/// `--simulate-node-failure-rate`.
+pub fn node_call<T, E: std::fmt::Display + From<NodeError>>(
+    method: &str,
+    endpoint: &str,
+    f: impl FnOnce() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    wait_for_node_request_slot();
+    if inject_failure() {
+        log::warn!("Simulated node failure: {} {}", method, endpoint);
+        return Err(NodeError::NodeUnreachable.into());
+    }
+    trace_node(method, endpoint, f)
+}
+
 pub trait SubmitTransaction {
     fn submit_transaction(&self, tx: &Transaction) -> Result<TxId>;
 }
@@ -33,7 +64,9 @@ pub trait SignTransaction {
 
 impl SignTransaction for NodeInterface {
     fn sign_transaction(&self, unsigned_tx: &UnsignedTransaction) -> Result<Transaction> {
-        self.sign_transaction(unsigned_tx, None, None)
+        node_call("POST", "/wallet/transaction/sign", || {
+            self.sign_transaction(unsigned_tx, None, None)
+        })
     }
 }
 
@@ -43,7 +76,7 @@ impl SubmitTransaction for NodeInterface {
             "Submitting signed transaction: {}",
             serde_json::to_string_pretty(&tx).unwrap()
         );
-        self.submit_transaction(tx)
+        node_call("POST", "/transactions", || self.submit_transaction(tx))
     }
 }
 
@@ -54,16 +87,18 @@ impl SignTransactionWithInputs for NodeInterface {
         inputs: ergo_lib::chain::transaction::TxIoVec<ErgoBox>,
         data_boxes: Option<ergo_lib::chain::transaction::TxIoVec<ErgoBox>>,
     ) -> Result<Transaction> {
-        self.sign_transaction(
-            unsigned_tx,
-            Some(inputs.as_vec().clone()),
-            data_boxes.map(|bs| bs.as_vec().clone()),
-        )
+        node_call("POST", "/wallet/transaction/sign", || {
+            self.sign_transaction(
+                unsigned_tx,
+                Some(inputs.as_vec().clone()),
+                data_boxes.map(|bs| bs.as_vec().clone()),
+            )
+        })
     }
 }
 
-pub fn assert_wallet_unlocked(node: &NodeInterface) {
-    let unlocked = node.wallet_status().unwrap().unlocked;
+pub fn assert_wallet_unlocked(node_api: &NodeApi) {
+    let unlocked = node_api.wallet_status().unwrap().unlocked;
     if !unlocked {
         error!("Wallet must be unlocked for node operations");
         std::process::exit(exitcode::SOFTWARE);
@@ -71,3 +106,250 @@ pub fn assert_wallet_unlocked(node: &NodeInterface) {
         debug!("Wallet unlocked");
     }
 }
+
+/// Check if the node wallet is unlocked. If it's locked and `password_env_var` is given, try to
+/// unlock it with the password from this environment variable.
+/// Returns `false` if the wallet is still locked.
+pub fn ensure_wallet_unlocked(
+    node_api: &NodeApi,
+    password_env_var: Option<&str>,
+) -> std::result::Result<bool, NodeApiError> {
+    if node_api.wallet_status()?.unlocked {
+        return Ok(true);
+    }
+    if let Some(env_var) = password_env_var {
+        match std::env::var(env_var) {
+            Ok(password) => {
+                if let Err(e) = node_api.unlock_wallet(&password) {
+                    error!("Failed to unlock wallet: {}", e);
+                }
+            }
+            Err(_) => error!(
+                "Cannot unlock wallet, environment variable {} is not set",
+                env_var
+            ),
+        }
+        if node_api.wallet_status()?.unlocked {
+            log::info!("Wallet unlocked");
+            return Ok(true);
+        }
+    }
+    error!(
+        "Wallet is locked. Unlock it in the node (POST /wallet/unlock) or set \
+        wallet_password_env_var in the oracle config to unlock it automatically"
+    );
+    Ok(false)
+}
+
+/// Whether the network reported by the node is the one of the addresses in the config. The
+/// addresses only tell mainnet from the rest, so any other node network (testnet, devnet) goes
+/// with the testnet addresses.
+pub fn node_network_matches(node_network: &str, configured: NetworkPrefix) -> bool {
+    let node_mainnet = node_network.eq_ignore_ascii_case("mainnet");
+    node_mainnet == (configured == NetworkPrefix::Mainnet)
+}
+
+/// Call `f` until it succeeds or `max_attempts` is reached, doubling the delay between the
+/// attempts. Returns the last error.
+pub fn retry_with_backoff<T, E: std::fmt::Display>(
+    description: &str,
+    max_attempts: u32,
+    mut f: impl FnMut() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    let mut attempt = 1;
+    loop {
+        log::info!("{}: attempt {} of {}", description, attempt, max_attempts);
+        match f() {
+            Ok(res) => return Ok(res),
+            Err(e) if attempt >= max_attempts => {
+                error!("{} failed after {} attempts: {}", description, attempt, e);
+                return Err(e);
+            }
+            Err(e) => {
+                let delay = retry_delay(attempt);
+                log::warn!(
+                    "{} failed: {}. Retrying in {} seconds",
+                    description,
+                    e,
+                    delay.as_secs()
+                );
+                if shutdown::sleep(delay) {
+                    log::info!("Shutdown requested, not retrying {}", description);
+                    return Err(e);
+                }
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Startup step retried on its own by [`run_startup_steps`]
+pub struct StartupStep<'a> {
+    pub name: &'static str,
+    /// Step (listed before this one) that must have succeeded before this one is run
+    pub after: Option<&'static str>,
+    pub run: Box<dyn FnMut() -> std::result::Result<(), String> + 'a>,
+}
+
+/// Run the steps until each has succeeded once, retrying only the failed ones with backoff. A
+/// failing step doesn't hold back the others (except the ones to run `after` it), so the node
+/// wallet and scan APIs can come up in any order. Gives up after `max_attempts` rounds and
+/// returns the names of the steps that never succeeded.
+pub fn run_startup_steps(steps: Vec<StartupStep>, max_attempts: u32) -> Vec<&'static str> {
+    run_startup_steps_with_sleep(steps, max_attempts, std::thread::sleep)
+}
+
+fn run_startup_steps_with_sleep(
+    steps: Vec<StartupStep>,
+    max_attempts: u32,
+    sleep: impl Fn(std::time::Duration),
+) -> Vec<&'static str> {
+    let mut pending = steps;
+    let mut attempt = 1;
+    loop {
+        let mut still_pending: Vec<StartupStep> = Vec::new();
+        for mut step in pending {
+            let waits_for = step
+                .after
+                .filter(|after| still_pending.iter().any(|p| p.name == *after));
+            if let Some(after) = waits_for {
+                log::info!("Startup step {} waits for {}", step.name, after);
+                still_pending.push(step);
+                continue;
+            }
+            match (step.run)() {
+                Ok(()) => log::info!("Startup step {} is ready", step.name),
+                Err(e) => {
+                    log::warn!("Startup step {} is not ready: {}", step.name, e);
+                    still_pending.push(step);
+                }
+            }
+        }
+        pending = still_pending;
+        let pending_names: Vec<&'static str> = pending.iter().map(|step| step.name).collect();
+        if pending_names.is_empty() || attempt >= max_attempts {
+            return pending_names;
+        }
+        let delay = retry_delay(attempt);
+        log::info!(
+            "Startup: attempt {} of {}, retrying {} in {} seconds",
+            attempt,
+            max_attempts,
+            pending_names.join(", "),
+            delay.as_secs()
+        );
+        sleep(delay);
+        attempt += 1;
+    }
+}
+
+/// Delay before the retry `attempt`, doubling from 2 seconds up to a minute
+pub(crate) fn retry_delay(attempt: u32) -> std::time::Duration {
+    let delay_secs = RETRY_INITIAL_DELAY_SECS
+        .saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1)))
+        .min(RETRY_MAX_DELAY_SECS);
+    std::time::Duration::from_secs(delay_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1).as_secs(), 2);
+        assert_eq!(retry_delay(2).as_secs(), 4);
+        assert_eq!(retry_delay(5).as_secs(), 32);
+        assert_eq!(retry_delay(6).as_secs(), 60);
+        assert_eq!(retry_delay(100).as_secs(), 60);
+    }
+
+    #[test]
+    fn test_node_network_matches() {
+        assert!(node_network_matches("mainnet", NetworkPrefix::Mainnet));
+        assert!(node_network_matches("testnet", NetworkPrefix::Testnet));
+        assert!(node_network_matches("devnet", NetworkPrefix::Testnet));
+        assert!(!node_network_matches("testnet", NetworkPrefix::Mainnet));
+        assert!(!node_network_matches("mainnet", NetworkPrefix::Testnet));
+    }
+
+    #[test]
+    fn test_retry_with_backoff_gives_up() {
+        let mut calls = 0;
+        let res: std::result::Result<(), String> = retry_with_backoff("test", 1, || {
+            calls += 1;
+            Err("node is down".to_string())
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_run_startup_steps_retries_only_failed_steps() {
+        let mut wallet_calls = 0;
+        let mut scans_calls = 0;
+        let not_ready = run_startup_steps_with_sleep(
+            vec![
+                StartupStep {
+                    name: "wallet",
+                    after: None,
+                    run: Box::new(|| {
+                        wallet_calls += 1;
+                        Err("wallet API is not up".to_string())
+                    }),
+                },
+                StartupStep {
+                    name: "scans",
+                    after: None,
+                    run: Box::new(|| {
+                        scans_calls += 1;
+                        Ok(())
+                    }),
+                },
+            ],
+            2,
+            |_| (),
+        );
+        assert_eq!(not_ready, vec!["wallet"]);
+        assert_eq!(wallet_calls, 2);
+        assert_eq!(scans_calls, 1);
+    }
+
+    #[test]
+    fn test_run_startup_steps_waits_for_the_previous_step() {
+        let mut network_calls = 0;
+        let mut scans_calls = 0;
+        let delays = std::cell::RefCell::new(Vec::new());
+        let not_ready = run_startup_steps_with_sleep(
+            vec![
+                StartupStep {
+                    name: "network",
+                    after: None,
+                    run: Box::new(|| {
+                        network_calls += 1;
+                        if network_calls < 3 {
+                            Err("node is down".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    }),
+                },
+                StartupStep {
+                    name: "scans",
+                    after: Some("network"),
+                    run: Box::new(|| {
+                        scans_calls += 1;
+                        Ok(())
+                    }),
+                },
+            ],
+            5,
+            |delay| delays.borrow_mut().push(delay.as_secs()),
+        );
+        assert!(not_ready.is_empty());
+        assert_eq!(network_calls, 3);
+        // not run while the network step was failing
+        assert_eq!(scans_calls, 1);
+        assert_eq!(*delays.borrow(), vec![2, 4]);
+    }
+}
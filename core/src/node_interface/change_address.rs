@@ -0,0 +1,124 @@
+//! Node wallet change address cached between the main loop iterations, refreshed every
+//! `change_address_refresh_secs` and retried `change_address_retry_attempts` times, so that a
+//! momentary wallet API failure doesn't fail the iteration.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+
+use crate::oracle_config::ORACLE_CONFIG;
+
+use super::node_api::NodeApi;
+use super::node_api::NodeApiError;
+use super::retry_with_backoff;
+
+pub const DEFAULT_CHANGE_ADDRESS_REFRESH_SECS: u64 = 600;
+pub const DEFAULT_CHANGE_ADDRESS_RETRY_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone)]
+struct CachedAddress {
+    address: NetworkAddress,
+    fetched_at: Instant,
+}
+
+static CHANGE_ADDRESS: Mutex<Option<CachedAddress>> = Mutex::new(None);
+
+/// The cached change address, fetched from the node if it's older than the refresh interval.
+/// If the refresh fails the cached one is used, the error is returned only when there is none.
+pub fn get_change_address(node_api: &NodeApi) -> Result<NetworkAddress, NodeApiError> {
+    let refresh_interval = Duration::from_secs(
+        ORACLE_CONFIG
+            .change_address_refresh_secs
+            .unwrap_or(DEFAULT_CHANGE_ADDRESS_REFRESH_SECS),
+    );
+    let retry_attempts = ORACLE_CONFIG
+        .change_address_retry_attempts
+        .unwrap_or(DEFAULT_CHANGE_ADDRESS_RETRY_ATTEMPTS);
+    get_or_fetch(
+        &mut CHANGE_ADDRESS.lock().unwrap(),
+        refresh_interval,
+        Instant::now(),
+        || {
+            retry_with_backoff("Get change address", retry_attempts, || {
+                node_api.get_change_address()
+            })
+        },
+    )
+}
+
+fn get_or_fetch(
+    cache: &mut Option<CachedAddress>,
+    refresh_interval: Duration,
+    now: Instant,
+    fetch: impl FnOnce() -> Result<NetworkAddress, NodeApiError>,
+) -> Result<NetworkAddress, NodeApiError> {
+    if let Some(cached) = cache.as_ref() {
+        if now.saturating_duration_since(cached.fetched_at) < refresh_interval {
+            return Ok(cached.address.clone());
+        }
+    }
+    match (fetch(), cache.as_ref()) {
+        (Ok(address), _) => {
+            *cache = Some(CachedAddress {
+                address: address.clone(),
+                fetched_at: now,
+            });
+            Ok(address)
+        }
+        (Err(e), Some(cached)) => {
+            log::warn!(
+                "Failed to refresh the change address, using the cached one: {}",
+                e
+            );
+            Ok(cached.address.clone())
+        }
+        (Err(e), None) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergotree_ir::chain::address::AddressEncoder;
+
+    use super::*;
+
+    #[test]
+    fn test_get_or_fetch() {
+        let address = AddressEncoder::unchecked_parse_network_address_from_str(
+            "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r",
+        )
+        .unwrap();
+        let interval = Duration::from_secs(600);
+        let start = Instant::now();
+        let mut cache = None;
+        assert!(get_or_fetch(&mut cache, interval, start, || Err(
+            NodeApiError::NoChangeAddressSetInNode
+        ))
+        .is_err());
+        assert_eq!(
+            get_or_fetch(&mut cache, interval, start, || Ok(address.clone())).unwrap(),
+            address
+        );
+        // cached, not fetched
+        assert_eq!(
+            get_or_fetch(
+                &mut cache,
+                interval,
+                start + Duration::from_secs(10),
+                || panic!("fetched")
+            )
+            .unwrap(),
+            address
+        );
+        // refresh failed, the cached one is used
+        assert_eq!(
+            get_or_fetch(&mut cache, interval, start + interval, || Err(
+                NodeApiError::NoChangeAddressSetInNode
+            ))
+            .unwrap(),
+            address
+        );
+    }
+}
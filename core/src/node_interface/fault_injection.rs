@@ -0,0 +1,77 @@
+//! Simulated node failures for resilience testing (`run --simulate-node-failure-rate`, hidden).
+//! Each node API call fails with the given probability as if the node was unreachable, without
+//! reaching the node, to check the retries and the readiness reporting.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// `f64` bits of the failure probability, 0 (off) by default
+static FAILURE_RATE: AtomicU64 = AtomicU64::new(0);
+static RNG_STATE: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_failure_rate(rate: f64) {
+    FAILURE_RATE.store(rate.to_bits(), Ordering::SeqCst);
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    // xorshift state must not be zero
+    RNG_STATE.store(seed | 1, Ordering::SeqCst);
+}
+
+/// Whether the node call should fail
+pub fn inject_failure() -> bool {
+    let rate = f64::from_bits(FAILURE_RATE.load(Ordering::SeqCst));
+    rate > 0.0 && fails(next_random(), rate)
+}
+
+/// clap value parser of the failure rate, a probability from 0 to 1
+pub fn parse_failure_rate(s: &str) -> Result<f64, String> {
+    let rate: f64 = s.parse().map_err(|_| format!("{} is not a number", s))?;
+    if (0.0..=1.0).contains(&rate) {
+        Ok(rate)
+    } else {
+        Err(format!("{} is not within 0 and 1", rate))
+    }
+}
+
+fn next_random() -> u64 {
+    let mut x = RNG_STATE.load(Ordering::SeqCst);
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    RNG_STATE.store(x, Ordering::SeqCst);
+    x
+}
+
+fn fails(random: u64, rate: f64) -> bool {
+    // u64::MAX converted to f64 is 1.0, which isn't below a rate of 1
+    rate >= 1.0 || (random as f64 / u64::MAX as f64) < rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fails() {
+        assert!(!fails(u64::MAX, 0.5));
+        assert!(fails(0, 0.5));
+        assert!(fails(u64::MAX / 4, 0.5));
+        assert!(!fails(u64::MAX / 4 * 3, 0.5));
+        assert!(fails(u64::MAX / 2, 1.0));
+        assert!(fails(u64::MAX, 1.0));
+        assert!(!fails(0, 0.0));
+    }
+
+    #[test]
+    fn test_parse_failure_rate() {
+        assert_eq!(parse_failure_rate("0.25"), Ok(0.25));
+        assert_eq!(parse_failure_rate("1"), Ok(1.0));
+        assert!(parse_failure_rate("1.5").is_err());
+        assert!(parse_failure_rate("-0.1").is_err());
+        assert!(parse_failure_rate("often").is_err());
+    }
+}
@@ -4,31 +4,129 @@ use ergo_lib::ergotree_ir::chain::address::AddressEncoder;
 use ergo_lib::ergotree_ir::chain::address::AddressEncoderError;
 use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
 use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_lib::ergotree_ir::chain::token::TokenId;
+use ergo_lib::wallet::secret_key::SecretKey;
 use ergo_node_interface::scanning::NodeError;
 use ergo_node_interface::NodeInterface;
 use ergo_node_interface::ScanId;
 use log::info;
+use reqwest::blocking::RequestBuilder;
+use reqwest::blocking::Response;
+use reqwest::header::ACCEPT;
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Url;
 use serde_json::json;
 use thiserror::Error;
 
+use crate::http_client::BLOCKING_HTTP_CLIENT;
+use crate::node_interface::node_call;
 use crate::scans::ScanID;
 use crate::wallet::WalletDataError;
 use crate::wallet::WalletDataSource;
 
 pub struct NodeApi {
+    /// For the scan registration and the transaction signing and submission, the other requests
+    /// are sent with the pooled [`BLOCKING_HTTP_CLIENT`]
     pub node: NodeInterface,
+    api_key: String,
+    node_url: Url,
+}
+
+/// Node wallet status returned by `/wallet/status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeWalletStatus {
+    pub unlocked: bool,
+    /// Height the wallet is scanned to
+    pub height: u64,
+    pub change_address: Option<String>,
 }
 
 impl NodeApi {
     pub fn new(api_key: String, node_url: &Url) -> Self {
         let node = NodeInterface::from_url(&api_key, node_url.clone());
-        Self { node }
+        Self {
+            node,
+            api_key,
+            node_url: node_url.clone(),
+        }
+    }
+
+    /// GET request to the node with the pooled client, with the headers of `ergo-node-interface`
+    pub fn send_get_req(&self, endpoint: &str) -> Result<Response, NodeError> {
+        self.send_req(BLOCKING_HTTP_CLIENT.get(self.endpoint_url(endpoint)))
+    }
+
+    /// POST request with a JSON body to the node with the pooled client
+    pub fn send_post_req(&self, endpoint: &str, body: String) -> Result<Response, NodeError> {
+        self.send_req(
+            BLOCKING_HTTP_CLIENT
+                .post(self.endpoint_url(endpoint))
+                .body(body),
+        )
+    }
+
+    fn endpoint_url(&self, endpoint: &str) -> String {
+        format!(
+            "{}{}",
+            self.node_url.as_str().trim_end_matches('/'),
+            endpoint
+        )
+    }
+
+    /// Fails with the reqwest error and its causes (e.g. connection refused, timeout)
+    fn send_req(&self, request: RequestBuilder) -> Result<Response, NodeError> {
+        request
+            .header(ACCEPT, "application/json")
+            .header(CONTENT_TYPE, "application/json")
+            .header("api_key", &self.api_key)
+            .send()
+            .map_err(|e| NodeError::Other(format!("Node request failed: {}", error_chain(&e))))
+    }
+
+    /// GET request through [`node_call`], so the methods below are rate limited and traced
+    /// without wrapping them at the call sites
+    fn get_json(&self, endpoint: &str) -> Result<json::JsonValue, NodeError> {
+        node_call("GET", endpoint, || {
+            let res = self.send_get_req(endpoint);
+            self.node.parse_response_to_json(res)
+        })
+    }
+
+    /// Full height of the node's best chain
+    pub fn current_block_height(&self) -> Result<u64, NodeError> {
+        let res_json = self.get_json("/info")?;
+        res_json["fullHeight"]
+            .as_u64()
+            .ok_or_else(|| NodeError::FailedParsingNodeResponse(res_json.dump()))
+    }
+
+    pub fn wallet_status(&self) -> Result<NodeWalletStatus, NodeError> {
+        let res_json = self.get_json("/wallet/status")?;
+        let parse_error = || NodeError::FailedParsingNodeResponse(res_json.dump());
+        Ok(NodeWalletStatus {
+            unlocked: res_json["isUnlocked"].as_bool().ok_or_else(parse_error)?,
+            height: res_json["walletHeight"].as_u64().ok_or_else(parse_error)?,
+            change_address: res_json["changeAddress"]
+                .as_str()
+                .filter(|address| !address.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// Unspent boxes of the scan
+    pub fn scan_boxes(&self, scan_id: ScanId) -> Result<Vec<ErgoBox>, NodeError> {
+        let res_json = self.get_json(&format!("/scan/unspentBoxes/{}", scan_id))?;
+        parse_boxes(&res_json)
+    }
+
+    /// Unspent boxes of the node wallet
+    pub fn unspent_boxes(&self) -> Result<Vec<ErgoBox>, NodeError> {
+        let res_json = self.get_json("/wallet/boxes/unspent")?;
+        parse_boxes(&res_json)
     }
 
     pub fn get_change_address(&self) -> Result<NetworkAddress, NodeApiError> {
         let change_address_str = self
-            .node
             .wallet_status()?
             .change_address
             .ok_or(NodeApiError::NoChangeAddressSetInNode)?;
@@ -38,7 +136,9 @@ impl NodeApi {
 
     /// Registers a scan with the node and either returns the `scan_id` or an error
     pub fn register_scan_raw(&self, scan_json: serde_json::Value) -> Result<ScanID, NodeApiError> {
-        let scan_id = self.node.register_scan(scan_json)?;
+        let scan_id = node_call("POST", "/scan/register", || {
+            self.node.register_scan(scan_json)
+        })?;
         Ok(scan_id.to_string())
     }
 
@@ -64,21 +164,268 @@ impl NodeApi {
         Ok(scan_id)
     }
 
+    /// IDs and names of the scans registered in the node
+    pub fn list_scans(&self) -> Result<Vec<(ScanId, String)>, NodeApiError> {
+        let res_json = node_call("GET", "/scan/listAll", || {
+            let res = self.send_get_req("/scan/listAll");
+            self.node.parse_response_to_json(res)
+        })?;
+        if !res_json.is_array() {
+            return Err(NodeApiError::UnexpectedResponse(res_json.dump()));
+        }
+        Ok(res_json
+            .members()
+            .filter_map(|scan| {
+                Some((
+                    scan["scanId"].as_u64()?.into(),
+                    scan["scanName"].as_str().unwrap_or_default().to_string(),
+                ))
+            })
+            .collect())
+    }
+
     pub fn deregister_scan(&self, scan_id: ScanId) -> Result<ScanId, NodeApiError> {
         log::info!("Deregistering Scan: {}", scan_id);
-        let scan_id = self.node.deregister_scan(scan_id)?;
+        let scan_id = node_call("POST", "/scan/deregister", || {
+            self.node.deregister_scan(scan_id)
+        })?;
         Ok(scan_id)
     }
 
     pub fn rescan_from_height(&self, height: u32) -> Result<(), NodeApiError> {
         log::info!("Triggering wallet rescan");
-        self.node.send_post_req(
-            "/wallet/rescan",
-            format!("{{ \"fromHeight\": {} }} ", height),
-        )?;
+        node_call("POST", "/wallet/rescan", || {
+            self.send_post_req(
+                "/wallet/rescan",
+                format!("{{ \"fromHeight\": {} }} ", height),
+            )
+        })?;
+        Ok(())
+    }
+
+    pub fn unlock_wallet(&self, password: &str) -> Result<(), NodeApiError> {
+        log::info!("Unlocking wallet");
+        node_call("POST", "/wallet/unlock", || {
+            self.send_post_req("/wallet/unlock", json!({ "pass": password }).to_string())
+        })?;
         Ok(())
     }
 
+    /// Secret key of the node wallet address (`/wallet/getPrivateKey`, the wallet must be
+    /// unlocked). Kept in memory only, the response is never logged.
+    pub fn wallet_secret_key(&self, address: &NetworkAddress) -> Result<SecretKey, NodeApiError> {
+        let res_json = node_call("POST", "/wallet/getPrivateKey", || {
+            let res = self.send_post_req(
+                "/wallet/getPrivateKey",
+                json!({ "address": address.to_base58() }).to_string(),
+            );
+            self.node.parse_response_to_json(res)
+        })?;
+        let secret_hex = match res_json.as_str() {
+            Some(secret_hex) => secret_hex,
+            // an error response, no key in it
+            None => return Err(NodeApiError::UnexpectedResponse(res_json.dump())),
+        };
+        base16::decode(secret_hex)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| SecretKey::dlog_from_bytes(&bytes))
+            .ok_or_else(|| {
+                NodeApiError::UnexpectedResponse("the private key is not a dlog secret".to_string())
+            })
+    }
+
+    /// Timestamp (ms since epoch) of the best block header known to the node
+    pub fn get_best_block_timestamp(&self) -> Result<u64, NodeApiError> {
+        let res_json = node_call("GET", "/blocks/lastHeaders/1", || {
+            let res = self.send_get_req("/blocks/lastHeaders/1");
+            self.node.parse_response_to_json(res)
+        })?;
+        res_json[0]["timestamp"]
+            .as_u64()
+            .ok_or_else(|| NodeApiError::UnexpectedResponse(res_json.dump()))
+    }
+
+    /// Timestamp (ms since epoch) of the block at the height on the node's best chain
+    pub fn get_block_timestamp(&self, height: u32) -> Result<u64, NodeApiError> {
+        let endpoint = format!("/blocks/at/{}", height);
+        let ids_json = node_call("GET", "/blocks/at", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        let header_id = ids_json[0]
+            .as_str()
+            .ok_or_else(|| NodeApiError::UnexpectedResponse(ids_json.dump()))?;
+        let endpoint = format!("/blocks/{}/header", header_id);
+        let header_json = node_call("GET", "/blocks/header", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        header_json["timestamp"]
+            .as_u64()
+            .ok_or_else(|| NodeApiError::UnexpectedResponse(header_json.dump()))
+    }
+
+    /// Network the node runs on as reported by `/info`, e.g. `mainnet` or `testnet`
+    pub fn get_network(&self) -> Result<String, NodeApiError> {
+        let res_json = node_call("GET", "/info", || {
+            let res = self.send_get_req("/info");
+            self.node.parse_response_to_json(res)
+        })?;
+        res_json["network"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| NodeApiError::UnexpectedResponse(res_json.dump()))
+    }
+
+    /// Whether the token was minted, `None` if the node can't tell (the `/blockchain` API is
+    /// only served with the extra indexer enabled, `extraIndex = true` in the node config)
+    pub fn token_exists(&self, token_id: &TokenId) -> Result<Option<bool>, NodeApiError> {
+        Ok(self
+            .get_indexed_token(token_id)?
+            .map(|token| token.is_some()))
+    }
+
+    /// Amount of the token minted, `None` if the node can't tell (see [`Self::token_exists`]) or
+    /// the token doesn't exist
+    pub fn get_token_emission_amount(
+        &self,
+        token_id: &TokenId,
+    ) -> Result<Option<u64>, NodeApiError> {
+        Ok(self
+            .get_indexed_token(token_id)?
+            .flatten()
+            .and_then(|token| token["emissionAmount"].as_u64()))
+    }
+
+    /// The token from the extra indexer, `None` if the indexer is off, `Some(None)` if the token
+    /// doesn't exist
+    fn get_indexed_token(
+        &self,
+        token_id: &TokenId,
+    ) -> Result<Option<Option<json::JsonValue>>, NodeApiError> {
+        let indexed_height = node_call("GET", "/blockchain/indexedHeight", || {
+            let res = self.send_get_req("/blockchain/indexedHeight");
+            self.node.parse_response_to_json(res)
+        });
+        if indexed_height.map_or(true, |json| json["indexedHeight"].is_null()) {
+            return Ok(None);
+        }
+        let endpoint = format!("/blockchain/token/byId/{}", String::from(*token_id));
+        let res_json = node_call("GET", &endpoint, || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        if res_json["id"].is_string() {
+            Ok(Some(Some(res_json)))
+        } else if res_json["error"].as_u32() == Some(404) {
+            Ok(Some(None))
+        } else {
+            Err(NodeApiError::UnexpectedResponse(res_json.dump()))
+        }
+    }
+
+    /// Fee in nanoERG the node recommends for a tx of `tx_size` bytes to be included within
+    /// `wait_time_minutes`, based on the fees of the txs in its mempool
+    pub fn recommended_fee(
+        &self,
+        wait_time_minutes: u32,
+        tx_size: usize,
+    ) -> Result<u64, NodeApiError> {
+        let endpoint = format!(
+            "/transactions/getFee?waitTime={}&txSize={}",
+            wait_time_minutes, tx_size
+        );
+        let res_json = node_call("GET", "/transactions/getFee", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        res_json
+            .as_u64()
+            .ok_or_else(|| NodeApiError::UnexpectedResponse(res_json.dump()))
+    }
+
+    /// Transaction of the node wallet, `None` if the wallet doesn't know it
+    pub fn get_wallet_transaction(
+        &self,
+        tx_id: &str,
+    ) -> Result<Option<WalletTransaction>, NodeApiError> {
+        let endpoint = format!("/wallet/transactionById?id={}", tx_id);
+        let res_json = node_call("GET", "/wallet/transactionById", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        if res_json["error"].as_u32() == Some(404) {
+            return Ok(None);
+        }
+        if !res_json["id"].is_string() {
+            return Err(NodeApiError::UnexpectedResponse(res_json.dump()));
+        }
+        let outputs = res_json["outputs"]
+            .members()
+            .map(|output| WalletTransactionOutput {
+                ergo_tree: output["ergoTree"].as_str().unwrap_or_default().to_string(),
+                tokens: output["assets"]
+                    .members()
+                    .map(|asset| {
+                        (
+                            asset["tokenId"].as_str().unwrap_or_default().to_string(),
+                            asset["amount"].as_u64().unwrap_or(0),
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(Some(WalletTransaction {
+            inclusion_height: res_json["inclusionHeight"].as_u32(),
+            num_confirmations: res_json["numConfirmations"].as_u32().unwrap_or(0),
+            input_box_ids: res_json["inputs"]
+                .members()
+                .filter_map(|input| input["boxId"].as_str().map(str::to_string))
+                .collect(),
+            outputs,
+        }))
+    }
+
+    /// Whether the tx is in the node mempool
+    pub fn is_transaction_in_mempool(&self, tx_id: &str) -> Result<bool, NodeApiError> {
+        let endpoint = format!("/transactions/unconfirmed/byTransactionId/{}", tx_id);
+        let res_json = node_call("GET", "/transactions/unconfirmed/byTransactionId", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        if res_json["id"].is_string() {
+            Ok(true)
+        } else if res_json["error"].as_u32() == Some(404) {
+            Ok(false)
+        } else {
+            Err(NodeApiError::UnexpectedResponse(res_json.dump()))
+        }
+    }
+
+    /// Whether the box is unspent and, if the node runs the extra indexer, the tx spending it
+    pub fn get_box_spending(&self, box_id: &str) -> Result<BoxSpending, NodeApiError> {
+        let endpoint = format!("/utxo/byId/{}", box_id);
+        let res_json = node_call("GET", "/utxo/byId", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })?;
+        if res_json["boxId"].is_string() {
+            return Ok(BoxSpending::Unspent);
+        }
+        if res_json["error"].as_u32() != Some(404) {
+            return Err(NodeApiError::UnexpectedResponse(res_json.dump()));
+        }
+        let endpoint = format!("/blockchain/box/byId/{}", box_id);
+        let spent_by = node_call("GET", "/blockchain/box/byId", || {
+            let res = self.send_get_req(&endpoint);
+            self.node.parse_response_to_json(res)
+        })
+        .ok()
+        .and_then(|json| json["spentTransactionId"].as_str().map(str::to_string));
+        Ok(BoxSpending::Spent { spent_by })
+    }
+
     /// Sign an `UnsignedTransaction` and then submit it to the mempool.
     pub fn sign_and_submit_transaction(
         &self,
@@ -88,18 +435,47 @@ impl NodeApi {
             "Signing transaction: {}",
             serde_json::to_string_pretty(&unsigned_tx).unwrap()
         );
-        let signed_tx = self.node.sign_transaction(unsigned_tx, None, None)?;
+        let signed_tx = node_call("POST", "/wallet/transaction/sign", || {
+            self.node.sign_transaction(unsigned_tx, None, None)
+        })?;
         log::trace!(
             "Submitting signed transaction: {}",
             serde_json::to_string_pretty(&signed_tx).unwrap()
         );
-        Ok(self.node.submit_transaction(&signed_tx)?)
+        Ok(node_call("POST", "/transactions", || {
+            self.node.submit_transaction(&signed_tx)
+        })?)
+    }
+}
+
+/// The error followed by its sources, reqwest keeps the cause of a failed request in them
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        chain.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    chain
+}
+
+/// Boxes of a `/scan/unspentBoxes` or `/wallet/boxes/unspent` response, each under `box`
+fn parse_boxes(res_json: &json::JsonValue) -> Result<Vec<ErgoBox>, NodeError> {
+    if !res_json.is_array() {
+        return Err(NodeError::FailedParsingNodeResponse(res_json.dump()));
     }
+    res_json
+        .members()
+        .map(|item| {
+            serde_json::from_str(&item["box"].dump())
+                .map_err(|e| NodeError::FailedParsingNodeResponse(e.to_string()))
+        })
+        .collect()
 }
 
 impl WalletDataSource for NodeApi {
     fn get_unspent_wallet_boxes(&self) -> Result<Vec<ErgoBox>, WalletDataError> {
-        self.node.unspent_boxes().map_err(Into::into)
+        self.unspent_boxes().map_err(Into::into)
     }
 
     fn get_change_address(&self) -> Result<NetworkAddress, WalletDataError> {
@@ -107,14 +483,43 @@ impl WalletDataSource for NodeApi {
     }
 }
 
+/// Transaction returned by the node wallet `/wallet/transactionById` endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletTransaction {
+    pub inclusion_height: Option<u32>,
+    pub num_confirmations: u32,
+    pub input_box_ids: Vec<String>,
+    pub outputs: Vec<WalletTransactionOutput>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletTransactionOutput {
+    /// Base16 encoded
+    pub ergo_tree: String,
+    /// Token ids and amounts
+    pub tokens: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoxSpending {
+    Unspent,
+    /// Not in the UTXO set, `spent_by` is the spending tx if the node runs the extra indexer
+    /// (`extraIndex = true` in the node config)
+    Spent {
+        spent_by: Option<String>,
+    },
+}
+
 #[derive(Debug, Error)]
 pub enum NodeApiError {
     #[error("Node error: {0}")]
     NodeInterfaceError(#[from] NodeError),
     #[error("AddressEncoder error: {0}")]
     AddressEncoderError(#[from] AddressEncoderError),
-    #[error("no change address is set in node")]
+    #[error("no change address is set in node (is the wallet locked?)")]
     NoChangeAddressSetInNode,
     #[error("invalid scan id: {0}")]
     InvalidScanId(String),
+    #[error("unexpected node response: {0}")]
+    UnexpectedResponse(String),
 }
@@ -0,0 +1,95 @@
+//! Global limit of the node API request rate (`node_max_requests_per_sec`), so that oracles
+//! sharing a node don't overwhelm it. Requests over the limit wait for their turn.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::oracle_config::ORACLE_CONFIG_OPT;
+
+/// Token bucket refilled at `rate` tokens per second, holding at most one second worth of
+/// tokens
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, now: Instant) -> Self {
+        let capacity = rate.max(1.0);
+        TokenBucket {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token and return how long to wait before making the request. The tokens go
+    /// negative while requests are waiting, so the waiting requests are spread at the rate.
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+lazy_static! {
+    static ref NODE_RATE_LIMITER: Option<Mutex<TokenBucket>> = ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.node_max_requests_per_sec)
+        .filter(|rate| *rate > 0.0)
+        .map(|rate| Mutex::new(TokenBucket::new(rate, Instant::now())));
+}
+
+/// Block until the next node request is allowed, returns immediately if there is no limit
+pub fn wait_for_node_request_slot() {
+    if let Some(limiter) = NODE_RATE_LIMITER.as_ref() {
+        let wait = limiter.lock().unwrap().reserve(Instant::now());
+        if !wait.is_zero() {
+            log::trace!("Node request rate limit, waiting {} ms", wait.as_millis());
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, start);
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        // bucket is empty, the next ones wait half a second each
+        assert_eq!(bucket.reserve(start), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(start), Duration::from_millis(1000));
+        // refilled after the waits, capacity is one second worth of tokens
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_token_bucket_below_one_per_sec() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(0.5, start);
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::from_secs(2));
+    }
+}
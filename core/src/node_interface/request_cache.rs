@@ -0,0 +1,158 @@
+//! Coalescing of the identical node queries (scan boxes, current height) made by different code
+//! paths within a main loop iteration or a command. A query made while the same one is in flight
+//! waits for its result, a recent result (`node_request_cache_secs` in the oracle config) is
+//! reused. The cache is cleared at the start of each main loop iteration. Off if not set.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
+use ergo_node_interface::node_interface::NodeError;
+use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
+
+use crate::oracle_config::ORACLE_CONFIG_OPT;
+
+use super::node_api::NodeApi;
+
+static SCAN_BOXES: Lazy<RequestCache<Vec<ErgoBox>>> = Lazy::new(RequestCache::default);
+static CURRENT_HEIGHT: Lazy<RequestCache<u32>> = Lazy::new(RequestCache::default);
+
+#[derive(Debug)]
+struct CacheEntry<T> {
+    value: OnceCell<T>,
+    created_at: Instant,
+}
+
+/// Results of the queries by key. Only successful results are kept, a failed query is made
+/// again by the next caller.
+#[derive(Debug)]
+pub struct RequestCache<T> {
+    entries: Mutex<HashMap<String, Arc<CacheEntry<T>>>>,
+}
+
+impl<T> Default for RequestCache<T> {
+    fn default() -> Self {
+        RequestCache {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> RequestCache<T> {
+    pub fn get_or_fetch<E>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        now: Instant,
+        fetch: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let entry = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|_, entry| now.saturating_duration_since(entry.created_at) < ttl);
+            entries
+                .entry(key.to_string())
+                .or_insert_with(|| {
+                    Arc::new(CacheEntry {
+                        value: OnceCell::new(),
+                        created_at: now,
+                    })
+                })
+                .clone()
+        };
+        // the lock is released, a concurrent query of the same key blocks here until the first
+        // one completes
+        entry.value.get_or_try_init(fetch).cloned()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+fn cache_ttl() -> Option<Duration> {
+    ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.node_request_cache_secs)
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Forget the results of the previous main loop iteration
+pub fn start_iteration() {
+    SCAN_BOXES.clear();
+    CURRENT_HEIGHT.clear();
+}
+
+/// Unspent boxes of the scan, coalesced with the identical queries
+pub fn scan_boxes<E>(
+    scan_id: &str,
+    fetch: impl FnOnce() -> Result<Vec<ErgoBox>, E>,
+) -> Result<Vec<ErgoBox>, E> {
+    match cache_ttl() {
+        Some(ttl) => SCAN_BOXES.get_or_fetch(scan_id, ttl, Instant::now(), fetch),
+        None => fetch(),
+    }
+}
+
+/// Current height of the node, coalesced with the identical queries
+pub fn current_height(node_api: &NodeApi) -> Result<u32, NodeError> {
+    let fetch = || node_api.current_block_height().map(|h| h as u32);
+    match cache_ttl() {
+        Some(ttl) => CURRENT_HEIGHT.get_or_fetch("/info", ttl, Instant::now(), fetch),
+        None => fetch(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_get_or_fetch() {
+        let cache = RequestCache::<u32>::default();
+        let ttl = Duration::from_secs(10);
+        let start = Instant::now();
+        let fetches = Cell::new(0);
+        let fetch = |value: u32| {
+            fetches.set(fetches.get() + 1);
+            Ok::<u32, ()>(value)
+        };
+        assert_eq!(cache.get_or_fetch("a", ttl, start, || fetch(1)), Ok(1));
+        assert_eq!(cache.get_or_fetch("a", ttl, start, || fetch(2)), Ok(1));
+        assert_eq!(cache.get_or_fetch("b", ttl, start, || fetch(3)), Ok(3));
+        assert_eq!(fetches.get(), 2);
+        // expired
+        assert_eq!(
+            cache.get_or_fetch("a", ttl, start + ttl, || fetch(4)),
+            Ok(4)
+        );
+        cache.clear();
+        assert_eq!(
+            cache.get_or_fetch("a", ttl, start + ttl, || fetch(5)),
+            Ok(5)
+        );
+        assert_eq!(fetches.get(), 4);
+    }
+
+    #[test]
+    fn test_errors_not_cached() {
+        let cache = RequestCache::<u32>::default();
+        let ttl = Duration::from_secs(10);
+        let now = Instant::now();
+        assert_eq!(
+            cache.get_or_fetch("a", ttl, now, || Err("node down")),
+            Err("node down")
+        );
+        assert_eq!(
+            cache.get_or_fetch("a", ttl, now, || Ok::<u32, &str>(1)),
+            Ok(1)
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use std::{
+    collections::BTreeMap,
     convert::TryFrom,
     io::Write,
+    net::{SocketAddr, ToSocketAddrs},
     path::{Path, PathBuf},
 };
 
 use ergo_lib::{
+    ergo_chain_types::EcPoint,
     ergotree_ir::chain::address::NetworkAddress,
     ergotree_ir::{
         chain::{
@@ -18,23 +21,386 @@ use ergo_lib::{
 use log::LevelFilter;
 use once_cell::sync;
 use reqwest::Url;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::datapoint_source::json_source::JsonSource;
+use crate::datapoint_source::predef_source_names;
+use crate::datapoint_source::transform::DatapointTransform;
 use crate::explorer_api::explorer_url::default_explorer_api_url;
+use crate::logging::LogTimezone;
+use crate::pool_commands::ActionKind;
+use crate::pool_config::PoolConfig;
+use crate::pool_config::PredefinedDataPointSource;
+use crate::pool_config::POOL_CONFIG_FILE_PATH;
 
 pub const DEFAULT_ORACLE_CONFIG_FILE_NAME: &str = "oracle_config.yaml";
+pub const DEFAULT_STARTUP_RETRY_ATTEMPTS: u32 = 5;
+#[cfg(unix)]
+pub const DEFAULT_REST_API_SOCKET_MODE: u32 = 0o660;
+/// Minimal fee accepted by the nodes with the default settings
+pub const MIN_TX_FEE: u64 = 1_000_000;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct OracleConfig {
     pub node_url: Url,
     pub node_api_key: String,
     pub base_fee: u64,
+    #[schemars(with = "Option<String>")]
     pub log_level: Option<LevelFilter>,
     pub core_api_port: u16,
+    #[schemars(with = "String")]
     pub oracle_address: NetworkAddress,
     pub data_point_source_custom_script: Option<String>,
     pub explorer_url: Option<Url>,
+    /// Name of the environment variable holding the node wallet password. If set, the oracle
+    /// tries to unlock the wallet when the node relocks it during a run.
+    pub wallet_password_env_var: Option<String>,
+    /// Allow posting zero and negative datapoints. Off by default since such values make no
+    /// sense for prices.
+    pub allow_non_positive_datapoints: Option<bool>,
+    /// Oracles expected to post datapoints. Oracles not on this list are reported as unknown in
+    /// the logs and REST API. Doesn't affect which datapoints are collected.
+    #[schemars(with = "Option<Vec<String>>")]
+    pub known_oracle_addresses: Option<Vec<NetworkAddress>>,
+    /// The REST API `/ready` endpoint reports not ready if the node's best block is older than
+    /// this (in seconds).
+    pub max_node_height_staleness_secs: Option<u64>,
+    /// Maximum idle HTTP connections kept per host for the node, datapoint sources and explorer
+    /// requests. Unlimited if not set.
+    pub http_pool_max_idle_per_host: Option<usize>,
+    /// Idle HTTP connections are closed after this many seconds (90 if not set)
+    pub http_pool_idle_timeout_secs: Option<u64>,
+    /// Keep HTTP connections open for reuse (on if not set)
+    pub http_keep_alive: Option<bool>,
+    /// Attempts to reach the node and register the scans on `run` startup, with the delay
+    /// between them doubling up to a minute (5 if not set)
+    pub startup_retry_attempts: Option<u32>,
+    /// Order in which the actions are attempted when several are eligible in the same main loop
+    /// iteration (publish_datapoint before refresh if not set). Kinds left out come last.
+    pub action_priority: Option<Vec<ActionKind>>,
+    /// Decimals of the prices shown next to the datapoints in the command output and the REST
+    /// API (2 if not set)
+    pub value_display_decimals: Option<usize>,
+    /// chrono format string of the log timestamps (ISO 8601 if not set)
+    pub log_timestamp_format: Option<String>,
+    /// Timezone of the log timestamps, `local` or `utc` (local if not set)
+    pub log_timezone: Option<LogTimezone>,
+    /// Maximum node API requests per second, requests over it wait. Unlimited if not set.
+    pub node_max_requests_per_sec: Option<f64>,
+    /// Fees overriding `base_fee` for the given transaction kinds
+    pub action_fees: Option<ActionFees>,
+    /// File the pool rate, epoch and height are written to (atomically) on every main loop
+    /// iteration
+    pub latest_value_file: Option<PathBuf>,
+    /// Blocks to wait after the epoch start (pool box creation) before publishing a datapoint,
+    /// so that a spike right at the epoch boundary isn't posted (0 if not set)
+    pub publish_grace_blocks: Option<u32>,
+    /// Where the REST API listens, `host:port` or `unix:///path/to/socket` (all interfaces on
+    /// `core_api_port` if not set)
+    pub rest_api_bind: Option<String>,
+    /// Octal permissions of the REST API Unix socket file ("660" if not set)
+    pub rest_api_socket_mode: Option<String>,
+    /// Name of the environment variable holding the key expected in the `api_key` header of the
+    /// REST API `/pause` and `/resume` endpoints. The endpoints are disabled if not set.
+    pub rest_api_control_key_env_var: Option<String>,
+    /// If set, the publish and refresh txs pay at least the fee the node recommends for them to
+    /// be included within this many blocks
+    pub fee_target_blocks: Option<u32>,
+    /// Cap in nanoERG of the fee recommended by the node with `fee_target_blocks` (not capped if
+    /// not set)
+    pub max_tx_fee: Option<u64>,
+    /// What to do when a tx can't be built (`continue` if not set)
+    pub build_failure_policy: Option<BuildFailurePolicy>,
+    /// What to do when the wallet can't pay for the publish datapoint or refresh tx
+    /// (`build_failure_policy` if not set)
+    pub insufficient_funds_policy: Option<BuildFailurePolicy>,
+    /// Number of times a failed tx submission is retried within the loop iteration (0 if not set)
+    pub submit_retry_attempts: Option<u32>,
+    /// Expression applied to the value of each predefined datapoint source before aggregation,
+    /// e.g. `x * 1.005 + 10`
+    #[schemars(with = "Option<String>")]
+    pub datapoint_transform: Option<DatapointTransform>,
+    /// What to do when the pool box runs out of reward tokens (`continue` if not set)
+    pub reward_depletion_policy: Option<RewardDepletionPolicy>,
+    /// Warn when the pool box reward tokens are enough for fewer refreshes than this (10 if not
+    /// set)
+    pub reward_warning_epochs: Option<u32>,
+    /// The last fetched datapoint is saved to the data dir and reused after a restart if it was
+    /// fetched less than this many seconds ago from the same source for the same pool. Not saved
+    /// if not set.
+    pub datapoint_reuse_window_secs: Option<u64>,
+    /// Maximum scan box requests sent to the node at the same time by the commands fetching
+    /// all the pool scans, `inspect-pool` and `snapshot-state` (4 if not set, 1 fetches them one
+    /// by one)
+    pub scan_fetch_concurrency: Option<usize>,
+    /// Maximum number of the posted and collected datapoint boxes that are processed. Unlimited
+    /// if not set.
+    pub max_scan_boxes: Option<usize>,
+    /// What to do when a scan returns more than `max_scan_boxes` boxes (`truncate` if not set)
+    pub scan_box_limit_policy: Option<ScanBoxLimitPolicy>,
+    /// Independent feed the posted datapoints are compared against, not used for posting
+    pub reference_feed: Option<ReferenceFeed>,
+    /// Log only 1-in-N of the debug messages logged every main loop iteration (all if not set).
+    /// Warnings and errors are always logged.
+    pub log_sample_rate: Option<u32>,
+    /// Message broker the oracle events (datapoint posted, refresh completed, error) are
+    /// published to as JSON. Not published if not set.
+    pub event_sink: Option<EventSink>,
+    /// Seconds the node wallet change address is cached for in the main loop (600 if not set)
+    pub change_address_refresh_secs: Option<u64>,
+    /// Attempts of the change address request before the cached one is used, or the main loop
+    /// iteration fails if there is none yet (3 if not set)
+    pub change_address_retry_attempts: Option<u32>,
+    /// Maximum spread of the predefined datapoint sources (the difference of the highest and
+    /// the lowest rate relative to their average) in percent. Not checked if not set.
+    pub max_source_spread_percent: Option<f64>,
+    /// What to do when the sources spread is above `max_source_spread_percent` (`refuse` if not
+    /// set)
+    pub source_spread_policy: Option<SourceSpreadPolicy>,
+    /// API keys of the predefined datapoint sources by source name (`coingecko`, `coincap`)
+    pub datapoint_source_credentials: Option<BTreeMap<String, SourceCredential>>,
+    /// Minimum number of the predefined datapoint sources that must respond for the datapoint to
+    /// be posted, can't be above the number of the configured sources
+    pub min_sources: Option<usize>,
+    /// What to do when the pool box epoch counter goes below the highest one seen (`refuse` if
+    /// not set)
+    pub epoch_regression_policy: Option<EpochRegressionPolicy>,
+    /// Seconds the results of the scan boxes and height node queries are reused for, and the
+    /// identical queries in flight coalesced. Cleared every main loop iteration. Off if not set.
+    pub node_request_cache_secs: Option<u64>,
+    /// What to do on start when the tx of the last action submitted before the restart isn't
+    /// confirmed yet (`wait` if not set)
+    pub pending_action_policy: Option<PendingActionPolicy>,
+    /// What to do on start when the node runs on another network than the one of
+    /// `oracle_address` (`refuse` if not set)
+    pub network_mismatch_policy: Option<NetworkMismatchPolicy>,
+    /// Confirmations the txs submitted by the commands (e.g. `extract-reward-tokens`) need before
+    /// they are reported as confirmed (1 if not set). The command fails if they don't get them in
+    /// time. The txs of the `run` main loop don't wait for it.
+    pub confirmation_depth: Option<u32>,
+    /// Version (`major.minor.patch`) below which a warning to upgrade is logged on start
+    pub min_recommended_version: Option<String>,
+    /// Fetch the datapoint from a REST API returning JSON instead of the pool config
+    /// `data_point_source`. `data_point_source_custom_script` takes precedence if set.
+    pub data_point_source_json: Option<JsonSource>,
+    /// Number of epochs the refresh has to fail to reach consensus in a row before an escalated
+    /// alert. Off if not set.
+    pub consensus_failure_escalation_epochs: Option<u32>,
+    /// What to do on the escalated consensus failure alert (`continue` if not set)
+    pub consensus_failure_policy: Option<BuildFailurePolicy>,
+    /// What to do when the REST server panics (`restart` if not set)
+    pub rest_server_panic_policy: Option<RestServerPanicPolicy>,
+    /// Temporarily stop fetching from a predefined datapoint source that keeps failing. Off if
+    /// not set.
+    pub source_failure_exclusion: Option<SourceFailureExclusion>,
+}
+
+/// Reaction to the failure to build a tx, e.g. not enough funds in the wallet or a contract
+/// mismatch. These don't go away by themselves, unlike the submit failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildFailurePolicy {
+    /// Log the error and try again in the next loop iteration
+    Continue,
+    /// Log the error and stop submitting txs until resumed with the REST API `/resume` endpoint
+    /// or restarted
+    Pause,
+}
+
+/// Reaction to the pool box not having enough reward tokens for a refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RewardDepletionPolicy {
+    /// Log the error and keep posting datapoints without rewards
+    Continue,
+    /// Log the error and stop submitting txs until resumed with the REST API `/resume` endpoint
+    /// or restarted
+    Pause,
+}
+
+/// Reaction to a scan returning more than `max_scan_boxes` boxes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanBoxLimitPolicy {
+    /// Log a warning and process the `max_scan_boxes` newest boxes
+    Truncate,
+    /// Fail the fetch
+    Fail,
+}
+
+/// Reaction to the predefined datapoint sources spreading more than `max_source_spread_percent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceSpreadPolicy {
+    /// Log a warning and post the aggregated datapoint
+    Warn,
+    /// Log an error and don't post until the sources agree again
+    Refuse,
+}
+
+/// Reaction to the pool box epoch counter going back, e.g. after a reorg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EpochRegressionPolicy {
+    /// Log a warning and act on the current pool state
+    Warn,
+    /// Log an error and don't act until the counter is back to the highest one seen, or stayed
+    /// lower for a whole epoch
+    Refuse,
+}
+
+/// Reaction to the tx of the last action submitted before the restart still being in the
+/// mempool on start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PendingActionPolicy {
+    /// Don't act until the tx is confirmed or dropped from the mempool
+    Wait,
+    /// Act on the current pool state right away, as if there was no pending tx
+    Rebuild,
+}
+
+/// Reaction to the node running on another network than the one of the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMismatchPolicy {
+    /// Log a warning and run anyway
+    Warn,
+    /// Log an error and exit
+    Refuse,
+}
+
+/// Reaction to the REST server task panicking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RestServerPanicPolicy {
+    /// Log the error and start the server again after a backoff
+    Restart,
+    /// Log the error and exit, e.g. to be restarted by the service manager
+    Exit,
+}
+
+/// `reference_feed` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReferenceFeed {
+    /// URL returning JSON
+    pub url: String,
+    /// Dot-separated path of the value in the response, e.g. `data.priceUsd` or `data.0.price`
+    pub json_path: String,
+    /// Expression converting the value to the datapoint units, e.g. `1000000000 / x`
+    #[schemars(with = "Option<String>")]
+    pub transform: Option<DatapointTransform>,
+    /// Warn when a posted datapoint deviates from the reference by more than this many percent
+    /// (5 if not set)
+    pub max_deviation_percent: Option<f64>,
+}
+
+/// `source_failure_exclusion` settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SourceFailureExclusion {
+    /// A source failing more than this many times within `window_secs` is excluded
+    pub max_failures: u32,
+    /// Period the failures are counted in (600 if not set)
+    pub window_secs: Option<u64>,
+    /// Seconds the source stays excluded before it's fetched again (300 if not set)
+    pub cooldown_secs: Option<u64>,
+}
+
+/// Where to read a datapoint source API key from. The OS keyring entry if set (an error if it
+/// can't be read), otherwise the first one found of the environment variable and the inline value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SourceCredential {
+    /// Account name of the OS keyring entry of the `oracle-core` service
+    pub keyring_entry: Option<String>,
+    pub env_var: Option<String>,
+    pub value: Option<String>,
+}
+
+/// `event_sink` settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EventSink {
+    pub kind: EventSinkKind,
+    /// `host:port` of the broker
+    pub address: String,
+    /// Redis channel or NATS subject
+    pub channel: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EventSinkKind {
+    /// Redis pub/sub `PUBLISH`
+    Redis,
+    /// NATS core `PUB`
+    Nats,
+}
+
+/// Parsed `rest_api_bind`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestApiBind {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix {
+        path: PathBuf,
+        mode: u32,
+    },
+}
+
+/// Fee overrides in nanoERG, `base_fee` is used for the kinds that are not set
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct ActionFees {
+    pub publish_datapoint: Option<u64>,
+    pub refresh: Option<u64>,
+    pub extract_reward_tokens: Option<u64>,
+    pub transfer_oracle_token: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeKind {
+    PublishDatapoint,
+    Refresh,
+    ExtractRewardTokens,
+    TransferOracleToken,
+}
+
+impl From<ActionKind> for FeeKind {
+    fn from(kind: ActionKind) -> Self {
+        match kind {
+            ActionKind::PublishDatapoint => FeeKind::PublishDatapoint,
+            ActionKind::Refresh => FeeKind::Refresh,
+        }
+    }
+}
+
+impl ActionFees {
+    pub fn get(&self, kind: FeeKind) -> Option<u64> {
+        match kind {
+            FeeKind::PublishDatapoint => self.publish_datapoint,
+            FeeKind::Refresh => self.refresh,
+            FeeKind::ExtractRewardTokens => self.extract_reward_tokens,
+            FeeKind::TransferOracleToken => self.transfer_oracle_token,
+        }
+    }
+
+    fn check_min_fee(&self) -> Result<(), OracleConfigFileError> {
+        let fees = [
+            ("publish_datapoint", self.publish_datapoint),
+            ("refresh", self.refresh),
+            ("extract_reward_tokens", self.extract_reward_tokens),
+            ("transfer_oracle_token", self.transfer_oracle_token),
+        ];
+        for (name, fee) in fees {
+            if let Some(fee) = fee.filter(|fee| *fee < MIN_TX_FEE) {
+                return Err(OracleConfigFileError::FeeTooLow {
+                    name: format!("action_fees.{}", name),
+                    fee,
+                });
+            }
+        }
+        Ok(())
+    }
 }
 
 impl OracleConfig {
@@ -45,7 +411,7 @@ impl OracleConfig {
         file.write_all(yaml_str.as_bytes()).unwrap();
     }
 
-    fn load() -> Result<Self, OracleConfigFileError> {
+    pub fn load() -> Result<Self, OracleConfigFileError> {
         let config_file_path = ORACLE_CONFIG_FILE_PATH.get().ok_or_else(|| {
             OracleConfigFileError::IoError("ORACLE_CONFIG_FILE_PATH not set".to_string())
         })?;
@@ -53,6 +419,11 @@ impl OracleConfig {
             .map_err(|e| OracleConfigFileError::IoError(e.to_string()))?;
         let config = Self::load_from_str(config_str)?;
         let _ = config.oracle_address_p2pk()?;
+        if let Some(action_fees) = &config.action_fees {
+            action_fees.check_min_fee()?;
+        }
+        let _ = config.rest_api_bind()?;
+        config.check_min_sources(pool_config_data_point_source().as_ref())?;
         Ok(config)
     }
 
@@ -68,6 +439,41 @@ impl OracleConfig {
         Ok(())
     }
 
+    /// Check `min_sources` against the number of the configured sources. `predef_source` is the
+    /// pool config `data_point_source`, used unless a custom script or JSON source is set.
+    fn check_min_sources(
+        &self,
+        predef_source: Option<&PredefinedDataPointSource>,
+    ) -> Result<(), OracleConfigFileError> {
+        let min_sources = match self.min_sources {
+            Some(min_sources) => min_sources,
+            None => return Ok(()),
+        };
+        if min_sources == 0 {
+            return Err(OracleConfigFileError::InvalidMinSources(
+                "min_sources must be at least 1".to_string(),
+            ));
+        }
+        let configured = if self.data_point_source_custom_script.is_some()
+            || self.data_point_source_json.is_some()
+        {
+            1
+        } else {
+            match predef_source {
+                Some(predef_source) => predef_source_names(predef_source).len(),
+                // checked when the datapoint source is created
+                None => return Ok(()),
+            }
+        };
+        if min_sources > configured {
+            return Err(OracleConfigFileError::InvalidMinSources(format!(
+                "min_sources {} is above the {} configured sources",
+                min_sources, configured
+            )));
+        }
+        Ok(())
+    }
+
     pub fn oracle_address_p2pk(&self) -> Result<ProveDlog, OracleConfigFileError> {
         if let Address::P2Pk(public_key) = self.oracle_address.address() {
             Ok(public_key.clone())
@@ -75,6 +481,82 @@ impl OracleConfig {
             Err(OracleConfigFileError::InvalidOracleAddress)
         }
     }
+
+    pub fn rest_api_bind(&self) -> Result<RestApiBind, OracleConfigFileError> {
+        let bind = match &self.rest_api_bind {
+            Some(bind) => bind,
+            None => {
+                return Ok(RestApiBind::Tcp(SocketAddr::from((
+                    [0, 0, 0, 0],
+                    self.core_api_port,
+                ))))
+            }
+        };
+        if let Some(path) = bind.strip_prefix("unix://") {
+            Self::unix_rest_api_bind(path, self.rest_api_socket_mode.as_deref())
+        } else {
+            // resolved to allow host names, e.g. localhost:9010
+            bind.to_socket_addrs()
+                .ok()
+                .and_then(|mut addrs| addrs.next())
+                .map(RestApiBind::Tcp)
+                .ok_or_else(|| {
+                    OracleConfigFileError::InvalidRestApiBind(format!(
+                        "rest_api_bind {} is neither host:port nor unix://path",
+                        bind
+                    ))
+                })
+        }
+    }
+
+    #[cfg(unix)]
+    fn unix_rest_api_bind(
+        path: &str,
+        socket_mode: Option<&str>,
+    ) -> Result<RestApiBind, OracleConfigFileError> {
+        let mode = match socket_mode {
+            Some(mode) => u32::from_str_radix(mode, 8)
+                .ok()
+                .filter(|mode| *mode <= 0o777)
+                .ok_or_else(|| {
+                    OracleConfigFileError::InvalidRestApiBind(format!(
+                        "rest_api_socket_mode {} is not an octal file mode",
+                        mode
+                    ))
+                })?,
+            None => DEFAULT_REST_API_SOCKET_MODE,
+        };
+        if path.is_empty() {
+            return Err(OracleConfigFileError::InvalidRestApiBind(
+                "rest_api_bind has no socket path after unix://".to_string(),
+            ));
+        }
+        Ok(RestApiBind::Unix {
+            path: PathBuf::from(path),
+            mode,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn unix_rest_api_bind(
+        _path: &str,
+        _socket_mode: Option<&str>,
+    ) -> Result<RestApiBind, OracleConfigFileError> {
+        Err(OracleConfigFileError::InvalidRestApiBind(
+            "rest_api_bind unix:// is only supported on Unix".to_string(),
+        ))
+    }
+
+    /// Returns `false` if `known_oracle_addresses` is set and doesn't contain the oracle with the
+    /// given public key
+    pub fn is_known_oracle(&self, public_key: &EcPoint) -> bool {
+        match &self.known_oracle_addresses {
+            Some(addresses) => addresses.iter().any(
+                |a| matches!(a.address(), Address::P2Pk(known_pk) if *known_pk.h == *public_key),
+            ),
+            None => true,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -85,6 +567,24 @@ pub enum OracleConfigFileError {
     ParseError(String),
     #[error("Invalid oracle address, must be P2PK")]
     InvalidOracleAddress,
+    #[error(
+        "{name} of {fee} nanoERG is below the minimal fee of {} nanoERG",
+        MIN_TX_FEE
+    )]
+    FeeTooLow { name: String, fee: u64 },
+    #[error("Invalid REST API bind: {0}")]
+    InvalidRestApiBind(String),
+    #[error("Invalid min_sources: {0}")]
+    InvalidMinSources(String),
+}
+
+/// `data_point_source` of the pool config if it's already there (not before `bootstrap`)
+fn pool_config_data_point_source() -> Option<PredefinedDataPointSource> {
+    let path = POOL_CONFIG_FILE_PATH.get()?;
+    if !path.exists() {
+        return None;
+    }
+    PoolConfig::load().ok()?.data_point_source
 }
 
 impl Default for OracleConfig {
@@ -102,6 +602,57 @@ impl Default for OracleConfig {
             log_level: LevelFilter::Info.into(),
             node_url: Url::parse("http://127.0.0.1:9053").unwrap(),
             explorer_url: Some(default_explorer_api_url(address.network())),
+            wallet_password_env_var: None,
+            allow_non_positive_datapoints: Some(false),
+            known_oracle_addresses: None,
+            max_node_height_staleness_secs: None,
+            http_pool_max_idle_per_host: None,
+            http_pool_idle_timeout_secs: None,
+            http_keep_alive: None,
+            startup_retry_attempts: None,
+            action_priority: None,
+            value_display_decimals: None,
+            log_timestamp_format: None,
+            log_timezone: None,
+            node_max_requests_per_sec: None,
+            action_fees: None,
+            latest_value_file: None,
+            publish_grace_blocks: None,
+            rest_api_bind: None,
+            rest_api_socket_mode: None,
+            rest_api_control_key_env_var: None,
+            fee_target_blocks: None,
+            max_tx_fee: None,
+            build_failure_policy: None,
+            insufficient_funds_policy: None,
+            submit_retry_attempts: None,
+            datapoint_transform: None,
+            reward_depletion_policy: None,
+            reward_warning_epochs: None,
+            datapoint_reuse_window_secs: None,
+            scan_fetch_concurrency: None,
+            max_scan_boxes: None,
+            scan_box_limit_policy: None,
+            reference_feed: None,
+            log_sample_rate: None,
+            event_sink: None,
+            change_address_refresh_secs: None,
+            change_address_retry_attempts: None,
+            max_source_spread_percent: None,
+            source_spread_policy: None,
+            datapoint_source_credentials: None,
+            min_sources: None,
+            epoch_regression_policy: None,
+            node_request_cache_secs: None,
+            pending_action_policy: None,
+            network_mismatch_policy: None,
+            confirmation_depth: None,
+            min_recommended_version: None,
+            data_point_source_json: None,
+            consensus_failure_escalation_epochs: None,
+            consensus_failure_policy: None,
+            rest_server_panic_policy: None,
+            source_failure_exclusion: None,
         }
     }
 }
@@ -117,7 +668,123 @@ lazy_static! {
         .unwrap_or_else(|_| SUGGESTED_TX_FEE());
 }
 
-/// Returns "core_api_port" from the config file
-pub fn get_core_api_port() -> String {
-    ORACLE_CONFIG.core_api_port.to_string()
+/// Fee for the transaction kind, [`BASE_FEE`] if not overridden in `action_fees`
+pub fn action_fee(kind: FeeKind) -> BoxValue {
+    ORACLE_CONFIG_OPT
+        .as_ref()
+        .ok()
+        .and_then(|c| c.action_fees.as_ref()?.get(kind))
+        .map(|fee| BoxValue::try_from(fee).unwrap())
+        .unwrap_or(*BASE_FEE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_fees() {
+        let action_fees = ActionFees {
+            refresh: Some(2_000_000),
+            ..ActionFees::default()
+        };
+        assert_eq!(action_fees.get(FeeKind::Refresh), Some(2_000_000));
+        assert_eq!(action_fees.get(FeeKind::PublishDatapoint), None);
+        assert!(action_fees.check_min_fee().is_ok());
+        let too_low = ActionFees {
+            extract_reward_tokens: Some(MIN_TX_FEE - 1),
+            ..action_fees
+        };
+        assert!(matches!(
+            too_low.check_min_fee(),
+            Err(OracleConfigFileError::FeeTooLow { name, .. }) if name == "action_fees.extract_reward_tokens"
+        ));
+    }
+
+    #[test]
+    fn test_check_min_sources() {
+        let config = OracleConfig::default();
+        assert!(config
+            .check_min_sources(Some(&PredefinedDataPointSource::NanoErgUsd))
+            .is_ok());
+        let config = OracleConfig {
+            min_sources: Some(2),
+            ..OracleConfig::default()
+        };
+        assert!(config
+            .check_min_sources(Some(&PredefinedDataPointSource::NanoErgUsd))
+            .is_ok());
+        assert!(matches!(
+            config.check_min_sources(Some(&PredefinedDataPointSource::NanoAdaUsd)),
+            Err(OracleConfigFileError::InvalidMinSources(_))
+        ));
+        assert!(config.check_min_sources(None).is_ok());
+        let config = OracleConfig {
+            min_sources: Some(0),
+            ..OracleConfig::default()
+        };
+        assert!(matches!(
+            config.check_min_sources(None),
+            Err(OracleConfigFileError::InvalidMinSources(_))
+        ));
+    }
+
+    #[test]
+    fn test_rest_api_bind() {
+        let config = OracleConfig::default();
+        assert_eq!(
+            config.rest_api_bind().unwrap(),
+            RestApiBind::Tcp("0.0.0.0:9010".parse().unwrap())
+        );
+        let config = OracleConfig {
+            rest_api_bind: Some("127.0.0.1:9011".to_string()),
+            ..OracleConfig::default()
+        };
+        assert_eq!(
+            config.rest_api_bind().unwrap(),
+            RestApiBind::Tcp("127.0.0.1:9011".parse().unwrap())
+        );
+        let config = OracleConfig {
+            rest_api_bind: Some("localhost:9011".to_string()),
+            ..OracleConfig::default()
+        };
+        assert!(matches!(
+            config.rest_api_bind().unwrap(),
+            RestApiBind::Tcp(addr) if addr.port() == 9011 && addr.ip().is_loopback()
+        ));
+        let config = OracleConfig {
+            rest_api_bind: Some("localhost".to_string()),
+            ..OracleConfig::default()
+        };
+        assert!(config.rest_api_bind().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_rest_api_bind() {
+        let config = OracleConfig {
+            rest_api_bind: Some("unix:///run/oracle-core/api.sock".to_string()),
+            ..OracleConfig::default()
+        };
+        assert_eq!(
+            config.rest_api_bind().unwrap(),
+            RestApiBind::Unix {
+                path: PathBuf::from("/run/oracle-core/api.sock"),
+                mode: 0o660
+            }
+        );
+        let config = OracleConfig {
+            rest_api_socket_mode: Some("600".to_string()),
+            ..config
+        };
+        assert!(matches!(
+            config.rest_api_bind().unwrap(),
+            RestApiBind::Unix { mode: 0o600, .. }
+        ));
+        let config = OracleConfig {
+            rest_api_socket_mode: Some("rw".to_string()),
+            ..config
+        };
+        assert!(config.rest_api_bind().is_err());
+    }
 }
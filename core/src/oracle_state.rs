@@ -7,14 +7,17 @@ use crate::box_kind::{
 };
 use crate::datapoint_source::DataPointSourceError;
 use crate::oracle_config::ORACLE_CONFIG;
-use crate::oracle_types::{BlockHeight, EpochCounter};
+use crate::oracle_types::{BlockHeight, EpochCounter, EpochLength};
 use crate::pool_config::POOL_CONFIG;
-use crate::scans::{GenericTokenScan, NodeScanRegistry, ScanError, ScanGetBoxes};
+use crate::scans::fetch_limit::FetchLimit;
+use crate::scans::{select_box, GenericTokenScan, NodeScanRegistry, ScanError, ScanGetBoxes};
 use crate::spec_token::{
     BallotTokenId, BuybackTokenId, OracleTokenId, PoolTokenId, RefreshTokenId, RewardTokenId,
     UpdateTokenId,
 };
 use anyhow::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use ergo_lib::ergotree_ir::mir::constant::TryExtractFromError;
 use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
@@ -22,6 +25,14 @@ use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, DataSourceError>;
 
+/// Whether the local oracle box was found since the start, to tell a missing oracle box from
+/// joining the pool
+static LOCAL_ORACLE_BOX_SEEN: AtomicBool = AtomicBool::new(false);
+
+pub fn local_oracle_box_was_seen() -> bool {
+    LOCAL_ORACLE_BOX_SEEN.load(Ordering::SeqCst)
+}
+
 #[derive(Debug, Error)]
 pub enum DataSourceError {
     #[error("unexpected data error: {0}")]
@@ -158,6 +169,103 @@ pub struct LiveEpochState {
     pub latest_pool_box_height: BlockHeight,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "pool box epoch counter went back from {} to {} at height {} (reorg or unexpected pool state)",
+    .highest.0,
+    .current.0,
+    .height.0
+)]
+pub struct EpochCounterRegression {
+    pub highest: EpochCounter,
+    pub current: EpochCounter,
+    pub height: BlockHeight,
+}
+
+/// Highest pool box epoch counter seen by the main loop, to detect it going back. A lower
+/// counter is a regression until the counter is back to the highest one, or until it stayed
+/// lower for a whole epoch and is taken as the new baseline (e.g. the reorg is final).
+#[derive(Debug, Clone, Default)]
+pub struct EpochCounterGuard {
+    highest: Option<EpochCounter>,
+    /// Height the current regression was first seen at
+    regressed_since: Option<BlockHeight>,
+}
+
+impl EpochCounterGuard {
+    pub fn check(
+        &mut self,
+        epoch: EpochCounter,
+        height: BlockHeight,
+        epoch_length: EpochLength,
+    ) -> std::result::Result<(), EpochCounterRegression> {
+        let highest = match self.highest {
+            Some(highest) if epoch < highest => highest,
+            _ => {
+                self.highest = Some(epoch);
+                self.regressed_since = None;
+                return Ok(());
+            }
+        };
+        let regressed_since = *self.regressed_since.get_or_insert(height);
+        if height.0 >= regressed_since.0 + epoch_length.0 as u32 {
+            log::warn!(
+                "Pool box epoch counter stayed at {} (below {}) since height {}, taking it as \
+                the new baseline",
+                epoch.0,
+                highest.0,
+                regressed_since.0
+            );
+            self.highest = Some(epoch);
+            self.regressed_since = None;
+            return Ok(());
+        }
+        Err(EpochCounterRegression {
+            highest,
+            current: epoch,
+            height,
+        })
+    }
+}
+
+/// Refresh consensus failures of the local oracle since the pool box was last refreshed. The
+/// pool box epoch counter doesn't move while no refresh succeeds, so the failing epochs are
+/// counted in epoch lengths since the first failure.
+#[derive(Debug, Clone, Default)]
+pub struct ConsensusFailureTracker {
+    epoch: Option<EpochCounter>,
+    failing_since: Option<BlockHeight>,
+    escalated: bool,
+}
+
+impl ConsensusFailureTracker {
+    /// Forget the failures once the pool box is in another epoch (refreshed by any oracle)
+    pub fn observe_epoch(&mut self, epoch: EpochCounter) {
+        if self.epoch != Some(epoch) {
+            self.epoch = Some(epoch);
+            self.failing_since = None;
+            self.escalated = false;
+        }
+    }
+
+    /// Record a failure, returns the number of epochs failing, the current one included
+    pub fn record_failure(&mut self, height: BlockHeight, epoch_length: EpochLength) -> u32 {
+        let since = *self.failing_since.get_or_insert(height);
+        height.0.saturating_sub(since.0) / epoch_length.0.max(1) as u32 + 1
+    }
+
+    /// Whether `failed_epochs` reached `threshold`, true only once until the failures are
+    /// forgotten
+    pub fn should_escalate(&mut self, failed_epochs: u32, threshold: u32) -> bool {
+        if failed_epochs >= threshold && !self.escalated {
+            self.escalated = true;
+            true
+        } else {
+            false
+        }
+    }
+}
+
 /// Last posted datapoint box info by the local oracle
 #[derive(Debug, Clone)]
 pub enum LocalDatapointState {
@@ -260,6 +368,9 @@ impl OraclePool {
                     height: BlockHeight(local_data_point_box.get_box().creation_height),
                 },
             });
+        if local_datapoint_box_state.is_some() {
+            LOCAL_ORACLE_BOX_SEEN.store(true, Ordering::SeqCst);
+        }
 
         let latest_pool_datapoint = pool_box.rate() as u64;
 
@@ -310,6 +421,61 @@ impl OraclePool {
             .as_ref()
             .map(|b| b as &dyn BuybackBoxSource)
     }
+
+    /// Fetch the boxes of all the pool scans in parallel, up to `limit` scan requests at the
+    /// same time
+    pub async fn fetch_all_boxes(self: &Arc<Self>, limit: &FetchLimit) -> PoolBoxes {
+        let op = self.clone();
+        let pool_box = limit.spawn(move || op.get_pool_box_source().get_pool_box());
+        let op = self.clone();
+        let refresh_box = limit.spawn(move || op.get_refresh_box_source().get_refresh_box());
+        let op = self.clone();
+        let update_box = limit.spawn(move || op.get_update_box_source().get_update_box());
+        let buyback_box = self.buyback_box_scan.as_ref().map(|_| {
+            let op = self.clone();
+            limit.spawn(move || {
+                op.get_buyback_box_source()
+                    .map_or(Ok(None), |source| source.get_buyback_box())
+            })
+        });
+        let op = self.clone();
+        let posted_boxes = limit.spawn(move || {
+            op.get_posted_datapoint_boxes_source()
+                .get_posted_datapoint_boxes()
+        });
+        let op = self.clone();
+        let collected_boxes = limit.spawn(move || {
+            op.get_collected_datapoint_boxes_source()
+                .get_collected_datapoint_boxes()
+        });
+        let op = self.clone();
+        let ballot_boxes = limit.spawn(move || op.get_ballot_boxes_source().get_ballot_boxes());
+        PoolBoxes {
+            pool_box: pool_box.await.unwrap(),
+            refresh_box: refresh_box.await.unwrap(),
+            update_box: update_box.await.unwrap(),
+            buyback_box: match buyback_box {
+                Some(buyback_box) => Some(buyback_box.await.unwrap()),
+                None => None,
+            },
+            posted_boxes: posted_boxes.await.unwrap(),
+            collected_boxes: collected_boxes.await.unwrap(),
+            ballot_boxes: ballot_boxes.await.unwrap(),
+        }
+    }
+}
+
+/// Boxes of all the pool scans fetched by [`OraclePool::fetch_all_boxes`], each with its own
+/// result
+pub struct PoolBoxes {
+    pub pool_box: Result<PoolBoxWrapper>,
+    pub refresh_box: Result<RefreshBoxWrapper>,
+    pub update_box: Result<UpdateBoxWrapper>,
+    /// `None` if the pool has no buyback box
+    pub buyback_box: Option<Result<Option<BuybackBoxWrapper>>>,
+    pub posted_boxes: Result<Vec<PostedOracleBox>>,
+    pub collected_boxes: Result<Vec<CollectedOracleBox>>,
+    pub ballot_boxes: Result<Vec<VoteBallotBoxWrapper>>,
 }
 
 impl PoolBoxSource for PoolBoxScan {
@@ -326,12 +492,23 @@ impl PoolBoxSource for PoolBoxScan {
 
 impl LocalBallotBoxSource for LocalBallotBoxScan {
     fn get_ballot_box(&self) -> Result<Option<BallotBoxWrapper>> {
-        Ok(self
+        let boxes: Vec<BallotBoxWrapper> = self
             .scan
-            .get_boxes()?
+            .get_capped_boxes()?
             .into_iter()
             .filter_map(|b| BallotBoxWrapper::new(b, &self.ballot_box_wrapper_inputs).ok())
-            .find(|b| b.ballot_token_owner() == *self.ballot_token_owner_pk.h))
+            .filter(|b| b.ballot_token_owner() == *self.ballot_token_owner_pk.h)
+            .collect();
+        Ok(select_box(
+            boxes,
+            |b| {
+                (
+                    b.get_box().creation_height,
+                    String::from(b.get_box().box_id()),
+                )
+            },
+            "local ballot box",
+        ))
     }
 }
 
@@ -349,12 +526,23 @@ impl RefreshBoxSource for RefreshBoxScan {
 
 impl LocalDatapointBoxSource for LocalOracleDatapointScan {
     fn get_local_oracle_datapoint_box(&self) -> Result<Option<OracleBoxWrapper>> {
-        Ok(self
+        let boxes: Vec<OracleBoxWrapper> = self
             .scan
-            .get_boxes()?
+            .get_capped_boxes()?
             .into_iter()
             .filter_map(|b| OracleBoxWrapper::new(b, &self.oracle_box_wrapper_inputs).ok())
-            .find(|b| b.public_key() == *self.oracle_pk.h))
+            .filter(|b| b.public_key() == *self.oracle_pk.h)
+            .collect();
+        Ok(select_box(
+            boxes,
+            |b| {
+                (
+                    b.get_box().creation_height,
+                    String::from(b.get_box().box_id()),
+                )
+            },
+            "local oracle box",
+        ))
     }
 }
 
@@ -423,3 +611,65 @@ impl BuybackBoxSource for BuybackBoxScan {
             .map(|ergo_box| BuybackBoxWrapper::new(ergo_box, self.reward_token_id.clone())))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_counter_guard() {
+        let epoch_length = EpochLength(30);
+        let mut guard = EpochCounterGuard::default();
+        assert!(guard
+            .check(EpochCounter(5), BlockHeight(100), epoch_length)
+            .is_ok());
+        assert!(guard
+            .check(EpochCounter(6), BlockHeight(130), epoch_length)
+            .is_ok());
+        assert_eq!(
+            guard.check(EpochCounter(5), BlockHeight(131), epoch_length),
+            Err(EpochCounterRegression {
+                highest: EpochCounter(6),
+                current: EpochCounter(5),
+                height: BlockHeight(131),
+            })
+        );
+        // back to the highest one
+        assert!(guard
+            .check(EpochCounter(6), BlockHeight(132), epoch_length)
+            .is_ok());
+        assert!(guard
+            .check(EpochCounter(5), BlockHeight(133), epoch_length)
+            .is_err());
+        assert!(guard
+            .check(EpochCounter(5), BlockHeight(162), epoch_length)
+            .is_err());
+        // lower for a whole epoch, the new baseline
+        assert!(guard
+            .check(EpochCounter(5), BlockHeight(163), epoch_length)
+            .is_ok());
+        assert!(guard
+            .check(EpochCounter(5), BlockHeight(164), epoch_length)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_consensus_failure_tracker() {
+        let epoch_length = EpochLength(30);
+        let mut tracker = ConsensusFailureTracker::default();
+        tracker.observe_epoch(EpochCounter(5));
+        assert_eq!(tracker.record_failure(BlockHeight(100), epoch_length), 1);
+        assert_eq!(tracker.record_failure(BlockHeight(129), epoch_length), 1);
+        assert!(!tracker.should_escalate(1, 2));
+        assert_eq!(tracker.record_failure(BlockHeight(130), epoch_length), 2);
+        assert!(tracker.should_escalate(2, 2));
+        assert!(!tracker.should_escalate(3, 2));
+        // same epoch, nothing forgotten
+        tracker.observe_epoch(EpochCounter(5));
+        assert_eq!(tracker.record_failure(BlockHeight(160), epoch_length), 3);
+        // refreshed
+        tracker.observe_epoch(EpochCounter(6));
+        assert_eq!(tracker.record_failure(BlockHeight(170), epoch_length), 1);
+        assert!(tracker.should_escalate(2, 2));
+    }
+}
@@ -7,9 +7,13 @@ use derive_more::From;
 use derive_more::Into;
 use derive_more::Mul;
 use derive_more::Sub;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
+/// Average Ergo block interval
+pub const BLOCK_TIME_MINUTES: u32 = 2;
+
 #[derive(PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Copy, Clone)]
 #[serde(transparent)]
 pub struct BlockHeight(pub u32);
@@ -35,7 +39,9 @@ impl std::fmt::Display for BlockHeight {
     }
 }
 
-#[derive(PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Copy, Clone)]
+#[derive(
+    PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, JsonSchema, Copy, Clone,
+)]
 #[serde(transparent)]
 pub struct EpochLength(pub i32);
 
@@ -43,7 +49,9 @@ pub struct EpochLength(pub i32);
 #[serde(transparent)]
 pub struct EpochCounter(pub u32);
 
-#[derive(PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, Copy, Clone)]
+#[derive(
+    PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize, JsonSchema, Copy, Clone,
+)]
 #[serde(transparent)]
 pub struct MinDatapoints(pub i32);
 
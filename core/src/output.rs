@@ -0,0 +1,67 @@
+//! Results of the CLI commands, printed or written to the file of the global `--output` for the
+//! pipelines that consume files.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use once_cell::sync;
+
+/// The global `--output`, set in `main`
+pub static OUTPUT_PATH: sync::OnceCell<PathBuf> = sync::OnceCell::new();
+
+/// File to write the result of the command to, if `--output` is given
+pub fn output_path() -> Option<&'static Path> {
+    OUTPUT_PATH.get().map(PathBuf::as_path)
+}
+
+/// Write the JSON result (`what` it is, for the message) to the [`output_path`], or print it if
+/// there is none
+pub fn write_json_result(what: &str, json: &str) -> Result<(), std::io::Error> {
+    match output_path() {
+        Some(path) => {
+            write_atomically(path, json.as_bytes())?;
+            println!("{} written to {}", what, path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// Write the contents to a temporary file next to `path` and rename it over `path`, so that the
+/// readers never see a partially written file
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), std::io::Error> {
+    let tmp_path = tmp_file_path(path);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)
+}
+
+pub fn tmp_file_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomically() {
+        let path = std::env::temp_dir().join("oracle_core_test_output.json");
+        write_atomically(&path, b"{\"a\":1}").unwrap();
+        write_atomically(&path, b"{}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        assert!(!tmp_file_path(&path).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_output_path() {
+        // the global `--output` isn't set in the tests
+        assert_eq!(output_path(), None);
+    }
+}
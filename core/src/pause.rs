@@ -0,0 +1,15 @@
+//! Oracle paused with the REST API `/pause` endpoint. While paused the main loop keeps running
+//! but doesn't submit any transactions, as with `run --read-only`.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::SeqCst);
+}
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,161 @@
+//! The tx of the last submitted action saved to the data dir, so that an oracle restarted before
+//! the tx is confirmed waits for it (`pending_action_policy` in the oracle config) instead of
+//! building the action again from the boxes the tx is spending.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::cli_commands::trace_tx::tx_status;
+use crate::cli_commands::trace_tx::TxStatus;
+use crate::node_interface::node_api::NodeApi;
+use crate::node_interface::node_api::NodeApiError;
+use crate::oracle_types::BlockHeight;
+use crate::oracle_types::EpochLength;
+use crate::pool_commands::ActionKind;
+use crate::scans::SCANS_DIR_PATH;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingAction {
+    pub tx_id: String,
+    pub kind: ActionKind,
+    /// Height the tx was submitted at
+    pub height: BlockHeight,
+}
+
+pub fn get_pending_action_file_path() -> PathBuf {
+    SCANS_DIR_PATH.get().unwrap().join("pending_action.json")
+}
+
+/// The action saved by the previous run, if any
+pub fn load(path: &Path) -> Option<PendingAction> {
+    match read(path) {
+        Ok(pending) => {
+            log::info!(
+                "Found the {:?} tx {} submitted at height {} before the restart",
+                pending.kind,
+                pending.tx_id,
+                pending.height
+            );
+            Some(pending)
+        }
+        Err(e) => {
+            log::debug!("No pending action loaded from {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Save the submitted action, replacing the previous one
+pub fn save(path: &Path, pending: &PendingAction) {
+    if let Err(e) = write(path, pending) {
+        log::warn!(
+            "Failed to save the pending action to {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Whether to hold off acting for `pending`. Clears it (and the file) once its tx is confirmed or
+/// dropped, or after an epoch since its submission height so that a tx stuck in the mempool
+/// doesn't stall the oracle (and an entry left from an earlier run isn't waited for).
+pub fn should_wait(
+    node_api: &NodeApi,
+    path: &Path,
+    pending: &mut Option<PendingAction>,
+    height: BlockHeight,
+    epoch_length: EpochLength,
+) -> Result<bool, NodeApiError> {
+    let action = match pending.as_ref() {
+        Some(action) => action,
+        None => return Ok(false),
+    };
+    if is_expired(action, height, epoch_length) {
+        log::warn!(
+            "The {:?} tx {} submitted at height {} is still not confirmed after an epoch ({} \
+             blocks), acting on the current pool state",
+            action.kind,
+            action.tx_id,
+            action.height,
+            epoch_length.0
+        );
+        clear(path, pending);
+        return Ok(false);
+    }
+    let status = tx_status(
+        node_api.is_transaction_in_mempool(&action.tx_id)?,
+        node_api.get_wallet_transaction(&action.tx_id)?.as_ref(),
+    );
+    if status == TxStatus::InMempool {
+        log::info!(
+            "Waiting for the {:?} tx {} submitted before the restart to be confirmed",
+            action.kind,
+            action.tx_id
+        );
+        return Ok(true);
+    }
+    log::info!(
+        "The {:?} tx {} submitted before the restart is {:?}, acting on the current pool state",
+        action.kind,
+        action.tx_id,
+        status
+    );
+    clear(path, pending);
+    Ok(false)
+}
+
+/// Whether more than an epoch has passed since `action` was submitted
+fn is_expired(action: &PendingAction, height: BlockHeight, epoch_length: EpochLength) -> bool {
+    height.0.saturating_sub(action.height.0) > epoch_length.0.max(0) as u32
+}
+
+fn clear(path: &Path, pending: &mut Option<PendingAction>) {
+    *pending = None;
+    if let Err(e) = std::fs::remove_file(path) {
+        log::warn!("Failed to remove {}: {}", path.display(), e);
+    }
+}
+
+fn read(path: &Path) -> Result<PendingAction, std::io::Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn write(path: &Path, pending: &PendingAction) -> Result<(), std::io::Error> {
+    std::fs::write(path, serde_json::to_string(pending)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_read() {
+        let path = std::env::temp_dir().join("oracle_core_test_pending_action.json");
+        let pending = PendingAction {
+            tx_id: "ab".repeat(32),
+            kind: ActionKind::Refresh,
+            height: BlockHeight(900000),
+        };
+        write(&path, &pending).unwrap();
+        assert_eq!(read(&path).unwrap(), pending);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let pending = PendingAction {
+            tx_id: "ab".repeat(32),
+            kind: ActionKind::PublishDatapoint,
+            height: BlockHeight(900000),
+        };
+        let epoch_length = EpochLength(30);
+        assert!(!is_expired(&pending, BlockHeight(900000), epoch_length));
+        assert!(!is_expired(&pending, BlockHeight(900030), epoch_length));
+        assert!(is_expired(&pending, BlockHeight(900031), epoch_length));
+        // a height below the submission one (e.g. a reorg) doesn't expire it
+        assert!(!is_expired(&pending, BlockHeight(899990), epoch_length));
+    }
+}
@@ -1,5 +1,8 @@
 use ergo_lib::ergo_chain_types::DigestNError;
 use ergo_lib::ergotree_ir::chain::address::{Address, AddressEncoderError};
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::action_report::PoolActionReport;
@@ -7,7 +10,7 @@ use crate::actions::PoolAction;
 use crate::box_kind::PoolBox;
 use crate::datapoint_source::RuntimeDataPointSource;
 use crate::oracle_config::ORACLE_CONFIG;
-use crate::oracle_state::{DataSourceError, OraclePool};
+use crate::oracle_state::{local_oracle_box_was_seen, DataSourceError, OraclePool};
 use crate::oracle_types::BlockHeight;
 use crate::pool_config::POOL_CONFIG;
 use crate::wallet::WalletDataSource;
@@ -31,6 +34,30 @@ pub enum PoolCommand {
     PublishSubsequentDataPoint { republish: bool },
 }
 
+/// Kind of action for the `action_priority` config setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    /// Publish the first datapoint or a subsequent one
+    PublishDatapoint,
+    Refresh,
+}
+
+/// Action attempted first when several are eligible, if `action_priority` is not set
+pub const DEFAULT_ACTION_PRIORITY: [ActionKind; 2] =
+    [ActionKind::PublishDatapoint, ActionKind::Refresh];
+
+impl PoolCommand {
+    pub fn kind(&self) -> ActionKind {
+        match self {
+            PoolCommand::Refresh => ActionKind::Refresh,
+            PoolCommand::PublishFirstDataPoint | PoolCommand::PublishSubsequentDataPoint { .. } => {
+                ActionKind::PublishDatapoint
+            }
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum PoolCommandError {
     #[error("data source error: {0}")]
@@ -56,6 +83,7 @@ pub fn build_action(
     height: BlockHeight,
     change_address: Address,
     datapoint_source: &RuntimeDataPointSource,
+    tx_fee: BoxValue,
 ) -> Result<(PoolAction, PoolActionReport), PoolCommandError> {
     let refresh_box_source = op.get_refresh_box_source();
     let datapoint_boxes_source = op.get_posted_datapoint_boxes_source();
@@ -67,17 +95,32 @@ pub fn build_action(
         } else {
             return Err(PoolCommandError::WrongOracleAddressType);
         };
+    let allow_non_positive_datapoint = ORACLE_CONFIG.allow_non_positive_datapoints.unwrap_or(false);
     match cmd {
-        PoolCommand::PublishFirstDataPoint => build_publish_first_datapoint_action(
-            wallet,
-            height,
-            change_address,
-            oracle_public_key,
-            POOL_CONFIG.oracle_box_wrapper_inputs.clone(),
-            datapoint_source,
-        )
-        .map_err(Into::into)
-        .map(|(action, report)| (action.into(), report.into())),
+        PoolCommand::PublishFirstDataPoint => {
+            // either joining the pool or the oracle box was spent (by a manual tx or a bug) and
+            // the oracle token is back in the wallet
+            if local_oracle_box_was_seen() {
+                log::warn!(
+                    "The oracle box is gone, creating a new one with the oracle token from the \
+                    wallet for epoch {}",
+                    current_epoch_counter.0
+                );
+            }
+            build_publish_first_datapoint_action(
+                wallet,
+                height,
+                change_address,
+                oracle_public_key,
+                POOL_CONFIG.oracle_box_wrapper_inputs.clone(),
+                datapoint_source,
+                current_epoch_counter,
+                allow_non_positive_datapoint,
+                tx_fee,
+            )
+            .map_err(Into::into)
+            .map(|(action, report)| (action.into(), report.into()))
+        }
         PoolCommand::PublishSubsequentDataPoint { republish: _ } => {
             if let Some(local_datapoint_box) = op
                 .get_local_datapoint_box_source()
@@ -92,6 +135,8 @@ pub fn build_action(
                     datapoint_source,
                     new_epoch_counter,
                     &POOL_CONFIG.token_ids.reward_token_id,
+                    allow_non_positive_datapoint,
+                    tx_fee,
                 )
                 .map_err(Into::into)
                 .map(|(action, report)| (action.into(), report.into()))
@@ -120,6 +165,7 @@ pub fn build_action(
             change_address,
             &oracle_public_key,
             op.get_buyback_box_source(),
+            tx_fee,
         )
         .map_err(Into::into)
         .map(|(action, report)| (action.into(), report.into())),
@@ -4,7 +4,11 @@ use ergo_lib::{
     chain::ergo_box::box_builder::ErgoBoxCandidateBuilderError,
     ergo_chain_types::EcPoint,
     ergotree_interpreter::sigma_protocol::prover::ContextExtension,
-    ergotree_ir::chain::{address::Address, token::TokenAmount},
+    ergotree_ir::chain::{
+        address::Address,
+        ergo_box::{box_value::BoxValue, ErgoBox},
+        token::{TokenAmount, TokenId},
+    },
     wallet::{
         box_selector::{BoxSelector, BoxSelectorError, SimpleBoxSelector},
         tx_builder::{TxBuilder, TxBuilderError},
@@ -18,11 +22,10 @@ use crate::{
     box_kind::{make_oracle_box_candidate, OracleBox, OracleBoxWrapper, OracleBoxWrapperInputs},
     contracts::oracle::{OracleContract, OracleContractError},
     datapoint_source::{DataPointSource, DataPointSourceError},
-    oracle_config::BASE_FEE,
     oracle_state::DataSourceError,
-    oracle_types::{BlockHeight, EpochCounter},
-    spec_token::{OracleTokenId, RewardTokenId, SpecToken},
-    wallet::{WalletDataError, WalletDataSource},
+    oracle_types::{BlockHeight, EpochCounter, Rate},
+    spec_token::{OracleTokenId, RewardTokenId, SpecToken, TokenIdKind},
+    wallet::{check_funds, InsufficientFunds, WalletDataError, WalletDataSource},
 };
 
 #[derive(Debug, Error)]
@@ -39,10 +42,31 @@ pub enum PublishDatapointActionError {
     WalletData(#[from] WalletDataError),
     #[error("box selector error: {0}")]
     BoxSelector(#[from] BoxSelectorError),
+    #[error("{0}")]
+    InsufficientFunds(#[from] InsufficientFunds),
     #[error("datapoint source error: {0}")]
     DataPointSource(#[from] DataPointSourceError),
     #[error("oracle contract error: {0}")]
     OracleContract(#[from] OracleContractError),
+    #[error("datapoint {0} is not positive (set allow_non_positive_datapoints in the oracle config to post it)")]
+    NonPositiveDatapoint(Rate),
+    #[error("No oracle token in the wallet to create the oracle box with")]
+    NoOracleTokenInWallet,
+    #[error("No reward token in the wallet to create the oracle box with")]
+    NoRewardTokenInWallet,
+}
+
+/// Reject zero and negative datapoints unless `allow_non_positive` is set (for feeds where such
+/// values are legitimate, unlike prices)
+fn check_datapoint(
+    datapoint: Rate,
+    allow_non_positive: bool,
+) -> Result<Rate, PublishDatapointActionError> {
+    if !allow_non_positive && i64::from(datapoint) <= 0 {
+        Err(PublishDatapointActionError::NonPositiveDatapoint(datapoint))
+    } else {
+        Ok(datapoint)
+    }
 }
 
 pub fn build_subsequent_publish_datapoint_action(
@@ -53,8 +77,13 @@ pub fn build_subsequent_publish_datapoint_action(
     datapoint_source: &dyn DataPointSource,
     new_epoch_counter: EpochCounter,
     reward_token_id: &RewardTokenId,
+    allow_non_positive_datapoint: bool,
+    tx_fee: BoxValue,
 ) -> Result<(PublishDataPointAction, PublishDatapointActionReport), PublishDatapointActionError> {
-    let new_datapoint = datapoint_source.get_datapoint()?;
+    let new_datapoint = check_datapoint(
+        datapoint_source.get_datapoint()?,
+        allow_non_positive_datapoint,
+    )?;
     let in_oracle_box = local_datapoint_box;
 
     let outbox_reward_tokens = if reward_token_id != &in_oracle_box.reward_token().token_id {
@@ -78,7 +107,6 @@ pub fn build_subsequent_publish_datapoint_action(
     )?;
 
     let mut unspent_boxes = wallet.get_unspent_wallet_boxes()?;
-    let tx_fee = *BASE_FEE;
     let box_selector = SimpleBoxSelector::new();
     let target_tokens = vec![
         in_oracle_box.oracle_token().into(),
@@ -86,6 +114,7 @@ pub fn build_subsequent_publish_datapoint_action(
     ];
     let target_balace = in_oracle_box.get_box().value.checked_add(&tx_fee).unwrap();
     unspent_boxes.push(in_oracle_box.get_box().clone());
+    check_funds(&unspent_boxes, target_balace)?;
     let selection = box_selector.select(unspent_boxes, target_balace, target_tokens.as_slice())?;
     let mut tx_builder = TxBuilder::new(
         selection,
@@ -115,10 +144,21 @@ pub fn build_publish_first_datapoint_action(
     public_key: EcPoint,
     inputs: OracleBoxWrapperInputs,
     datapoint_source: &dyn DataPointSource,
+    epoch_counter: EpochCounter,
+    allow_non_positive_datapoint: bool,
+    tx_fee: BoxValue,
 ) -> Result<(PublishDataPointAction, PublishDatapointActionReport), PublishDatapointActionError> {
-    let new_datapoint = datapoint_source.get_datapoint()?;
     let unspent_boxes = wallet.get_unspent_wallet_boxes()?;
-    let tx_fee = *BASE_FEE;
+    if wallet_token_amount(&unspent_boxes, &inputs.oracle_token_id.token_id()) == 0 {
+        return Err(PublishDatapointActionError::NoOracleTokenInWallet);
+    }
+    if wallet_token_amount(&unspent_boxes, &inputs.reward_token_id.token_id()) == 0 {
+        return Err(PublishDatapointActionError::NoRewardTokenInWallet);
+    }
+    let new_datapoint = check_datapoint(
+        datapoint_source.get_datapoint()?,
+        allow_non_positive_datapoint,
+    )?;
     let box_selector = SimpleBoxSelector::new();
     let oracle_token: SpecToken<OracleTokenId> = SpecToken {
         token_id: inputs.oracle_token_id.clone(),
@@ -132,6 +172,7 @@ pub fn build_publish_first_datapoint_action(
     let contract = OracleContract::checked_load(&inputs.contract_inputs)?;
     let min_storage_rent = contract.parameters().min_storage_rent;
     let target_balance = min_storage_rent.checked_add(&tx_fee).unwrap();
+    check_funds(&unspent_boxes, target_balance)?;
 
     let wallet_boxes_selection = box_selector.select(
         unspent_boxes.clone(),
@@ -143,7 +184,7 @@ pub fn build_publish_first_datapoint_action(
         &contract,
         public_key,
         new_datapoint,
-        EpochCounter(1),
+        epoch_counter,
         oracle_token,
         reward_token,
         min_storage_rent,
@@ -171,6 +212,15 @@ pub fn build_publish_first_datapoint_action(
     Ok((PublishDataPointAction { tx }, report))
 }
 
+fn wallet_token_amount(unspent_boxes: &[ErgoBox], token_id: &TokenId) -> u64 {
+    unspent_boxes
+        .iter()
+        .flat_map(|b| b.tokens.iter().flat_map(|tokens| tokens.iter()))
+        .filter(|t| t.token_id == *token_id)
+        .map(|t| *t.amount.as_u64())
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryInto;
@@ -179,6 +229,7 @@ mod tests {
     use crate::box_kind::PoolBox;
     use crate::contracts::oracle::OracleContractParameters;
     use crate::contracts::pool::PoolContractParameters;
+    use crate::oracle_config::BASE_FEE;
     use crate::oracle_state::PoolBoxSource;
     use crate::oracle_types::{EpochLength, Rate};
     use crate::pool_commands::test_utils::{
@@ -279,6 +330,8 @@ mod tests {
             &datapoint_source,
             pool_box_epoch_id,
             &token_ids.reward_token_id,
+            false,
+            *BASE_FEE,
         )
         .unwrap();
 
@@ -368,6 +421,9 @@ mod tests {
             &MockDatapointSource {
                 datapoint: 201.into(),
             },
+            EpochCounter(1),
+            false,
+            *BASE_FEE,
         )
         .unwrap();
 
@@ -382,6 +438,51 @@ mod tests {
         let _signed_tx = wallet.sign_transaction(tx_context, &ctx, None).unwrap();
     }
 
+    #[test]
+    fn test_first_publish_datapoint_without_oracle_token() {
+        let ctx = force_any_val::<ErgoStateContext>();
+        let height = BlockHeight(ctx.pre_header.height);
+        let token_ids = generate_token_ids();
+        let secret = force_any_val::<DlogProverInput>();
+        let reward_tokens = BoxTokens::from_vec(vec![Token {
+            token_id: token_ids.reward_token_id.token_id(),
+            amount: 100u64.try_into().unwrap(),
+        }])
+        .unwrap();
+        let unspent_boxes = vec![make_wallet_unspent_box(
+            secret.public_image(),
+            BASE_FEE.checked_mul_u32(10000).unwrap(),
+            Some(reward_tokens),
+        )];
+        let change_address = AddressEncoder::unchecked_parse_network_address_from_str(
+            "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r",
+        )
+        .unwrap();
+        let oracle_box_wrapper_inputs =
+            OracleBoxWrapperInputs::try_from((OracleContractParameters::default(), &token_ids))
+                .unwrap();
+        let res = build_publish_first_datapoint_action(
+            &WalletDataMock {
+                unspent_boxes,
+                change_address: change_address.clone(),
+            },
+            height,
+            change_address.address(),
+            *secret.public_image().h,
+            oracle_box_wrapper_inputs,
+            &MockDatapointSource {
+                datapoint: 201.into(),
+            },
+            EpochCounter(5),
+            false,
+            *BASE_FEE,
+        );
+        assert!(matches!(
+            res,
+            Err(PublishDatapointActionError::NoOracleTokenInWallet)
+        ));
+    }
+
     #[test]
     fn test_subsequent_publish_datapoint_with_minted_reward_token() {
         let ctx = force_any_val::<ErgoStateContext>();
@@ -462,6 +563,8 @@ mod tests {
             &datapoint_source,
             pool_box_epoch_id,
             &minted_reward_token_id,
+            false,
+            *BASE_FEE,
         )
         .unwrap();
 
@@ -480,4 +583,19 @@ mod tests {
 
         let _signed_tx = wallet.sign_transaction(tx_context, &ctx, None).unwrap();
     }
+
+    #[test]
+    fn test_check_datapoint_boundary() {
+        assert!(matches!(
+            check_datapoint(0.into(), false),
+            Err(PublishDatapointActionError::NonPositiveDatapoint(_))
+        ));
+        assert!(matches!(
+            check_datapoint((-1).into(), false),
+            Err(PublishDatapointActionError::NonPositiveDatapoint(_))
+        ));
+        assert_eq!(check_datapoint(1.into(), false).unwrap(), 1.into());
+        assert_eq!(check_datapoint(0.into(), true).unwrap(), 0.into());
+        assert_eq!(check_datapoint((-1).into(), true).unwrap(), (-1).into());
+    }
 }
@@ -8,7 +8,6 @@ use crate::box_kind::PoolBoxWrapper;
 use crate::box_kind::PostedOracleBox;
 use crate::box_kind::RefreshBox;
 use crate::box_kind::RefreshBoxWrapper;
-use crate::oracle_config::BASE_FEE;
 use crate::oracle_state::BuybackBoxSource;
 use crate::oracle_state::DataSourceError;
 use crate::oracle_state::PoolBoxSource;
@@ -20,6 +19,8 @@ use crate::oracle_types::MinDatapoints;
 use crate::oracle_types::Rate;
 use crate::spec_token::RewardTokenId;
 use crate::spec_token::SpecToken;
+use crate::wallet::check_funds;
+use crate::wallet::InsufficientFunds;
 use crate::wallet::WalletDataError;
 use crate::wallet::WalletDataSource;
 
@@ -27,6 +28,7 @@ use ergo_lib::chain::ergo_box::box_builder::ErgoBoxCandidateBuilderError;
 use ergo_lib::ergo_chain_types::EcPoint;
 use ergo_lib::ergotree_interpreter::sigma_protocol::prover::ContextExtension;
 use ergo_lib::ergotree_ir::chain::address::Address;
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
 use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBoxCandidate;
 use ergo_lib::ergotree_ir::chain::token::TokenAmount;
 use ergo_lib::wallet::box_selector::BoxSelection;
@@ -55,6 +57,8 @@ pub enum RefreshActionError {
     WalletData(#[from] WalletDataError),
     #[error("box selector error: {0}")]
     BoxSelectorError(#[from] BoxSelectorError),
+    #[error("{0}")]
+    InsufficientFunds(#[from] InsufficientFunds),
     #[error("tx builder error: {0}")]
     TxBuilderError(#[from] TxBuilderError),
     #[error("box builder error: {0}")]
@@ -75,8 +79,8 @@ pub fn build_refresh_action(
     change_address: Address,
     my_oracle_pk: &EcPoint,
     buyback_box_source: Option<&dyn BuybackBoxSource>,
+    tx_fee: BoxValue,
 ) -> Result<(RefreshAction, RefreshActionReport), RefreshActionError> {
-    let tx_fee = *BASE_FEE;
     let in_pool_box = pool_box_source.get_pool_box()?;
     let in_refresh_box = refresh_box_source.get_refresh_box()?;
     let min_start_height = height - in_refresh_box.contract().epoch_length();
@@ -123,6 +127,7 @@ pub fn build_refresh_action(
 
     let unspent_boxes = wallet.get_unspent_wallet_boxes()?;
     let box_selector = SimpleBoxSelector::new();
+    check_funds(&unspent_boxes, tx_fee)?;
     let selection = box_selector.select(unspent_boxes, tx_fee, &[])?;
 
     let mut input_boxes = vec![
@@ -208,11 +213,12 @@ pub fn build_refresh_action(
             .iter()
             .map(|b| b.public_key())
             .collect(),
+        pool_rate: rate,
     };
     Ok((RefreshAction { tx }, report))
 }
 
-fn filtered_oracle_boxes_by_rate<T>(
+pub fn filtered_oracle_boxes_by_rate<T>(
     oracle_boxes: Vec<T>,
     deviation_range: u32,
 ) -> Result<Vec<Rate>, RefreshActionError>
@@ -585,6 +591,7 @@ mod tests {
             change_address.address(),
             &oracle_pub_key,
             None,
+            *BASE_FEE,
         )
         .unwrap();
 
@@ -632,6 +639,7 @@ mod tests {
             change_address.address(),
             &oracle_pub_key,
             None,
+            *BASE_FEE,
         );
         dbg!(&wrong_epoch_res);
         assert!(matches!(
@@ -681,6 +689,7 @@ mod tests {
             change_address.address(),
             &oracle_pub_key,
             Some(&buyback_source),
+            *BASE_FEE,
         )
         .unwrap();
 
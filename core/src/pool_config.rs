@@ -2,7 +2,9 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use anyhow::anyhow;
+use ergo_lib::ergotree_ir::chain::token::TokenId;
 use once_cell::sync;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use thiserror::Error;
@@ -24,6 +26,7 @@ use crate::spec_token::OracleTokenId;
 use crate::spec_token::PoolTokenId;
 use crate::spec_token::RefreshTokenId;
 use crate::spec_token::RewardTokenId;
+use crate::spec_token::TokenIdKind;
 use crate::spec_token::UpdateTokenId;
 
 pub const DEFAULT_POOL_CONFIG_FILE_NAME: &str = "pool_config.yaml";
@@ -50,7 +53,7 @@ pub struct PoolConfig {
     pub buyback_token_id: Option<BuybackTokenId>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Copy, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema, Debug, Copy, Clone)]
 #[allow(clippy::enum_variant_names)]
 pub enum PredefinedDataPointSource {
     NanoErgUsd,
@@ -59,40 +62,60 @@ pub enum PredefinedDataPointSource {
 }
 
 /// Holds the token ids of every important token used by the oracle pool.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct TokenIds {
     #[serde(
         serialize_with = "crate::serde::token_id_as_base16_string",
         deserialize_with = "crate::serde::token_id_from_base16"
     )]
+    #[schemars(with = "String")]
     pub pool_nft_token_id: PoolTokenId,
     #[serde(
         serialize_with = "crate::serde::token_id_as_base16_string",
         deserialize_with = "crate::serde::token_id_from_base16"
     )]
+    #[schemars(with = "String")]
     pub refresh_nft_token_id: RefreshTokenId,
     #[serde(
         serialize_with = "crate::serde::token_id_as_base16_string",
         deserialize_with = "crate::serde::token_id_from_base16"
     )]
+    #[schemars(with = "String")]
     pub update_nft_token_id: UpdateTokenId,
     #[serde(
         serialize_with = "crate::serde::token_id_as_base16_string",
         deserialize_with = "crate::serde::token_id_from_base16"
     )]
+    #[schemars(with = "String")]
     pub oracle_token_id: OracleTokenId,
     #[serde(
         serialize_with = "crate::serde::token_id_as_base16_string",
         deserialize_with = "crate::serde::token_id_from_base16"
     )]
+    #[schemars(with = "String")]
     pub reward_token_id: RewardTokenId,
     #[serde(
         serialize_with = "crate::serde::token_id_as_base16_string",
         deserialize_with = "crate::serde::token_id_from_base16"
     )]
+    #[schemars(with = "String")]
     pub ballot_token_id: BallotTokenId,
 }
 
+impl TokenIds {
+    /// Token ids with their config field names
+    pub fn named_token_ids(&self) -> [(&'static str, TokenId); 6] {
+        [
+            ("pool_nft_token_id", self.pool_nft_token_id.token_id()),
+            ("refresh_nft_token_id", self.refresh_nft_token_id.token_id()),
+            ("update_nft_token_id", self.update_nft_token_id.token_id()),
+            ("oracle_token_id", self.oracle_token_id.token_id()),
+            ("reward_token_id", self.reward_token_id.token_id()),
+            ("ballot_token_id", self.ballot_token_id.token_id()),
+        ]
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum PoolConfigError {
     #[error("Oracle contract error: {0}")]
@@ -154,7 +177,7 @@ impl PoolConfig {
         })
     }
 
-    fn load() -> Result<Self, anyhow::Error> {
+    pub fn load() -> Result<Self, anyhow::Error> {
         let config_file_path = POOL_CONFIG_FILE_PATH
             .get()
             .ok_or_else(|| anyhow!("Pool config file path not set"))?;
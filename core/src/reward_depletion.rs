@@ -0,0 +1,186 @@
+//! Check of the reward tokens left in the pool box. Every refresh moves 2 reward tokens per
+//! collected datapoint from the pool box to the oracle boxes, so a pool box with fewer than
+//! that for `min_data_points` datapoints can't be refreshed.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use crate::box_kind::PoolBox;
+use crate::events;
+use crate::events::OracleEvent;
+use crate::oracle_config::RewardDepletionPolicy;
+use crate::oracle_config::ORACLE_CONFIG;
+use crate::oracle_state::OraclePool;
+use crate::oracle_types::BlockHeight;
+use crate::pause;
+use crate::pool_config::POOL_CONFIG;
+
+const DEFAULT_REWARD_WARNING_EPOCHS: u32 = 10;
+
+/// Whether the oracle was paused by `reward_depletion_policy: pause`, so that only this pause is
+/// lifted once the reward tokens are replenished
+static PAUSED_ON_DEPLETION: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardSupply {
+    Sufficient,
+    /// Enough for fewer than the warning number of refreshes
+    Low {
+        refreshes_left: u64,
+    },
+    /// Not enough for a single refresh
+    Depleted {
+        available: u64,
+        required: u64,
+    },
+}
+
+/// `available` reward tokens in the pool box measured in refreshes collecting
+/// `min_data_points` datapoints
+pub fn reward_supply(available: u64, min_data_points: u32, warning_epochs: u32) -> RewardSupply {
+    let per_refresh = (min_data_points as u64 * 2).max(1);
+    let refreshes_left = available / per_refresh;
+    if refreshes_left == 0 {
+        RewardSupply::Depleted {
+            available,
+            required: per_refresh,
+        }
+    } else if refreshes_left < warning_epochs as u64 {
+        RewardSupply::Low { refreshes_left }
+    } else {
+        RewardSupply::Sufficient
+    }
+}
+
+/// Resume the oracle paused by `reward_depletion_policy: pause` once the pool box has the reward
+/// tokens for a refresh again (e.g. after an update of the pool box), without waiting for a new
+/// epoch
+pub fn check_replenished(oracle_pool: &OraclePool) -> Result<(), anyhow::Error> {
+    if !PAUSED_ON_DEPLETION.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    let available = available_reward_tokens(oracle_pool)?;
+    if !matches!(
+        reward_supply(available, min_data_points(), warning_epochs()),
+        RewardSupply::Depleted { .. }
+    ) {
+        resume_on_replenished(available);
+    }
+    Ok(())
+}
+
+/// Log the reward token supply of the pool box if it's low and apply `reward_depletion_policy`
+/// if it ran out. Resumes the oracle paused by the policy once the tokens are replenished.
+pub fn check_reward_supply(
+    oracle_pool: &OraclePool,
+    height: BlockHeight,
+) -> Result<(), anyhow::Error> {
+    let available = available_reward_tokens(oracle_pool)?;
+    match reward_supply(available, min_data_points(), warning_epochs()) {
+        RewardSupply::Sufficient => resume_on_replenished(available),
+        RewardSupply::Low { refreshes_left } => {
+            resume_on_replenished(available);
+            log::warn!(
+                "Pool box reward tokens ({}) are running out, enough for {} more refreshes",
+                available,
+                refreshes_left
+            )
+        }
+        RewardSupply::Depleted {
+            available,
+            required,
+        } => {
+            log::error!(
+                "Pool box reward tokens are depleted: {} left, a refresh needs at least {}",
+                available,
+                required
+            );
+            events::emit(OracleEvent::RewardDepletion {
+                height,
+                available,
+                required,
+            });
+            match ORACLE_CONFIG
+                .reward_depletion_policy
+                .unwrap_or(RewardDepletionPolicy::Continue)
+            {
+                RewardDepletionPolicy::Continue => (),
+                RewardDepletionPolicy::Pause => {
+                    if !PAUSED_ON_DEPLETION.swap(true, Ordering::SeqCst) {
+                        pause::set_paused(true);
+                        log::error!(
+                            "Pausing the oracle (reward_depletion_policy: pause) until the reward \
+                            tokens are replenished. Resume it with the REST API /resume endpoint \
+                            or restart it"
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resume_on_replenished(available: u64) {
+    if PAUSED_ON_DEPLETION.swap(false, Ordering::SeqCst) {
+        pause::set_paused(false);
+        log::info!(
+            "Pool box reward tokens are replenished ({}), resuming the oracle",
+            available
+        );
+    }
+}
+
+fn available_reward_tokens(oracle_pool: &OraclePool) -> Result<u64, anyhow::Error> {
+    Ok(*oracle_pool
+        .get_pool_box_source()
+        .get_pool_box()?
+        .reward_token()
+        .amount
+        .as_u64())
+}
+
+fn min_data_points() -> u32 {
+    POOL_CONFIG
+        .refresh_box_wrapper_inputs
+        .contract_inputs
+        .contract_parameters()
+        .min_data_points()
+        .0
+        .max(0) as u32
+}
+
+fn warning_epochs() -> u32 {
+    ORACLE_CONFIG
+        .reward_warning_epochs
+        .unwrap_or(DEFAULT_REWARD_WARNING_EPOCHS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reward_supply() {
+        assert_eq!(reward_supply(1000, 4, 10), RewardSupply::Sufficient);
+        assert_eq!(reward_supply(80, 4, 10), RewardSupply::Sufficient);
+        assert_eq!(
+            reward_supply(79, 4, 10),
+            RewardSupply::Low { refreshes_left: 9 }
+        );
+        assert_eq!(
+            reward_supply(7, 4, 10),
+            RewardSupply::Depleted {
+                available: 7,
+                required: 8
+            }
+        );
+        assert_eq!(
+            reward_supply(0, 0, 10),
+            RewardSupply::Depleted {
+                available: 0,
+                required: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Record of the reward tokens extracted with `extract-reward-tokens`. Stored as JSON lines in
+//! the data dir, one record per submitted extraction tx.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::jsonl;
+use crate::oracle_types::BlockHeight;
+use crate::scans::SCANS_DIR_PATH;
+
+pub fn get_reward_extraction_history_file_path() -> PathBuf {
+    SCANS_DIR_PATH
+        .get()
+        .unwrap()
+        .join("reward_extractions.jsonl")
+}
+
+#[derive(Debug, Error)]
+pub enum RewardExtractionHistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardExtractionRecord {
+    pub tx_id: String,
+    /// Height the tx was built at
+    pub height: BlockHeight,
+    pub reward_token_id: String,
+    pub transfers: Vec<RewardTransfer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardTransfer {
+    /// Base58 encoded destination address
+    pub address: String,
+    pub amount: u64,
+}
+
+pub fn append_record(
+    file_path: &Path,
+    record: &RewardExtractionRecord,
+) -> Result<(), RewardExtractionHistoryError> {
+    jsonl::append_record(file_path, record)
+}
+
+/// The recorded extractions, the lines that can't be parsed are skipped with a warning
+pub fn read_records(
+    file_path: &Path,
+) -> Result<Vec<RewardExtractionRecord>, RewardExtractionHistoryError> {
+    Ok(jsonl::read_records::<_, RewardExtractionHistoryError>(file_path)?.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_read_records() {
+        let path = std::env::temp_dir().join("oracle_core_test_reward_extractions.jsonl");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(read_records(&path).unwrap(), vec![]);
+        let record = RewardExtractionRecord {
+            tx_id: "a".repeat(64),
+            height: BlockHeight(900000),
+            reward_token_id: "b".repeat(64),
+            transfers: vec![RewardTransfer {
+                address: "9hEQHEMyY1K1vs79vJXFtNjr2dbQbtWXF99oVWGJ5c4xbcLdBsw".to_string(),
+                amount: 42,
+            }],
+        };
+        append_record(&path, &record).unwrap();
+        append_record(&path, &record).unwrap();
+        assert_eq!(read_records(&path).unwrap(), vec![record.clone(), record]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -2,6 +2,8 @@ use crate::address_util::AddressUtilError;
 use crate::contracts::pool::PoolContractError;
 use crate::contracts::refresh::RefreshContractError;
 use crate::node_interface::node_api::{NodeApi, NodeApiError};
+use crate::node_interface::request_cache;
+use crate::oracle_config::ScanBoxLimitPolicy;
 use crate::oracle_config::ORACLE_CONFIG;
 
 use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
@@ -9,6 +11,7 @@ use ergo_node_interface::node_interface::NodeError;
 use ergo_node_interface::ScanId;
 use thiserror::Error;
 
+pub mod fetch_limit;
 mod generic_token_scan;
 mod registry;
 
@@ -36,6 +39,12 @@ pub enum ScanError {
     PoolContract(#[from] PoolContractError),
     #[error("address util error: {0}")]
     AddressUtilError(#[from] AddressUtilError),
+    #[error("scan {scan} returned {found} boxes, more than max_scan_boxes ({max})")]
+    TooManyBoxes {
+        scan: String,
+        found: usize,
+        max: usize,
+    },
 }
 
 pub trait NodeScanId {
@@ -45,11 +54,150 @@ pub trait NodeScanId {
 pub trait ScanGetBoxes: NodeScanId {
     fn get_boxes(&self) -> Result<Vec<ErgoBox>, ScanError> {
         let node_api = NodeApi::new(ORACLE_CONFIG.node_api_key.clone(), &ORACLE_CONFIG.node_url);
-        let boxes = node_api.node.scan_boxes(self.scan_id())?;
-        Ok(boxes)
+        Ok(request_cache::scan_boxes(
+            &self.scan_id().to_string(),
+            || node_api.scan_boxes(self.scan_id()),
+        )?)
+    }
+
+    /// Boxes capped at `max_scan_boxes` (oracle config). Only for the box sets where the newest
+    /// boxes are the ones that matter (e.g. the datapoints of the current epoch), a lookup of one
+    /// box (the pool box, the local oracle box) must use [`ScanGetBoxes::get_boxes`] so that the
+    /// cap can't drop it.
+    fn get_capped_boxes(&self) -> Result<Vec<ErgoBox>, ScanError> {
+        let boxes = self.get_boxes()?;
+        match ORACLE_CONFIG.max_scan_boxes {
+            Some(max) => cap_boxes(
+                boxes,
+                max,
+                ORACLE_CONFIG
+                    .scan_box_limit_policy
+                    .unwrap_or(ScanBoxLimitPolicy::Truncate),
+                |b| b.creation_height,
+                &self.scan_id().to_string(),
+            ),
+            None => Ok(boxes),
+        }
     }
 
     fn get_box(&self) -> Result<Option<ErgoBox>, ScanError> {
-        Ok(self.get_boxes()?.first().cloned())
+        Ok(select_box(
+            self.get_boxes()?,
+            |b| (b.creation_height, String::from(b.box_id())),
+            &format!("scan {}", self.scan_id()),
+        ))
+    }
+}
+
+/// Pick one of the boxes when there should be only one (e.g. duplicates after an unusual chain
+/// state): the highest creation height wins, on a tie the lowest box id. Logs a warning if there
+/// are several candidates.
+pub fn select_box<T>(
+    candidates: Vec<T>,
+    creation_height_and_box_id: impl Fn(&T) -> (u32, String),
+    description: &str,
+) -> Option<T> {
+    if candidates.len() > 1 {
+        log::warn!(
+            "{} boxes found for the {} where one is expected: {}. Using the one with the highest \
+            creation height (lowest box id on a tie)",
+            candidates.len(),
+            description,
+            candidates
+                .iter()
+                .map(|c| {
+                    let (height, box_id) = creation_height_and_box_id(c);
+                    format!("{} (height {})", box_id, height)
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+    }
+    candidates.into_iter().max_by(|a, b| {
+        let (a_height, a_box_id) = creation_height_and_box_id(a);
+        let (b_height, b_box_id) = creation_height_and_box_id(b);
+        a_height
+            .cmp(&b_height)
+            .then_with(|| b_box_id.cmp(&a_box_id))
+    })
+}
+
+/// Guard against a pathological scan result: with more than `max` boxes either keep the `max`
+/// newest ones (highest creation height) or fail
+pub fn cap_boxes<T>(
+    mut boxes: Vec<T>,
+    max: usize,
+    policy: ScanBoxLimitPolicy,
+    creation_height: impl Fn(&T) -> u32,
+    scan: &str,
+) -> Result<Vec<T>, ScanError> {
+    if boxes.len() <= max {
+        return Ok(boxes);
+    }
+    match policy {
+        ScanBoxLimitPolicy::Truncate => {
+            log::warn!(
+                "Scan {} returned {} boxes, processing only the {} newest (max_scan_boxes)",
+                scan,
+                boxes.len(),
+                max
+            );
+            boxes.sort_by_key(|b| std::cmp::Reverse(creation_height(b)));
+            boxes.truncate(max);
+            Ok(boxes)
+        }
+        ScanBoxLimitPolicy::Fail => Err(ScanError::TooManyBoxes {
+            scan: scan.to_string(),
+            found: boxes.len(),
+            max,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_boxes() {
+        let heights = vec![5, 9, 7, 3];
+        assert_eq!(
+            cap_boxes(heights.clone(), 4, ScanBoxLimitPolicy::Fail, |h| *h, "test").unwrap(),
+            heights
+        );
+        assert_eq!(
+            cap_boxes(
+                heights.clone(),
+                2,
+                ScanBoxLimitPolicy::Truncate,
+                |h| *h,
+                "test"
+            )
+            .unwrap(),
+            vec![9, 7]
+        );
+        assert!(matches!(
+            cap_boxes(heights, 2, ScanBoxLimitPolicy::Fail, |h| *h, "test"),
+            Err(ScanError::TooManyBoxes {
+                found: 4,
+                max: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_select_box() {
+        let key = |c: &(u32, &str)| (c.0, c.1.to_string());
+        assert_eq!(select_box(Vec::<(u32, &str)>::new(), key, "test"), None);
+        assert_eq!(select_box(vec![(5, "bb")], key, "test"), Some((5, "bb")));
+        assert_eq!(
+            select_box(vec![(5, "bb"), (7, "cc"), (6, "aa")], key, "test"),
+            Some((7, "cc"))
+        );
+        assert_eq!(
+            select_box(vec![(7, "cc"), (7, "aa"), (7, "bb")], key, "test"),
+            Some((7, "aa"))
+        );
     }
 }
@@ -0,0 +1,81 @@
+//! Limit of the scan box requests run at the same time (`scan_fetch_concurrency` in the oracle
+//! config), for fetching the boxes of several scans in parallel on the tokio runtime.
+
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+use crate::oracle_config::ORACLE_CONFIG;
+
+pub const DEFAULT_SCAN_FETCH_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct FetchLimit {
+    permits: Arc<Semaphore>,
+}
+
+impl FetchLimit {
+    /// At least one fetch is allowed, 1 runs them one after another
+    pub fn new(max: usize) -> Self {
+        FetchLimit {
+            permits: Arc::new(Semaphore::new(max.max(1))),
+        }
+    }
+
+    pub fn from_config() -> Self {
+        FetchLimit::new(
+            ORACLE_CONFIG
+                .scan_fetch_concurrency
+                .unwrap_or(DEFAULT_SCAN_FETCH_CONCURRENCY),
+        )
+    }
+
+    /// Run `fetch` once less than the maximum fetches are running. It runs on the blocking
+    /// thread pool since the node requests are blocking.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        fetch: impl FnOnce() -> T + Send + 'static,
+    ) -> JoinHandle<T> {
+        let permits = self.permits.clone();
+        tokio::spawn(async move {
+            // the semaphore is never closed
+            let _permit = permits.acquire_owned().await.unwrap();
+            tokio::task::spawn_blocking(fetch).await.unwrap()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_limit() {
+        let limit = FetchLimit::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<JoinHandle<usize>> = (0..6)
+            .map(|i| {
+                let (running, max_running) = (running.clone(), max_running.clone());
+                limit.spawn(move || {
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now_running, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    i
+                })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap());
+        }
+        assert_eq!(results, vec![0, 1, 2, 3, 4, 5]);
+        assert!(max_running.load(Ordering::SeqCst) <= 2);
+    }
+}
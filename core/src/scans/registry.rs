@@ -1,17 +1,23 @@
+use std::path::Path;
 use std::path::PathBuf;
 
 use crate::node_interface::node_api::NodeApi;
 use crate::node_interface::node_api::NodeApiError;
 use crate::pool_config::PoolConfig;
+use crate::serde::discard_unparsable_file;
+use crate::shutdown;
 use crate::spec_token::BallotTokenId;
 use crate::spec_token::BuybackTokenId;
 use crate::spec_token::OracleTokenId;
 use crate::spec_token::PoolTokenId;
 use crate::spec_token::RefreshTokenId;
+use crate::spec_token::TokenIdKind;
 use crate::spec_token::UpdateTokenId;
 
 use ::serde::Deserialize;
 use ::serde::Serialize;
+use ergo_lib::ergo_chain_types::blake2b256_hash;
+use ergo_node_interface::ScanId;
 use once_cell::sync;
 use thiserror::Error;
 
@@ -38,6 +44,10 @@ pub struct NodeScanRegistry {
     #[serde(rename = "Update Box Scan")]
     pub update_token_scan: GenericTokenScan<UpdateTokenId>,
     pub buyback_token_scan: Option<GenericTokenScan<BuybackTokenId>>,
+    /// [`scan_config_fingerprint`] of the pool config the scans were registered for (missing in
+    /// the files saved by older versions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_fingerprint: Option<String>,
 }
 
 impl NodeScanRegistry {
@@ -62,42 +72,110 @@ impl NodeScanRegistry {
         pool_config: &PoolConfig,
     ) -> std::result::Result<Self, anyhow::Error> {
         log::info!("Registering UTXO-Set Scans");
-        let oracle_token_scan =
-            GenericTokenScan::register(node_api, &pool_config.token_ids.oracle_token_id)?;
-        let pool_token_scan =
-            GenericTokenScan::register(node_api, &pool_config.token_ids.pool_nft_token_id)?;
-        let ballot_token_scan =
-            GenericTokenScan::register(node_api, &pool_config.token_ids.ballot_token_id)?;
-        let refresh_token_scan =
-            GenericTokenScan::register(node_api, &pool_config.token_ids.refresh_nft_token_id)?;
-        let update_token_scan =
-            GenericTokenScan::register(node_api, &pool_config.token_ids.update_nft_token_id)?;
+        let mut registered_scan_ids = Vec::new();
+        let res = Self::register_scans(node_api, pool_config, &mut registered_scan_ids).and_then(
+            |registry| {
+                // saved only after the rescan, a failed registration leaves no file behind
+                node_api.rescan_from_height(0)?;
+                registry.save_to_json_file(&get_scans_file_path())?;
+                Ok(registry)
+            },
+        );
+        if res.is_err() {
+            // don't leave orphaned scans in the node when the registration is retried
+            for scan_id in registered_scan_ids {
+                if let Err(e) = node_api.deregister_scan(scan_id) {
+                    log::warn!("Failed to deregister scan {}: {}", scan_id, e);
+                }
+            }
+        }
+        res
+    }
+
+    fn register_scans(
+        node_api: &NodeApi,
+        pool_config: &PoolConfig,
+        registered_scan_ids: &mut Vec<ScanId>,
+    ) -> std::result::Result<Self, anyhow::Error> {
+        let oracle_token_scan = register_scan_tracked(
+            node_api,
+            &pool_config.token_ids.oracle_token_id,
+            registered_scan_ids,
+        )?;
+        let pool_token_scan = register_scan_tracked(
+            node_api,
+            &pool_config.token_ids.pool_nft_token_id,
+            registered_scan_ids,
+        )?;
+        let ballot_token_scan = register_scan_tracked(
+            node_api,
+            &pool_config.token_ids.ballot_token_id,
+            registered_scan_ids,
+        )?;
+        let refresh_token_scan = register_scan_tracked(
+            node_api,
+            &pool_config.token_ids.refresh_nft_token_id,
+            registered_scan_ids,
+        )?;
+        let update_token_scan = register_scan_tracked(
+            node_api,
+            &pool_config.token_ids.update_nft_token_id,
+            registered_scan_ids,
+        )?;
         let buyback_token_scan =
             if let Some(buyback_token_id) = pool_config.buyback_token_id.clone() {
-                Some(GenericTokenScan::register(node_api, &buyback_token_id)?)
+                Some(register_scan_tracked(
+                    node_api,
+                    &buyback_token_id,
+                    registered_scan_ids,
+                )?)
             } else {
                 None
             };
-        let registry = Self {
+        Ok(Self {
             oracle_token_scan,
             pool_token_scan,
             ballot_token_scan,
             refresh_token_scan,
             update_token_scan,
             buyback_token_scan,
-        };
-        registry.save_to_json_file(&get_scans_file_path())?;
-        node_api.rescan_from_height(0)?;
-        Ok(registry)
+            config_fingerprint: Some(scan_config_fingerprint(pool_config)),
+        })
     }
 
+    /// Fails if the scans were registered for other token ids than the ones in the pool config,
+    /// e.g. when the token ids were edited without registering the scans again. A scans file
+    /// without a fingerprint can't be checked, it's only warned about.
+    pub fn check_config_fingerprint(
+        &self,
+        pool_config: &PoolConfig,
+    ) -> Result<(), NodeScanRegistryError> {
+        match &self.config_fingerprint {
+            Some(fingerprint) if *fingerprint != scan_config_fingerprint(pool_config) => Err(
+                NodeScanRegistryError::StaleScans(get_scans_file_path().display().to_string()),
+            ),
+            Some(_) => Ok(()),
+            None => {
+                log::warn!(
+                    "{} has no pool config fingerprint (saved by an older version), the scans \
+                    can't be checked against the pool config. To be sure they are for the \
+                    current pool config deregister them, remove the file and register them again \
+                    with `oracle-core scans register`",
+                    get_scans_file_path().display()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Load the scans file without touching the node. A file that doesn't parse is an error, it's
+    /// discarded and the scans are registered again on `run` startup.
     pub fn load() -> Result<Self, anyhow::Error> {
         let path = get_scans_file_path();
         log::info!("Loading scan IDs from {}", path.display());
         let json_str =
-            std::fs::read_to_string(path).map_err(|e| NodeScanRegistryError::Io(e.to_string()))?;
-        let registry = Self::load_from_json_str(&json_str)?;
-        Ok(registry)
+            std::fs::read_to_string(&path).map_err(|e| NodeScanRegistryError::Io(e.to_string()))?;
+        Self::load_from_json_str(&json_str)
     }
 
     pub fn ensure_node_registered_scans(
@@ -106,8 +184,18 @@ impl NodeScanRegistry {
     ) -> std::result::Result<Self, anyhow::Error> {
         let path = get_scans_file_path();
         log::info!("Loading scan IDs from {}", path.display());
-        let registry = if let Ok(json_str) = std::fs::read_to_string(path) {
-            let loaded_registry = Self::load_from_json_str(&json_str)?;
+        let loaded_registry = match std::fs::read_to_string(&path) {
+            Ok(json_str) => match Self::load_from_json_str(&json_str) {
+                Ok(loaded_registry) => Some(loaded_registry),
+                Err(e) => {
+                    discard_unparsable_scans_file(node_api, &path, &json_str, &e)?;
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+        let registry = if let Some(loaded_registry) = loaded_registry {
+            loaded_registry.check_config_fingerprint(pool_config)?;
             if let Some(pool_config_buyback_token_id) = pool_config.buyback_token_id.clone() {
                 log::info!("Buyback token is found in pool config, checking if scan is registered");
                 if loaded_registry.buyback_token_scan.is_some() {
@@ -148,6 +236,150 @@ impl NodeScanRegistry {
         Ok(registry)
     }
 
+    /// Register all the scans, save them to the scans file and rescan
+    pub fn register_and_save_scans(
+        node_api: &NodeApi,
+        pool_config: &PoolConfig,
+    ) -> std::result::Result<Self, anyhow::Error> {
+        Self::register_and_save_scans_inner(node_api, pool_config)
+    }
+
+    /// Name (the scans file field) and node scan ID of each scan
+    pub fn scans(&self) -> Vec<(&'static str, ScanId)> {
+        let mut scans = vec![
+            ("oracle_token_scan", self.oracle_token_scan.scan_id()),
+            ("pool_token_scan", self.pool_token_scan.scan_id()),
+            ("ballot_token_scan", self.ballot_token_scan.scan_id()),
+            ("refresh_token_scan", self.refresh_token_scan.scan_id()),
+            ("update_token_scan", self.update_token_scan.scan_id()),
+        ];
+        if let Some(buyback_token_scan) = &self.buyback_token_scan {
+            scans.push(("buyback_token_scan", buyback_token_scan.scan_id()));
+        }
+        scans
+    }
+
+    /// Register again the scans that are not in `node_scan_ids` (and the buyback token scan if
+    /// it's in the pool config but not in the registry), then rescan and save the registry.
+    /// Returns the names of the registered scans. On failure the newly registered scans are
+    /// deregistered and the registry is left as it was.
+    pub fn register_missing_scans(
+        &mut self,
+        node_api: &NodeApi,
+        pool_config: &PoolConfig,
+        node_scan_ids: &[ScanId],
+    ) -> std::result::Result<Vec<&'static str>, anyhow::Error> {
+        let original = self.clone();
+        let res = self.register_missing_scans_inner(node_api, pool_config, node_scan_ids);
+        if res.is_err() {
+            let original_scans = original.scans();
+            for (_, scan_id) in self
+                .scans()
+                .into_iter()
+                .filter(|scan| !original_scans.contains(scan))
+            {
+                if let Err(e) = node_api.deregister_scan(scan_id) {
+                    log::warn!("Failed to deregister scan {}: {}", scan_id, e);
+                }
+            }
+            *self = original;
+        }
+        res
+    }
+
+    fn register_missing_scans_inner(
+        &mut self,
+        node_api: &NodeApi,
+        pool_config: &PoolConfig,
+        node_scan_ids: &[ScanId],
+    ) -> std::result::Result<Vec<&'static str>, anyhow::Error> {
+        let token_ids = &pool_config.token_ids;
+        let mut registered = Vec::new();
+        register_if_missing(
+            node_api,
+            "oracle_token_scan",
+            &mut self.oracle_token_scan,
+            &token_ids.oracle_token_id,
+            node_scan_ids,
+            &mut registered,
+        )?;
+        register_if_missing(
+            node_api,
+            "pool_token_scan",
+            &mut self.pool_token_scan,
+            &token_ids.pool_nft_token_id,
+            node_scan_ids,
+            &mut registered,
+        )?;
+        register_if_missing(
+            node_api,
+            "ballot_token_scan",
+            &mut self.ballot_token_scan,
+            &token_ids.ballot_token_id,
+            node_scan_ids,
+            &mut registered,
+        )?;
+        register_if_missing(
+            node_api,
+            "refresh_token_scan",
+            &mut self.refresh_token_scan,
+            &token_ids.refresh_nft_token_id,
+            node_scan_ids,
+            &mut registered,
+        )?;
+        register_if_missing(
+            node_api,
+            "update_token_scan",
+            &mut self.update_token_scan,
+            &token_ids.update_nft_token_id,
+            node_scan_ids,
+            &mut registered,
+        )?;
+        match (&mut self.buyback_token_scan, &pool_config.buyback_token_id) {
+            (Some(scan), Some(buyback_token_id)) => register_if_missing(
+                node_api,
+                "buyback_token_scan",
+                scan,
+                buyback_token_id,
+                node_scan_ids,
+                &mut registered,
+            )?,
+            (None, Some(buyback_token_id)) => {
+                self.buyback_token_scan =
+                    Some(GenericTokenScan::register(node_api, buyback_token_id)?);
+                registered.push("buyback_token_scan");
+            }
+            (_, None) => (),
+        }
+        if !registered.is_empty() {
+            node_api.rescan_from_height(0)?;
+            self.save_to_json_file(&get_scans_file_path())?;
+        }
+        Ok(registered)
+    }
+
+    /// Deregister the scan with the name (see [`Self::scans`]) in the node. The buyback token
+    /// scan is removed from the scans file too, the others stay in it until registered again
+    /// with [`Self::register_missing_scans`].
+    pub fn deregister_scan(
+        &mut self,
+        node_api: &NodeApi,
+        name: &str,
+    ) -> std::result::Result<ScanId, anyhow::Error> {
+        let scan_id = self
+            .scans()
+            .into_iter()
+            .find(|(scan_name, _)| *scan_name == name)
+            .map(|(_, scan_id)| scan_id)
+            .ok_or_else(|| NodeScanRegistryError::UnknownScan(name.to_string()))?;
+        node_api.deregister_scan(scan_id)?;
+        if name == "buyback_token_scan" {
+            self.buyback_token_scan = None;
+            self.save_to_json_file(&get_scans_file_path())?;
+        }
+        Ok(scan_id)
+    }
+
     pub fn deregister_all_scans(self, node_api: &NodeApi) -> Result<(), NodeApiError> {
         node_api.deregister_scan(self.oracle_token_scan.scan_id())?;
         node_api.deregister_scan(self.pool_token_scan.scan_id())?;
@@ -161,22 +393,112 @@ impl NodeScanRegistry {
     }
 }
 
+/// Hash of the token ids of the scans. The buyback token is left out, its scan is registered
+/// or deregistered on startup when it's changed in the pool config.
+pub fn scan_config_fingerprint(pool_config: &PoolConfig) -> String {
+    let token_ids = &pool_config.token_ids;
+    let scanned_token_ids = [
+        token_ids.oracle_token_id.token_id(),
+        token_ids.pool_nft_token_id.token_id(),
+        token_ids.ballot_token_id.token_id(),
+        token_ids.refresh_nft_token_id.token_id(),
+        token_ids.update_nft_token_id.token_id(),
+    ]
+    .map(String::from)
+    .join(",");
+    String::from(blake2b256_hash(scanned_token_ids.as_bytes()))
+}
+
+/// Move the unparsable scans file away and deregister the scans whose IDs can still be read from
+/// it, so that registering the scans again doesn't leave them behind in the node
+fn discard_unparsable_scans_file(
+    node_api: &NodeApi,
+    path: &Path,
+    json_str: &str,
+    error: &anyhow::Error,
+) -> Result<(), NodeScanRegistryError> {
+    discard_unparsable_file(path, error).map_err(|e| NodeScanRegistryError::Io(e.to_string()))?;
+    let scan_ids = recoverable_scan_ids(json_str);
+    if scan_ids.is_empty() {
+        log::warn!("No scan IDs could be read from the discarded file, its scans are left registered in the node");
+    }
+    for scan_id in scan_ids {
+        log::info!("Deregistering scan {} of the discarded file", scan_id);
+        if let Err(e) = node_api.deregister_scan(scan_id) {
+            log::warn!("Failed to deregister scan {}: {}", scan_id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Scan IDs of the fields of a scans file that doesn't parse as a [`NodeScanRegistry`], none if
+/// it's not a JSON object
+fn recoverable_scan_ids(json_str: &str) -> Vec<ScanId> {
+    let fields = match serde_json::from_str::<serde_json::Value>(json_str) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return vec![],
+    };
+    fields
+        .iter()
+        .filter(|(name, _)| name.as_str() != "config_fingerprint")
+        .filter_map(|(_, value)| match value {
+            serde_json::Value::String(id) => id.parse::<u64>().ok(),
+            serde_json::Value::Number(id) => id.as_u64(),
+            _ => None,
+        })
+        .map(ScanId::from)
+        .collect()
+}
+
+fn register_if_missing<T: TokenIdKind + Clone>(
+    node_api: &NodeApi,
+    name: &'static str,
+    scan: &mut GenericTokenScan<T>,
+    token_id: &T,
+    node_scan_ids: &[ScanId],
+    registered: &mut Vec<&'static str>,
+) -> Result<(), ScanError> {
+    if !node_scan_ids.contains(&scan.scan_id()) {
+        log::info!(
+            "{} (scan {}) is not registered in the node, registering it",
+            name,
+            scan.scan_id()
+        );
+        *scan = GenericTokenScan::register(node_api, token_id)?;
+        registered.push(name);
+    }
+    Ok(())
+}
+
+fn register_scan_tracked<T: TokenIdKind + Clone>(
+    node_api: &NodeApi,
+    token_id: &T,
+    registered_scan_ids: &mut Vec<ScanId>,
+) -> Result<GenericTokenScan<T>, ScanError> {
+    let scan = GenericTokenScan::register(node_api, token_id)?;
+    registered_scan_ids.push(scan.scan_id());
+    Ok(scan)
+}
+
 pub fn wait_for_node_rescan(node_api: &NodeApi) -> Result<(), NodeApiError> {
-    let wallet_height = node_api.node.wallet_status()?.height;
-    let block_height = node_api.node.current_block_height()?;
+    let wallet_height = node_api.wallet_status()?.height;
+    let block_height = node_api.current_block_height()?;
     if wallet_height == block_height {
         log::debug!("No wallet scan is running");
         return Ok(());
     }
     Ok(loop {
-        let wallet_height = node_api.node.wallet_status()?.height;
-        let block_height = node_api.node.current_block_height()?;
+        let wallet_height = node_api.wallet_status()?.height;
+        let block_height = node_api.current_block_height()?;
         println!("Scanned {}/{} blocks", wallet_height, block_height);
         if wallet_height == block_height {
             log::info!("Wallet Scan Complete!");
             break;
         }
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        if shutdown::sleep(std::time::Duration::from_secs(1)) {
+            log::info!("Shutdown requested, not waiting for the wallet scan");
+            break;
+        }
     })
 }
 
@@ -186,15 +508,23 @@ pub enum NodeScanRegistryError {
     Scan(#[from] ScanError),
     #[error("Error node: {0}")]
     NodeApi(#[from] NodeApiError),
-    #[error("Error parsing scans file: {0}")]
+    #[error(
+        "Error parsing scans file: {0}. Start the oracle with `run` to register the scans again"
+    )]
     Parse(String),
     #[error("Error reading/writing file: {0}")]
     Io(String),
+    #[error("Scans in {0} were registered for other token ids than in the pool config. Deregister them in the node (POST /scan/deregister), remove the file and restart to register the scans for the current pool config")]
+    StaleScans(String),
+    #[error("No scan {0} in the scans file")]
+    UnknownScan(String),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli_commands::bootstrap::BootstrapConfig;
+    use crate::pool_commands::test_utils::generate_token_ids;
     use crate::scans::NodeScanId;
     use ergo_node_interface::ScanId;
     use expect_test::expect;
@@ -204,6 +534,21 @@ mod tests {
         expected_json.assert_eq(json_str);
     }
 
+    #[test]
+    fn test_recoverable_scan_ids() {
+        let json_str = r#"{
+        "All Datapoints Scan": "185",
+        "Pool Box Scan": 187,
+        "Ballot Box Scan": { "unknown": "format" },
+        "config_fingerprint": "12"
+        }"#;
+        assert_eq!(
+            recoverable_scan_ids(json_str),
+            vec![ScanId::from(185), ScanId::from(187)]
+        );
+        assert!(recoverable_scan_ids("{ truncated").is_empty());
+    }
+
     #[test]
     fn parse_legacy_json() {
         let json_str = r#"{ 
@@ -229,6 +574,7 @@ mod tests {
             refresh_token_scan: GenericTokenScan::new(ScanId::from(188)),
             update_token_scan: GenericTokenScan::new(ScanId::from(186)),
             buyback_token_scan: None,
+            config_fingerprint: None,
         };
         let json_str = registry.save_to_json_str();
         expect_json(
@@ -254,6 +600,7 @@ mod tests {
             refresh_token_scan: GenericTokenScan::new(ScanId::from(188)),
             update_token_scan: GenericTokenScan::new(ScanId::from(186)),
             buyback_token_scan: None,
+            config_fingerprint: None,
         };
         let json_str = registry.save_to_json_str();
         let registry2 = NodeScanRegistry::load_from_json_str(&json_str).unwrap();
@@ -269,9 +616,58 @@ mod tests {
             refresh_token_scan: GenericTokenScan::new(ScanId::from(188)),
             update_token_scan: GenericTokenScan::new(ScanId::from(186)),
             buyback_token_scan: Some(GenericTokenScan::new(ScanId::from(192))),
+            config_fingerprint: Some("fingerprint".to_string()),
         };
         let json_str = registry.save_to_json_str();
         let registry2 = NodeScanRegistry::load_from_json_str(&json_str).unwrap();
         assert_eq!(registry, registry2);
     }
+
+    #[test]
+    fn test_scans() {
+        let mut registry = NodeScanRegistry {
+            oracle_token_scan: GenericTokenScan::new(ScanId::from(185)),
+            pool_token_scan: GenericTokenScan::new(ScanId::from(187)),
+            ballot_token_scan: GenericTokenScan::new(ScanId::from(191)),
+            refresh_token_scan: GenericTokenScan::new(ScanId::from(188)),
+            update_token_scan: GenericTokenScan::new(ScanId::from(186)),
+            buyback_token_scan: None,
+            config_fingerprint: None,
+        };
+        let scans = registry.scans();
+        assert_eq!(scans.len(), 5);
+        assert_eq!(scans[1], ("pool_token_scan", ScanId::from(187)));
+        registry.buyback_token_scan = Some(GenericTokenScan::new(ScanId::from(192)));
+        assert_eq!(
+            registry.scans().last(),
+            Some(&("buyback_token_scan", ScanId::from(192)))
+        );
+    }
+
+    #[test]
+    fn test_check_config_fingerprint() {
+        let pool_config =
+            PoolConfig::create(BootstrapConfig::default(), generate_token_ids()).unwrap();
+        let other_pool_config =
+            PoolConfig::create(BootstrapConfig::default(), generate_token_ids()).unwrap();
+        let mut registry = NodeScanRegistry {
+            oracle_token_scan: GenericTokenScan::new(ScanId::from(185)),
+            pool_token_scan: GenericTokenScan::new(ScanId::from(187)),
+            ballot_token_scan: GenericTokenScan::new(ScanId::from(191)),
+            refresh_token_scan: GenericTokenScan::new(ScanId::from(188)),
+            update_token_scan: GenericTokenScan::new(ScanId::from(186)),
+            buyback_token_scan: None,
+            config_fingerprint: None,
+        };
+        // no fingerprint saved by older versions
+        assert!(registry
+            .check_config_fingerprint(&other_pool_config)
+            .is_ok());
+        registry.config_fingerprint = Some(scan_config_fingerprint(&pool_config));
+        assert!(registry.check_config_fingerprint(&pool_config).is_ok());
+        assert!(matches!(
+            registry.check_config_fingerprint(&other_pool_config),
+            Err(NodeScanRegistryError::StaleScans(_))
+        ));
+    }
 }
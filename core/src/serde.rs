@@ -1,11 +1,15 @@
-//! Types to allow oracle configuration to convert to and from Serde.
+//! Types to allow oracle configuration to convert to and from Serde, and the handling of the
+//! persisted files that fail to deserialize.
 
 use std::convert::{TryFrom, TryInto};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 
 use ergo_lib::{
     ergo_chain_types::Digest32,
     ergotree_ir::chain::{address::AddressEncoderError, ergo_box::box_value::BoxValueError},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -33,7 +37,7 @@ use crate::{
     spec_token::{BuybackTokenId, TokenIdKind},
 };
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub(crate) struct PoolConfigSerde {
     data_point_source: Option<PredefinedDataPointSource>,
     oracle_contract_parameters: OracleContractParametersSerde,
@@ -42,6 +46,7 @@ pub(crate) struct PoolConfigSerde {
     update_contract_parameters: UpdateContractParametersSerde,
     ballot_contract_parameters: BallotContractParametersSerde,
     token_ids: TokenIds,
+    #[schemars(with = "Option<String>")]
     buyback_token_id: Option<BuybackTokenId>,
 }
 
@@ -307,7 +312,7 @@ impl TryFrom<BootstrapConfigSerde> for BootstrapConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OracleContractParametersSerde {
     ergo_tree_bytes: String,
     pool_nft_index: usize,
@@ -326,7 +331,7 @@ impl From<OracleContractParameters> for OracleContractParametersSerde {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct PoolContractParametersSerde {
     ergo_tree_bytes: String,
     refresh_nft_index: usize,
@@ -343,7 +348,7 @@ impl From<PoolContractParameters> for PoolContractParametersSerde {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct RefreshContractParametersSerde {
     ergo_tree_bytes: String,
     pool_nft_index: usize,
@@ -376,7 +381,7 @@ impl From<RefreshContractParameters> for RefreshContractParametersSerde {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct BallotContractParametersSerde {
     ergo_tree_bytes: String,
     min_storage_rent_index: usize,
@@ -396,7 +401,7 @@ impl From<BallotContractParameters> for BallotContractParametersSerde {
 }
 
 /// Used to (de)serialize `OracleContractParameters` instance.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct UpdateContractParametersSerde {
     ergo_tree_bytes: String,
     pool_nft_index: usize,
@@ -506,3 +511,29 @@ where
             .into(),
     ))
 }
+
+/// Move a persisted file (scan ids, epoch history) that fails to deserialize out of the way, so
+/// that it's rebuilt instead of failing every start. Such files are usually written by another
+/// version of oracle-core or ergo-lib with a different serialization.
+pub fn discard_unparsable_file(
+    path: &Path,
+    error: &dyn Display,
+) -> Result<PathBuf, std::io::Error> {
+    // timestamped to keep the files discarded before
+    let discarded_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut discarded_file_name = path.file_name().unwrap_or_default().to_os_string();
+    discarded_file_name.push(format!(".{}.discarded", discarded_at));
+    let discarded_path = path.with_file_name(discarded_file_name);
+    log::error!(
+        "Failed to parse {} (written by another oracle-core/ergo-lib version?): {}. Moving it to {} \
+        and rebuilding it",
+        path.display(),
+        error,
+        discarded_path.display()
+    );
+    std::fs::rename(path, &discarded_path)?;
+    Ok(discarded_path)
+}
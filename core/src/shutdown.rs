@@ -0,0 +1,33 @@
+//! Shutdown requested with SIGINT or SIGTERM in `run`. The main loop stops after the current
+//! iteration and the long waits in it (the tx submit retries, the node wallet rescan) end early.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// How often [`sleep`] checks for the shutdown request
+const SLEEP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Sleep for `duration` or until a shutdown is requested. Returns whether it was requested.
+pub fn sleep(duration: Duration) -> bool {
+    let started = Instant::now();
+    while !is_requested() {
+        let elapsed = started.elapsed();
+        if elapsed >= duration {
+            return false;
+        }
+        std::thread::sleep(SLEEP_CHECK_INTERVAL.min(duration - elapsed));
+    }
+    true
+}
@@ -1,8 +1,10 @@
+use crate::logging::sampled_debug;
 use crate::oracle_state::LiveEpochState;
 use crate::oracle_state::LocalDatapointState::Collected;
 use crate::oracle_state::LocalDatapointState::Posted;
 use crate::oracle_types::BlockHeight;
 use crate::oracle_types::EpochLength;
+use crate::pool_commands::ActionKind;
 use crate::pool_commands::PoolCommand;
 
 pub struct EpochState {
@@ -16,22 +18,59 @@ pub enum PoolState {
     LiveEpoch(LiveEpochState),
 }
 
+/// Decide which action to take. When several are eligible the one with the highest
+/// `action_priority` is chosen. No datapoint is published in the first `publish_grace_blocks`
+/// blocks after the pool box was created.
 pub fn process(
     pool_state: PoolState,
     epoch_length: EpochLength,
     current_height: BlockHeight,
+    action_priority: &[ActionKind],
+    publish_grace_blocks: u32,
 ) -> Option<PoolCommand> {
+    let eligible = eligible_commands(
+        pool_state,
+        epoch_length,
+        current_height,
+        publish_grace_blocks,
+    );
+    if eligible.len() > 1 {
+        log::debug!("Eligible commands: {:?}", eligible);
+    }
+    prioritize(eligible, action_priority)
+}
+
+/// The eligible command ranked first in `action_priority`, kinds not listed rank last
+fn prioritize(commands: Vec<PoolCommand>, action_priority: &[ActionKind]) -> Option<PoolCommand> {
+    commands.into_iter().min_by_key(|cmd| {
+        action_priority
+            .iter()
+            .position(|kind| *kind == cmd.kind())
+            .unwrap_or(action_priority.len())
+    })
+}
+
+fn eligible_commands(
+    pool_state: PoolState,
+    epoch_length: EpochLength,
+    current_height: BlockHeight,
+    publish_grace_blocks: u32,
+) -> Vec<PoolCommand> {
     let min_start_height = current_height - epoch_length;
     match pool_state {
         PoolState::NeedsBootstrap => {
             log::warn!(
                 "No oracle pool found, needs bootstrap or wait for bootstrap txs to be on-chain"
             );
-            None
+            vec![]
         }
         PoolState::LiveEpoch(live_epoch) => {
-            log::debug!("Height {current_height}. Live epoch state: {live_epoch:?}");
-            if let Some(local_datapoint_box_state) = live_epoch.local_datapoint_box_state {
+            sampled_debug!("Height {current_height}. Live epoch state: {live_epoch:?}");
+            let in_publish_grace_window =
+                current_height.0 < live_epoch.latest_pool_box_height.0 + publish_grace_blocks;
+            let mut commands = if let Some(local_datapoint_box_state) =
+                live_epoch.local_datapoint_box_state
+            {
                 match local_datapoint_box_state {
                     Collected { height: _ } => {
                         // publish datapoint after some blocks have passed after the pool box published
@@ -40,29 +79,145 @@ pub fn process(
                         if current_height.0
                             > live_epoch.latest_pool_box_height.0 + (epoch_length.0 as u32) / 2
                         {
-                            Some(PoolCommand::PublishSubsequentDataPoint { republish: false })
+                            vec![PoolCommand::PublishSubsequentDataPoint { republish: false }]
                         } else {
-                            None
+                            vec![]
                         }
                     }
                     Posted { epoch_id, height } => {
-                        if height < min_start_height || epoch_id != live_epoch.pool_box_epoch_id {
-                            Some(PoolCommand::PublishSubsequentDataPoint { republish: true })
-                        } else if live_epoch.latest_pool_box_height < min_start_height
+                        let mut commands = vec![];
+                        // a box created right at the epoch window start is as stale as an
+                        // older one, the refresh only collects the ones created after it
+                        if height <= min_start_height || epoch_id != live_epoch.pool_box_epoch_id {
+                            commands
+                                .push(PoolCommand::PublishSubsequentDataPoint { republish: true });
+                        }
+                        // the refresh collects the own box only if it's newer than the
+                        // epoch window start, a stale one has to be republished first
+                        if live_epoch.latest_pool_box_height < min_start_height
                             && epoch_id == live_epoch.pool_box_epoch_id
+                            && height > min_start_height
                         {
-                            Some(PoolCommand::Refresh)
-                        } else {
-                            None
+                            commands.push(PoolCommand::Refresh);
                         }
+                        commands
                     }
                 }
             } else {
                 // no local datapoint found
-                Some(PoolCommand::PublishFirstDataPoint)
+                vec![PoolCommand::PublishFirstDataPoint]
+            };
+            if in_publish_grace_window {
+                // let the sources settle after the epoch start
+                commands.retain(|cmd| cmd.kind() != ActionKind::PublishDatapoint);
             }
+            commands
         }
     }
 }
 
-// TODO: add tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle_types::EpochCounter;
+    use crate::pool_commands::DEFAULT_ACTION_PRIORITY;
+
+    fn eligible() -> Vec<PoolCommand> {
+        vec![
+            PoolCommand::PublishSubsequentDataPoint { republish: true },
+            PoolCommand::Refresh,
+        ]
+    }
+
+    #[test]
+    fn test_prioritize() {
+        assert_eq!(
+            prioritize(eligible(), &DEFAULT_ACTION_PRIORITY).map(|c| c.kind()),
+            Some(ActionKind::PublishDatapoint)
+        );
+        assert_eq!(
+            prioritize(
+                eligible(),
+                &[ActionKind::Refresh, ActionKind::PublishDatapoint]
+            )
+            .map(|c| c.kind()),
+            Some(ActionKind::Refresh)
+        );
+        assert_eq!(
+            prioritize(eligible(), &[ActionKind::Refresh]).map(|c| c.kind()),
+            Some(ActionKind::Refresh)
+        );
+        // unlisted kinds keep their order
+        assert_eq!(
+            prioritize(eligible(), &[]).map(|c| c.kind()),
+            Some(ActionKind::PublishDatapoint)
+        );
+        assert!(prioritize(vec![], &DEFAULT_ACTION_PRIORITY).is_none());
+    }
+
+    #[test]
+    fn test_publish_grace_window() {
+        let pool_state = PoolState::LiveEpoch(LiveEpochState {
+            pool_box_epoch_id: EpochCounter(5),
+            local_datapoint_box_state: None,
+            latest_pool_datapoint: 1000,
+            latest_pool_box_height: BlockHeight(100),
+        });
+        let eligible_at = |height: u32| {
+            eligible_commands(pool_state.clone(), EpochLength(30), BlockHeight(height), 3)
+        };
+        assert!(eligible_at(102).is_empty());
+        assert!(matches!(
+            eligible_at(103).as_slice(),
+            [PoolCommand::PublishFirstDataPoint]
+        ));
+    }
+
+    #[test]
+    fn test_stale_own_box_is_republished_before_refresh() {
+        let live_epoch = |own_box_height: u32| {
+            PoolState::LiveEpoch(LiveEpochState {
+                pool_box_epoch_id: EpochCounter(5),
+                local_datapoint_box_state: Some(Posted {
+                    epoch_id: EpochCounter(5),
+                    height: BlockHeight(own_box_height),
+                }),
+                latest_pool_datapoint: 1000,
+                latest_pool_box_height: BlockHeight(100),
+            })
+        };
+        let refresh_first = [ActionKind::Refresh, ActionKind::PublishDatapoint];
+        // the epoch window starts at 140 - 30 = 110
+        assert!(matches!(
+            process(
+                live_epoch(105),
+                EpochLength(30),
+                BlockHeight(140),
+                &refresh_first,
+                0
+            ),
+            Some(PoolCommand::PublishSubsequentDataPoint { republish: true })
+        ));
+        assert!(matches!(
+            process(
+                live_epoch(120),
+                EpochLength(30),
+                BlockHeight(140),
+                &refresh_first,
+                0
+            ),
+            Some(PoolCommand::Refresh)
+        ));
+        // created at the window start, not collected by the refresh
+        assert!(matches!(
+            process(
+                live_epoch(110),
+                EpochLength(30),
+                BlockHeight(140),
+                &refresh_first,
+                0
+            ),
+            Some(PoolCommand::PublishSubsequentDataPoint { republish: true })
+        ));
+    }
+}
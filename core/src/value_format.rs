@@ -0,0 +1,129 @@
+//! Human-readable formatting of the datapoint values. The posted datapoints are integers in the
+//! smallest unit of the asset (e.g. nanoERG per 1 USD), which are converted to a price here.
+
+use crate::oracle_types::Rate;
+use crate::pool_config::PredefinedDataPointSource;
+
+/// Decimals shown if `value_display_decimals` is not set
+pub const DEFAULT_DISPLAY_DECIMALS: usize = 2;
+
+const NANOERGS_PER_ERG: f64 = 1_000_000_000.0;
+const LOVELACES_PER_ADA: f64 = 1_000_000.0;
+
+/// The datapoint followed by the price it stands for, e.g. "769230769 ($1.30 per ERG)". Only the
+/// datapoint is returned for custom sources since their scale is unknown.
+pub fn describe_rate(
+    rate: Rate,
+    data_point_source: Option<PredefinedDataPointSource>,
+    decimals: usize,
+) -> String {
+    match data_point_source.and_then(|source| format_price(rate, source, decimals)) {
+        Some(price) => format!("{} ({})", rate, price),
+        None => rate.to_string(),
+    }
+}
+
+/// Price of the asset for the datapoint of the predefined source, `None` for a non-positive
+/// datapoint
+pub fn format_price(
+    rate: Rate,
+    data_point_source: PredefinedDataPointSource,
+    decimals: usize,
+) -> Option<String> {
+    let rate = i64::from(rate);
+    if rate <= 0 {
+        return None;
+    }
+    let rate = rate as f64;
+    Some(match data_point_source {
+        PredefinedDataPointSource::NanoErgUsd => format!(
+            "${} per ERG",
+            format_with_thousands(NANOERGS_PER_ERG / rate, decimals)
+        ),
+        PredefinedDataPointSource::NanoAdaUsd => format!(
+            "${} per ADA",
+            format_with_thousands(LOVELACES_PER_ADA / rate, decimals)
+        ),
+        PredefinedDataPointSource::NanoErgXau => format!(
+            "{} ERG per kg of gold",
+            format_with_thousands(rate / NANOERGS_PER_ERG, decimals)
+        ),
+    })
+}
+
+/// Format with the given number of decimals and commas between the thousands
+pub fn format_with_thousands(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (formatted.as_str(), None),
+    };
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let sign = if value < 0.0 && formatted.chars().any(|c| c != '0' && c != '.') {
+        "-"
+    } else {
+        ""
+    };
+    match frac_part {
+        Some(frac_part) => format!("{}{}.{}", sign, grouped, frac_part),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_with_thousands() {
+        assert_eq!(format_with_thousands(1234.5678, 2), "1,234.57");
+        assert_eq!(format_with_thousands(999.999, 2), "1,000.00");
+        assert_eq!(format_with_thousands(1234567.0, 0), "1,234,567");
+        assert_eq!(format_with_thousands(0.5, 3), "0.500");
+        assert_eq!(format_with_thousands(-1234.5, 1), "-1,234.5");
+        assert_eq!(format_with_thousands(-0.001, 2), "0.00");
+    }
+
+    #[test]
+    fn test_describe_rate() {
+        assert_eq!(
+            describe_rate(
+                Rate::from(769230769),
+                Some(PredefinedDataPointSource::NanoErgUsd),
+                2
+            ),
+            "769230769 ($1.30 per ERG)"
+        );
+        assert_eq!(
+            describe_rate(
+                Rate::from(2857142),
+                Some(PredefinedDataPointSource::NanoAdaUsd),
+                3
+            ),
+            "2857142 ($0.350 per ADA)"
+        );
+        assert_eq!(
+            describe_rate(
+                Rate::from(45_123_456_789_000),
+                Some(PredefinedDataPointSource::NanoErgXau),
+                2
+            ),
+            "45123456789000 (45,123.46 ERG per kg of gold)"
+        );
+        assert_eq!(describe_rate(Rate::from(1000), None, 2), "1000");
+        assert_eq!(
+            describe_rate(
+                Rate::from(0),
+                Some(PredefinedDataPointSource::NanoErgUsd),
+                2
+            ),
+            "0"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Advisory check of the running version against the `min_recommended_version` of the oracle
+//! config, e.g. set by the pool coordinator in the config template shared with the operators.
+
+/// `major.minor.patch` of a version string like `2.0.1` or `v2.0.1-beta`
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(|c| c == '-' || c == '+').next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// Whether `current` is older than `minimum`, None if either can't be parsed
+pub fn is_outdated(current: &str, minimum: &str) -> Option<bool> {
+    Some(parse_version(current)? < parse_version(minimum)?)
+}
+
+/// Log a warning if this binary is older than `min_recommended_version`
+pub fn warn_if_outdated(min_recommended_version: Option<&str>) {
+    let minimum = match min_recommended_version {
+        Some(minimum) => minimum,
+        None => return,
+    };
+    let current = env!("CARGO_PKG_VERSION");
+    match is_outdated(current, minimum) {
+        Some(true) => log::warn!(
+            "!!! oracle-core v{} is older than the minimum recommended version {} of the pool, \
+            please upgrade !!!",
+            current,
+            minimum
+        ),
+        Some(false) => {
+            log::debug!("oracle-core v{} is at least {}", current, minimum)
+        }
+        None => log::warn!(
+            "Invalid min_recommended_version {:?}, expected major.minor.patch",
+            minimum
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_outdated() {
+        assert_eq!(is_outdated("2.0.1", "2.1.0"), Some(true));
+        assert_eq!(is_outdated("2.1.0", "v2.1.0"), Some(false));
+        assert_eq!(is_outdated("2.10.0", "2.9"), Some(false));
+        assert_eq!(is_outdated("2.0.0-beta", "2.0.1"), Some(true));
+        assert_eq!(is_outdated("2.0.0", "latest"), None);
+    }
+}
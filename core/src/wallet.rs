@@ -1,5 +1,6 @@
 use ergo_lib::ergotree_ir::chain::address::AddressEncoderError;
 use ergo_lib::ergotree_ir::chain::address::NetworkAddress;
+use ergo_lib::ergotree_ir::chain::ergo_box::box_value::BoxValue;
 use ergo_lib::ergotree_ir::chain::ergo_box::ErgoBox;
 use ergo_node_interface::node_interface::NodeError;
 use thiserror::Error;
@@ -18,8 +19,64 @@ pub enum WalletDataError {
     NodeApiError(#[from] NodeApiError),
 }
 
+/// The ERG of the boxes available to an action are short of what it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("insufficient funds: {needed} nanoERG needed, {available} nanoERG available")]
+pub struct InsufficientFunds {
+    pub needed: u64,
+    pub available: u64,
+}
+
+impl InsufficientFunds {
+    /// nanoERG to add to the wallet
+    pub fn missing(&self) -> u64 {
+        self.needed.saturating_sub(self.available)
+    }
+}
+
+/// Check that the boxes hold at least `needed` ERG before the box selection, which fails with a
+/// less telling error
+pub fn check_funds(boxes: &[ErgoBox], needed: BoxValue) -> Result<(), InsufficientFunds> {
+    let available: u64 = boxes.iter().map(|b| u64::from(b.value)).sum();
+    let needed = u64::from(needed);
+    if available < needed {
+        Err(InsufficientFunds { needed, available })
+    } else {
+        Ok(())
+    }
+}
+
 // TODO: remove and pass unspent boxes and change address directly?
 pub trait WalletDataSource {
     fn get_unspent_wallet_boxes(&self) -> Result<Vec<ErgoBox>, WalletDataError>;
     fn get_change_address(&self) -> Result<NetworkAddress, WalletDataError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+    use sigma_test_util::force_any_val;
+
+    use super::*;
+    use crate::pool_commands::test_utils::make_wallet_unspent_box;
+
+    #[test]
+    fn test_check_funds() {
+        let boxes = vec![
+            make_wallet_unspent_box(
+                force_any_val::<ProveDlog>(),
+                BoxValue::new(1_000_000).unwrap(),
+                None,
+            ),
+            make_wallet_unspent_box(
+                force_any_val::<ProveDlog>(),
+                BoxValue::new(2_000_000).unwrap(),
+                None,
+            ),
+        ];
+        assert!(check_funds(&boxes, BoxValue::new(3_000_000).unwrap()).is_ok());
+        let funds = check_funds(&boxes, BoxValue::new(3_100_000).unwrap()).unwrap_err();
+        assert_eq!(funds.available, 3_000_000);
+        assert_eq!(funds.missing(), 100_000);
+    }
+}